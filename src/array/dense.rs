@@ -98,6 +98,21 @@
 //!                  &array.times(2.0)));
 //! ```
 //!
+//! In-place variants return the array, allowing operations to be chained:
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//!
+//! let mut array = Array::from(&vec![vec![0.0, 1.0],
+//!                                   vec![2.0, 3.0]]);
+//!
+//! array.add_inplace(1.0).times_inplace(2.0);
+//!
+//! assert!(allclose(&array,
+//!                  &Array::from(&vec![vec![2.0, 4.0],
+//!                                     vec![6.0, 8.0]])));
+//! ```
+//!
 //! # Matrix multiplication
 //!
 //! ```
@@ -108,11 +123,38 @@
 //!
 //! let dot = x.dot(&y.T());
 //! ```
+//!
+//! # Numpy interoperability
+//!
+//! Arrays can be saved to and loaded from numpy's `.npy` format:
+//!
+//! ```no_run
+//! use rustlearn::prelude::*;
+//!
+//! let array = Array::from(&vec![vec![0.0, 1.0],
+//!                               vec![2.0, 3.0]]);
+//!
+//! array.save_npy("array.npy").unwrap();
+//!
+//! let loaded = Array::load_npy("array.npy").unwrap();
+//! ```
 
-use std::iter::Iterator;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::f32;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::iter::{Cloned, Enumerate, Iterator};
 use std::ops::Range;
+use std::slice::Iter;
 
 use array::traits::*;
+use error::RustlearnError;
+use utils::percentile;
+
+use rand::{Rng, StdRng};
 
 #[derive(Clone, Copy, Debug)]
 enum ArrayIteratorAxis {
@@ -343,6 +385,13 @@ impl<'a> NonzeroIterable for ArrayView<'a> {
     }
 }
 
+impl<'a> NonzeroIterable for &'a [f32] {
+    type Output = Enumerate<Cloned<Iter<'a, f32>>>;
+    fn iter_nonzero(&self) -> Enumerate<Cloned<Iter<'a, f32>>> {
+        self.iter().cloned().enumerate()
+    }
+}
+
 impl IndexableMatrix for Array {
     fn rows(&self) -> usize {
         self.rows
@@ -400,14 +449,208 @@ impl Array {
         }
     }
 
-    fn empty(rows: usize, cols: usize) -> Array {
-        let data: Vec<f32> = Vec::with_capacity(rows * cols);
+    /// Create a `rows` by `cols` row-major array from a flat vector,
+    /// taking ownership of the data without copying.
+    ///
+    /// # Failures
+    /// Fails if the length of `data` is not equal to `rows * cols`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from_vec(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0], 2, 3).unwrap();
+    ///
+    /// assert!(array.get(1, 0) == 3.0);
+    /// assert!(Array::from_vec(vec![0.0, 1.0], 2, 3).is_err());
+    /// ```
+    pub fn from_vec(data: Vec<f32>, rows: usize, cols: usize) -> Result<Array, RustlearnError> {
+        if data.len() != rows * cols {
+            return Err(RustlearnError::DimensionMismatch(
+                "Data length does not match the number of rows and columns.",
+            ));
+        }
 
-        Array {
+        Ok(Array {
             rows: rows,
             cols: cols,
             order: MatrixOrder::RowMajor,
             data: data,
+        })
+    }
+
+    /// Create an `n` by `n` identity matrix.
+    ///
+    /// # Panics
+    /// This will panic if `n` is zero.
+    pub fn eye(n: usize) -> Array {
+        assert!(n > 0, "Identity matrix must have at least one row");
+
+        let mut array = Array::zeros(n, n);
+
+        for idx in 0..n {
+            array.set(idx, idx, 1.0);
+        }
+
+        array
+    }
+
+    /// Return the main diagonal of the array as a
+    /// `min(rows, cols)` by 1 array.
+    pub fn diagonal(&self) -> Array {
+        let n = if self.rows < self.cols {
+            self.rows
+        } else {
+            self.cols
+        };
+
+        let mut diagonal = Array::zeros(n, 1);
+
+        for idx in 0..n {
+            diagonal.set(idx, 0, self.get(idx, idx));
+        }
+
+        diagonal
+    }
+
+    /// Return a copy of the rectangular block of the array
+    /// spanning the `rows` and `cols` ranges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![1.0, 2.0, 3.0],
+    ///                               vec![4.0, 5.0, 6.0],
+    ///                               vec![7.0, 8.0, 9.0]]);
+    ///
+    /// let block = array.submatrix(1..3, 0..2);
+    ///
+    /// assert!(block == Array::from(&vec![vec![4.0, 5.0], vec![7.0, 8.0]]));
+    /// ```
+    ///
+    /// # Panics
+    /// This will panic if either range is empty or out of bounds.
+    pub fn submatrix(&self, rows: Range<usize>, cols: Range<usize>) -> Array {
+        assert!(
+            rows.start < rows.end && rows.end <= self.rows,
+            "Row range must be nonempty and within bounds"
+        );
+        assert!(
+            cols.start < cols.end && cols.end <= self.cols,
+            "Column range must be nonempty and within bounds"
+        );
+
+        let mut data = Vec::with_capacity(rows.len() * cols.len());
+
+        for row_idx in rows.clone() {
+            for col_idx in cols.clone() {
+                data.push(self.get(row_idx, col_idx));
+            }
+        }
+
+        Array {
+            rows: rows.len(),
+            cols: cols.len(),
+            order: MatrixOrder::RowMajor,
+            data: data,
+        }
+    }
+
+    /// Find the dominant eigenpair of a square matrix using power iteration.
+    ///
+    /// Returns the eigenvalue of largest magnitude (the largest eigenvalue,
+    /// for positive semi-definite matrices) and its unit-norm eigenvector
+    /// as an `n` by 1 array. Iteration stops after `n_iter` steps, or once the
+    /// eigenvector estimate changes by less than `tol` in Euclidean norm.
+    ///
+    /// Convergence is fast when the largest eigenvalue is well separated
+    /// from the others, and may be slow or fail otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let arr = Array::from(&vec![vec![2.0, 1.0], vec![1.0, 2.0]]);
+    /// let (eigenvalue, eigenvector) = arr.power_iteration(100, 1e-6).unwrap();
+    ///
+    /// assert!((eigenvalue - 3.0).abs() < 1e-4);
+    /// assert!((eigenvector.get(0, 0) - eigenvector.get(1, 0)).abs() < 1e-4);
+    /// ```
+    pub fn power_iteration(&self, n_iter: usize, tol: f32) -> Result<(f32, Array), RustlearnError> {
+        if self.rows != self.cols {
+            return Err(RustlearnError::InvalidInput(
+                "Power iteration requires a square matrix.",
+            ));
+        }
+
+        if self.rows == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Power iteration requires a non-empty matrix.",
+            ));
+        }
+
+        if n_iter == 0 {
+            return Err(RustlearnError::InvalidInput(
+                "Number of iterations must be positive.",
+            ));
+        }
+
+        let n = self.rows;
+
+        // Start from a fixed vector unlikely to be orthogonal to the dominant eigenvector
+        let mut vector = (0..n).map(|idx| (idx + 1) as f32).collect::<Vec<_>>();
+        normalize(&mut vector);
+
+        let mut product = vec![0.0; n];
+
+        for _ in 0..n_iter {
+            self.matrix_vector_product(&vector, &mut product);
+
+            if !normalize(&mut product) {
+                // The vector is in the null space, with eigenvalue zero
+                break;
+            }
+
+            // Negative eigenvalues flip the sign of the vector on every step
+            let dot = vector.iter().zip(&product).map(|(x, y)| x * y).sum::<f32>();
+            if dot < 0.0 {
+                for value in &mut product {
+                    *value = -*value;
+                }
+            }
+
+            let change = vector
+                .iter()
+                .zip(&product)
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f32>()
+                .sqrt();
+
+            vector.copy_from_slice(&product);
+
+            if change < tol {
+                break;
+            }
+        }
+
+        // Rayleigh quotient of the unit-norm eigenvector estimate
+        self.matrix_vector_product(&vector, &mut product);
+        let eigenvalue = vector.iter().zip(&product).map(|(x, y)| x * y).sum::<f32>();
+
+        Ok((eigenvalue, Array::from(vector)))
+    }
+
+    fn matrix_vector_product(&self, vector: &[f32], product: &mut [f32]) {
+        for (row_idx, value) in product.iter_mut().enumerate() {
+            *value = vector
+                .iter()
+                .enumerate()
+                .map(|(col_idx, x)| self.get(row_idx, col_idx) * x)
+                .sum();
         }
     }
 
@@ -459,6 +702,39 @@ impl Array {
         &mut self.data[..]
     }
 
+    /// Iterate over the rows of the array as slices into the
+    /// underlying data buffer, without copying.
+    ///
+    /// # Failures
+    /// Fails if the array is not row-major (for example, if it is
+    /// the result of a transpose), as its rows are then not
+    /// contiguous in memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![0.0, 1.0],
+    ///                               vec![2.0, 3.0]]);
+    ///
+    /// for row in array.row_iter().unwrap() {
+    ///     assert!(row.len() == 2);
+    /// }
+    /// ```
+    pub fn row_iter(&self) -> Result<RowSlices<'_>, RustlearnError> {
+        match self.order {
+            MatrixOrder::RowMajor => Ok(RowSlices {
+                data: &self.data[..],
+                cols: self.cols,
+                remaining: self.rows,
+            }),
+            MatrixOrder::ColumnMajor => Err(RustlearnError::Unsupported(
+                "Cannot iterate over row slices of a column-major array.",
+            )),
+        }
+    }
+
     /// Transpose the matrix.
     pub fn T(mut self) -> Array {
         let (rows, cols) = (self.cols, self.rows);
@@ -482,164 +758,1171 @@ impl Array {
     pub fn mean(&self) -> f32 {
         self.sum() / ((self.cols * self.rows) as f32)
     }
-}
 
-impl From<Vec<f32>> for Array {
-    /// Construct an array from a vector.
+    /// Compute the sum of the array along an axis.
     ///
-    /// # Panics
-    /// This will panic if the input vector is emtpy.
-    fn from(data: Vec<f32>) -> Array {
-        assert!(data.len() > 0);
-
-        Array {
-            rows: data.len(),
-            cols: 1,
-            order: MatrixOrder::RowMajor,
-            data: data,
-        }
+    /// Summing along `axis` 0 reduces over rows, giving a `(1, cols)`
+    /// array of column sums; `axis` 1 reduces over columns, giving a
+    /// `(rows, 1)` array of row sums.
+    ///
+    /// # Failures
+    /// Fails if `axis` is not 0 or 1, or if the array is empty along `axis`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![0.0, 1.0],
+    ///                               vec![2.0, 3.0]]);
+    ///
+    /// assert!(allclose(&array.sum_axis(0).unwrap(),
+    ///                  &Array::from(&vec![vec![2.0, 4.0]])));
+    /// assert!(allclose(&array.sum_axis(1).unwrap(),
+    ///                  &Array::from(vec![1.0, 5.0])));
+    /// ```
+    pub fn sum_axis(&self, axis: usize) -> Result<Array, RustlearnError> {
+        self.reduce_axis(axis, |acc, x| acc + x)
     }
-}
 
-impl<'a> From<&'a Vec<Vec<f32>>> for Array {
-    /// Construct an array from a vector of vectors.
+    /// Compute the maximum of the array along an axis.
     ///
-    /// # Panics
-    /// This will panic if the input vector is emtpy
-    /// or if its rows are of unequal length.
-    fn from(input: &Vec<Vec<f32>>) -> Array {
-        assert!(input.len() > 0);
-
-        let rows = input.len();
-        let cols = input[0].len();
+    /// Axes are interpreted as in [`sum_axis`](#method.sum_axis).
+    ///
+    /// # Failures
+    /// Fails if `axis` is not 0 or 1, or if the array is empty along `axis`.
+    pub fn max_axis(&self, axis: usize) -> Result<Array, RustlearnError> {
+        self.reduce_axis(axis, |acc, x| if x > acc { x } else { acc })
+    }
 
-        let mut data: Vec<f32> = Vec::with_capacity(rows * cols);
+    /// Compute the minimum of the array along an axis.
+    ///
+    /// Axes are interpreted as in [`sum_axis`](#method.sum_axis).
+    ///
+    /// # Failures
+    /// Fails if `axis` is not 0 or 1, or if the array is empty along `axis`.
+    pub fn min_axis(&self, axis: usize) -> Result<Array, RustlearnError> {
+        self.reduce_axis(axis, |acc, x| if x < acc { x } else { acc })
+    }
 
-        for row in input {
-            assert!(row.len() == cols);
-            for &e in row {
-                data.push(e);
+    /// Compute summary statistics of every column of the array.
+    ///
+    /// Returns an array with one column per column of the array, and one
+    /// row per statistic, in order: the count, mean, standard deviation,
+    /// minimum, 25th percentile, median, 75th percentile, and maximum.
+    /// `NaN` values are treated as missing and ignored. The standard
+    /// deviation uses the sample formula, and is defined as zero for columns
+    /// with a single value, which do not vary. Percentiles linearly interpolate
+    /// between the two nearest values. All statistics other than the count
+    /// are `NaN` for columns without any values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![0.0, 1.0],
+    ///                               vec![2.0, 3.0],
+    ///                               vec![4.0, 8.0]]);
+    ///
+    /// let summary = array.describe();
+    ///
+    /// assert!(summary.rows() == 8);
+    /// assert!(summary.get(1, 0) == 2.0);
+    /// assert!(summary.get(5, 1) == 3.0);
+    /// ```
+    pub fn describe(&self) -> Array {
+        let mut summary = Array::zeros(8, self.cols);
+
+        for col_idx in 0..self.cols {
+            let mut values = (0..self.rows)
+                .map(|row_idx| self.get(row_idx, col_idx))
+                .filter(|value| !value.is_nan())
+                .collect::<Vec<_>>();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            let count = values.len() as f32;
+            let mean = values.iter().sum::<f32>() / count;
+            let std = if values.len() > 1 {
+                (values.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / (count - 1.0)).sqrt()
+            } else {
+                0.0
+            };
+
+            let statistics = if values.is_empty() {
+                [
+                    0.0,
+                    f32::NAN,
+                    f32::NAN,
+                    f32::NAN,
+                    f32::NAN,
+                    f32::NAN,
+                    f32::NAN,
+                    f32::NAN,
+                ]
+            } else {
+                [
+                    count,
+                    mean,
+                    std,
+                    values[0],
+                    percentile(&values, 0.25),
+                    percentile(&values, 0.5),
+                    percentile(&values, 0.75),
+                    values[values.len() - 1],
+                ]
+            };
+
+            for (row_idx, &statistic) in statistics.iter().enumerate() {
+                summary.set(row_idx, col_idx, statistic);
             }
         }
 
-        Array {
-            rows: rows,
-            cols: cols,
-            order: MatrixOrder::RowMajor,
-            data: data,
-        }
+        summary
     }
-}
-
-impl ElementwiseArrayOps<f32> for Array {
-    type Output = Array;
 
-    fn add(&self, rhs: f32) -> Array {
-        Array {
-            rows: self.rows,
-            cols: self.cols,
-            order: MatrixOrder::RowMajor,
-            data: self.data.iter().map(|&x| x + rhs).collect::<Vec<f32>>(),
+    /// Save the array to `path` in numpy's `.npy` format.
+    ///
+    /// The array is written as a little-endian `float32` array
+    /// in C (row-major) order, regardless of its internal storage order.
+    pub fn save_npy(&self, path: &str) -> io::Result<()> {
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.rows, self.cols
+        );
+
+        // The magic string, version, and header length take up 10 bytes;
+        // the header is padded so that the data starts on a 64-byte boundary.
+        let padding = 64 - (NPY_PREAMBLE_LEN + header.len() + 1) % 64;
+        header.extend((0..padding % 64).map(|_| ' '));
+        header.push('\n');
+
+        let mut writer = BufWriter::new(try!(File::create(path)));
+
+        try!(writer.write_all(NPY_MAGIC));
+        try!(writer.write_all(&[1, 0]));
+        try!(writer.write_all(&(header.len() as u16).to_le_bytes()));
+        try!(writer.write_all(header.as_bytes()));
+
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                try!(writer.write_all(&self.get(row, col).to_bits().to_le_bytes()));
+            }
         }
+
+        writer.flush()
     }
 
-    fn add_inplace(&mut self, rhs: f32) {
-        for v in &mut self.data {
-            *v += rhs;
+    /// Load an array from a numpy `.npy` file at `path`.
+    ///
+    /// Only little-endian `float32` arrays with one or two dimensions
+    /// are supported; one-dimensional arrays are loaded as column vectors.
+    ///
+    /// # Failures
+    /// Fails if the file cannot be read or is not a supported `.npy` file,
+    /// including if its length does not match the shape given in its header.
+    pub fn load_npy(path: &str) -> io::Result<Array> {
+        let file = try!(File::open(path));
+        let file_len = try!(file.metadata()).len();
+        let mut reader = BufReader::new(file);
+
+        let mut preamble = [0; NPY_PREAMBLE_LEN];
+        try!(reader.read_exact(&mut preamble));
+
+        if &preamble[..6] != NPY_MAGIC || preamble[6] != 1 {
+            return Err(invalid_npy("Not a version 1.0 .npy file."));
         }
-    }
 
-    fn sub(&self, rhs: f32) -> Array {
-        self.add(-rhs)
-    }
+        let header_len = u16::from_le_bytes([preamble[8], preamble[9]]) as usize;
+        let mut header = vec![0; header_len];
+        try!(reader.read_exact(&mut header));
 
-    fn sub_inplace(&mut self, rhs: f32) {
-        self.add_inplace(-rhs);
-    }
+        let header = try!(String::from_utf8(header).map_err(|_| invalid_npy("Invalid header.")));
+        let (fortran_order, rows, cols) = try!(parse_npy_header(&header).map_err(invalid_npy));
 
-    fn times(&self, rhs: f32) -> Array {
-        Array {
-            rows: self.rows,
-            cols: self.cols,
-            order: MatrixOrder::RowMajor,
-            data: self.data.iter().map(|&x| x * rhs).collect::<Vec<f32>>(),
+        // Check the shape against the payload before allocating for it
+        let payload_len = file_len.saturating_sub((NPY_PREAMBLE_LEN + header_len) as u64);
+        let data_len = rows
+            .checked_mul(cols)
+            .and_then(|len| len.checked_mul(4))
+            .map(|len| len as u64);
+
+        if data_len != Some(payload_len) {
+            return Err(invalid_npy("Data length does not match the header shape."));
         }
-    }
 
-    fn times_inplace(&mut self, rhs: f32) {
-        for v in &mut self.data {
-            *v *= rhs;
+        let mut data = Vec::with_capacity(rows * cols);
+        let mut buffer = [0; 4];
+
+        for _ in 0..(rows * cols) {
+            try!(reader.read_exact(&mut buffer));
+            data.push(f32::from_bits(u32::from_le_bytes(buffer)));
         }
+
+        Ok(Array {
+            rows: rows,
+            cols: cols,
+            order: if fortran_order {
+                MatrixOrder::ColumnMajor
+            } else {
+                MatrixOrder::RowMajor
+            },
+            data: data,
+        })
     }
 
-    fn div(&self, rhs: f32) -> Array {
-        Array {
-            rows: self.rows,
-            cols: self.cols,
-            order: MatrixOrder::RowMajor,
-            data: self.data.iter().map(|&x| x / rhs).collect::<Vec<f32>>(),
+    /// Serialize the array into a compact, self-describing binary representation
+    /// that does not depend on `serde`.
+    ///
+    /// The representation consists of the 8 magic bytes `RLARRAY\0`, the format
+    /// version as a little-endian `u32`, the number of rows and columns as
+    /// little-endian `u64`s, and the elements as little-endian `f32`s in
+    /// row-major order, regardless of the internal storage order of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![0.0, 1.0],
+    ///                               vec![2.0, 3.0]]);
+    ///
+    /// let bytes = array.to_bytes();
+    ///
+    /// assert!(bytes.len() == 28 + 4 * 4);
+    /// assert!(Array::from_bytes(&bytes).unwrap() == array);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BYTES_HEADER_LEN + 4 * self.rows * self.cols);
+
+        bytes.extend_from_slice(BYTES_MAGIC);
+        bytes.extend_from_slice(&BYTES_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.rows as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.cols as u64).to_le_bytes());
+
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                bytes.extend_from_slice(&self.get(row, col).to_bits().to_le_bytes());
+            }
         }
+
+        bytes
     }
 
-    fn div_inplace(&mut self, rhs: f32) {
-        for v in &mut self.data {
-            *v /= rhs;
+    /// Deserialize an array from the binary representation
+    /// produced by [`to_bytes`](#method.to_bytes).
+    ///
+    /// # Failures
+    /// Fails if the input does not start with the expected magic bytes,
+    /// has an unsupported version, or is not exactly as long as its
+    /// shape requires.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Array, RustlearnError> {
+        if bytes.len() < BYTES_HEADER_LEN {
+            return Err(RustlearnError::InvalidInput(
+                "Input is too short to contain an array header.",
+            ));
         }
-    }
-}
 
-/// Perform elementwise operations between two arrays.
-///
-/// # Panics
-/// Will panic if the two operands are not of the same shape.
-impl<'a> ElementwiseArrayOps<&'a Array> for Array {
-    type Output = Array;
+        if &bytes[..8] != BYTES_MAGIC {
+            return Err(RustlearnError::InvalidInput(
+                "Input does not start with the array magic bytes.",
+            ));
+        }
 
-    fn add(&self, rhs: &'a Array) -> Array {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+        let mut version = [0; 4];
+        version.copy_from_slice(&bytes[8..12]);
 
-        let mut data = Vec::with_capacity(self.rows * self.cols);
+        if u32::from_le_bytes(version) != BYTES_VERSION {
+            return Err(RustlearnError::InvalidInput(
+                "Unsupported array format version.",
+            ));
+        }
 
-        unsafe {
-            for i in 0..self.rows {
-                for j in 0..self.cols {
-                    data.push(self.get_unchecked(i, j) + rhs.get_unchecked(i, j));
-                }
-            }
+        let mut rows = [0; 8];
+        let mut cols = [0; 8];
+        rows.copy_from_slice(&bytes[12..20]);
+        cols.copy_from_slice(&bytes[20..28]);
+
+        let data_len = usize::try_from(u64::from_le_bytes(rows))
+            .ok()
+            .and_then(|rows| {
+                usize::try_from(u64::from_le_bytes(cols))
+                    .ok()
+                    .and_then(|cols| rows.checked_mul(cols))
+            })
+            .and_then(|len| len.checked_mul(4));
+
+        if data_len != Some(bytes.len() - BYTES_HEADER_LEN) {
+            return Err(RustlearnError::InvalidInput(
+                "Input length does not match the array shape.",
+            ));
         }
 
-        Array {
-            rows: self.rows,
-            cols: self.cols,
+        let data = bytes[BYTES_HEADER_LEN..]
+            .chunks(4)
+            .map(|chunk| {
+                f32::from_bits(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            })
+            .collect();
+
+        Ok(Array {
+            rows: u64::from_le_bytes(rows) as usize,
+            cols: u64::from_le_bytes(cols) as usize,
             order: MatrixOrder::RowMajor,
             data: data,
-        }
+        })
+    }
+
+    /// Determine whether two arrays have the same shape and all their
+    /// elements differ by at most `tol`.
+    ///
+    /// The comparison is independent of the storage order of the arrays.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![0.0, 1.0],
+    ///                               vec![2.0, 3.0]]);
+    ///
+    /// assert!(array.approx_eq(&array.add(0.01), 0.1));
+    /// assert!(!array.approx_eq(&array.add(0.5), 0.1));
+    /// ```
+    pub fn approx_eq(&self, other: &Array, tol: f32) -> bool {
+        self.all_pairs(other, |a, b| (a - b).abs() <= tol)
     }
 
-    fn add_inplace(&mut self, rhs: &'a Array) {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+    /// Check that `other` has the same shape, and that `f`
+    /// holds for all pairs of corresponding elements.
+    fn all_pairs<F: Fn(f32, f32) -> bool>(&self, other: &Array, f: F) -> bool {
+        if self.rows != other.rows || self.cols != other.cols {
+            return false;
+        }
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
-                    let v = self.get_unchecked_mut(i, j);
-                    *v += rhs.get_unchecked(i, j);
+                    if !f(self.get_unchecked(i, j), other.get_unchecked(i, j)) {
+                        return false;
+                    }
                 }
             }
         }
-    }
 
-    fn sub(&self, rhs: &'a Array) -> Array {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+        true
+    }
 
-        let mut data = Vec::with_capacity(self.rows * self.cols);
+    fn reduce_axis<F: Fn(f32, f32) -> f32>(
+        &self,
+        axis: usize,
+        f: F,
+    ) -> Result<Array, RustlearnError> {
+        // Reduce over the inner index for every outer index
+        let (num_outer, num_inner) = match axis {
+            0 => (self.cols, self.rows),
+            1 => (self.rows, self.cols),
+            _ => return Err(RustlearnError::InvalidInput("Axis must be either 0 or 1.")),
+        };
 
-        unsafe {
-            for i in 0..self.rows {
-                for j in 0..self.cols {
-                    data.push(self.get_unchecked(i, j) - rhs.get_unchecked(i, j));
-                }
-            }
+        if num_inner == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot reduce along an axis of length zero.",
+            ));
+        }
+
+        let get = |outer, inner| {
+            if axis == 0 {
+                self.get(inner, outer)
+            } else {
+                self.get(outer, inner)
+            }
+        };
+
+        let mut output = if axis == 0 {
+            Array::zeros(1, num_outer)
+        } else {
+            Array::zeros(num_outer, 1)
+        };
+
+        for outer in 0..num_outer {
+            output.data[outer] =
+                (1..num_inner).fold(get(outer, 0), |acc, inner| f(acc, get(outer, inner)));
+        }
+
+        Ok(output)
+    }
+
+    /// Return a new array of the same shape, with `f` applied
+    /// to every element.
+    ///
+    /// Elements are visited in row-major order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![-1.0, 0.5],
+    ///                               vec![2.0, 3.0]]);
+    ///
+    /// let clipped = array.map(|x| x.max(0.0).min(1.0));
+    ///
+    /// assert!(allclose(&clipped,
+    ///                  &Array::from(&vec![vec![0.0, 0.5],
+    ///                                     vec![1.0, 1.0]])));
+    /// ```
+    pub fn map<F: Fn(f32) -> f32>(&self, f: F) -> Array {
+        let mut output = self.clone();
+        output.apply_inplace(f);
+
+        output
+    }
+
+    /// Apply `f` to every element of the array in place,
+    /// returning the array to allow chaining.
+    ///
+    /// Elements are visited in row-major order.
+    pub fn apply_inplace<F: Fn(f32) -> f32>(&mut self, f: F) -> &mut Array {
+        match self.order {
+            MatrixOrder::RowMajor => {
+                for value in self.data.iter_mut() {
+                    *value = f(*value);
+                }
+            }
+            MatrixOrder::ColumnMajor => {
+                for row in 0..self.rows {
+                    for col in 0..self.cols {
+                        let value = f(self.get(row, col));
+                        self.set(row, col, value);
+                    }
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Return a new array with every element clamped into `[min, max]`.
+    ///
+    /// `NaN` elements are left unchanged.
+    ///
+    /// # Panics
+    /// This will panic if `min` is greater than `max`, or either is `NaN`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![-100.0, 0.5],
+    ///                               vec![2.0, 300.0]]);
+    ///
+    /// let clipped = array.clip(-10.0, 10.0);
+    ///
+    /// assert!(clipped == Array::from(&vec![vec![-10.0, 0.5],
+    ///                                      vec![2.0, 10.0]]));
+    /// ```
+    pub fn clip(&self, min: f32, max: f32) -> Array {
+        let mut output = self.clone();
+        output.clip_inplace(min, max);
+
+        output
+    }
+
+    /// Clamp every element of the array into `[min, max]` in place,
+    /// returning the array to allow chaining.
+    ///
+    /// `NaN` elements are left unchanged.
+    ///
+    /// # Panics
+    /// This will panic if `min` is greater than `max`, or either is `NaN`.
+    pub fn clip_inplace(&mut self, min: f32, max: f32) -> &mut Array {
+        assert!(
+            min <= max,
+            "Lower clipping bound must not exceed the upper bound"
+        );
+
+        self.apply_inplace(|x| {
+            if x < min {
+                min
+            } else if x > max {
+                max
+            } else {
+                x
+            }
+        })
+    }
+
+    /// Divide the array elementwise by `other` in place, returning the
+    /// array to allow chaining.
+    ///
+    /// Unlike `div_inplace`, elements whose denominator has an absolute
+    /// value of at most `f32::EPSILON` are set to `fill` rather than
+    /// becoming infinite or `NaN`. This is useful when scaling by statistics
+    /// such as standard deviations, which are zero for constant columns.
+    ///
+    /// # Panics
+    /// This will panic if the arrays have different shapes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let mut array = Array::from(&vec![vec![1.0, 2.0],
+    ///                                   vec![3.0, 4.0]]);
+    /// let denominator = Array::from(&vec![vec![2.0, 0.0],
+    ///                                     vec![2.0, 0.0]]);
+    ///
+    /// array.div_inplace_safe(&denominator, 0.0);
+    ///
+    /// assert!(array == Array::from(&vec![vec![0.5, 0.0],
+    ///                                    vec![1.5, 0.0]]));
+    /// ```
+    pub fn div_inplace_safe(&mut self, other: &Array, fill: f32) -> &mut Array {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "Arrays must have the same shape"
+        );
+
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let denominator = other.get(row, col);
+
+                let value = if denominator.abs() <= f32::EPSILON {
+                    fill
+                } else {
+                    self.get(row, col) / denominator
+                };
+
+                self.set(row, col, value);
+            }
+        }
+
+        self
+    }
+
+    /// Return the row indices that sort column `col` of the array,
+    /// in ascending order or, if `descending` is set, in descending order.
+    ///
+    /// The sort is stable: rows with equal values keep their original
+    /// relative order. `NaN` values are placed last in either order.
+    ///
+    /// # Panics
+    /// This will panic if `col` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(vec![3.0, 1.0, 2.0, 1.0]);
+    ///
+    /// assert!(array.argsort_column(0, false) == vec![1, 3, 2, 0]);
+    /// assert!(array.argsort_column(0, true) == vec![0, 2, 1, 3]);
+    /// ```
+    pub fn argsort_column(&self, col: usize, descending: bool) -> Vec<usize> {
+        assert!(col < self.cols, "Column index out of bounds");
+
+        let mut indices = (0..self.rows).collect::<Vec<_>>();
+        indices.sort_by(|&a, &b| {
+            let (x, y) = (self.get(a, col), self.get(b, col));
+
+            match (x.is_nan(), y.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    if descending {
+                        y.partial_cmp(&x).unwrap()
+                    } else {
+                        x.partial_cmp(&y).unwrap()
+                    }
+                }
+            }
+        });
+
+        indices
+    }
+
+    /// Return the ranks of the values in column `col` of the array,
+    /// as a column vector with one row per row of the array.
+    ///
+    /// Ranks start at 1 for the smallest value. Tied values are all
+    /// assigned the average of the ranks they span, and `NaN` values
+    /// are ranked as `NaN`.
+    ///
+    /// # Panics
+    /// This will panic if `col` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(vec![3.0, 1.0, 2.0, 1.0]);
+    ///
+    /// assert!(array.rankdata_column(0).data() == &vec![4.0, 1.5, 3.0, 1.5]);
+    /// ```
+    pub fn rankdata_column(&self, col: usize) -> Array {
+        let order = self.argsort_column(col, false);
+        let mut ranks = Array::zeros(self.rows, 1);
+
+        let mut start = 0;
+        while start < order.len() {
+            let value = self.get(order[start], col);
+
+            let mut end = start + 1;
+            while end < order.len() && self.get(order[end], col) == value {
+                end += 1;
+            }
+
+            let rank = if value.is_nan() {
+                f32::NAN
+            } else {
+                (start + end + 1) as f32 / 2.0
+            };
+
+            for &row in &order[start..end] {
+                ranks.set(row, 0, rank);
+            }
+
+            start = end;
+        }
+
+        ranks
+    }
+
+    /// Compute the Pearson correlation coefficient of columns `col_a` and `col_b`.
+    ///
+    /// Returns 0.0 if either column is constant.
+    ///
+    /// # Panics
+    /// This will panic if either column index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![1.0, 2.0],
+    ///                               vec![2.0, 4.0],
+    ///                               vec![3.0, 6.0]]);
+    ///
+    /// assert!((array.pearson_corr(0, 1) - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn pearson_corr(&self, col_a: usize, col_b: usize) -> f32 {
+        assert!(
+            col_a < self.cols && col_b < self.cols,
+            "Column index out of bounds"
+        );
+
+        let a = (0..self.rows)
+            .map(|row| self.get(row, col_a))
+            .collect::<Vec<_>>();
+        let b = (0..self.rows)
+            .map(|row| self.get(row, col_b))
+            .collect::<Vec<_>>();
+
+        pearson(&a, &b)
+    }
+
+    /// Compute the Spearman rank correlation coefficient of columns `col_a` and
+    /// `col_b`: the Pearson correlation of their ranks, as computed by
+    /// [`rankdata_column`](#method.rankdata_column).
+    ///
+    /// Returns 0.0 if either column is constant.
+    ///
+    /// # Panics
+    /// This will panic if either column index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let array = Array::from(&vec![vec![1.0, 1.0],
+    ///                               vec![2.0, 8.0],
+    ///                               vec![3.0, 27.0]]);
+    ///
+    /// assert!((array.spearman_corr(0, 1) - 1.0).abs() < 1e-6);
+    /// ```
+    pub fn spearman_corr(&self, col_a: usize, col_b: usize) -> f32 {
+        assert!(
+            col_a < self.cols && col_b < self.cols,
+            "Column index out of bounds"
+        );
+
+        pearson(
+            self.rankdata_column(col_a).data(),
+            self.rankdata_column(col_b).data(),
+        )
+    }
+
+    /// Return a copy of the array with its rows shuffled using `rng`,
+    /// together with the permutation applied: the `i`-th row of the
+    /// shuffled array is row `permutation[i]` of the original array.
+    ///
+    /// Use [`permute_rows`](fn.permute_rows.html) to apply the same
+    /// permutation to the targets, keeping them aligned with the rows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate rustlearn;
+    ///
+    /// use rustlearn::prelude::*;
+    ///
+    /// use rand::{SeedableRng, StdRng};
+    ///
+    /// # fn main() {
+    /// let X = Array::from(&vec![vec![0.0, 0.0],
+    ///                           vec![1.0, 1.0],
+    ///                           vec![2.0, 2.0]]);
+    /// let y = Array::from(vec![0.0, 1.0, 2.0]);
+    ///
+    /// let mut rng = StdRng::from_seed(&[100]);
+    /// let (shuffled_X, permutation) = X.shuffle_rows(&mut rng);
+    /// let shuffled_y = permute_rows(&y, &permutation);
+    ///
+    /// for row_idx in 0..X.rows() {
+    ///     assert!(shuffled_X.get(row_idx, 0) == shuffled_y.get(row_idx, 0));
+    /// }
+    /// # }
+    /// ```
+    pub fn shuffle_rows(&self, rng: &mut StdRng) -> (Array, Vec<usize>) {
+        let mut permutation = (0..self.rows).collect::<Vec<_>>();
+        rng.shuffle(&mut permutation);
+
+        (permute_rows(self, &permutation), permutation)
+    }
+
+    /// Return the distinct rows of the array, in the order in which they first
+    /// occur, along with the index of the distinct row equal to each original row.
+    ///
+    /// Rows are compared using exact floating point equality: `0.0` and `-0.0`
+    /// are equal, while rows containing a `NaN` are never equal to any other row.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let X = Array::from(&vec![vec![0.0, 1.0],
+    ///                           vec![2.0, 3.0],
+    ///                           vec![0.0, 1.0]]);
+    ///
+    /// let (unique, mapping) = X.unique_rows();
+    ///
+    /// assert!(unique.rows() == 2);
+    /// assert!(mapping == vec![0, 1, 0]);
+    /// ```
+    pub fn unique_rows(&self) -> (Array, Vec<usize>) {
+        let mut seen = HashMap::new();
+        let mut unique_indices = Vec::new();
+        let mut mapping = Vec::with_capacity(self.rows);
+
+        for (row_idx, row) in self.iter_rows().enumerate() {
+            let values = row.iter().collect::<Vec<_>>();
+
+            if values.iter().any(|x| x.is_nan()) {
+                mapping.push(unique_indices.len());
+                unique_indices.push(row_idx);
+                continue;
+            }
+
+            // Adding zero maps -0.0 to 0.0, so that equal values have equal bits
+            let key = values
+                .iter()
+                .map(|&x| (x + 0.0).to_bits())
+                .collect::<Vec<_>>();
+            let num_unique = unique_indices.len();
+
+            let unique_idx = *seen.entry(key).or_insert(num_unique);
+
+            if unique_idx == num_unique {
+                unique_indices.push(row_idx);
+            }
+
+            mapping.push(unique_idx);
+        }
+
+        (self.get_rows(&unique_indices), mapping)
+    }
+}
+
+/// Return a copy of `array` whose `i`-th row is row `permutation[i]`
+/// of `array`, as returned by [`Array::shuffle_rows`](struct.Array.html#method.shuffle_rows).
+///
+/// # Panics
+/// This will panic if the length of `permutation` differs from the
+/// number of rows of `array`, or if it contains an out-of-bounds index.
+pub fn permute_rows(array: &Array, permutation: &[usize]) -> Array {
+    assert!(
+        permutation.len() == array.rows(),
+        "Permutation length must equal the number of rows"
+    );
+    assert!(
+        permutation.iter().all(|&row_idx| row_idx < array.rows()),
+        "Permutation index out of bounds"
+    );
+
+    array.get_rows(&permutation.to_vec())
+}
+
+/// The Pearson correlation coefficient of `a` and `b`,
+/// or 0.0 if either is constant.
+fn pearson(a: &[f32], b: &[f32]) -> f32 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().map(|&x| x as f64).sum::<f64>() / n;
+    let mean_b = b.iter().map(|&x| x as f64).sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        let (dx, dy) = (x as f64 - mean_a, y as f64 - mean_b);
+
+        covariance += dx * dy;
+        variance_a += dx * dx;
+        variance_b += dy * dy;
+    }
+
+    if variance_a == 0.0 || variance_b == 0.0 {
+        0.0
+    } else {
+        (covariance / (variance_a * variance_b).sqrt()) as f32
+    }
+}
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+const NPY_PREAMBLE_LEN: usize = 10;
+
+const BYTES_MAGIC: &[u8] = b"RLARRAY\0";
+const BYTES_VERSION: u32 = 1;
+/// The magic bytes, version, and shape take up 28 bytes.
+const BYTES_HEADER_LEN: usize = 28;
+
+fn invalid_npy(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Extract the value following `key` in a `.npy` header dictionary.
+fn npy_header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, &'static str> {
+    let start = match header.find(&format!("'{}':", key)) {
+        Some(idx) => idx + key.len() + 3,
+        None => return Err("Missing key in .npy header."),
+    };
+
+    Ok(header[start..].trim_start())
+}
+
+/// Parse a `.npy` header, returning whether the data is in
+/// Fortran order and the (rows, cols) shape.
+fn parse_npy_header(header: &str) -> Result<(bool, usize, usize), &'static str> {
+    let descr = try!(npy_header_value(header, "descr"));
+    if !(descr.starts_with("'<f4'") || descr.starts_with("'|f4'")) {
+        return Err("Only little-endian float32 .npy files are supported.");
+    }
+
+    let fortran_order = try!(npy_header_value(header, "fortran_order"));
+    let fortran_order = if fortran_order.starts_with("True") {
+        true
+    } else if fortran_order.starts_with("False") {
+        false
+    } else {
+        return Err("Invalid fortran_order in .npy header.");
+    };
+
+    let shape = try!(npy_header_value(header, "shape"));
+    let shape = match (shape.find('('), shape.find(')')) {
+        (Some(0), Some(end)) => &shape[1..end],
+        _ => return Err("Invalid shape in .npy header."),
+    };
+
+    let mut dims = Vec::new();
+    for dim in shape.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        dims.push(try!(dim
+            .parse::<usize>()
+            .map_err(|_| "Invalid shape in .npy header.")));
+    }
+
+    match dims.len() {
+        1 => Ok((fortran_order, dims[0], 1)),
+        2 => Ok((fortran_order, dims[0], dims[1])),
+        _ => Err("Only one- and two-dimensional .npy files are supported."),
+    }
+}
+
+impl PartialEq for Array {
+    /// Arrays are equal if they have the same shape and
+    /// identical elements, regardless of storage order.
+    fn eq(&self, other: &Array) -> bool {
+        self.all_pairs(other, |a, b| a == b)
+    }
+}
+
+impl From<Vec<f32>> for Array {
+    /// Construct an array from a vector.
+    ///
+    /// # Panics
+    /// This will panic if the input vector is emtpy.
+    fn from(data: Vec<f32>) -> Array {
+        assert!(data.len() > 0);
+
+        Array {
+            rows: data.len(),
+            cols: 1,
+            order: MatrixOrder::RowMajor,
+            data: data,
+        }
+    }
+}
+
+impl<'a> From<&'a Vec<Vec<f32>>> for Array {
+    /// Construct an array from a vector of vectors.
+    ///
+    /// # Panics
+    /// This will panic if the input vector is emtpy
+    /// or if its rows are of unequal length.
+    fn from(input: &Vec<Vec<f32>>) -> Array {
+        assert!(input.len() > 0);
+
+        let rows = input.len();
+        let cols = input[0].len();
+
+        let mut data: Vec<f32> = Vec::with_capacity(rows * cols);
+
+        for row in input {
+            assert!(row.len() == cols);
+            for &e in row {
+                data.push(e);
+            }
+        }
+
+        Array {
+            rows: rows,
+            cols: cols,
+            order: MatrixOrder::RowMajor,
+            data: data,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a [Vec<f32>]> for Array {
+    type Error = RustlearnError;
+
+    /// Construct an array from a slice of rows, taking the
+    /// number of columns from the first row.
+    ///
+    /// Fails if the slice is empty or if its rows are of unequal length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::convert::TryFrom;
+    ///
+    /// use rustlearn::prelude::*;
+    ///
+    /// let rows = vec![vec![1.0, 2.0], vec![3.0, 4.0]];
+    /// let array = Array::try_from(&rows[..]).unwrap();
+    ///
+    /// assert!(array.rows() == 2 && array.cols() == 2);
+    /// assert!(Array::try_from(&[vec![1.0, 2.0], vec![3.0]][..]).is_err());
+    /// ```
+    fn try_from(input: &[Vec<f32>]) -> Result<Array, RustlearnError> {
+        if input.is_empty() {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot construct an array from zero rows.",
+            ));
+        }
+
+        let cols = input[0].len();
+
+        if input.iter().any(|row| row.len() != cols) {
+            return Err(RustlearnError::DimensionMismatch(
+                "All rows must have the same number of columns.",
+            ));
+        }
+
+        let mut data = Vec::with_capacity(input.len() * cols);
+
+        for row in input {
+            data.extend_from_slice(row);
+        }
+
+        Array::from_vec(data, input.len(), cols)
+    }
+}
+
+impl ElementwiseArrayOps<f32> for Array {
+    type Output = Array;
+
+    fn add(&self, rhs: f32) -> Array {
+        Array {
+            rows: self.rows,
+            cols: self.cols,
+            order: MatrixOrder::RowMajor,
+            data: self.data.iter().map(|&x| x + rhs).collect::<Vec<f32>>(),
+        }
+    }
+
+    fn add_inplace(&mut self, rhs: f32) -> &mut Array {
+        for v in &mut self.data {
+            *v += rhs;
+        }
+
+        self
+    }
+
+    fn sub(&self, rhs: f32) -> Array {
+        self.add(-rhs)
+    }
+
+    fn sub_inplace(&mut self, rhs: f32) -> &mut Array {
+        self.add_inplace(-rhs)
+    }
+
+    fn times(&self, rhs: f32) -> Array {
+        Array {
+            rows: self.rows,
+            cols: self.cols,
+            order: MatrixOrder::RowMajor,
+            data: self.data.iter().map(|&x| x * rhs).collect::<Vec<f32>>(),
+        }
+    }
+
+    fn times_inplace(&mut self, rhs: f32) -> &mut Array {
+        for v in &mut self.data {
+            *v *= rhs;
+        }
+
+        self
+    }
+
+    fn div(&self, rhs: f32) -> Array {
+        Array {
+            rows: self.rows,
+            cols: self.cols,
+            order: MatrixOrder::RowMajor,
+            data: self.data.iter().map(|&x| x / rhs).collect::<Vec<f32>>(),
+        }
+    }
+
+    fn div_inplace(&mut self, rhs: f32) -> &mut Array {
+        for v in &mut self.data {
+            *v /= rhs;
+        }
+
+        self
+    }
+}
+
+/// Return the row and column index multipliers used to read
+/// the right-hand operand of an elementwise operation.
+///
+/// A right-hand operand of the same shape is read elementwise,
+/// a `(1, cols)` row vector is applied to every row, and a
+/// `(rows, 1)` column vector is applied to every column.
+///
+/// # Panics
+/// Will panic if the right-hand operand cannot be broadcast
+/// to the shape of the left-hand operand.
+fn broadcast_steps(lhs: &Array, rhs: &Array) -> (usize, usize) {
+    if lhs.rows == rhs.rows && lhs.cols == rhs.cols {
+        (1, 1)
+    } else if rhs.rows == 1 && lhs.cols == rhs.cols {
+        (0, 1)
+    } else if lhs.rows == rhs.rows && rhs.cols == 1 {
+        (1, 0)
+    } else {
+        panic!(
+            "Operands of shape ({}, {}) and ({}, {}) cannot be broadcast together",
+            lhs.rows, lhs.cols, rhs.rows, rhs.cols
+        )
+    }
+}
+
+/// Perform elementwise operations between two arrays.
+///
+/// The right-hand operand must either have the same shape as
+/// the left-hand operand, or be a `(1, cols)` row vector or a
+/// `(rows, 1)` column vector, in which case it is broadcast
+/// across every row or column respectively.
+///
+/// # Examples
+///
+/// ```
+/// use rustlearn::prelude::*;
+///
+/// let mut X = Array::from(&vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+/// let column_means = Array::from(&vec![vec![2.0, 3.0]]);
+///
+/// X.sub_inplace(&column_means);
+///
+/// assert!(X.data() == &vec![-1.0, -1.0, 1.0, 1.0]);
+/// ```
+///
+/// # Panics
+/// Will panic if the right-hand operand cannot be broadcast
+/// to the shape of the left-hand operand.
+impl<'a> ElementwiseArrayOps<&'a Array> for Array {
+    type Output = Array;
+
+    fn add(&self, rhs: &'a Array) -> Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
+
+        let mut data = Vec::with_capacity(self.rows * self.cols);
+
+        unsafe {
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    data.push(
+                        self.get_unchecked(i, j) + rhs.get_unchecked(i * row_step, j * col_step),
+                    );
+                }
+            }
+        }
+
+        Array {
+            rows: self.rows,
+            cols: self.cols,
+            order: MatrixOrder::RowMajor,
+            data: data,
+        }
+    }
+
+    fn add_inplace(&mut self, rhs: &'a Array) -> &mut Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
+
+        unsafe {
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    let v = self.get_unchecked_mut(i, j);
+                    *v += rhs.get_unchecked(i * row_step, j * col_step);
+                }
+            }
+        }
+
+        self
+    }
+
+    fn sub(&self, rhs: &'a Array) -> Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
+
+        let mut data = Vec::with_capacity(self.rows * self.cols);
+
+        unsafe {
+            for i in 0..self.rows {
+                for j in 0..self.cols {
+                    data.push(
+                        self.get_unchecked(i, j) - rhs.get_unchecked(i * row_step, j * col_step),
+                    );
+                }
+            }
         }
 
         Array {
@@ -650,28 +1933,32 @@ impl<'a> ElementwiseArrayOps<&'a Array> for Array {
         }
     }
 
-    fn sub_inplace(&mut self, rhs: &'a Array) {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+    fn sub_inplace(&mut self, rhs: &'a Array) -> &mut Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
                     let v = self.get_unchecked_mut(i, j);
-                    *v -= rhs.get_unchecked(i, j);
+                    *v -= rhs.get_unchecked(i * row_step, j * col_step);
                 }
             }
         }
+
+        self
     }
 
     fn times(&self, rhs: &'a Array) -> Array {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+        let (row_step, col_step) = broadcast_steps(self, rhs);
 
         let mut data = Vec::with_capacity(self.rows * self.cols);
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
-                    data.push(self.get_unchecked(i, j) * rhs.get_unchecked(i, j));
+                    data.push(
+                        self.get_unchecked(i, j) * rhs.get_unchecked(i * row_step, j * col_step),
+                    );
                 }
             }
         }
@@ -684,28 +1971,32 @@ impl<'a> ElementwiseArrayOps<&'a Array> for Array {
         }
     }
 
-    fn times_inplace(&mut self, rhs: &'a Array) {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+    fn times_inplace(&mut self, rhs: &'a Array) -> &mut Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
                     let v = self.get_unchecked_mut(i, j);
-                    *v *= rhs.get_unchecked(i, j);
+                    *v *= rhs.get_unchecked(i * row_step, j * col_step);
                 }
             }
         }
+
+        self
     }
 
     fn div(&self, rhs: &'a Array) -> Array {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+        let (row_step, col_step) = broadcast_steps(self, rhs);
 
         let mut data = Vec::with_capacity(self.rows * self.cols);
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
-                    data.push(self.get_unchecked(i, j) / rhs.get_unchecked(i, j));
+                    data.push(
+                        self.get_unchecked(i, j) / rhs.get_unchecked(i * row_step, j * col_step),
+                    );
                 }
             }
         }
@@ -718,27 +2009,59 @@ impl<'a> ElementwiseArrayOps<&'a Array> for Array {
         }
     }
 
-    fn div_inplace(&mut self, rhs: &'a Array) {
-        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+    fn div_inplace(&mut self, rhs: &'a Array) -> &mut Array {
+        let (row_step, col_step) = broadcast_steps(self, rhs);
 
         unsafe {
             for i in 0..self.rows {
                 for j in 0..self.cols {
                     let v = self.get_unchecked_mut(i, j);
-                    *v /= rhs.get_unchecked(i, j);
+                    *v /= rhs.get_unchecked(i * row_step, j * col_step);
                 }
             }
         }
+
+        self
+    }
+}
+
+/// Iterator over the rows of a row-major array as slices,
+/// returned by [`Array::row_iter`](struct.Array.html#method.row_iter).
+pub struct RowSlices<'a> {
+    data: &'a [f32],
+    cols: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for RowSlices<'a> {
+    type Item = &'a [f32];
+
+    fn next(&mut self) -> Option<&'a [f32]> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let (row, rest) = self.data.split_at(self.cols);
+        self.data = rest;
+        self.remaining -= 1;
+
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<'a> ExactSizeIterator for RowSlices<'a> {}
+
 impl<'a> Dot<&'a Array> for Array {
     type Output = Array;
 
     fn dot(&self, rhs: &'a Array) -> Array {
         assert!(self.cols == rhs.rows);
 
-        let mut output = Array::empty(self.rows, rhs.cols);
+        let mut output = Array::zeros(self.rows, rhs.cols);
 
         unsafe {
             for i in 0..output.rows {
@@ -780,27 +2103,25 @@ impl RowIndex<Vec<usize>> for Array {
     }
 }
 
-/// Determines whether two arrays are sufficiently close to each other.
-pub fn allclose(x: &Array, y: &Array) -> bool {
-    let atol = 1e-08;
-    let rtol = 1e-05;
+/// Scale `vector` to unit Euclidean norm, returning
+/// `false` and leaving it unchanged if it is zero.
+fn normalize(vector: &mut [f32]) -> bool {
+    let norm = vector.iter().map(|x| x.powi(2)).sum::<f32>().sqrt();
 
-    if x.rows == y.rows && x.cols == y.cols {
-        unsafe {
-            for i in 0..x.rows {
-                for j in 0..x.cols {
-                    let a = x.get_unchecked(i, j);
-                    let b = y.get_unchecked(i, j);
-                    if !((a - b).abs() < (atol + rtol * b.abs())) {
-                        return false;
-                    }
-                }
-            }
-            true
-        }
-    } else {
-        false
+    if norm == 0.0 {
+        return false;
     }
+
+    for value in vector.iter_mut() {
+        *value /= norm;
+    }
+
+    true
+}
+
+/// Determines whether two arrays are sufficiently close to each other.
+pub fn allclose(x: &Array, y: &Array) -> bool {
+    x.all_pairs(y, close)
 }
 
 /// Determines whether two floats are sufficiently close to each other.
@@ -814,6 +2135,9 @@ pub fn close(x: f32, y: f32) -> bool {
 #[cfg(test)]
 mod tests {
 
+    use std::env;
+    use std::fs::remove_file;
+
     use bincode;
 
     use super::*;
@@ -877,6 +2201,593 @@ mod tests {
         assert!(allclose(&expected, &arr));
     }
 
+    #[test]
+    fn elementwise_map() {
+        let arr = Array::from(&vec![vec![1.0, -2.0, 3.0], vec![4.0, 5.0, -6.0]]);
+
+        let expected = Array::from(&vec![vec![1.0, 4.0, 9.0], vec![16.0, 25.0, 36.0]]);
+
+        let squared = arr.map(|x| x * x);
+
+        assert!(squared.rows() == 2 && squared.cols() == 3);
+        for row in 0..2 {
+            for col in 0..3 {
+                assert!(squared.get(row, col) == arr.get(row, col) * arr.get(row, col));
+            }
+        }
+        assert!(allclose(&squared, &expected));
+
+        let mut transposed = arr.clone().T();
+        transposed.apply_inplace(|x| x * x);
+        assert!(allclose(&transposed, &expected.T()));
+
+        let mut arr = arr;
+        arr.apply_inplace(|x| x * x);
+        assert!(allclose(&arr, &squared));
+    }
+
+    #[test]
+    fn clipping() {
+        let arr = Array::from(&vec![vec![-5.0, 0.0, 0.5], vec![1.0, 7.0, f32::NAN]]);
+
+        let clipped = arr.clip(0.0, 1.0);
+
+        assert!(clipped.get(0, 0) == 0.0);
+        assert!(clipped.get(0, 1) == 0.0);
+        assert!(clipped.get(0, 2) == 0.5);
+        assert!(clipped.get(1, 0) == 1.0);
+        assert!(clipped.get(1, 1) == 1.0);
+        assert!(clipped.get(1, 2).is_nan());
+
+        let mut transposed = arr.clone().T();
+        transposed.clip_inplace(-1.0, -1.0);
+        assert!(transposed.get(0, 0) == -1.0 && transposed.get(1, 1) == -1.0);
+        assert!(transposed.get(2, 1).is_nan());
+    }
+
+    #[test]
+    #[should_panic]
+    fn clipping_invalid_bounds() {
+        Array::zeros(2, 2).clip(1.0, 0.0);
+    }
+
+    #[test]
+    fn safe_division() {
+        let mut arr = Array::from(&vec![vec![1.0, -2.0, 3.0], vec![0.0, 5.0, 6.0]]);
+        let denominator = Array::from(&vec![vec![2.0, 0.0, 1e-9], vec![0.0, -0.0, 3.0]]);
+
+        let mut unsafe_arr = arr.clone();
+        unsafe_arr.div_inplace(&denominator);
+        assert!(unsafe_arr.data().iter().any(|x| !x.is_finite()));
+
+        arr.div_inplace_safe(&denominator, 1.0);
+
+        assert!(arr.data().iter().all(|x| x.is_finite()));
+        assert!(arr == Array::from(&vec![vec![0.5, 1.0, 1.0], vec![1.0, 1.0, 2.0]]));
+
+        let mut transposed = Array::from(&vec![vec![4.0, 1.0]]).T();
+        transposed.div_inplace_safe(&Array::from(&vec![vec![2.0], vec![0.0]]), -1.0);
+        assert!(transposed.get(0, 0) == 2.0 && transposed.get(1, 0) == -1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn safe_division_shape_mismatch() {
+        Array::zeros(2, 2).div_inplace_safe(&Array::ones(2, 3), 0.0);
+    }
+
+    #[test]
+    fn argsort_and_rankdata() {
+        let arr = Array::from(&vec![
+            vec![0.0, 2.0],
+            vec![0.0, -1.0],
+            vec![0.0, 2.0],
+            vec![0.0, 5.0],
+            vec![0.0, -1.0],
+            vec![0.0, 2.0],
+        ]);
+
+        assert!(arr.argsort_column(1, false) == vec![1, 4, 0, 2, 5, 3]);
+        assert!(arr.argsort_column(1, true) == vec![3, 0, 2, 5, 1, 4]);
+        assert!(arr.argsort_column(0, true) == vec![0, 1, 2, 3, 4, 5]);
+
+        let ranks = arr.rankdata_column(1);
+        assert!(ranks.rows() == 6 && ranks.cols() == 1);
+        assert!(ranks.data() == &vec![4.0, 1.5, 4.0, 6.0, 1.5, 4.0]);
+        assert!(arr.rankdata_column(0).data() == &vec![3.5; 6]);
+
+        let with_nan = Array::from(vec![1.0, f32::NAN, 0.0]);
+        assert!(with_nan.argsort_column(0, false) == vec![2, 0, 1]);
+        assert!(with_nan.argsort_column(0, true) == vec![0, 2, 1]);
+
+        let ranks = with_nan.rankdata_column(0);
+        assert!(ranks.get(0, 0) == 2.0 && ranks.get(1, 0).is_nan() && ranks.get(2, 0) == 1.0);
+    }
+
+    #[test]
+    fn correlation() {
+        let rows = (0..20)
+            .map(|i| {
+                let x = i as f32 / 4.0;
+                vec![x, 3.0 * x - 2.0, x.exp(), -x, 1.0]
+            })
+            .collect::<Vec<_>>();
+        let arr = Array::from(&rows);
+
+        // Perfectly linear pairs
+        assert!((arr.pearson_corr(0, 1) - 1.0).abs() < 1e-6);
+        assert!((arr.pearson_corr(0, 3) + 1.0).abs() < 1e-6);
+        assert!((arr.pearson_corr(0, 0) - 1.0).abs() < 1e-6);
+
+        // A monotone, non-linear pair
+        assert!((arr.spearman_corr(0, 2) - 1.0).abs() < 1e-6);
+        assert!(arr.pearson_corr(0, 2) < 0.9);
+        assert!((arr.spearman_corr(2, 3) + 1.0).abs() < 1e-6);
+
+        // Constant columns
+        assert!(arr.pearson_corr(0, 4) == 0.0);
+        assert!(arr.spearman_corr(4, 1) == 0.0);
+
+        // Ties are ranked by their average rank
+        let tied = Array::from(&vec![
+            vec![1.0, 1.0],
+            vec![2.0, 2.0],
+            vec![2.0, 2.0],
+            vec![3.0, 10.0],
+        ]);
+        assert!((tied.spearman_corr(0, 1) - 1.0).abs() < 1e-6);
+        assert!(tied.pearson_corr(0, 1) < 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn correlation_out_of_bounds() {
+        Array::zeros(3, 2).spearman_corr(0, 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn argsort_column_out_of_bounds() {
+        Array::zeros(3, 2).argsort_column(2, false);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rankdata_column_out_of_bounds() {
+        Array::zeros(3, 2).rankdata_column(2);
+    }
+
+    #[test]
+    fn broadcasting() {
+        let arr = Array::from(&vec![vec![1.0, -2.0, 3.0], vec![4.0, 5.0, -6.0]]);
+        let row = Array::from(&vec![vec![2.0, 4.0, -1.0]]);
+        let column = Array::from(vec![0.5, -3.0]);
+
+        let tiled_row = Array::from(&vec![vec![2.0, 4.0, -1.0], vec![2.0, 4.0, -1.0]]);
+        let tiled_column = Array::from(&vec![vec![0.5, 0.5, 0.5], vec![-3.0, -3.0, -3.0]]);
+
+        for &(rhs, tiled) in &[(&row, &tiled_row), (&column, &tiled_column)] {
+            assert!(allclose(&arr.add(rhs), &arr.add(tiled)));
+            assert!(allclose(&arr.sub(rhs), &arr.sub(tiled)));
+            assert!(allclose(&arr.times(rhs), &arr.times(tiled)));
+            assert!(allclose(&arr.div(rhs), &arr.div(tiled)));
+
+            let mut broadcast = arr.clone();
+            let mut expected = arr.clone();
+            broadcast.add_inplace(rhs).times_inplace(rhs);
+            expected.add_inplace(tiled).times_inplace(tiled);
+            broadcast.sub_inplace(rhs).div_inplace(rhs);
+            expected.sub_inplace(tiled).div_inplace(tiled);
+            assert!(allclose(&broadcast, &expected));
+        }
+
+        let transposed = arr.T();
+        assert!(allclose(
+            &transposed.add(&column.T()),
+            &transposed.add(&tiled_column.T())
+        ));
+        assert!(allclose(
+            &transposed.sub(&row.T()),
+            &transposed.sub(&tiled_row.T())
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn broadcasting_shape_mismatch() {
+        Array::zeros(2, 3).add_inplace(&Array::ones(1, 2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn broadcasting_row_count_mismatch() {
+        Array::zeros(2, 3).sub(&Array::ones(3, 1));
+    }
+
+    #[test]
+    fn axis_reductions() {
+        let arr = Array::from(&vec![vec![1.0, -2.0, 3.0], vec![4.0, 5.0, -6.0]]);
+
+        let sum = arr.sum_axis(0).unwrap();
+        assert!(sum.rows() == 1 && sum.cols() == 3);
+        assert!(allclose(&sum, &Array::from(&vec![vec![5.0, 3.0, -3.0]])));
+
+        let sum = arr.sum_axis(1).unwrap();
+        assert!(sum.rows() == 2 && sum.cols() == 1);
+        assert!(allclose(&sum, &Array::from(vec![2.0, 3.0])));
+
+        assert!(allclose(
+            &arr.max_axis(0).unwrap(),
+            &Array::from(&vec![vec![4.0, 5.0, 3.0]])
+        ));
+        assert!(allclose(
+            &arr.max_axis(1).unwrap(),
+            &Array::from(vec![3.0, 5.0])
+        ));
+
+        assert!(allclose(
+            &arr.min_axis(0).unwrap(),
+            &Array::from(&vec![vec![1.0, -2.0, -6.0]])
+        ));
+        assert!(allclose(
+            &arr.min_axis(1).unwrap(),
+            &Array::from(vec![-2.0, -6.0])
+        ));
+
+        assert!(allclose(
+            &arr.clone().T().sum_axis(1).unwrap(),
+            &Array::from(vec![5.0, 3.0, -3.0])
+        ));
+
+        assert!(arr.sum_axis(2).is_err());
+        assert!(arr.max_axis(2).is_err());
+        assert!(arr.min_axis(2).is_err());
+
+        let empty = Array::zeros(0, 3);
+        assert!(empty.sum_axis(0).is_err());
+        assert!(empty.max_axis(0).is_err());
+
+        let sum = empty.sum_axis(1).unwrap();
+        assert!(sum.rows() == 0 && sum.cols() == 1);
+
+        let no_cols = Array::zeros(2, 0);
+        assert!(no_cols.min_axis(1).is_err());
+
+        let min = no_cols.min_axis(0).unwrap();
+        assert!(min.rows() == 1 && min.cols() == 0);
+    }
+
+    #[test]
+    fn npy_round_trip() {
+        let arr = Array::from(&vec![vec![1.0, -2.5, 3.0], vec![4.0, 5.0, 1e-8]]);
+
+        let mut path = env::temp_dir();
+        path.push("rustlearn_npy_round_trip.npy");
+        let path = path.to_str().unwrap();
+
+        arr.save_npy(path).unwrap();
+        let loaded = Array::load_npy(path).unwrap();
+
+        let transposed = arr.clone().T();
+        transposed.save_npy(path).unwrap();
+        let loaded_transposed = Array::load_npy(path).unwrap();
+
+        remove_file(path).unwrap();
+
+        assert!(loaded.rows() == 2 && loaded.cols() == 3);
+        for row in 0..2 {
+            for col in 0..3 {
+                assert!(loaded.get(row, col) == arr.get(row, col));
+                assert!(loaded_transposed.get(col, row) == arr.get(row, col));
+            }
+        }
+    }
+
+    #[test]
+    fn npy_length_mismatch() {
+        let mut path = env::temp_dir();
+        path.push("rustlearn_npy_length_mismatch.npy");
+        let path = path.to_str().unwrap();
+
+        Array::zeros(2, 3).save_npy(path).unwrap();
+        let mut bytes = Vec::new();
+        File::open(path).unwrap().read_to_end(&mut bytes).unwrap();
+
+        // Truncated data
+        File::create(path)
+            .unwrap()
+            .write_all(&bytes[..bytes.len() - 4])
+            .unwrap();
+        assert!(Array::load_npy(path).is_err());
+
+        // Trailing data
+        bytes.extend_from_slice(&[0; 4]);
+        File::create(path).unwrap().write_all(&bytes).unwrap();
+        assert!(Array::load_npy(path).is_err());
+
+        // A shape far larger than the data
+        let header =
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (4000000000, 4000000000), }\n";
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        bytes.extend_from_slice(&[0; 16]);
+        File::create(path).unwrap().write_all(&bytes).unwrap();
+        assert!(Array::load_npy(path).is_err());
+
+        remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let arr = Array::from(&vec![vec![1.0, -2.5, 3.0], vec![4.0, f32::INFINITY, 1e-8]]);
+
+        let loaded = Array::from_bytes(&arr.to_bytes()).unwrap();
+        assert!(loaded.rows() == 2 && loaded.cols() == 3);
+        assert!(loaded.data() == arr.data());
+
+        let transposed = arr.clone().T();
+        let loaded_transposed = Array::from_bytes(&transposed.to_bytes()).unwrap();
+        assert!(loaded_transposed == transposed);
+        assert!(loaded_transposed.to_bytes() == transposed.to_bytes());
+
+        let empty = Array::zeros(0, 3);
+        let loaded_empty = Array::from_bytes(&empty.to_bytes()).unwrap();
+        assert!(loaded_empty.rows() == 0 && loaded_empty.cols() == 3);
+    }
+
+    #[test]
+    fn bytes_invalid() {
+        let bytes = Array::from(&vec![vec![1.0, 2.0], vec![3.0, 4.0]]).to_bytes();
+
+        // Truncated data and header
+        assert!(Array::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Array::from_bytes(&bytes[..20]).is_err());
+        assert!(Array::from_bytes(&[]).is_err());
+
+        // Trailing data
+        let mut extended = bytes.clone();
+        extended.extend_from_slice(&[0; 4]);
+        assert!(Array::from_bytes(&extended).is_err());
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Array::from_bytes(&bad_magic).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[8] = 2;
+        assert!(Array::from_bytes(&bad_version).is_err());
+
+        // A shape whose size overflows
+        let mut bad_shape = bytes.clone();
+        for byte in &mut bad_shape[12..28] {
+            *byte = 0xff;
+        }
+        assert!(Array::from_bytes(&bad_shape).is_err());
+    }
+
+    #[test]
+    fn npy_header() {
+        let header = "{'descr': '<f4', 'fortran_order': True, 'shape': (3,), }";
+        assert!(parse_npy_header(header) == Ok((true, 3, 1)));
+
+        let header = "{'descr': '<f8', 'fortran_order': False, 'shape': (2, 3), }";
+        assert!(parse_npy_header(header).is_err());
+
+        let header = "{'descr': '<f4', 'fortran_order': False, 'shape': (2, 3, 4), }";
+        assert!(parse_npy_header(header).is_err());
+    }
+
+    #[test]
+    fn from_vec() {
+        let data = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let data_ptr = data.as_ptr();
+
+        let arr = Array::from_vec(data, 2, 3).unwrap();
+
+        assert!(arr.rows() == 2 && arr.cols() == 3);
+        assert!(arr.data().as_ptr() == data_ptr);
+        assert!(allclose(
+            &arr,
+            &Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]])
+        ));
+    }
+
+    #[test]
+    fn from_vec_length_mismatch() {
+        assert!(Array::from_vec(vec![1.0, 2.0, 3.0], 2, 2).is_err());
+        assert!(Array::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0], 2, 2).is_err());
+        assert!(Array::from_vec(Vec::new(), 1, 1).is_err());
+    }
+
+    #[test]
+    fn equality() {
+        let arr = Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+
+        assert!(arr == arr.clone());
+        assert!(arr == arr.clone().T().T());
+        assert!(
+            arr.clone().T() == Array::from(&vec![vec![1.0, 4.0], vec![2.0, 5.0], vec![3.0, 6.0]])
+        );
+
+        let mut other = arr.clone();
+        other.set(1, 2, 6.0001);
+        assert!(arr != other);
+
+        let mut reshaped = arr.clone();
+        reshaped.reshape(3, 2);
+        assert!(arr != reshaped);
+    }
+
+    #[test]
+    fn approximate_equality() {
+        let arr = Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+
+        assert!(arr.approx_eq(&arr, 0.0));
+        assert!(arr.approx_eq(&arr.add(0.05), 0.1));
+        assert!(!arr.approx_eq(&arr.add(0.2), 0.1));
+
+        let mut reshaped = arr.clone();
+        reshaped.reshape(3, 2);
+        assert!(!arr.approx_eq(&reshaped, 100.0));
+        assert!(!arr.approx_eq(&arr.clone().T(), 100.0));
+    }
+
+    #[test]
+    fn row_slice_iteration() {
+        let arr = Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+
+        let row_sums = arr
+            .row_iter()
+            .unwrap()
+            .map(|row| row.iter().sum::<f32>())
+            .collect::<Vec<_>>();
+
+        assert!(arr.row_iter().unwrap().len() == 2);
+        assert!(arr.row_iter().unwrap().all(|row| row.len() == 3));
+        assert!(arr.row_iter().unwrap().nth(1).unwrap() == &[4.0, 5.0, 6.0]);
+        assert!(Array::from(row_sums) == arr.sum_axis(1).unwrap());
+
+        let no_cols = Array::zeros(3, 0);
+        assert!(no_cols.row_iter().unwrap().count() == 3);
+        assert!(no_cols.row_iter().unwrap().all(|row| row.is_empty()));
+
+        assert!(Array::zeros(0, 3).row_iter().unwrap().next().is_none());
+    }
+
+    #[test]
+    fn row_slice_iteration_column_major() {
+        let arr = Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]).T();
+
+        assert!(arr.row_iter().is_err());
+    }
+
+    #[test]
+    fn chained_inplace() {
+        let arr = Array::from(&vec![vec![1.0, 10.0], vec![3.0, 30.0], vec![5.0, 50.0]]);
+
+        let mean = Array::from(&vec![vec![3.0, 30.0]; 3]);
+        let std = Array::from(&vec![vec![2.0, 20.0]; 3]);
+
+        let expected = Array::from(&vec![vec![-1.0, -1.0], vec![0.0, 0.0], vec![1.0, 1.0]]);
+
+        let mut standardized = arr.clone();
+        standardized.sub_inplace(&mean).div_inplace(&std);
+        assert!(allclose(&standardized, &expected));
+
+        let mut column = Array::from(vec![1.0, 3.0, 5.0]);
+        column
+            .sub_inplace(3.0)
+            .div_inplace(2.0)
+            .apply_inplace(|x| x * x);
+        assert!(allclose(&column, &Array::from(vec![1.0, 0.0, 1.0])));
+    }
+
+    #[test]
+    fn identity() {
+        let eye = Array::eye(3);
+
+        assert!(allclose(
+            &eye,
+            &Array::from(&vec![
+                vec![1.0, 0.0, 0.0],
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0],
+            ])
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_identity() {
+        Array::eye(0);
+    }
+
+    #[test]
+    fn diagonal() {
+        let arr = Array::from(&vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+
+        let diagonal = arr.diagonal();
+        assert!(diagonal.rows() == 2 && diagonal.cols() == 1);
+        assert!(allclose(&diagonal, &Array::from(vec![1.0, 5.0])));
+
+        assert!(allclose(&arr.T().diagonal(), &Array::from(vec![1.0, 5.0])));
+        assert!(allclose(&Array::eye(4).diagonal(), &Array::ones(4, 1)));
+    }
+
+    #[test]
+    fn try_from_rows() {
+        let rows = vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]];
+
+        let arr = Array::try_from(&rows[..]).unwrap();
+        assert!(arr == Array::from(&rows));
+
+        match Array::try_from(&[vec![1.0, 2.0], vec![3.0]][..]) {
+            Err(RustlearnError::DimensionMismatch(_)) => {}
+            _ => panic!("Expected a dimension mismatch"),
+        }
+
+        let no_rows: Vec<Vec<f32>> = Vec::new();
+        match Array::try_from(&no_rows[..]) {
+            Err(RustlearnError::EmptyInput(_)) => {}
+            _ => panic!("Expected an empty input error"),
+        }
+    }
+
+    #[test]
+    fn submatrix() {
+        let arr = Array::from(&vec![
+            vec![1.0, 2.0, 3.0],
+            vec![4.0, 5.0, 6.0],
+            vec![7.0, 8.0, 9.0],
+        ]);
+
+        let expected = Array::from(&vec![vec![5.0, 6.0], vec![8.0, 9.0]]);
+
+        assert!(arr.submatrix(1..3, 1..3) == expected);
+        assert!(arr.submatrix(0..3, 0..3) == arr);
+        assert!(arr.T().submatrix(1..3, 1..3) == expected.T());
+    }
+
+    #[test]
+    #[should_panic]
+    fn submatrix_out_of_bounds() {
+        Array::zeros(3, 3).submatrix(1..4, 0..2);
+    }
+
+    #[test]
+    fn power_iteration() {
+        let arr = Array::from(&vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 5.0, 0.0],
+            vec![0.0, 0.0, 2.0],
+        ]);
+
+        let (eigenvalue, eigenvector) = arr.power_iteration(1000, 1e-7).unwrap();
+
+        assert!(close(eigenvalue, 5.0));
+        assert!(eigenvector.rows() == 3 && eigenvector.cols() == 1);
+        assert!(eigenvector.approx_eq(&Array::from(vec![0.0, 1.0, 0.0]), 1e-5));
+
+        // Dominant negative eigenvalue
+        let arr = Array::from(&vec![vec![-4.0, 0.0], vec![0.0, 1.0]]);
+        let (eigenvalue, eigenvector) = arr.power_iteration(1000, 1e-7).unwrap();
+
+        assert!(close(eigenvalue, -4.0));
+        assert!(close(eigenvector.get(0, 0).abs(), 1.0));
+
+        // Stopping early leaves a rough estimate
+        let arr = Array::from(&vec![vec![2.0, 0.0], vec![0.0, 1.9]]);
+        let (rough, _) = arr.power_iteration(2, 1e-7).unwrap();
+        let (precise, _) = arr.power_iteration(1000, 1e-7).unwrap();
+
+        assert!((precise - 2.0).abs() < (rough - 2.0).abs());
+
+        assert!(Array::zeros(2, 3).power_iteration(10, 1e-6).is_err());
+        assert!(Array::eye(2).power_iteration(0, 1e-6).is_err());
+    }
+
     #[test]
     fn serialization() {
         let arr = Array::from(&vec![vec![0.0, 1.0], vec![2.0, 3.0]]);
@@ -979,6 +2890,109 @@ mod tests {
 
     use datasets::iris;
 
+    #[test]
+    fn describe() {
+        let array = Array::from(&vec![
+            vec![1.0, -2.0, f32::NAN],
+            vec![4.0, 0.0, f32::NAN],
+            vec![2.0, 10.0, f32::NAN],
+            vec![3.0, 4.0, f32::NAN],
+            vec![5.0, f32::NAN, f32::NAN],
+        ]);
+
+        let summary = array.describe();
+
+        assert!(summary.rows() == 8 && summary.cols() == 3);
+
+        // Counts, ignoring missing values
+        assert!(summary.get(0, 0) == 5.0);
+        assert!(summary.get(0, 1) == 4.0);
+        assert!(summary.get(0, 2) == 0.0);
+
+        // Minima, medians and maxima
+        assert!(summary.get(3, 0) == 1.0 && summary.get(3, 1) == -2.0);
+        assert!(summary.get(5, 0) == 3.0 && summary.get(5, 1) == 2.0);
+        assert!(summary.get(7, 0) == 5.0 && summary.get(7, 1) == 10.0);
+
+        // Mean, sample standard deviation, and interpolated quartiles
+        assert!(close(summary.get(1, 0), 3.0));
+        assert!(close(summary.get(2, 0), 2.5f32.sqrt()));
+        assert!(close(summary.get(4, 1), -0.5));
+        assert!(close(summary.get(6, 1), 5.5));
+
+        assert!((1..8).all(|row_idx| summary.get(row_idx, 2).is_nan()));
+
+        // A single value does not vary
+        let summary = Array::from(&vec![vec![3.0, 1.0], vec![f32::NAN, 2.0]]).describe();
+        assert!(summary.get(0, 0) == 1.0);
+        assert!(summary.get(2, 0) == 0.0);
+        assert!(close(summary.get(2, 1), 0.5f32.sqrt()));
+    }
+
+    #[test]
+    fn unique_rows() {
+        let arr = Array::from(&vec![
+            vec![1.0, 2.0],
+            vec![3.0, 4.0],
+            vec![1.0, 2.0],
+            vec![0.0, 5.0],
+            vec![3.0, 4.0],
+            vec![-0.0, 5.0],
+            vec![f32::NAN, 1.0],
+            vec![f32::NAN, 1.0],
+        ]);
+
+        let (unique, mapping) = arr.unique_rows();
+
+        assert!(unique.rows() == 5);
+        assert!(unique.cols() == 2);
+        assert!(mapping == vec![0, 1, 0, 2, 1, 2, 3, 4]);
+
+        for (row_idx, &unique_idx) in mapping.iter().enumerate().take(6) {
+            for col_idx in 0..arr.cols() {
+                assert!(unique.get(unique_idx, col_idx) == arr.get(row_idx, col_idx));
+            }
+        }
+
+        let (empty, empty_mapping) = Array::zeros(0, 3).unique_rows();
+        assert!(empty.rows() == 0);
+        assert!(empty_mapping.is_empty());
+    }
+
+    #[test]
+    fn shuffle_rows() {
+        use rand::{SeedableRng, StdRng};
+
+        let (data, target) = iris::load_data();
+
+        let (shuffled_data, permutation) = data.shuffle_rows(&mut StdRng::from_seed(&[100]));
+        let shuffled_target = permute_rows(&target, &permutation);
+
+        assert!(permutation != (0..data.rows()).collect::<Vec<_>>());
+
+        let mut sorted_permutation = permutation.clone();
+        sorted_permutation.sort();
+        assert!(sorted_permutation == (0..data.rows()).collect::<Vec<_>>());
+
+        for (row_idx, &original_idx) in permutation.iter().enumerate() {
+            for col_idx in 0..data.cols() {
+                assert!(shuffled_data.get(row_idx, col_idx) == data.get(original_idx, col_idx));
+            }
+            assert!(shuffled_target.get(row_idx, 0) == target.get(original_idx, 0));
+        }
+
+        let (reshuffled_data, repeated_permutation) =
+            data.shuffle_rows(&mut StdRng::from_seed(&[100]));
+        assert!(repeated_permutation == permutation);
+        assert!(reshuffled_data == shuffled_data);
+    }
+
+    #[test]
+    #[should_panic]
+    fn permute_rows_length_mismatch() {
+        permute_rows(&Array::zeros(3, 2), &[0, 1]);
+    }
+
     #[test]
     fn range_iteration() {
         let (data, _) = iris::load_data();
@@ -213,6 +213,20 @@ unsafe fn get_mut<'a>(
     }
 }
 
+/// Compute the index pointer array of a compressed sparse
+/// representation from per-row (or per-column) index vectors.
+fn compressed_indptr(indices: &[Vec<usize>]) -> Vec<usize> {
+    let mut indptr = Vec::with_capacity(indices.len() + 1);
+    indptr.push(0);
+
+    for x in indices {
+        let last = indptr[indptr.len() - 1];
+        indptr.push(last + x.len());
+    }
+
+    indptr
+}
+
 impl SparseRowArray {
     /// Initialise an empty (`rows` by `cols`) matrix.
     pub fn zeros(rows: usize, cols: usize) -> SparseRowArray {
@@ -237,6 +251,30 @@ impl SparseRowArray {
         self.indices.iter().fold(0, |sum, x| sum + x.len())
     }
 
+    /// Return the CSR index pointer array: the entries of the `i`-th row
+    /// are at positions `indptr[i]..indptr[i + 1]` of `indices()` and `data()`.
+    ///
+    /// Entries are stored in a separate vector for every row, so the
+    /// compressed arrays are assembled on each call; they list the stored
+    /// entries in exactly the order in which they are kept internally.
+    pub fn indptr(&self) -> Vec<usize> {
+        compressed_indptr(&self.indices)
+    }
+
+    /// Return the column indices of the stored entries, in CSR order.
+    /// The indices of every row are sorted.
+    pub fn indices(&self) -> Vec<usize> {
+        self.indices
+            .iter()
+            .flat_map(|x| x.iter().cloned())
+            .collect()
+    }
+
+    /// Return the values of the stored entries, in CSR order.
+    pub fn data(&self) -> Vec<f32> {
+        self.data.iter().flat_map(|x| x.iter().cloned()).collect()
+    }
+
     pub fn todense(&self) -> Array {
         let mut array = Array::zeros(self.rows, self.cols);
 
@@ -250,6 +288,98 @@ impl SparseRowArray {
 
         array
     }
+
+    /// Convert a dense array, storing only entries whose absolute
+    /// value is strictly greater than `threshold`.
+    ///
+    /// `NaN` entries are always stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let dense = Array::from(&vec![vec![1e-6, 1.0],
+    ///                               vec![-2.0, -1e-4]]);
+    ///
+    /// let sparse = SparseRowArray::from_dense_with_threshold(&dense, 1e-3);
+    ///
+    /// assert!(sparse.nnz() == 2);
+    /// ```
+    pub fn from_dense_with_threshold(array: &Array, threshold: f32) -> SparseRowArray {
+        let mut sparse = SparseRowArray::zeros(array.rows(), array.cols());
+
+        for (row_idx, row) in array.iter_rows().enumerate() {
+            for (col_idx, value) in row.iter().enumerate() {
+                if value.is_nan() || value.abs() > threshold {
+                    sparse.set(row_idx, col_idx, value);
+                }
+            }
+        }
+
+        sparse
+    }
+
+    /// Return a new array made up of the columns listed in `keep`, in that
+    /// order. Column `keep[i]` becomes column `i` of the result; only the
+    /// stored entries of the kept columns are copied.
+    ///
+    /// Rows can be selected in a similar way using `get_rows`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the indices in `keep` is out of bounds or if
+    /// `keep` contains duplicates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// let sparse = SparseRowArray::from(&Array::from(&vec![vec![1.0, 0.0, 2.0],
+    ///                                                      vec![0.0, 3.0, 0.0]]));
+    ///
+    /// let selected = sparse.select_columns(&[2, 1]);
+    ///
+    /// assert!(selected.cols() == 2);
+    /// assert!(selected.nnz() == 2);
+    /// assert!(selected.get(0, 0) == 2.0);
+    /// assert!(selected.get(1, 1) == 3.0);
+    /// ```
+    pub fn select_columns(&self, keep: &[usize]) -> SparseRowArray {
+        let mut column_map = vec![None; self.cols];
+
+        for (new_idx, &col_idx) in keep.iter().enumerate() {
+            assert!(col_idx < self.cols, "Column index out of bounds");
+            assert!(
+                column_map[col_idx].is_none(),
+                "Column indices must not repeat"
+            );
+            column_map[col_idx] = Some(new_idx);
+        }
+
+        let mut indices = Vec::with_capacity(self.rows);
+        let mut data = Vec::with_capacity(self.rows);
+
+        for (row_indices, row_data) in self.indices.iter().zip(self.data.iter()) {
+            let mut entries = row_indices
+                .iter()
+                .zip(row_data.iter())
+                .filter_map(|(&col_idx, &value)| column_map[col_idx].map(|idx| (idx, value)))
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|&(idx, _)| idx);
+
+            indices.push(entries.iter().map(|&(idx, _)| idx).collect());
+            data.push(entries.iter().map(|&(_, value)| value).collect());
+        }
+
+        SparseRowArray {
+            rows: self.rows,
+            cols: keep.len(),
+            indices: indices,
+            data: data,
+        }
+    }
 }
 
 impl<'a> From<&'a Array> for SparseRowArray {
@@ -339,6 +469,30 @@ impl SparseColumnArray {
         self.indices.iter().fold(0, |sum, x| sum + x.len())
     }
 
+    /// Return the CSC index pointer array: the entries of the `i`-th column
+    /// are at positions `indptr[i]..indptr[i + 1]` of `indices()` and `data()`.
+    ///
+    /// Entries are stored in a separate vector for every column, so the
+    /// compressed arrays are assembled on each call; they list the stored
+    /// entries in exactly the order in which they are kept internally.
+    pub fn indptr(&self) -> Vec<usize> {
+        compressed_indptr(&self.indices)
+    }
+
+    /// Return the row indices of the stored entries, in CSC order.
+    /// The indices of every column are sorted.
+    pub fn indices(&self) -> Vec<usize> {
+        self.indices
+            .iter()
+            .flat_map(|x| x.iter().cloned())
+            .collect()
+    }
+
+    /// Return the values of the stored entries, in CSC order.
+    pub fn data(&self) -> Vec<f32> {
+        self.data.iter().flat_map(|x| x.iter().cloned()).collect()
+    }
+
     pub fn todense(&self) -> Array {
         let mut array = Array::zeros(self.rows, self.cols);
 
@@ -369,16 +523,48 @@ impl<'a> From<&'a Array> for SparseColumnArray {
 }
 
 impl<'a> From<&'a SparseRowArray> for SparseColumnArray {
+    /// Transpose a row-wise sparse matrix in a single pass.
+    ///
+    /// Nonzero entries are first counted per column so that every column
+    /// is allocated exactly once; entries are then appended in row order,
+    /// which keeps the row indices of every column sorted.
     fn from(array: &SparseRowArray) -> SparseColumnArray {
-        let mut sparse = SparseColumnArray::zeros(array.rows(), array.cols());
+        let mut column_counts = vec![0; array.cols];
 
-        for (row_idx, row) in array.iter_rows().enumerate() {
-            for (col_idx, value) in row.iter_nonzero() {
-                sparse.set(row_idx, col_idx, value);
+        for (row_indices, row_values) in array.indices.iter().zip(array.data.iter()) {
+            for (&col_idx, &value) in row_indices.iter().zip(row_values.iter()) {
+                if value != 0.0 {
+                    column_counts[col_idx] += 1;
+                }
             }
         }
 
-        sparse
+        let mut indices = column_counts
+            .iter()
+            .map(|&count| Vec::with_capacity(count))
+            .collect::<Vec<_>>();
+        let mut data = column_counts
+            .iter()
+            .map(|&count| Vec::with_capacity(count))
+            .collect::<Vec<_>>();
+
+        for (row_idx, (row_indices, row_values)) in
+            array.indices.iter().zip(array.data.iter()).enumerate()
+        {
+            for (&col_idx, &value) in row_indices.iter().zip(row_values.iter()) {
+                if value != 0.0 {
+                    indices[col_idx].push(row_idx);
+                    data[col_idx].push(value);
+                }
+            }
+        }
+
+        SparseColumnArray {
+            rows: array.rows,
+            cols: array.cols,
+            indices: indices,
+            data: data,
+        }
     }
 }
 
@@ -512,6 +698,8 @@ mod tests {
 
     use bincode;
 
+    use rand::{Rng, SeedableRng, StdRng};
+
     #[test]
     fn row_construction_and_indexing() {
         let dense_arr = Array::from(&vec![vec![0.0, 1.0], vec![2.0, 0.0]]);
@@ -526,6 +714,34 @@ mod tests {
         assert!(arr.get(1, 1) == 0.0);
     }
 
+    #[test]
+    fn row_construction_with_threshold() {
+        let dense_arr = Array::from(&vec![
+            vec![0.001, 1.0, -0.5],
+            vec![-0.01, 0.0, 0.1],
+            vec![0.05, -2.0, ::std::f32::NAN],
+        ]);
+        let arr = SparseRowArray::from_dense_with_threshold(&dense_arr, 0.1);
+
+        assert!(arr.nnz() == 4);
+
+        let dense = arr.todense();
+        let expected = dense_arr.map(|x| if x.abs() > 0.1 { x } else { 0.0 });
+
+        for row_idx in 0..3 {
+            for col_idx in 0..3 {
+                if row_idx == 2 && col_idx == 2 {
+                    assert!(dense.get(row_idx, col_idx).is_nan());
+                } else {
+                    assert!(dense.get(row_idx, col_idx) == expected.get(row_idx, col_idx));
+                }
+            }
+        }
+
+        let arr = SparseRowArray::from_dense_with_threshold(&dense_arr, 0.0);
+        assert!(arr.nnz() == SparseRowArray::from(&dense_arr).nnz());
+    }
+
     #[test]
     fn column_construction_and_indexing() {
         let dense_arr = Array::from(&vec![vec![0.0, 1.0], vec![2.0, 0.0]]);
@@ -609,6 +825,49 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn column_selection() {
+        let dense_arr = Array::from(&vec![
+            vec![0.0, 1.0, 0.0, 2.0],
+            vec![3.0, 0.0, 0.0, 4.0],
+            vec![0.0, 0.0, 5.0, 0.0],
+        ]);
+        let arr = SparseRowArray::from(&dense_arr);
+
+        let keep = vec![3, 0, 2];
+        let selected = arr.select_columns(&keep);
+
+        let mut expected = Array::zeros(dense_arr.rows(), keep.len());
+        for row_idx in 0..dense_arr.rows() {
+            for (new_idx, &col_idx) in keep.iter().enumerate() {
+                expected.set(row_idx, new_idx, dense_arr.get(row_idx, col_idx));
+            }
+        }
+
+        assert!(selected.rows() == 3);
+        assert!(selected.cols() == 3);
+        assert!(selected.nnz() == 4);
+        assert!(allclose(&selected.todense(), &expected));
+
+        for row in selected.iter_rows() {
+            assert!(row.indices().windows(2).all(|x| x[0] < x[1]));
+        }
+
+        assert!(allclose(
+            &selected.get_rows(&vec![2, 0]).todense(),
+            &expected.get_rows(&vec![2, 0])
+        ));
+
+        assert!(arr.select_columns(&[]).nnz() == 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn column_selection_out_of_bounds() {
+        let arr = SparseRowArray::zeros(2, 2);
+        arr.select_columns(&[2]);
+    }
+
     use datasets::iris;
 
     #[test]
@@ -637,4 +896,120 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn compressed_representation() {
+        let array = random_sparse_rows(50, 30, 0.1);
+
+        let (indptr, indices, data) = (array.indptr(), array.indices(), array.data());
+        assert!(indptr.len() == array.rows() + 1);
+        assert!(indptr[array.rows()] == array.nnz());
+        assert!(indices.len() == array.nnz() && data.len() == array.nnz());
+
+        let mut dense = Array::zeros(array.rows(), array.cols());
+        for row_idx in 0..array.rows() {
+            for entry_idx in indptr[row_idx]..indptr[row_idx + 1] {
+                dense.set(row_idx, indices[entry_idx], data[entry_idx]);
+            }
+        }
+        assert!(dense == array.todense());
+
+        let array = SparseColumnArray::from(&array);
+
+        let (indptr, indices, data) = (array.indptr(), array.indices(), array.data());
+        assert!(indptr.len() == array.cols() + 1);
+        assert!(indptr[array.cols()] == array.nnz());
+
+        let mut dense = Array::zeros(array.rows(), array.cols());
+        for col_idx in 0..array.cols() {
+            for entry_idx in indptr[col_idx]..indptr[col_idx + 1] {
+                dense.set(indices[entry_idx], col_idx, data[entry_idx]);
+            }
+        }
+        assert!(dense == array.todense());
+
+        let empty = SparseRowArray::zeros(3, 4);
+        assert!(empty.indptr() == vec![0, 0, 0, 0]);
+        assert!(empty.indices().is_empty() && empty.data().is_empty());
+    }
+
+    fn random_sparse_rows(rows: usize, cols: usize, density: f32) -> SparseRowArray {
+        let mut rng = StdRng::from_seed(&[100]);
+        let mut array = SparseRowArray::zeros(rows, cols);
+
+        for row_idx in 0..rows {
+            for col_idx in 0..cols {
+                if rng.next_f32() < density {
+                    array.set(row_idx, col_idx, rng.next_f32() - 0.5);
+                }
+            }
+        }
+
+        array
+    }
+
+    fn naive_column_conversion(array: &SparseRowArray) -> SparseColumnArray {
+        let mut sparse = SparseColumnArray::zeros(array.rows(), array.cols());
+
+        for (row_idx, row) in array.iter_rows().enumerate() {
+            for (col_idx, value) in row.iter_nonzero() {
+                sparse.set(row_idx, col_idx, value);
+            }
+        }
+
+        sparse
+    }
+
+    #[test]
+    fn row_to_column_conversion() {
+        let array = random_sparse_rows(500, 300, 0.05);
+        let bootstrap_indices = (0..500)
+            .map(|idx| (idx * 7) % 500 / 2)
+            .collect::<Vec<usize>>();
+        let bootstrapped = array.get_rows(&bootstrap_indices);
+
+        for array in &[array, bootstrapped] {
+            let fast = SparseColumnArray::from(array);
+            let naive = naive_column_conversion(array);
+
+            assert!(fast.rows() == naive.rows() && fast.cols() == naive.cols());
+            assert!(fast.nnz() == array.nnz());
+            assert!(fast.indices == naive.indices);
+            assert!(fast.data == naive.data);
+        }
+
+        let empty = SparseColumnArray::from(&SparseRowArray::zeros(3, 0));
+        assert!(empty.rows() == 3 && empty.cols() == 0 && empty.nnz() == 0);
+    }
+}
+
+#[cfg(feature = "bench")]
+#[allow(unused_imports)]
+mod bench {
+
+    use array::traits::*;
+
+    use super::*;
+
+    use rand::{Rng, SeedableRng, StdRng};
+
+    use test::Bencher;
+
+    #[bench]
+    fn bench_row_to_column_conversion(b: &mut Bencher) {
+        let (rows, cols) = (5000, 2000);
+
+        let mut rng = StdRng::from_seed(&[100]);
+        let mut array = SparseRowArray::zeros(rows, cols);
+
+        for row_idx in 0..rows {
+            for col_idx in 0..cols {
+                if rng.next_f32() < 0.01 {
+                    array.set(row_idx, col_idx, rng.next_f32());
+                }
+            }
+        }
+
+        b.iter(|| SparseColumnArray::from(&array));
+    }
 }
@@ -159,13 +159,13 @@ where
 pub trait ElementwiseArrayOps<Rhs> {
     type Output;
     fn add(&self, rhs: Rhs) -> Self::Output;
-    fn add_inplace(&mut self, rhs: Rhs);
+    fn add_inplace(&mut self, rhs: Rhs) -> &mut Self;
     fn sub(&self, rhs: Rhs) -> Self::Output;
-    fn sub_inplace(&mut self, rhs: Rhs);
+    fn sub_inplace(&mut self, rhs: Rhs) -> &mut Self;
     fn times(&self, rhs: Rhs) -> Self::Output;
-    fn times_inplace(&mut self, rhs: Rhs);
+    fn times_inplace(&mut self, rhs: Rhs) -> &mut Self;
     fn div(&self, rhs: Rhs) -> Self::Output;
-    fn div_inplace(&mut self, rhs: Rhs);
+    fn div_inplace(&mut self, rhs: Rhs) -> &mut Self;
 }
 
 /// A matrix multiplication trait.
@@ -0,0 +1,286 @@
+//! Isotonic regression.
+//!
+//! Fits a non-decreasing step function to one-dimensional data using the
+//! pool-adjacent-violators algorithm: neighbouring values that violate the
+//! ordering are repeatedly pooled into their mean until the fitted values are
+//! monotone. Predictions linearly interpolate between the learned breakpoints,
+//! and are clamped to the fitted values at the ends of the training range.
+//!
+//! Used by [`CalibratedClassifier`](../struct.CalibratedClassifier.html) as a
+//! non-parametric alternative to Platt scaling.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::calibration::IsotonicRegression;
+//!
+//! let x = Array::from(vec![1.0, 2.0, 3.0, 4.0]);
+//! let y = Array::from(vec![1.0, 3.0, 2.0, 4.0]);
+//!
+//! let mut model = IsotonicRegression::new();
+//! model.fit(&x, &y).unwrap();
+//!
+//! let prediction = model.transform(&x).unwrap();
+//!
+//! assert!(prediction.data() == &vec![1.0, 2.5, 2.5, 4.0]);
+//! ```
+
+use std::cmp::Ordering;
+use std::f32;
+
+use prelude::*;
+
+use utils::check_matched_dimensions;
+
+/// A non-decreasing step function fit using the pool-adjacent-violators algorithm.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct IsotonicRegression {
+    x_thresholds: Vec<f32>,
+    y_thresholds: Vec<f32>,
+}
+
+impl IsotonicRegression {
+    /// Create a new, unfitted model.
+    pub fn new() -> IsotonicRegression {
+        IsotonicRegression {
+            x_thresholds: Vec::new(),
+            y_thresholds: Vec::new(),
+        }
+    }
+
+    /// The breakpoints of the fitted function, in increasing order.
+    pub fn x_thresholds(&self) -> &[f32] {
+        &self.x_thresholds
+    }
+
+    /// The fitted values at the breakpoints, in non-decreasing order.
+    pub fn y_thresholds(&self) -> &[f32] {
+        &self.y_thresholds
+    }
+
+    /// Fit the model to the single-column inputs `x` and targets `y`.
+    pub fn fit(&mut self, x: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(x, y));
+
+        if x.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit isotonic regression on an empty array.",
+            ));
+        }
+
+        if x.cols() != 1 || y.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Isotonic regression requires single-column inputs and targets.",
+            ));
+        }
+
+        if x.data()
+            .iter()
+            .chain(y.data().iter())
+            .any(|v| !v.is_finite())
+        {
+            return Err(RustlearnError::InvalidInput(
+                "Isotonic regression inputs and targets must be finite.",
+            ));
+        }
+
+        let mut pairs = x
+            .data()
+            .iter()
+            .cloned()
+            .zip(y.data().iter().cloned())
+            .collect::<Vec<_>>();
+        pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        // Blocks of (smallest x, largest x, sum of y, count), with
+        // tied inputs starting out in the same block.
+        let mut blocks: Vec<(f32, f32, f64, usize)> = Vec::with_capacity(pairs.len());
+
+        for &(x_value, y_value) in &pairs {
+            let tied = match blocks.last() {
+                Some(&(_, last_x, _, _)) => last_x == x_value,
+                None => false,
+            };
+
+            if tied {
+                let block = blocks.last_mut().unwrap();
+                block.2 += y_value as f64;
+                block.3 += 1;
+            } else {
+                blocks.push((x_value, x_value, y_value as f64, 1));
+            }
+
+            // Pool adjacent violators
+            while blocks.len() > 1 {
+                let (_, x_max, sum, count) = blocks[blocks.len() - 1];
+                let (_, _, previous_sum, previous_count) = blocks[blocks.len() - 2];
+
+                if previous_sum / previous_count as f64 <= sum / count as f64 {
+                    break;
+                }
+
+                blocks.pop();
+                let previous = blocks.last_mut().unwrap();
+                previous.1 = x_max;
+                previous.2 += sum;
+                previous.3 += count;
+            }
+        }
+
+        self.x_thresholds.clear();
+        self.y_thresholds.clear();
+
+        for &(x_min, x_max, sum, count) in &blocks {
+            let value = (sum / count as f64) as f32;
+
+            self.x_thresholds.push(x_min);
+            self.y_thresholds.push(value);
+
+            if x_max > x_min {
+                self.x_thresholds.push(x_max);
+                self.y_thresholds.push(value);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Predict the fitted function at the single-column inputs `x`,
+    /// linearly interpolating between breakpoints.
+    ///
+    /// Inputs outside the training range are given the fitted value
+    /// at the nearest end of the range, and `NaN` inputs map to `NaN`.
+    pub fn transform(&self, x: &Array) -> Result<Array, RustlearnError> {
+        if self.x_thresholds.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        if x.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Isotonic regression requires single-column inputs.",
+            ));
+        }
+
+        Ok(x.map(|value| self.interpolate(value)))
+    }
+
+    fn interpolate(&self, value: f32) -> f32 {
+        let (xs, ys) = (&self.x_thresholds, &self.y_thresholds);
+        let last = xs.len() - 1;
+
+        if value.is_nan() {
+            return f32::NAN;
+        } else if value <= xs[0] {
+            return ys[0];
+        } else if value >= xs[last] {
+            return ys[last];
+        }
+
+        // Index of the first breakpoint above the value
+        let upper = xs.iter().position(|&x| x > value).unwrap();
+        let lower = upper - 1;
+
+        let fraction = (value - xs[lower]) / (xs[upper] - xs[lower]);
+
+        ys[lower] + fraction * (ys[upper] - ys[lower])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use rand::{Rng, SeedableRng, StdRng};
+
+    use super::*;
+
+    #[test]
+    fn monotone_fit() {
+        let mut rng = StdRng::from_seed(&[100]);
+
+        let x = (0..200).map(|i| i as f32 / 10.0).collect::<Vec<_>>();
+        let y = x
+            .iter()
+            .map(|&x| x.sqrt() + rng.gen_range(-1.0, 1.0))
+            .collect::<Vec<_>>();
+
+        let x = Array::from(x);
+        let y = Array::from(y);
+
+        let mut model = IsotonicRegression::new();
+        model.fit(&x, &y).unwrap();
+
+        // The noise makes the targets themselves non-monotone
+        assert!(y.data().windows(2).any(|pair| pair[1] < pair[0]));
+
+        let grid = Array::from((0..500).map(|i| i as f32 / 20.0 - 2.5).collect::<Vec<_>>());
+        let prediction = model.transform(&grid).unwrap();
+
+        assert!(prediction.data().windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(model
+            .y_thresholds()
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+        assert!(model
+            .x_thresholds()
+            .windows(2)
+            .all(|pair| pair[0] <= pair[1]));
+
+        // The fit is closer to the targets than the best constant
+        let fitted = model.transform(&x).unwrap();
+        let mean = y.mean();
+        let fit_error = y
+            .data()
+            .iter()
+            .zip(fitted.data().iter())
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>();
+        let constant_error = y.data().iter().map(|a| (a - mean).powi(2)).sum::<f32>();
+
+        assert!(fit_error < constant_error);
+    }
+
+    #[test]
+    fn pooling_and_interpolation() {
+        let x = Array::from(vec![3.0, 1.0, 2.0, 2.0, 4.0, 5.0]);
+        let y = Array::from(vec![1.0, 0.0, 2.0, 1.0, 3.0, 2.0]);
+
+        let mut model = IsotonicRegression::new();
+        model.fit(&x, &y).unwrap();
+
+        // The tied inputs at 2 average to 1.5, violating the ordering with
+        // the value of 1 at 3, and the values at 4 and 5 are pooled to 2.5.
+        assert!(model.x_thresholds() == &[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(model.y_thresholds() == &[0.0, 4.0 / 3.0, 4.0 / 3.0, 2.5, 2.5]);
+
+        let prediction = model
+            .transform(&Array::from(vec![0.0, 1.5, 3.5, 10.0, f32::NAN]))
+            .unwrap();
+
+        assert!(prediction.get(0, 0) == 0.0);
+        assert!((prediction.get(1, 0) - 2.0 / 3.0).abs() < 1e-6);
+        assert!((prediction.get(2, 0) - (4.0 / 3.0 + 2.5) / 2.0).abs() < 1e-6);
+        assert!(prediction.get(3, 0) == 2.5);
+        assert!(prediction.get(4, 0).is_nan());
+    }
+
+    #[test]
+    fn errors() {
+        let mut model = IsotonicRegression::new();
+
+        assert!(model.transform(&Array::from(vec![1.0])).is_err());
+        assert!(model.fit(&Array::zeros(3, 2), &Array::zeros(3, 1)).is_err());
+        assert!(model.fit(&Array::zeros(3, 1), &Array::zeros(2, 1)).is_err());
+        assert!(model
+            .fit(&Array::from(vec![1.0, f32::NAN]), &Array::zeros(2, 1))
+            .is_err());
+
+        model
+            .fit(&Array::from(vec![1.0, 2.0]), &Array::from(vec![0.0, 1.0]))
+            .unwrap();
+        assert!(model.transform(&Array::zeros(2, 2)).is_err());
+    }
+}
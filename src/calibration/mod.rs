@@ -0,0 +1,455 @@
+//! Probability calibration of classifier scores.
+//!
+//! The decision functions of many classifiers (such as
+//! [logistic regression](../linear_models/sgdclassifier/index.html) trained for a single
+//! epoch, or [random forests](../ensemble/random_forest/index.html)) rank samples well,
+//! but are not well-calibrated probabilities. A `CalibratedClassifier` wraps such a
+//! two-class model and corrects its scores using Platt scaling: a one-dimensional
+//! logistic regression mapping the score `s` of the model to the probability
+//! `1 / (1 + exp(a * s + b))` of the positive class.
+//!
+//! Platt scaling assumes the miscalibration of the scores is sigmoidal. Setting
+//! the method to [`CalibrationMethod::Isotonic`](enum.CalibrationMethod.html) instead
+//! fits a non-parametric, non-decreasing [isotonic regression](isotonic/index.html)
+//! of the labels on the scores, which requires more calibration data but makes no
+//! assumption about the shape of the mapping.
+//!
+//! When fitting, the training data is randomly split into two parts: the wrapped model
+//! is fit on one, and the scaling parameters are fit on the model's scores on the
+//! other, held-out part. The size of the held-out part is set by `calibration_fraction`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::calibration::CalibratedClassifier;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//!
+//! let (X, y) = iris::load_data();
+//! let y = y.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+//!
+//! let model = Hyperparameters::new(X.cols()).learning_rate(0.5).build();
+//!
+//! let mut calibrated = CalibratedClassifier::new(model, 0.3);
+//! calibrated.fit(&X, &y).unwrap();
+//!
+//! let probabilities = calibrated.predict_proba(&X).unwrap();
+//! ```
+
+pub mod isotonic;
+
+pub use self::isotonic::IsotonicRegression;
+
+use rand::{Rng, StdRng};
+
+use prelude::*;
+
+use utils::{check_matched_dimensions, check_valid_labels, EncodableRng};
+
+/// The method used to map the scores of a model to probabilities.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum CalibrationMethod {
+    /// A one-dimensional logistic regression on the scores.
+    Platt,
+    /// A non-decreasing isotonic regression on the scores.
+    Isotonic,
+}
+
+fn default_method() -> CalibrationMethod {
+    CalibrationMethod::Platt
+}
+
+/// A two-class model whose scores are calibrated using Platt scaling
+/// or isotonic regression.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CalibratedClassifier<T> {
+    model: T,
+    calibration_fraction: f32,
+    #[serde(default = "default_method")]
+    method: CalibrationMethod,
+
+    a: f32,
+    b: f32,
+    #[serde(default)]
+    isotonic: IsotonicRegression,
+    fitted: bool,
+
+    rng: EncodableRng,
+}
+
+impl<T> CalibratedClassifier<T> {
+    /// Wrap an unfitted two-class `model`, holding out `calibration_fraction`
+    /// of the training data for calibration.
+    ///
+    /// # Panics
+    /// Panics if `calibration_fraction` is not strictly between 0 and 1.
+    pub fn new(model: T, calibration_fraction: f32) -> CalibratedClassifier<T> {
+        assert!(
+            calibration_fraction > 0.0 && calibration_fraction < 1.0,
+            "Calibration fraction must be between 0 and 1"
+        );
+
+        CalibratedClassifier {
+            model: model,
+            calibration_fraction: calibration_fraction,
+            method: CalibrationMethod::Platt,
+            a: 0.0,
+            b: 0.0,
+            isotonic: IsotonicRegression::new(),
+            fitted: false,
+            rng: EncodableRng::new(),
+        }
+    }
+
+    /// Set the random number generator used for splitting
+    /// the training and calibration data.
+    pub fn set_rng(&mut self, rng: StdRng) {
+        self.rng.rng = rng;
+    }
+
+    /// Set the calibration method. Defaults to `CalibrationMethod::Platt`.
+    pub fn set_method(&mut self, method: CalibrationMethod) {
+        self.method = method;
+    }
+
+    /// The calibration method.
+    pub fn method(&self) -> CalibrationMethod {
+        self.method
+    }
+
+    /// The wrapped model.
+    pub fn model(&self) -> &T {
+        &self.model
+    }
+
+    /// The fitted Platt scaling parameters `(a, b)`.
+    pub fn calibration_parameters(&self) -> (f32, f32) {
+        (self.a, self.b)
+    }
+
+    /// The fitted isotonic regression, when calibrating
+    /// with `CalibrationMethod::Isotonic`.
+    pub fn isotonic_regression(&self) -> &IsotonicRegression {
+        &self.isotonic
+    }
+}
+
+impl<'a, T> CalibratedClassifier<T>
+where
+    T: SupervisedModel<&'a Array>,
+{
+    /// Predict the calibrated probability of the positive class.
+    pub fn predict_proba(&self, X: &'a Array) -> Result<Array, RustlearnError> {
+        if !self.fitted {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        let scores = try!(self.model.decision_function(X));
+
+        if scores.cols() != 1 {
+            return Err(RustlearnError::Unsupported(
+                "Wrapped model must output a single decision score.",
+            ));
+        }
+
+        match self.method {
+            CalibrationMethod::Platt => {
+                Ok(scores.map(|score| 1.0 / (1.0 + (self.a * score + self.b).exp())))
+            }
+            CalibrationMethod::Isotonic => self.isotonic.transform(&scores),
+        }
+    }
+}
+
+impl<T> SeedableModel for CalibratedClassifier<T> {
+    /// Reseed the generator used for splitting the training data into folds.
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = EncodableRng::from_seed(seed);
+    }
+}
+
+impl<'a, T> SupervisedModel<&'a Array> for CalibratedClassifier<T>
+where
+    T: for<'b> SupervisedModel<&'b Array>,
+{
+    fn fit(&mut self, X: &'a Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+        try!(check_valid_labels(y));
+
+        let num_calibration = (self.calibration_fraction * X.rows() as f32).round() as usize;
+
+        if num_calibration == 0 || num_calibration == X.rows() {
+            return Err(RustlearnError::InvalidInput(
+                "Not enough data to split into training and calibration sets.",
+            ));
+        }
+
+        let mut indices = (0..X.rows()).collect::<Vec<_>>();
+        self.rng.rng.shuffle(&mut indices);
+
+        let (calibration_indices, train_indices) = indices.split_at(num_calibration);
+        let calibration_indices = calibration_indices.to_vec();
+        let train_indices = train_indices.to_vec();
+
+        try!(self
+            .model
+            .fit(&X.get_rows(&train_indices), &y.get_rows(&train_indices)));
+
+        let scores = try!(self
+            .model
+            .decision_function(&X.get_rows(&calibration_indices)));
+
+        if scores.cols() != 1 {
+            return Err(RustlearnError::Unsupported(
+                "Wrapped model must output a single decision score.",
+            ));
+        }
+
+        let calibration_y = y.get_rows(&calibration_indices);
+
+        match self.method {
+            CalibrationMethod::Platt => {
+                let (a, b) = platt_scaling(scores.data(), calibration_y.data());
+
+                self.a = a;
+                self.b = b;
+            }
+            CalibrationMethod::Isotonic => {
+                try!(self.isotonic.fit(&scores, &calibration_y));
+            }
+        }
+
+        self.fitted = true;
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &'a Array) -> Result<Array, RustlearnError> {
+        self.predict_proba(X)
+    }
+}
+
+/// Fit the parameters `(a, b)` of `1 / (1 + exp(a * score + b))` to the labels
+/// using Newton's method with backtracking, as described in Lin, Lin and Weng,
+/// "A note on Platt's probabilistic outputs for support vector machines" (2007).
+fn platt_scaling(scores: &[f32], labels: &[f32]) -> (f32, f32) {
+    let num_positive = labels.iter().filter(|&&y| y == 1.0).count() as f64;
+    let num_negative = labels.len() as f64 - num_positive;
+
+    // Regularized targets, to avoid overfitting on separable data
+    let high_target = (num_positive + 1.0) / (num_positive + 2.0);
+    let low_target = 1.0 / (num_negative + 2.0);
+
+    let data = scores
+        .iter()
+        .zip(labels.iter())
+        .map(|(&score, &y)| {
+            (
+                score as f64,
+                if y == 1.0 { high_target } else { low_target },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let objective = |a: f64, b: f64| {
+        data.iter()
+            .map(|&(score, target)| {
+                let f = a * score + b;
+
+                if f >= 0.0 {
+                    target * f + (-f).exp().ln_1p()
+                } else {
+                    (target - 1.0) * f + f.exp().ln_1p()
+                }
+            })
+            .sum::<f64>()
+    };
+
+    let mut a = 0.0;
+    let mut b = ((num_negative + 1.0) / (num_positive + 1.0)).ln();
+    let mut value = objective(a, b);
+
+    for _ in 0..100 {
+        let (mut h11, mut h22, mut h21) = (1e-12, 1e-12, 0.0);
+        let (mut g1, mut g2) = (0.0, 0.0);
+
+        for &(score, target) in &data {
+            let f = a * score + b;
+            let (p, q) = if f >= 0.0 {
+                let e = (-f).exp();
+                (e / (1.0 + e), 1.0 / (1.0 + e))
+            } else {
+                let e = f.exp();
+                (1.0 / (1.0 + e), e / (1.0 + e))
+            };
+
+            let d2 = p * q;
+            h11 += score * score * d2;
+            h22 += d2;
+            h21 += score * d2;
+
+            let d1 = target - p;
+            g1 += score * d1;
+            g2 += d1;
+        }
+
+        if g1.abs() < 1e-5 && g2.abs() < 1e-5 {
+            break;
+        }
+
+        let det = h11 * h22 - h21 * h21;
+        let delta_a = -(h22 * g1 - h21 * g2) / det;
+        let delta_b = -(-h21 * g1 + h11 * g2) / det;
+        let directional_derivative = g1 * delta_a + g2 * delta_b;
+
+        let mut step = 1.0;
+
+        while step >= 1e-10 {
+            let new_a = a + step * delta_a;
+            let new_b = b + step * delta_b;
+            let new_value = objective(new_a, new_b);
+
+            if new_value < value + 1e-4 * step * directional_derivative {
+                a = new_a;
+                b = new_b;
+                value = new_value;
+                break;
+            }
+
+            step /= 2.0;
+        }
+
+        if step < 1e-10 {
+            break;
+        }
+    }
+
+    (a as f32, b as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use datasets::iris::load_data;
+    use linear_models::sgdclassifier::Hyperparameters;
+
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    fn log_loss(y: &Array, probabilities: &Array) -> f32 {
+        y.data()
+            .iter()
+            .zip(probabilities.data().iter())
+            .map(|(&y, &p)| {
+                let p = p.max(1e-7).min(1.0 - 1e-7);
+                -(y * p.ln() + (1.0 - y) * (1.0 - p).ln())
+            })
+            .sum::<f32>()
+            / y.rows() as f32
+    }
+
+    fn brier_score(y: &Array, probabilities: &Array) -> f32 {
+        y.data()
+            .iter()
+            .zip(probabilities.data().iter())
+            .map(|(&y, &p)| (y - p).powi(2))
+            .sum::<f32>()
+            / y.rows() as f32
+    }
+
+    #[test]
+    fn test_platt_scaling() {
+        // Scores of positive samples are mostly higher
+        let scores = vec![-2.0, -1.0, -0.5, 0.5, 0.0, 1.0, 1.5, 2.0];
+        let labels = vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+
+        let (a, b) = platt_scaling(&scores, &labels);
+
+        println!("Parameters {} {}", a, b);
+
+        // Probabilities increase with the score
+        assert!(a < 0.0);
+        assert!(b.abs() < 0.5);
+    }
+
+    #[test]
+    fn test_calibration_iris() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|y| if y == 2.0 { 1.0 } else { 0.0 });
+
+        let model = Hyperparameters::new(data.cols())
+            .learning_rate(0.5)
+            .l2_penalty(0.0)
+            .build();
+
+        let mut calibrated = CalibratedClassifier::new(model, 0.3);
+        calibrated.set_rng(StdRng::from_seed(&[100]));
+        calibrated.fit(&data, &binary_target).unwrap();
+
+        let raw_probabilities = calibrated.model().decision_function(&data).unwrap();
+        let probabilities = calibrated.predict_proba(&data).unwrap();
+
+        let raw_loss = log_loss(&binary_target, &raw_probabilities);
+        let calibrated_loss = log_loss(&binary_target, &probabilities);
+
+        println!("Log loss {} -> {}", raw_loss, calibrated_loss);
+
+        assert!(calibrated_loss < raw_loss);
+        assert!(probabilities.data().iter().all(|&p| p >= 0.0 && p <= 1.0));
+    }
+
+    #[test]
+    fn test_isotonic_calibration_iris() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|y| if y == 2.0 { 1.0 } else { 0.0 });
+
+        let model = Hyperparameters::new(data.cols())
+            .learning_rate(0.5)
+            .l2_penalty(0.0)
+            .build();
+
+        let mut calibrated = CalibratedClassifier::new(model, 0.3);
+        calibrated.set_method(CalibrationMethod::Isotonic);
+        calibrated.set_rng(StdRng::from_seed(&[100]));
+        calibrated.fit(&data, &binary_target).unwrap();
+
+        let raw_probabilities = calibrated.model().decision_function(&data).unwrap();
+        let probabilities = calibrated.predict_proba(&data).unwrap();
+
+        // Isotonic regression can output probabilities of exactly 0 or 1,
+        // so compare the Brier score rather than the log loss.
+        let raw_loss = brier_score(&binary_target, &raw_probabilities);
+        let calibrated_loss = brier_score(&binary_target, &probabilities);
+
+        println!("Brier score {} -> {}", raw_loss, calibrated_loss);
+
+        assert!(calibrated_loss < raw_loss);
+        assert!(probabilities.data().iter().all(|&p| p >= 0.0 && p <= 1.0));
+
+        // Calibrated probabilities preserve the ordering of the scores
+        let order = raw_probabilities.argsort_column(0, false);
+        assert!(order
+            .windows(2)
+            .all(|pair| probabilities.get(pair[0], 0) <= probabilities.get(pair[1], 0)));
+    }
+
+    #[test]
+    fn test_errors() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|y| if y == 2.0 { 1.0 } else { 0.0 });
+
+        let mut calibrated =
+            CalibratedClassifier::new(Hyperparameters::new(data.cols()).build(), 0.3);
+
+        assert!(calibrated.predict_proba(&data).is_err());
+        assert!(calibrated.fit(&data, &target).is_err());
+        assert!(calibrated
+            .fit(&data.get_rows(&vec![0]), &binary_target.get_rows(&vec![0]))
+            .is_err());
+    }
+}
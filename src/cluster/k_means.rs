@@ -0,0 +1,318 @@
+//! K-means clustering.
+//!
+//! Partitions the rows of a dense array into `n_clusters` clusters using
+//! Lloyd's algorithm: each row is assigned to its nearest centroid, and each
+//! centroid is then moved to the mean of the rows assigned to it. This is
+//! repeated until the assignments stop changing, or for at most `max_iter`
+//! iterations.
+//!
+//! Initial centroids are chosen using the k-means++ scheme: the first centroid
+//! is a uniformly sampled row, and every subsequent centroid is a row sampled with
+//! probability proportional to its squared distance to the nearest centroid
+//! chosen so far. Fix the random number generator using `set_rng` for
+//! reproducible results.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::cluster::KMeans;
+//! use rustlearn::datasets::iris;
+//!
+//! let (X, _) = iris::load_data();
+//!
+//! let mut model = KMeans::new(3, 100);
+//!
+//! model.fit(&X).unwrap();
+//!
+//! let clusters = model.predict(&X).unwrap();
+//!
+//! assert!(model.centroids().rows() == 3);
+//! ```
+
+use std::f32;
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, EncodableRng};
+
+use rand;
+use rand::distributions::{IndependentSample, Range};
+use rand::Rng;
+
+/// K-means clustering model.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KMeans {
+    n_clusters: usize,
+    max_iter: usize,
+
+    centroids: Array,
+    inertia: f32,
+    rng: EncodableRng,
+}
+
+impl KMeans {
+    /// Create a new, unfitted model finding `n_clusters` clusters
+    /// in at most `max_iter` iterations.
+    ///
+    /// # Panics
+    /// Panics if `n_clusters` is zero.
+    pub fn new(n_clusters: usize, max_iter: usize) -> KMeans {
+        assert!(n_clusters > 0, "Number of clusters must be positive");
+
+        KMeans {
+            n_clusters: n_clusters,
+            max_iter: max_iter,
+            centroids: Array::zeros(0, 0),
+            inertia: 0.0,
+            rng: EncodableRng::new(),
+        }
+    }
+
+    /// Set the random number generator used for initializing centroids.
+    pub fn set_rng(&mut self, rng: rand::StdRng) {
+        self.rng.rng = rng;
+    }
+
+    /// The cluster centroids, one per row.
+    pub fn centroids(&self) -> &Array {
+        &self.centroids
+    }
+
+    /// The sum of squared distances of the training rows
+    /// to their nearest centroid at the end of fitting.
+    pub fn inertia(&self) -> f32 {
+        self.inertia
+    }
+
+    /// Fit the model, running k-means++ initialization followed
+    /// by Lloyd's algorithm.
+    pub fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() < self.n_clusters {
+            return Err(RustlearnError::InvalidInput(
+                "Number of samples must be at least the number of clusters.",
+            ));
+        }
+
+        self.centroids = self.initial_centroids(X);
+
+        let mut assignments = vec![0; X.rows()];
+        let mut counts = vec![0; self.n_clusters];
+
+        for iteration in 0..self.max_iter {
+            let mut changed = false;
+
+            for row_idx in 0..X.rows() {
+                let (cluster, _) = self.nearest_centroid(X, row_idx);
+
+                if cluster != assignments[row_idx] {
+                    assignments[row_idx] = cluster;
+                    changed = true;
+                }
+            }
+
+            if iteration > 0 && !changed {
+                break;
+            }
+
+            let mut sums = Array::zeros(self.n_clusters, X.cols());
+
+            for count in counts.iter_mut() {
+                *count = 0;
+            }
+
+            for (row_idx, &cluster) in assignments.iter().enumerate() {
+                counts[cluster] += 1;
+
+                for col_idx in 0..X.cols() {
+                    *sums.get_mut(cluster, col_idx) += X.get(row_idx, col_idx);
+                }
+            }
+
+            // Empty clusters keep their previous centroid
+            for (cluster, &count) in counts.iter().enumerate() {
+                if count > 0 {
+                    for col_idx in 0..X.cols() {
+                        self.centroids.set(
+                            cluster,
+                            col_idx,
+                            sums.get(cluster, col_idx) / count as f32,
+                        );
+                    }
+                }
+            }
+        }
+
+        self.inertia = (0..X.rows())
+            .map(|row_idx| self.nearest_centroid(X, row_idx).1)
+            .sum();
+
+        Ok(())
+    }
+
+    /// Assign each row of `X` to the nearest centroid,
+    /// returning a column of cluster indices.
+    pub fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        if self.centroids.rows() == 0 {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.centroids.cols(), X));
+
+        Ok(Array::from(
+            (0..X.rows())
+                .map(|row_idx| self.nearest_centroid(X, row_idx).0 as f32)
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    fn initial_centroids(&mut self, X: &Array) -> Array {
+        let mut centroid_indices = Vec::with_capacity(self.n_clusters);
+
+        centroid_indices.push(Range::new(0, X.rows()).ind_sample(&mut self.rng.rng));
+
+        let mut distances = (0..X.rows())
+            .map(|row_idx| squared_distance(X, row_idx, X, centroid_indices[0]))
+            .collect::<Vec<_>>();
+
+        while centroid_indices.len() < self.n_clusters {
+            let total_distance = distances.iter().sum::<f32>();
+
+            let next_idx = if total_distance > 0.0 {
+                let threshold = self.rng.rng.gen::<f32>() * total_distance;
+                let mut cumulative_distance = 0.0;
+
+                distances
+                    .iter()
+                    .position(|&distance| {
+                        cumulative_distance += distance;
+                        distance > 0.0 && cumulative_distance >= threshold
+                    })
+                    .unwrap_or_else(|| distances.iter().rposition(|&d| d > 0.0).unwrap())
+            } else {
+                // All rows coincide with a centroid
+                Range::new(0, X.rows()).ind_sample(&mut self.rng.rng)
+            };
+
+            centroid_indices.push(next_idx);
+
+            for (row_idx, distance) in distances.iter_mut().enumerate() {
+                *distance = distance.min(squared_distance(X, row_idx, X, next_idx));
+            }
+        }
+
+        X.get_rows(&centroid_indices)
+    }
+
+    /// Return the index of, and the squared distance to, the
+    /// centroid nearest to the `row_idx`-th row of `X`.
+    fn nearest_centroid(&self, X: &Array, row_idx: usize) -> (usize, f32) {
+        let mut nearest = (0, f32::INFINITY);
+
+        for cluster in 0..self.centroids.rows() {
+            let distance = squared_distance(X, row_idx, &self.centroids, cluster);
+
+            if distance < nearest.1 {
+                nearest = (cluster, distance);
+            }
+        }
+
+        nearest
+    }
+}
+
+fn squared_distance(X: &Array, row_idx: usize, Y: &Array, other_row_idx: usize) -> f32 {
+    (0..X.cols())
+        .map(|col_idx| (X.get(row_idx, col_idx) - Y.get(other_row_idx, col_idx)).powi(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    fn make_blobs(centers: &[(f32, f32)], n_per_blob: usize) -> (Array, Vec<usize>) {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 0.5);
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for (label, &(x, y)) in centers.iter().enumerate() {
+            for _ in 0..n_per_blob {
+                rows.push(vec![
+                    x + normal.ind_sample(&mut rng) as f32,
+                    y + normal.ind_sample(&mut rng) as f32,
+                ]);
+                labels.push(label);
+            }
+        }
+
+        (Array::from(&rows), labels)
+    }
+
+    #[test]
+    fn test_k_means_blobs() {
+        let (X, labels) = make_blobs(&[(0.0, 0.0), (10.0, 10.0), (-10.0, 10.0)], 50);
+
+        let mut model = KMeans::new(3, 100);
+        model.set_rng(StdRng::from_seed(&[100]));
+        model.fit(&X).unwrap();
+
+        let clusters = model.predict(&X).unwrap();
+
+        // Every blob maps to a single, distinct cluster
+        let mut blob_clusters = Vec::new();
+
+        for blob in 0..3 {
+            let cluster = clusters.get(labels.iter().position(|&l| l == blob).unwrap(), 0);
+
+            for (row_idx, &label) in labels.iter().enumerate() {
+                if label == blob {
+                    assert!(clusters.get(row_idx, 0) == cluster);
+                }
+            }
+
+            assert!(!blob_clusters.contains(&cluster));
+            blob_clusters.push(cluster);
+        }
+
+        println!("Inertia {}", model.inertia());
+
+        // Expected inertia is 2 * 0.5^2 per row
+        assert!(model.inertia() / (X.rows() as f32) < 1.0);
+    }
+
+    #[test]
+    fn test_k_means_single_cluster() {
+        let X = Array::from(&vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]]);
+
+        let mut model = KMeans::new(1, 10);
+        model.fit(&X).unwrap();
+
+        assert!(model
+            .centroids()
+            .approx_eq(&Array::from(&vec![vec![3.0, 4.0]]), 1e-6));
+        assert!((model.inertia() - 16.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_k_means_errors() {
+        let model = KMeans::new(3, 10);
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+
+        let mut model = KMeans::new(3, 10);
+        assert!(model.fit(&Array::zeros(2, 2)).is_err());
+
+        model.fit(&Array::zeros(5, 2)).unwrap();
+        assert!(model.predict(&Array::zeros(2, 3)).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+//! Clustering models.
+
+pub mod k_means;
+
+pub use self::k_means::KMeans;
@@ -22,12 +22,46 @@
 //!     // Model fitting happens here
 //! }
 //! ```
+//!
+//! When only the validation scores are of interest, `cross_val_score`
+//! takes care of the loop:
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::cross_validation::cross_val_score;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//! use rustlearn::metrics::accuracy_score;
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let (X, y) = iris::load_data();
+//!
+//! let scores = cross_val_score(|| Hyperparameters::new(X.cols()).one_vs_rest(),
+//!                              &X,
+//!                              &y,
+//!                              accuracy_score,
+//!                              10,
+//!                              StdRng::from_seed(&[100]))
+//!     .unwrap();
+//!
+//! assert!(scores.len() == 10);
+//! # }
+//! ```
 
 use std::iter::Iterator;
 
 use rand;
 use rand::{Rng, StdRng};
 
+use prelude::*;
+
+use utils::check_matched_dimensions;
+
 
 pub struct CrossValidation {
     n_samples: usize,
@@ -71,6 +105,36 @@ impl CrossValidation {
         self.indices = (0..self.n_samples).collect::<Vec<_>>();
         self.rng.shuffle(&mut self.indices);
     }
+
+    /// Return the (training indices, validation indices) pairs of all folds.
+    ///
+    /// These are the folds produced by iterating over the cross validation
+    /// utility, regardless of how far it has been iterated. Being plain
+    /// vectors, the folds can be cloned and serialized, allowing several
+    /// models to be evaluated on exactly the same splits.
+    ///
+    /// When the number of samples is not divisible by the number of folds,
+    /// the remaining samples only appear in training sets.
+    pub fn splits(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        (0..self.n_folds).map(|fold_idx| self.fold_indices(fold_idx)).collect()
+    }
+
+    fn fold_indices(&self, fold_idx: usize) -> (Vec<usize>, Vec<usize>) {
+        let fold_step = self.n_samples / self.n_folds;
+
+        let validation_start = fold_idx * fold_step;
+        let validation_stop = (fold_idx + 1) * fold_step;
+
+        let train = (0..validation_start)
+            .chain(validation_stop..self.indices.len())
+            .map(|i| self.indices[i])
+            .collect::<Vec<_>>();
+        let test = (validation_start..validation_stop)
+            .map(|i| self.indices[i])
+            .collect::<Vec<_>>();
+
+        (train, test)
+    }
 }
 
 
@@ -80,20 +144,7 @@ impl Iterator for CrossValidation {
     fn next(&mut self) -> Option<(Vec<usize>, Vec<usize>)> {
 
         let ret = if self.iter < self.n_folds {
-            let fold_step = self.n_samples / self.n_folds;
-
-            let validation_start = self.iter * fold_step;
-            let validation_stop = (self.iter + 1) * fold_step;
-
-            let train = (0..validation_start)
-                .chain(validation_stop..self.indices.len())
-                .map(|i| self.indices[i])
-                .collect::<Vec<_>>();
-            let test = (validation_start..validation_stop)
-                .map(|i| self.indices[i])
-                .collect::<Vec<_>>();
-
-            Some((train, test))
+            Some(self.fold_indices(self.iter))
         } else {
             None
         };
@@ -105,11 +156,63 @@ impl Iterator for CrossValidation {
 }
 
 
+/// Evaluate a model using `n_folds`-fold cross-validation, returning
+/// the validation score of each fold.
+///
+/// For every fold, a fresh model is constructed using `build_model`,
+/// fitted on the training part, and scored on the validation part
+/// using `scorer`, which takes the true and predicted targets.
+/// The folds are determined by `rng`.
+pub fn cross_val_score<M, B, S>(build_model: B,
+                                X: &Array,
+                                y: &Array,
+                                scorer: S,
+                                n_folds: usize,
+                                rng: StdRng)
+                                -> Result<Vec<f32>, RustlearnError>
+    where M: for<'a> SupervisedModel<&'a Array>,
+          B: Fn() -> M,
+          S: Fn(&Array, &Array) -> f32
+{
+    try!(check_matched_dimensions(X, y));
+
+    if n_folds <= 1 || n_folds >= X.rows() {
+        return Err(RustlearnError::InvalidHyperparameter("Number of folds must be greater than one \
+                                                          and smaller than number of samples."));
+    }
+
+    let mut cv = CrossValidation::new(X.rows(), n_folds);
+    cv.set_rng(rng);
+
+    let mut scores = Vec::with_capacity(n_folds);
+
+    for (train_idx, test_idx) in cv {
+        let mut model = build_model();
+
+        try!(model.fit(&X.get_rows(&train_idx), &y.get_rows(&train_idx)));
+        let prediction = try!(model.predict(&X.get_rows(&test_idx)));
+
+        scores.push(scorer(&y.get_rows(&test_idx), &prediction));
+    }
+
+    Ok(scores)
+}
+
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::collections::HashSet;
 
+    use datasets::iris::load_data;
+    use ensemble::random_forest::Hyperparameters;
+    use metrics::accuracy_score;
+    use trees::decision_tree;
+
+    use rand::SeedableRng;
+
+    use serde_json;
+
     extern crate rand;
 
     #[test]
@@ -143,4 +246,114 @@ mod tests {
             assert!(set.len() == 100);
         }
     }
+
+
+    #[test]
+    fn materialized_splits() {
+        let (data, target) = load_data();
+
+        let mut cv = CrossValidation::new(data.rows(), 5);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        let splits = cv.splits();
+
+        let mut test_indices = HashSet::new();
+
+        for &(ref train, ref test) in &splits {
+            assert!(train.len() + test.len() == data.rows());
+            assert!(train.iter().all(|idx| !test.contains(idx)));
+
+            for &idx in test {
+                // Validation sets are disjoint
+                assert!(test_indices.insert(idx));
+            }
+        }
+
+        assert!(test_indices.len() == data.rows());
+
+        // Splits are the folds of the iterator, and survive serialization
+        assert!(cv.collect::<Vec<_>>() == splits);
+
+        let encoded = serde_json::to_string(&splits).unwrap();
+        let decoded: Vec<(Vec<usize>, Vec<usize>)> = serde_json::from_str(&encoded).unwrap();
+        assert!(decoded == splits);
+
+        let evaluate = |splits: &[(Vec<usize>, Vec<usize>)]| {
+            splits.iter()
+                .map(|&(ref train, ref test)| {
+                    let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+                    tree_params.max_features(4);
+
+                    let mut model = Hyperparameters::new(tree_params, 5)
+                        .seed(100)
+                        .one_vs_rest();
+
+                    model.fit(&data.get_rows(train), &target.get_rows(train)).unwrap();
+
+                    accuracy_score(&target.get_rows(test),
+                                   &model.predict(&data.get_rows(test)).unwrap())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert!(evaluate(&splits) == evaluate(&decoded));
+    }
+
+    #[test]
+    fn cross_val_score_random_forest() {
+        let (data, target) = load_data();
+
+        let build_model = || {
+            let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+            tree_params.min_samples_split(10)
+                .max_features(4)
+                .rng(StdRng::from_seed(&[100]));
+
+            Hyperparameters::new(tree_params, 10)
+                .rng(StdRng::from_seed(&[100]))
+                .one_vs_rest()
+        };
+
+        let scores = cross_val_score(build_model,
+                                     &data,
+                                     &target,
+                                     accuracy_score,
+                                     5,
+                                     StdRng::from_seed(&[100]))
+            .unwrap();
+
+        let mean_score = scores.iter().sum::<f32>() / scores.len() as f32;
+
+        println!("Mean accuracy {}", mean_score);
+
+        assert!(scores.len() == 5);
+        assert!(mean_score > 0.9);
+
+        let repeated_scores = cross_val_score(build_model,
+                                              &data,
+                                              &target,
+                                              accuracy_score,
+                                              5,
+                                              StdRng::from_seed(&[100]))
+            .unwrap();
+
+        assert!(scores == repeated_scores);
+    }
+
+    #[test]
+    fn cross_val_score_invalid_folds() {
+        let (data, target) = load_data();
+
+        let build_model = || {
+            Hyperparameters::new(decision_tree::Hyperparameters::new(data.cols()), 1).one_vs_rest()
+        };
+
+        assert!(cross_val_score(build_model,
+                                &data,
+                                &target,
+                                accuracy_score,
+                                1,
+                                StdRng::from_seed(&[100]))
+            .is_err());
+    }
 }
@@ -3,5 +3,5 @@
 pub mod cross_validation;
 pub mod shuffle_split;
 
-pub use self::cross_validation::CrossValidation;
+pub use self::cross_validation::{cross_val_score, CrossValidation};
 pub use self::shuffle_split::ShuffleSplit;
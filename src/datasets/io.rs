@@ -0,0 +1,492 @@
+//! Loading datasets from files.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::io::load_csv;
+//!
+//! // Use the last of five columns as the target.
+//! let (X, y) = load_csv("data.csv", true, Some(4)).unwrap();
+//! let y = y.unwrap();
+//!
+//! assert!(X.rows() == y.rows());
+//! ```
+//!
+//! Sparse data in the libsvm format can be loaded into a sparse array:
+//!
+//! ```no_run
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::io::load_libsvm;
+//!
+//! let (X, y) = load_libsvm("data.libsvm", None).unwrap();
+//!
+//! assert!(X.rows() == y.rows());
+//! ```
+//!
+//! # Compressed files
+//!
+//! When `rustlearn` is built with the optional `flate2` feature, files whose
+//! path ends in `.gz` are transparently decompressed while being read. Without
+//! the feature, loading a `.gz` file fails with an error.
+
+use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+
+#[cfg(feature = "flate2")]
+use flate2::read::GzDecoder;
+
+use prelude::*;
+
+/// Open the file at `path` for buffered reading, decompressing it
+/// if its path ends in `.gz`.
+#[cfg(feature = "flate2")]
+fn open_reader(path: &str) -> io::Result<Box<dyn BufRead>> {
+    let file = try!(File::open(path));
+
+    if path.ends_with(".gz") {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// Open the file at `path` for buffered reading, failing if
+/// it is gzip-compressed.
+#[cfg(not(feature = "flate2"))]
+fn open_reader(path: &str) -> io::Result<Box<dyn BufRead>> {
+    if path.ends_with(".gz") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Reading gzip-compressed files requires the flate2 feature.",
+        ));
+    }
+
+    Ok(Box::new(BufReader::new(try!(File::open(path)))))
+}
+
+/// Load a numeric CSV file into a dense array.
+///
+/// Files whose path ends in `.gz` are decompressed while reading,
+/// if `rustlearn` is built with the `flate2` feature.
+///
+/// If `has_header` is true, the first line of the file is skipped.
+/// If `target_col` is given, that column is split off into a separate
+/// `(n_rows, 1)` target array and the remaining columns form the
+/// `(n_rows, n_features)` feature array.
+///
+/// # Failures
+/// Will fail if the file cannot be read, if it contains no data rows,
+/// if rows have differing numbers of columns, if `target_col` is out
+/// of bounds, or if any cell cannot be parsed as a number. Rows and
+/// columns in error messages are zero-indexed and do not count the header.
+/// Malformed gzip-compressed files fail with an IO error.
+pub fn load_csv(
+    path: &str,
+    has_header: bool,
+    target_col: Option<usize>,
+) -> Result<(Array, Option<Array>), Box<dyn Error>> {
+    let reader = try!(open_reader(path));
+
+    let mut cols = None;
+    let mut rows = 0;
+    let mut data = Vec::new();
+    let mut target = Vec::new();
+
+    let skip = if has_header { 1 } else { 0 };
+
+    for line in reader.lines().skip(skip) {
+        let line = try!(line);
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let values = line.split(',').map(|x| x.trim()).collect::<Vec<_>>();
+
+        match cols {
+            None => {
+                if let Some(target_col) = target_col {
+                    if target_col >= values.len() {
+                        return Err(format!(
+                            "Target column {} out of bounds for {} columns.",
+                            target_col,
+                            values.len()
+                        ).into());
+                    }
+                }
+                cols = Some(values.len());
+            }
+            Some(cols) => {
+                if values.len() != cols {
+                    return Err(format!(
+                        "Row {} has {} columns, expected {}.",
+                        rows,
+                        values.len(),
+                        cols
+                    ).into());
+                }
+            }
+        }
+
+        for (col, value) in values.iter().enumerate() {
+            let value = match value.parse::<f32>() {
+                Ok(value) => value,
+                Err(_) => {
+                    return Err(format!(
+                        "Non-numeric value '{}' at row {}, column {}.",
+                        value, rows, col
+                    ).into())
+                }
+            };
+
+            if Some(col) == target_col {
+                target.push(value);
+            } else {
+                data.push(value);
+            }
+        }
+
+        rows += 1;
+    }
+
+    if rows == 0 {
+        return Err("Input file contains no data rows.".into());
+    }
+
+    let feature_cols = data.len() / rows;
+
+    if feature_cols == 0 {
+        return Err("Input file contains no feature columns.".into());
+    }
+
+    let mut X = Array::from(data);
+    X.reshape(rows, feature_cols);
+
+    let y = match target_col {
+        Some(_) => Some(Array::from(target)),
+        None => None,
+    };
+
+    Ok((X, y))
+}
+
+/// Load a file in the libsvm (svmlight) format into a sparse feature
+/// array and a `(n_rows, 1)` target array.
+///
+/// Files whose path ends in `.gz` are decompressed while reading,
+/// if `rustlearn` is built with the `flate2` feature.
+///
+/// Every line holds a target value followed by `index:value` pairs,
+/// with feature indices starting at 1. Blank lines are skipped, as is
+/// anything following a `#`. The number of columns is `n_features` if
+/// given, or else the largest feature index in the file.
+///
+/// # Failures
+/// Will fail if the file cannot be read, if it contains no data rows,
+/// if any target or feature value cannot be parsed as a number, or if
+/// a feature index is zero, malformed, or exceeds `n_features`. Rows in
+/// error messages are zero-indexed and do not count skipped lines.
+/// Malformed gzip-compressed files fail with an IO error.
+pub fn load_libsvm(
+    path: &str,
+    n_features: Option<usize>,
+) -> Result<(SparseRowArray, Array), Box<dyn Error>> {
+    let reader = try!(open_reader(path));
+
+    let mut rows = Vec::new();
+    let mut target = Vec::new();
+    let mut max_index = 0;
+
+    for line in reader.lines() {
+        let line = try!(line);
+
+        let line = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => &line[..],
+        };
+
+        let mut tokens = line.split_whitespace();
+
+        let label = match tokens.next() {
+            Some(label) => label,
+            None => continue,
+        };
+
+        let row_idx = target.len();
+
+        match label.parse::<f32>() {
+            Ok(label) => target.push(label),
+            Err(_) => {
+                return Err(format!("Non-numeric target '{}' at row {}.", label, row_idx).into())
+            }
+        }
+
+        let mut row = Vec::new();
+
+        for token in tokens {
+            let mut parts = token.splitn(2, ':');
+
+            let (index, value) = match (parts.next(), parts.next()) {
+                (Some(index), Some(value)) => (index, value),
+                _ => {
+                    return Err(format!(
+                        "Malformed feature '{}' at row {}, expected index:value.",
+                        token, row_idx
+                    ).into())
+                }
+            };
+
+            let index = match index.parse::<usize>() {
+                Ok(index) if index > 0 => index,
+                _ => {
+                    return Err(format!(
+                        "Invalid feature index '{}' at row {}, indices start at 1.",
+                        index, row_idx
+                    ).into())
+                }
+            };
+
+            if let Some(n_features) = n_features {
+                if index > n_features {
+                    return Err(format!(
+                        "Feature index {} at row {} out of bounds for {} features.",
+                        index, row_idx, n_features
+                    ).into());
+                }
+            }
+
+            let value = match value.parse::<f32>() {
+                Ok(value) => value,
+                Err(_) => {
+                    return Err(format!(
+                        "Non-numeric value '{}' at row {}, feature {}.",
+                        value, row_idx, index
+                    ).into())
+                }
+            };
+
+            max_index = max_index.max(index);
+            row.push((index - 1, value));
+        }
+
+        rows.push(row);
+    }
+
+    if rows.is_empty() {
+        return Err("Input file contains no data rows.".into());
+    }
+
+    let mut X = SparseRowArray::zeros(rows.len(), n_features.unwrap_or(max_index));
+
+    for (row_idx, row) in rows.iter().enumerate() {
+        for &(col_idx, value) in row.iter() {
+            if value != 0.0 {
+                X.set(row_idx, col_idx, value);
+            }
+        }
+    }
+
+    Ok((X, Array::from(target)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{remove_file, File};
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let mut path = env::temp_dir();
+        path.push(name);
+
+        let mut file = File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn load_with_header() {
+        let path = write_temp_file(
+            "rustlearn_load_with_header.csv",
+            "a,b,label\n1.0,2.0,0\n3.0,4.0,1\n5.0,6.0,1\n",
+        );
+
+        let (X, y) = load_csv(path.to_str().unwrap(), true, Some(2)).unwrap();
+        remove_file(&path).unwrap();
+
+        let y = y.unwrap();
+
+        assert!(allclose(
+            &X,
+            &Array::from(&vec![vec![1.0, 2.0], vec![3.0, 4.0], vec![5.0, 6.0]])
+        ));
+        assert!(allclose(&y, &Array::from(vec![0.0, 1.0, 1.0])));
+    }
+
+    #[test]
+    fn load_without_header() {
+        let path = write_temp_file(
+            "rustlearn_load_without_header.csv",
+            "0,1.0,2.0\n1,3.0,4.0\n",
+        );
+
+        let (X, y) = load_csv(path.to_str().unwrap(), false, None).unwrap();
+        remove_file(&path).unwrap();
+
+        assert!(y.is_none());
+        assert!(allclose(
+            &X,
+            &Array::from(&vec![vec![0.0, 1.0, 2.0], vec![1.0, 3.0, 4.0]])
+        ));
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn load_gzipped() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let contents = "a,b,label\n1.0,2.0,0\n3.0,4.0,1\n5.0,6.0,1\n";
+
+        let plain_path = write_temp_file("rustlearn_load_gzipped.csv", contents);
+
+        let mut gzipped_path = env::temp_dir();
+        gzipped_path.push("rustlearn_load_gzipped.csv.gz");
+
+        let mut encoder =
+            GzEncoder::new(File::create(&gzipped_path).unwrap(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let (X, y) = load_csv(plain_path.to_str().unwrap(), true, Some(2)).unwrap();
+        let (gzipped_X, gzipped_y) =
+            load_csv(gzipped_path.to_str().unwrap(), true, Some(2)).unwrap();
+        remove_file(&plain_path).unwrap();
+        remove_file(&gzipped_path).unwrap();
+
+        assert!(X == gzipped_X);
+        assert!(y.unwrap() == gzipped_y.unwrap());
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn load_libsvm_gzipped() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let contents = "1 1:0.5 3:2.0\n0 2:1.5\n1 1:1.0 2:-1.0 3:3.0\n";
+
+        let plain_path = write_temp_file("rustlearn_load_libsvm_gzipped.libsvm", contents);
+
+        let mut gzipped_path = env::temp_dir();
+        gzipped_path.push("rustlearn_load_libsvm_gzipped.libsvm.gz");
+
+        let mut encoder =
+            GzEncoder::new(File::create(&gzipped_path).unwrap(), Compression::default());
+        encoder.write_all(contents.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let (X, y) = load_libsvm(plain_path.to_str().unwrap(), None).unwrap();
+        let (gzipped_X, gzipped_y) = load_libsvm(gzipped_path.to_str().unwrap(), None).unwrap();
+        remove_file(&plain_path).unwrap();
+        remove_file(&gzipped_path).unwrap();
+
+        assert!(X.todense() == gzipped_X.todense());
+        assert!(y == gzipped_y);
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn load_malformed_gzip() {
+        let path = write_temp_file("rustlearn_load_malformed_gzip.csv.gz", "1.0,2.0\n3.0,4.0\n");
+
+        let result = load_csv(path.to_str().unwrap(), false, None);
+        remove_file(&path).unwrap();
+
+        assert!(result.unwrap_err().downcast_ref::<io::Error>().is_some());
+    }
+
+    #[cfg(not(feature = "flate2"))]
+    #[test]
+    fn load_gzipped_without_feature() {
+        let path = write_temp_file("rustlearn_load_gzipped_without_feature.csv.gz", "1.0,2.0\n");
+
+        let result = load_csv(path.to_str().unwrap(), false, None);
+        remove_file(&path).unwrap();
+
+        let error = result.unwrap_err();
+        assert!(error.downcast_ref::<io::Error>().unwrap().kind() == io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn load_non_numeric() {
+        let path = write_temp_file(
+            "rustlearn_load_non_numeric.csv",
+            "1.0,2.0\n3.0,abc\n",
+        );
+
+        let result = load_csv(path.to_str().unwrap(), false, None);
+        remove_file(&path).unwrap();
+
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("row 1, column 1"));
+    }
+
+    #[test]
+    fn load_libsvm_sparse() {
+        let path = write_temp_file(
+            "rustlearn_load_libsvm_sparse.libsvm",
+            "# comment\n1 1:0.5 3:2.0\n\n-1 2:1.5 # trailing comment\n1\n",
+        );
+
+        let (X, y) = load_libsvm(path.to_str().unwrap(), None).unwrap();
+        let (wide_X, _) = load_libsvm(path.to_str().unwrap(), Some(5)).unwrap();
+        remove_file(&path).unwrap();
+
+        assert!(X.rows() == 3 && X.cols() == 3);
+        assert!(X.nnz() == 3);
+        assert!(allclose(
+            &X.todense(),
+            &Array::from(&vec![
+                vec![0.5, 0.0, 2.0],
+                vec![0.0, 1.5, 0.0],
+                vec![0.0, 0.0, 0.0],
+            ])
+        ));
+        assert!(allclose(&y, &Array::from(vec![1.0, -1.0, 1.0])));
+
+        assert!(wide_X.cols() == 5);
+    }
+
+    #[test]
+    fn load_libsvm_invalid() {
+        let cases = [
+            ("1 0:1.0\n", "indices start at 1"),
+            ("1 1:1.0\n0 2-1.0\n", "row 1, expected index:value"),
+            ("1 1:abc\n", "row 0, feature 1"),
+            ("abc 1:1.0\n", "Non-numeric target"),
+            ("1 4:1.0\n", "out of bounds for 3 features"),
+            ("# only a comment\n", "no data rows"),
+        ];
+
+        for (idx, &(contents, expected)) in cases.iter().enumerate() {
+            let path = write_temp_file(
+                &format!("rustlearn_load_libsvm_invalid_{}.libsvm", idx),
+                contents,
+            );
+
+            let result = load_libsvm(path.to_str().unwrap(), Some(3));
+            remove_file(&path).unwrap();
+
+            let message = result.err().unwrap().to_string();
+            assert!(message.contains(expected), "{}", message);
+        }
+    }
+}
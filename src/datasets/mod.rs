@@ -2,6 +2,8 @@
 
 pub mod iris;
 pub mod boston;
+pub mod io;
+pub mod synthetic;
 
 #[cfg(test)]
 #[cfg(any(feature = "all_tests", feature = "bench"))]
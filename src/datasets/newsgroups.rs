@@ -2,10 +2,15 @@
 //!
 //! This is only available when running the full test suite.
 
+use std::cmp::Ordering;
+
 use csv;
 
+use rand::Rng;
+
 use prelude::*;
 use feature_extraction::dict_vectorizer::*;
+use utils::seeded_rng;
 
 /// Load the newsgroups dataset.
 pub fn load_data() -> (SparseRowArray, Array) {
@@ -32,3 +37,71 @@ pub fn load_data() -> (SparseRowArray, Array) {
 
     (X, y)
 }
+
+
+/// Load a reproducible random subset of the newsgroups dataset, holding
+/// `n_per_class` documents of every class.
+///
+/// Documents are drawn without replacement using a random number generator
+/// seeded with `seed`, so that the same seed always selects the same documents.
+/// Rows are ordered by class, and the columns are those of the full dataset.
+///
+/// # Failures
+/// Returns an error if any class has fewer than `n_per_class` documents.
+pub fn load_subset(n_per_class: usize, seed: u64) -> Result<(SparseRowArray, Array), RustlearnError> {
+
+    let (X, y) = load_data();
+
+    let mut classes = y.data().clone();
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    classes.dedup();
+
+    let mut rng = seeded_rng(seed);
+    let mut indices = Vec::with_capacity(n_per_class * classes.len());
+
+    for &class in &classes {
+        let mut class_indices = (0..y.rows())
+            .filter(|&idx| y.get(idx, 0) == class)
+            .collect::<Vec<_>>();
+
+        if class_indices.len() < n_per_class {
+            return Err(RustlearnError::InvalidInput("Not enough documents in a class for the \
+                                                     requested subset size."));
+        }
+
+        rng.shuffle(&mut class_indices);
+        indices.extend_from_slice(&class_indices[..n_per_class]);
+    }
+
+    Ok((X.get_rows(&indices), y.get_rows(&indices)))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use prelude::*;
+
+    use super::load_subset;
+
+    #[test]
+    fn subset_reproducibility() {
+        let (X, y) = load_subset(10, 42).unwrap();
+        let (X_again, y_again) = load_subset(10, 42).unwrap();
+        let (X_other, _) = load_subset(10, 43).unwrap();
+
+        assert!(X.rows() == 200);
+        assert!(y.rows() == 200);
+
+        for class in 0..20 {
+            assert!(y.data().iter().filter(|&&x| x == class as f32).count() == 10);
+        }
+
+        assert!(y.data() == y_again.data());
+        assert!(X.indices() == X_again.indices());
+        assert!(X.data() == X_again.data());
+        assert!(X.indices() != X_other.indices());
+
+        assert!(load_subset(1000, 42).is_err());
+    }
+}
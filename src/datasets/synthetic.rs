@@ -0,0 +1,273 @@
+//! Synthetic dataset generators.
+//!
+//! Useful for quick tests and benchmarks where a real dataset
+//! is not needed.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::synthetic::make_classification;
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let mut rng = StdRng::from_seed(&[100]);
+//! let (X, y) = make_classification(100, 10, 3, 4, &mut rng);
+//!
+//! assert!(X.rows() == 100 && X.cols() == 10);
+//! assert!(y.rows() == 100);
+//! # }
+//! ```
+
+use rand::distributions::normal::Normal;
+use rand::distributions::{IndependentSample, Range};
+use rand::{Rng, StdRng};
+
+use prelude::*;
+
+/// Generate a random classification problem.
+///
+/// Each of the `n_classes` classes is a Gaussian cluster with unit
+/// variance around a randomly drawn centroid in the space of the first
+/// `n_informative` features. The remaining features are standard normal
+/// noise that carries no information about the class. Rows are shuffled,
+/// and classes are (up to rounding) equally represented.
+///
+/// Returns a tuple of (`n_samples` by `n_features` features, class labels),
+/// with class labels taking values `0.0` to `n_classes - 1`.
+///
+/// # Panics
+/// Will panic if `n_informative` is zero or greater than `n_features`,
+/// or if `n_classes` is less than two.
+pub fn make_classification(
+    n_samples: usize,
+    n_features: usize,
+    n_classes: usize,
+    n_informative: usize,
+    rng: &mut StdRng,
+) -> (Array, Array) {
+    assert!(n_informative > 0, "Need at least one informative feature");
+    assert!(
+        n_informative <= n_features,
+        "Number of informative features must not exceed number of features"
+    );
+    assert!(n_classes > 1, "Need at least two classes");
+
+    let class_separation = 2.0;
+    let normal = Normal::new(0.0, 1.0);
+
+    let centroids = (0..n_classes)
+        .map(|_| {
+            (0..n_informative)
+                .map(|_| (class_separation * normal.ind_sample(rng)) as f32)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut labels = (0..n_samples).map(|i| i % n_classes).collect::<Vec<_>>();
+    rng.shuffle(&mut labels);
+
+    let mut X = Array::zeros(n_samples, n_features);
+
+    for (row_idx, &label) in labels.iter().enumerate() {
+        for col_idx in 0..n_features {
+            let noise = normal.ind_sample(rng) as f32;
+
+            let value = if col_idx < n_informative {
+                centroids[label][col_idx] + noise
+            } else {
+                noise
+            };
+
+            X.set(row_idx, col_idx, value);
+        }
+    }
+
+    let y = Array::from(labels.iter().map(|&x| x as f32).collect::<Vec<_>>());
+
+    (X, y)
+}
+
+/// Generate a random linear regression problem.
+///
+/// Features are drawn from a standard normal distribution. The target
+/// is a linear combination of the first `n_informative` features, with
+/// coefficients drawn uniformly from `[0, 100)`, plus Gaussian noise with
+/// standard deviation `noise`. The remaining features do not affect the target.
+///
+/// Returns a tuple of (`n_samples` by `n_features` features, targets). Use
+/// [`make_regression_with_coefficients`](fn.make_regression_with_coefficients.html)
+/// to also obtain the true coefficients.
+///
+/// # Panics
+/// Will panic if `n_informative` is greater than `n_features`.
+pub fn make_regression(
+    n_samples: usize,
+    n_features: usize,
+    n_informative: usize,
+    noise: f32,
+    rng: &mut StdRng,
+) -> (Array, Array) {
+    let (X, y, _) =
+        make_regression_with_coefficients(n_samples, n_features, n_informative, noise, rng);
+
+    (X, y)
+}
+
+/// Generate a random linear regression problem, as in
+/// [`make_regression`](fn.make_regression.html), additionally
+/// returning the `n_features` by 1 array of true coefficients.
+///
+/// Coefficients of the non-informative features are zero.
+///
+/// # Panics
+/// Will panic if `n_informative` is greater than `n_features`.
+pub fn make_regression_with_coefficients(
+    n_samples: usize,
+    n_features: usize,
+    n_informative: usize,
+    noise: f32,
+    rng: &mut StdRng,
+) -> (Array, Array, Array) {
+    assert!(
+        n_informative <= n_features,
+        "Number of informative features must not exceed number of features"
+    );
+
+    let normal = Normal::new(0.0, 1.0);
+    let uniform = Range::new(0.0, 100.0);
+
+    let mut coefficients = Array::zeros(n_features, 1);
+
+    for idx in 0..n_informative {
+        coefficients.set(idx, 0, uniform.ind_sample(rng));
+    }
+
+    let mut X = Array::zeros(n_samples, n_features);
+
+    for row_idx in 0..n_samples {
+        for col_idx in 0..n_features {
+            X.set(row_idx, col_idx, normal.ind_sample(rng) as f32);
+        }
+    }
+
+    let mut y = X.dot(&coefficients);
+
+    if noise > 0.0 {
+        for value in y.as_mut_slice().iter_mut() {
+            *value += noise * normal.ind_sample(rng) as f32;
+        }
+    }
+
+    (X, y, coefficients)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, StdRng};
+
+    use linear_models::Ridge;
+    use metrics::accuracy_score;
+    use trees::decision_tree::Hyperparameters;
+
+    use super::*;
+
+    #[test]
+    fn classification_shape() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_classification(90, 5, 3, 2, &mut rng);
+
+        assert!(X.rows() == 90 && X.cols() == 5);
+        assert!(y.rows() == 90 && y.cols() == 1);
+
+        for class in 0..3 {
+            let count = y.data().iter().filter(|&&v| v == class as f32).count();
+            assert!(count == 30);
+        }
+    }
+
+    #[test]
+    fn classification_reproducible() {
+        let (X1, y1) = make_classification(50, 5, 2, 3, &mut StdRng::from_seed(&[100]));
+        let (X2, y2) = make_classification(50, 5, 2, 3, &mut StdRng::from_seed(&[100]));
+
+        assert!(X1.data() == X2.data());
+        assert!(y1.data() == y2.data());
+    }
+
+    #[test]
+    fn classification_learnable() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_classification(300, 10, 3, 4, &mut rng);
+
+        let (train, test) = (0..200, 200..300);
+
+        let mut model = Hyperparameters::new(X.cols())
+            .min_samples_split(5)
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+
+        model.fit(&X.get_rows(&train), &y.get_rows(&train)).unwrap();
+
+        let accuracy = accuracy_score(
+            &y.get_rows(&test),
+            &model.predict(&X.get_rows(&test)).unwrap(),
+        );
+
+        println!("Accuracy {}", accuracy);
+
+        // Chance is 1/3.
+        assert!(accuracy > 0.6);
+    }
+
+    #[test]
+    fn regression_shape() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, coefficients) = make_regression_with_coefficients(50, 6, 3, 1.0, &mut rng);
+
+        assert!(X.rows() == 50 && X.cols() == 6);
+        assert!(y.rows() == 50 && y.cols() == 1);
+        assert!(coefficients.rows() == 6);
+
+        for idx in 3..6 {
+            assert!(coefficients.get(idx, 0) == 0.0);
+        }
+
+        let (X2, y2) = make_regression(50, 6, 3, 1.0, &mut StdRng::from_seed(&[100]));
+
+        assert!(X.data() == X2.data());
+        assert!(y.data() == y2.data());
+    }
+
+    #[test]
+    fn regression_coefficient_recovery() {
+        let mut rng = StdRng::from_seed(&[100]);
+
+        // Ordinary least squares recovers the true coefficients exactly from
+        // noise-free targets, and approximately from noisy ones.
+        for &(noise, tolerance) in &[(0.0, 1e-3), (2.0, 0.2)] {
+            let (X, y, coefficients) =
+                make_regression_with_coefficients(2000, 5, 3, noise, &mut rng);
+
+            let mut model = Ridge::new(0.0);
+            model.fit_intercept(false);
+            model.fit(&X, &y).unwrap();
+
+            for col_idx in 0..X.cols() {
+                let expected = coefficients.get(col_idx, 0);
+                let estimated = model.coefficients().get(col_idx, 0);
+
+                println!(
+                    "Coefficient {}: true {} estimated {}",
+                    col_idx, expected, estimated
+                );
+
+                assert!((expected - estimated).abs() < tolerance);
+            }
+        }
+    }
+}
@@ -0,0 +1,5 @@
+//! Matrix decomposition and dimensionality reduction.
+
+pub mod pca;
+
+pub use self::pca::PCA;
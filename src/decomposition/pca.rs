@@ -0,0 +1,391 @@
+//! Principal component analysis.
+//!
+//! Projects the data onto the `n_components` orthogonal directions of largest
+//! variance. The data is centered using the column means estimated during
+//! fitting, and the principal components are found as the leading eigenvectors
+//! of the sample covariance matrix, computed using the Jacobi eigenvalue
+//! algorithm. This is exact, but costs `O(d^3)` in the number of features `d`,
+//! and is therefore best suited to data with up to a few hundred features.
+//!
+//! The sign of each component is chosen so that its entry of largest
+//! absolute value is positive, making the results deterministic.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::decomposition::PCA;
+//!
+//! let (X, _) = iris::load_data();
+//!
+//! let mut pca = PCA::new(2);
+//! let X_reduced = pca.fit_transform(&X).unwrap();
+//!
+//! assert!(X_reduced.rows() == X.rows() && X_reduced.cols() == 2);
+//! assert!(pca.explained_variance_ratio()[0] > 0.9);
+//!
+//! let X_reconstructed = pca.inverse_transform(&X_reduced).unwrap();
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::check_data_dimensionality;
+
+/// Maximum number of sweeps of the Jacobi eigenvalue algorithm.
+const MAX_JACOBI_SWEEPS: usize = 100;
+
+/// Principal component analysis.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PCA {
+    n_components: usize,
+
+    mean: Vec<f32>,
+    components: Array,
+    explained_variance: Vec<f32>,
+    explained_variance_ratio: Vec<f32>,
+}
+
+impl PCA {
+    /// Create a new, unfitted model retaining `n_components` components.
+    ///
+    /// # Panics
+    /// Panics if `n_components` is zero.
+    pub fn new(n_components: usize) -> PCA {
+        assert!(n_components > 0, "Number of components must be positive");
+
+        PCA {
+            n_components: n_components,
+            mean: Vec::new(),
+            components: Array::zeros(0, 0),
+            explained_variance: Vec::new(),
+            explained_variance_ratio: Vec::new(),
+        }
+    }
+
+    /// The principal components, one per row, ordered
+    /// by decreasing explained variance.
+    pub fn components(&self) -> &Array {
+        &self.components
+    }
+
+    /// Per-column means estimated during fitting.
+    pub fn mean(&self) -> &[f32] {
+        &self.mean
+    }
+
+    /// The variance of the data along each of the components.
+    pub fn explained_variance(&self) -> &[f32] {
+        &self.explained_variance
+    }
+
+    /// The fraction of the total variance of the data
+    /// explained by each of the components.
+    pub fn explained_variance_ratio(&self) -> &[f32] {
+        &self.explained_variance_ratio
+    }
+
+    /// Map data in the reduced space back to the original feature space.
+    pub fn inverse_transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(self.check_fitted());
+        try!(check_data_dimensionality(self.n_components, X));
+
+        let mut reconstructed = X.dot(&self.components);
+
+        for row_idx in 0..reconstructed.rows() {
+            for (col_idx, &mean) in self.mean.iter().enumerate() {
+                *reconstructed.get_mut(row_idx, col_idx) += mean;
+            }
+        }
+
+        Ok(reconstructed)
+    }
+
+    fn check_fitted(&self) -> Result<(), RustlearnError> {
+        if self.mean.is_empty() {
+            Err(RustlearnError::NotFitted(
+                "PCA must be fit before transforming",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<'a> Transformer<&'a Array> for PCA {
+    /// Estimate the column means and principal components of `X`.
+    fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() < 2 {
+            return Err(RustlearnError::InvalidInput(
+                "At least two samples are required to fit PCA.",
+            ));
+        }
+
+        if self.n_components > X.cols() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Number of components exceeds the number of features.",
+            ));
+        }
+
+        let num_rows = X.rows() as f64;
+        let dim = X.cols();
+
+        let mean = X
+            .iter_columns()
+            .map(|column| column.iter().map(|x| x as f64).sum::<f64>() / num_rows)
+            .collect::<Vec<_>>();
+
+        let mut covariance = vec![vec![0.0; dim]; dim];
+
+        for row_idx in 0..X.rows() {
+            for i in 0..dim {
+                let x_i = X.get(row_idx, i) as f64 - mean[i];
+
+                for j in i..dim {
+                    covariance[i][j] += x_i * (X.get(row_idx, j) as f64 - mean[j]);
+                }
+            }
+        }
+
+        for i in 0..dim {
+            for j in i..dim {
+                covariance[i][j] /= num_rows - 1.0;
+                covariance[j][i] = covariance[i][j];
+            }
+        }
+
+        let (eigenvalues, eigenvectors) = symmetric_eigen(covariance);
+
+        let mut order = (0..dim).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+            eigenvalues[b]
+                .partial_cmp(&eigenvalues[a])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        // Covariance matrices are positive semidefinite; clamp rounding errors.
+        let total_variance = eigenvalues.iter().map(|&x| x.max(0.0)).sum::<f64>();
+
+        let mut components = Array::zeros(self.n_components, dim);
+        self.explained_variance = Vec::with_capacity(self.n_components);
+        self.explained_variance_ratio = Vec::with_capacity(self.n_components);
+
+        for (component_idx, &eigen_idx) in order.iter().take(self.n_components).enumerate() {
+            let component = (0..dim)
+                .map(|i| eigenvectors[i][eigen_idx])
+                .collect::<Vec<_>>();
+
+            let largest = component
+                .iter()
+                .max_by(|a, b| a.abs().partial_cmp(&b.abs()).unwrap_or(Ordering::Equal))
+                .unwrap();
+            let sign = if *largest < 0.0 { -1.0 } else { 1.0 };
+
+            for (i, &x) in component.iter().enumerate() {
+                components.set(component_idx, i, (sign * x) as f32);
+            }
+
+            let variance = eigenvalues[eigen_idx].max(0.0);
+
+            self.explained_variance.push(variance as f32);
+            self.explained_variance_ratio.push(if total_variance > 0.0 {
+                (variance / total_variance) as f32
+            } else {
+                0.0
+            });
+        }
+
+        self.mean = mean.iter().map(|&x| x as f32).collect();
+        self.components = components;
+
+        Ok(())
+    }
+
+    /// Project `X` onto the principal components.
+    fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(self.check_fitted());
+        try!(check_data_dimensionality(self.mean.len(), X));
+
+        let mut centered = X.clone();
+
+        for row_idx in 0..centered.rows() {
+            for (col_idx, &mean) in self.mean.iter().enumerate() {
+                *centered.get_mut(row_idx, col_idx) -= mean;
+            }
+        }
+
+        Ok(centered.dot(&self.components.clone().T()))
+    }
+}
+
+/// Compute the eigenvalues and eigenvectors of a symmetric matrix using the
+/// cyclic Jacobi eigenvalue algorithm. The `i`-th eigenvector is stored in the
+/// `i`-th column of the returned matrix.
+fn symmetric_eigen(mut a: Vec<Vec<f64>>) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = a.len();
+
+    let mut v = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        v[i][i] = 1.0;
+    }
+
+    for _ in 0..MAX_JACOBI_SWEEPS {
+        let off_diagonal = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j].powi(2))
+            .sum::<f64>();
+        let diagonal = (0..n).map(|i| a[i][i].powi(2)).sum::<f64>();
+
+        if off_diagonal <= 1e-24 * diagonal.max(1e-300) {
+            break;
+        }
+
+        for p in 0..n {
+            for q in (p + 1)..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+
+                // Rotation angle zeroing out a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta.powi(2) + 1.0).sqrt());
+                let c = 1.0 / (t.powi(2) + 1.0).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let a_kp = a[k][p];
+                    let a_kq = a[k][q];
+                    a[k][p] = c * a_kp - s * a_kq;
+                    a[k][q] = s * a_kp + c * a_kq;
+                }
+
+                for k in 0..n {
+                    let a_pk = a[p][k];
+                    let a_qk = a[q][k];
+                    a[p][k] = c * a_pk - s * a_qk;
+                    a[q][k] = s * a_pk + c * a_qk;
+                }
+
+                for k in 0..n {
+                    let v_kp = v[k][p];
+                    let v_kq = v[k][q];
+                    v[k][p] = c * v_kp - s * v_kq;
+                    v[k][q] = s * v_kp + c * v_kq;
+                }
+            }
+        }
+    }
+
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    /// A 100 by 5 array of rank two, offset from the origin.
+    fn rank_two_data() -> Array {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let latent = Array::from_vec(
+            (0..200)
+                .map(|_| normal.ind_sample(&mut rng) as f32)
+                .collect(),
+            100,
+            2,
+        )
+        .unwrap();
+
+        let loadings = Array::from(&vec![
+            vec![1.0, 2.0, 0.0, -1.0, 3.0],
+            vec![0.5, -1.0, 2.0, 0.0, 1.0],
+        ]);
+
+        let mut X = latent.dot(&loadings);
+        X.add_inplace(10.0);
+
+        X
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let (eigenvalues, eigenvectors) = symmetric_eigen(vec![vec![2.0, 1.0], vec![1.0, 2.0]]);
+
+        let mut sorted = eigenvalues.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert!((sorted[0] - 1.0).abs() < 1e-10);
+        assert!((sorted[1] - 3.0).abs() < 1e-10);
+
+        for i in 0..2 {
+            let (x, y) = (eigenvectors[0][i], eigenvectors[1][i]);
+            assert!((2.0 * x + y - eigenvalues[i] * x).abs() < 1e-10);
+            assert!((x + 2.0 * y - eigenvalues[i] * y).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_pca_reconstruction() {
+        let X = rank_two_data();
+
+        let mut pca = PCA::new(2);
+        let reduced = pca.fit_transform(&X).unwrap();
+        let reconstructed = pca.inverse_transform(&reduced).unwrap();
+
+        assert!(reduced.cols() == 2);
+        assert!(reconstructed.approx_eq(&X, 1e-3));
+
+        let ratio = pca.explained_variance_ratio();
+        assert!((ratio[0] + ratio[1] - 1.0).abs() < 1e-4);
+        assert!(ratio[0] >= ratio[1]);
+    }
+
+    #[test]
+    fn test_pca_orthonormal_components() {
+        let X = rank_two_data();
+
+        let mut pca = PCA::new(3);
+        pca.fit(&X).unwrap();
+
+        let components = pca.components();
+        let gram = components.dot(&components.clone().T());
+
+        assert!(gram.approx_eq(&Array::eye(3), 1e-4));
+    }
+
+    #[test]
+    fn test_fit_transform() {
+        let X = rank_two_data();
+
+        let mut pca = PCA::new(2);
+        let transformed = pca.fit_transform(&X).unwrap();
+
+        let mut fitted = PCA::new(2);
+        fitted.fit(&X).unwrap();
+
+        assert!(transformed == fitted.transform(&X).unwrap());
+    }
+
+    #[test]
+    fn test_pca_errors() {
+        let pca = PCA::new(2);
+        assert!(pca.transform(&Array::zeros(3, 3)).is_err());
+
+        let mut pca = PCA::new(4);
+        assert!(pca.fit(&Array::zeros(3, 3)).is_err());
+
+        let mut pca = PCA::new(2);
+        pca.fit(&rank_two_data()).unwrap();
+        assert!(pca.transform(&Array::zeros(3, 3)).is_err());
+        assert!(pca.inverse_transform(&Array::zeros(3, 3)).is_err());
+    }
+}
@@ -0,0 +1,349 @@
+//! Bagging ensembles over arbitrary base models.
+//!
+//! A `BaggingClassifier` fits a number of copies of a base model, each
+//! on a bootstrap sample of the training data (drawn with replacement, of the same
+//! size as the training data). The decision function of the ensemble is the average
+//! of the decision functions of its models; this works best when the base models
+//! output probabilities. Bagging reduces the variance of unstable models, much like
+//! [random forests](../random_forest/index.html) do for decision trees.
+//!
+//! The base models are created by a builder closure when the ensemble is constructed,
+//! so any model can be bagged. Two-class models with a single-column decision function
+//! predict the positive class when the averaged decision function exceeds 0.5, a threshold
+//! that can be changed using [`BaggingClassifier::set_threshold`](struct.BaggingClassifier.html#method.set_threshold)
+//! for models outputting margins rather than probabilities. Natively multiclass models
+//! (such as [k-nearest neighbors](../../neighbors/kneighbors_classifier/index.html))
+//! must output one column per class, and predict the class with the highest average.
+//! Two-class models can be extended to multiclass problems by wrapping the ensemble in a
+//! [`OneVsRestWrapper`](../../multiclass/struct.OneVsRestWrapper.html).
+//!
+//! Bootstrap samples are drawn separately within each class, holding as many samples of
+//! every class as the training data, so that every model is fit on all classes and the
+//! columns of their decision functions line up.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//!
+//! use rustlearn::datasets::iris;
+//! use rustlearn::ensemble::bagging::BaggingClassifier;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//! use rustlearn::multiclass::OneVsRestWrapper;
+//!
+//! let (data, target) = iris::load_data();
+//!
+//! let params = Hyperparameters::new(data.cols());
+//! let bagging = BaggingClassifier::new(|| params.build(), 10);
+//!
+//! let mut model = OneVsRestWrapper::new(bagging);
+//! model.fit(&data, &target).unwrap();
+//!
+//! let prediction = model.predict(&data).unwrap();
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::{check_matched_dimensions, predict_from_decision, EncodableRng};
+
+use rand::distributions::{IndependentSample, Range};
+use rand::{Rng, StdRng};
+
+fn default_threshold() -> f32 {
+    0.5
+}
+
+/// An ensemble of base models fit on bootstrap samples.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BaggingClassifier<T> {
+    estimators: Vec<T>,
+    class_labels: Vec<f32>,
+    rng: EncodableRng,
+    #[serde(default = "default_threshold")]
+    threshold: f32,
+}
+
+impl<T> BaggingClassifier<T> {
+    /// Create a new ensemble of `n_estimators` unfitted models, each created by `builder`.
+    ///
+    /// # Panics
+    /// Panics if `n_estimators` is zero.
+    pub fn new<F>(builder: F, n_estimators: usize) -> BaggingClassifier<T>
+    where
+        F: Fn() -> T,
+    {
+        assert!(n_estimators > 0, "Ensemble must contain at least one model");
+
+        BaggingClassifier {
+            estimators: (0..n_estimators).map(|_| builder()).collect(),
+            class_labels: Vec::new(),
+            rng: EncodableRng::new(),
+            threshold: default_threshold(),
+        }
+    }
+
+    /// Set the random number generator used for drawing bootstrap samples.
+    pub fn set_rng(&mut self, rng: StdRng) {
+        self.rng.rng = rng;
+    }
+
+    /// Return a reference to the constituent models.
+    pub fn estimators(&self) -> &Vec<T> {
+        &self.estimators
+    }
+
+    /// Set the threshold above which the averaged single-column decision function
+    /// of two-class models predicts the positive class.
+    ///
+    /// Defaults to 0.5, suitable for models outputting probabilities. Models
+    /// outputting margins or log-odds, such as hinge-loss linear models, need a
+    /// threshold of 0.0 instead.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+}
+
+macro_rules! impl_bagging_supervised_model {
+    ($t:ty) => {
+        impl<'a, T> SupervisedModel<&'a $t> for BaggingClassifier<T>
+        where
+            T: for<'b> SupervisedModel<&'b $t>,
+        {
+            fn fit(&mut self, X: &'a $t, y: &Array) -> Result<(), RustlearnError> {
+                try!(check_matched_dimensions(X, y));
+
+                if X.rows() == 0 {
+                    return Err(RustlearnError::EmptyInput("Cannot fit on an empty array."));
+                }
+
+                let mut class_labels = y.data().clone();
+                class_labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                class_labels.dedup();
+
+                let class_indices = class_labels
+                    .iter()
+                    .map(|&label| {
+                        (0..y.rows())
+                            .filter(|&idx| y.get(idx, 0) == label)
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                let rng = &mut self.rng.rng;
+
+                for estimator in &mut self.estimators {
+                    let mut indices = Vec::with_capacity(X.rows());
+
+                    // Draw within each class, so that every class is represented
+                    for class in &class_indices {
+                        let range = Range::new(0, class.len());
+
+                        for _ in 0..class.len() {
+                            indices.push(class[range.ind_sample(rng)]);
+                        }
+                    }
+
+                    rng.shuffle(&mut indices);
+
+                    try!(estimator.fit(&X.get_rows(&indices), &y.get_rows(&indices)));
+                }
+
+                self.class_labels = class_labels;
+
+                Ok(())
+            }
+
+            /// Return the average of the decision functions of the constituent models.
+            fn decision_function(&self, X: &'a $t) -> Result<Array, RustlearnError> {
+                let mut df = try!(self.estimators[0].decision_function(X));
+
+                for estimator in &self.estimators[1..] {
+                    let estimator_df = try!(estimator.decision_function(X));
+
+                    if estimator_df.cols() != df.cols() {
+                        return Err(RustlearnError::DimensionMismatch(
+                            "Models output decision functions of different shapes.",
+                        ));
+                    }
+
+                    df.add_inplace(&estimator_df);
+                }
+
+                df.div_inplace(self.estimators.len() as f32);
+
+                Ok(df)
+            }
+
+            fn predict(&self, X: &'a $t) -> Result<Array, RustlearnError> {
+                let decision = try!(self.decision_function(X));
+                predict_from_decision(&decision, &self.class_labels, self.threshold)
+            }
+        }
+    };
+}
+
+impl_bagging_supervised_model!(Array);
+impl_bagging_supervised_model!(SparseRowArray);
+
+impl<T> SeedableModel for BaggingClassifier<T> {
+    /// Reseed the generator used for drawing bootstrap samples.
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = EncodableRng::from_seed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use linear_models::sgdclassifier::{Hyperparameters, Loss, SGDClassifier};
+    use metrics::accuracy_score;
+    use multiclass::OneVsRestWrapper;
+    use neighbors::KNeighborsClassifier;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    fn bagged_sgd(dim: usize) -> OneVsRestWrapper<BaggingClassifier<SGDClassifier>> {
+        let mut params = Hyperparameters::new(dim);
+        params.learning_rate(0.5).l2_penalty(0.0001);
+
+        let mut bagging = BaggingClassifier::new(|| params.build(), 10);
+        bagging.set_rng(StdRng::from_seed(&[100]));
+
+        OneVsRestWrapper::new(bagging)
+    }
+
+    #[test]
+    fn test_bagging_sgd_iris() {
+        let (data, target) = load_data();
+
+        let mut test_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+
+            let mut model = bagged_sgd(data.cols());
+
+            for _ in 0..5 {
+                model.fit(&x_train, &y_train).unwrap();
+            }
+
+            for bagging in model.models() {
+                assert!(bagging.estimators().len() == 10);
+            }
+
+            let test_prediction = model.predict(&x_test).unwrap();
+
+            test_accuracy += accuracy_score(&target.get_rows(&test_idx), &test_prediction);
+        }
+
+        test_accuracy /= no_splits as f32;
+
+        println!("Accuracy {}", test_accuracy);
+
+        assert!(test_accuracy > 0.8);
+    }
+
+    #[test]
+    fn test_bagging_sparse() {
+        let (data, target) = load_data();
+        let data = SparseRowArray::from(&data);
+
+        let mut model = bagged_sgd(data.cols());
+        model.fit(&data, &target).unwrap();
+
+        assert!(model.predict(&data).unwrap().rows() == data.rows());
+    }
+
+    #[test]
+    fn test_bagging_knn() {
+        let (data, target) = load_data();
+        let mut model = BaggingClassifier::new(|| KNeighborsClassifier::new(5), 5);
+        model.set_rng(StdRng::from_seed(&[100]));
+        model.fit(&data, &target).unwrap();
+
+        assert!(model.decision_function(&data).unwrap().cols() == 3);
+
+        let accuracy = accuracy_score(&target, &model.predict(&data).unwrap());
+
+        println!("Accuracy {}", accuracy);
+
+        assert!(accuracy > 0.9);
+    }
+
+    #[test]
+    fn test_bagging_rare_class() {
+        let (data, mut target) = load_data();
+
+        // A class with only two samples is missed by most plain bootstrap samples
+        target.set(0, 0, 3.0);
+        target.set(1, 0, 3.0);
+
+        let mut model = BaggingClassifier::new(|| KNeighborsClassifier::new(1), 10);
+        model.set_rng(StdRng::from_seed(&[100]));
+        model.fit(&data, &target).unwrap();
+
+        assert!(model.decision_function(&data).unwrap().cols() == 4);
+
+        let prediction = model.predict(&data).unwrap();
+        assert!(prediction.get(0, 0) == 3.0 && prediction.get(1, 0) == 3.0);
+    }
+
+    #[test]
+    fn test_bagging_margin_threshold() {
+        let (data, target) = load_data();
+        let target = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+
+        let mut params = Hyperparameters::new(data.cols());
+        params.loss(Loss::Hinge).fit_intercept(true);
+
+        let mut model = BaggingClassifier::new(|| params.build(), 10);
+        model.set_rng(StdRng::from_seed(&[100]));
+        model.set_threshold(0.0);
+
+        for _ in 0..5 {
+            model.fit(&data, &target).unwrap();
+        }
+
+        let decision = model.decision_function(&data).unwrap();
+        let prediction = model.predict(&data).unwrap();
+
+        assert!(prediction == decision.map(|x| if x > 0.0 { 1.0 } else { 0.0 }));
+
+        let accuracy = accuracy_score(&target, &prediction);
+
+        println!("Accuracy {}", accuracy);
+
+        assert!(accuracy > 0.95);
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, target) = load_data();
+
+        let mut model = bagged_sgd(data.cols());
+        model.fit(&data, &target).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: OneVsRestWrapper<BaggingClassifier<SGDClassifier>> =
+            bincode::deserialize(&encoded).unwrap();
+
+        assert!(model.predict(&data).unwrap() == decoded.predict(&data).unwrap());
+    }
+}
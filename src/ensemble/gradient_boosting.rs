@@ -0,0 +1,390 @@
+//! Gradient boosted trees.
+//!
+//! Fits an additive model of shallow regression trees in a forward
+//! stagewise fashion. Starting from the log-odds of the positive class
+//! in the training data, each tree is fit to the negative gradient of the
+//! logistic loss (the difference between the labels and the currently
+//! predicted probabilities). The value of each leaf is then set using a
+//! single Newton-Raphson step on the loss of the samples reaching it, and
+//! the tree's predictions, shrunk by the learning rate, are added to the model.
+//! Smaller learning rates need more estimators, but tend to generalize better.
+//!
+//! The model is a two-class classifier. Its decision function is the raw
+//! additive score (the log-odds of the positive class); use
+//! [`predict_proba`](struct.GradientBoosting.html#method.predict_proba)
+//! to obtain probabilities.
+//!
+//! Unlike the linear models, calling `fit` refits the model from scratch.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//!
+//! use rustlearn::ensemble::gradient_boosting::Hyperparameters;
+//! use rustlearn::datasets::iris;
+//!
+//! let (data, target) = iris::load_data();
+//!
+//! let mut model = Hyperparameters::new(data.cols())
+//!     .learning_rate(0.1)
+//!     .n_estimators(50)
+//!     .max_depth(3)
+//!     .one_vs_rest();
+//!
+//! model.fit(&data, &target).unwrap();
+//!
+//! let prediction = model.predict(&data).unwrap();
+//! ```
+
+use prelude::*;
+
+use trees::regression_tree::{self, RegressionTree};
+
+use multiclass::OneVsRestWrapper;
+use utils::{check_data_dimensionality, check_matched_dimensions, check_valid_labels};
+
+/// Bound on the initial positive class proportion, keeping
+/// the initial log-odds finite for single-class data.
+const PROPORTION_EPSILON: f32 = 1e-6;
+/// Lower bound on the sum of second derivatives in a leaf,
+/// avoiding division by zero in the Newton-Raphson step.
+const HESSIAN_EPSILON: f32 = 1e-6;
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Hyperparameters for a `GradientBoosting` model.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hyperparameters {
+    dim: usize,
+
+    learning_rate: f32,
+    n_estimators: usize,
+    max_depth: usize,
+}
+
+impl Hyperparameters {
+    /// Creates new Hyperparameters.
+    pub fn new(dim: usize) -> Hyperparameters {
+        Hyperparameters {
+            dim: dim,
+            learning_rate: 0.1,
+            n_estimators: 100,
+            max_depth: 3,
+        }
+    }
+
+    /// Set the learning rate, the factor by which the
+    /// predictions of each tree are shrunk.
+    ///
+    /// Defaults to 0.1.
+    pub fn learning_rate(&mut self, learning_rate: f32) -> &mut Hyperparameters {
+        self.learning_rate = learning_rate;
+        self
+    }
+    /// Set the number of boosting stages, each adding one tree.
+    ///
+    /// Defaults to 100.
+    pub fn n_estimators(&mut self, n_estimators: usize) -> &mut Hyperparameters {
+        self.n_estimators = n_estimators;
+        self
+    }
+    /// Set the maximum depth of the individual trees.
+    ///
+    /// Defaults to 3.
+    pub fn max_depth(&mut self, max_depth: usize) -> &mut Hyperparameters {
+        self.max_depth = max_depth;
+        self
+    }
+    /// Build a two-class gradient boosting model.
+    pub fn build(&self) -> GradientBoosting {
+        let mut tree_hyperparameters = regression_tree::Hyperparameters::new(self.dim);
+        tree_hyperparameters.max_depth(self.max_depth);
+
+        GradientBoosting {
+            dim: self.dim,
+            learning_rate: self.learning_rate,
+            n_estimators: self.n_estimators,
+            tree_hyperparameters: tree_hyperparameters,
+            initial_score: 0.0,
+            trees: Vec::new(),
+            leaf_values: Vec::new(),
+        }
+    }
+    /// Build a one-vs-rest multi-class gradient boosting model.
+    pub fn one_vs_rest(&self) -> OneVsRestWrapper<GradientBoosting> {
+        let base_model = self.build();
+
+        OneVsRestWrapper::new(base_model)
+    }
+}
+
+/// A two-class gradient boosted trees model.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GradientBoosting {
+    dim: usize,
+
+    learning_rate: f32,
+    n_estimators: usize,
+    tree_hyperparameters: regression_tree::Hyperparameters,
+
+    initial_score: f32,
+    trees: Vec<RegressionTree>,
+    leaf_values: Vec<Vec<f32>>,
+}
+
+impl<'a> SupervisedModel<&'a Array> for GradientBoosting {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_data_dimensionality(self.dim, X));
+        try!(check_matched_dimensions(X, y));
+        try!(check_valid_labels(y));
+
+        if self.n_estimators == 0 {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Number of estimators must be positive.",
+            ));
+        }
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit on an empty array."));
+        }
+
+        let proportion = (y.data().iter().sum::<f32>() / y.rows() as f32)
+            .max(PROPORTION_EPSILON)
+            .min(1.0 - PROPORTION_EPSILON);
+
+        self.initial_score = (proportion / (1.0 - proportion)).ln();
+        self.trees = Vec::with_capacity(self.n_estimators);
+        self.leaf_values = Vec::with_capacity(self.n_estimators);
+
+        let mut scores = vec![self.initial_score; X.rows()];
+
+        for _ in 0..self.n_estimators {
+            let probabilities = scores
+                .iter()
+                .map(|&score| sigmoid(score))
+                .collect::<Vec<_>>();
+            let negative_gradient = Array::from(
+                y.data()
+                    .iter()
+                    .zip(probabilities.iter())
+                    .map(|(&y, &p)| y - p)
+                    .collect::<Vec<_>>(),
+            );
+
+            let mut tree = self.tree_hyperparameters.build();
+            try!(tree.fit(X, &negative_gradient));
+
+            let leaves = try!(tree.apply(X));
+            let mut gradient_sums = vec![0.0; tree.num_leaves()];
+            let mut hessian_sums = vec![0.0; tree.num_leaves()];
+
+            for ((&leaf, &gradient), &p) in leaves
+                .iter()
+                .zip(negative_gradient.data().iter())
+                .zip(probabilities.iter())
+            {
+                gradient_sums[leaf] += gradient;
+                hessian_sums[leaf] += p * (1.0 - p);
+            }
+
+            let leaf_values = gradient_sums
+                .iter()
+                .zip(hessian_sums.iter())
+                .map(|(&gradient, &hessian)| gradient / hessian.max(HESSIAN_EPSILON))
+                .collect::<Vec<_>>();
+
+            for (score, &leaf) in scores.iter_mut().zip(leaves.iter()) {
+                *score += self.learning_rate * leaf_values[leaf];
+            }
+
+            self.trees.push(tree);
+            self.leaf_values.push(leaf_values);
+        }
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(check_data_dimensionality(self.dim, X));
+
+        if self.trees.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        let mut scores = vec![self.initial_score; X.rows()];
+
+        for (tree, leaf_values) in self.trees.iter().zip(self.leaf_values.iter()) {
+            for (score, leaf) in scores.iter_mut().zip(try!(tree.apply(X))) {
+                *score += self.learning_rate * leaf_values[leaf];
+            }
+        }
+
+        Ok(Array::from(scores))
+    }
+
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let scores = try!(self.decision_function(X));
+
+        Ok(scores.map(|score| if score > 0.0 { 1.0 } else { 0.0 }))
+    }
+}
+
+impl SeedableModel for GradientBoosting {}
+
+impl GradientBoosting {
+    /// Return a reference to the consituent trees vector.
+    pub fn trees(&self) -> &Vec<RegressionTree> {
+        &self.trees
+    }
+
+    /// Predict the probability of the positive class.
+    pub fn predict_proba(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let scores = try!(self.decision_function(X));
+
+        Ok(scores.map(sigmoid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use metrics::accuracy_score;
+    use multiclass::OneVsRest;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    /// Mean logistic loss of the one-vs-rest models on the training data.
+    fn training_loss(n_estimators: usize) -> f32 {
+        let (data, target) = load_data();
+
+        let mut model = Hyperparameters::new(data.cols())
+            .n_estimators(n_estimators)
+            .one_vs_rest();
+        model.fit(&data, &target).unwrap();
+
+        let mut loss = 0.0;
+
+        for (class_model, (_, binary_target)) in
+            model.models().iter().zip(OneVsRest::split(&target))
+        {
+            let probabilities = class_model.predict_proba(&data).unwrap();
+
+            for (&y, &p) in binary_target.data().iter().zip(probabilities.data().iter()) {
+                loss -= if y == 1.0 { p.ln() } else { (1.0 - p).ln() };
+            }
+        }
+
+        loss / (data.rows() * model.models().len()) as f32
+    }
+
+    #[test]
+    fn test_gradient_boosting_iris() {
+        let (data, target) = load_data();
+
+        let mut test_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+
+            let mut model = Hyperparameters::new(data.cols())
+                .n_estimators(20)
+                .one_vs_rest();
+
+            model.fit(&x_train, &y_train).unwrap();
+
+            let test_prediction = model.predict(&x_test).unwrap();
+
+            test_accuracy += accuracy_score(&target.get_rows(&test_idx), &test_prediction);
+        }
+
+        test_accuracy /= no_splits as f32;
+
+        println!("Accuracy {}", test_accuracy);
+
+        assert!(test_accuracy > 0.9);
+    }
+
+    #[test]
+    fn test_gradient_boosting_training_loss() {
+        let few_estimators_loss = training_loss(5);
+        let many_estimators_loss = training_loss(50);
+
+        println!(
+            "Loss with 5 estimators {}, with 50 estimators {}",
+            few_estimators_loss, many_estimators_loss
+        );
+
+        assert!(many_estimators_loss < few_estimators_loss);
+    }
+
+    #[test]
+    fn test_predict_proba() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&x| if x == 0.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut model = Hyperparameters::new(data.cols()).n_estimators(10).build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let probabilities = model.predict_proba(&data).unwrap();
+        let prediction = model.predict(&data).unwrap();
+
+        assert!(probabilities.data().iter().all(|&p| p > 0.0 && p < 1.0));
+        assert!(prediction
+            .data()
+            .iter()
+            .zip(probabilities.data().iter())
+            .all(|(&y, &p)| y == if p > 0.5 { 1.0 } else { 0.0 }));
+        assert!(accuracy_score(&binary_target, &prediction) == 1.0);
+    }
+
+    #[test]
+    fn test_fit_errors() {
+        let mut model = Hyperparameters::new(2).n_estimators(0).build();
+
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+        assert!(model.fit(&Array::zeros(2, 2), &Array::zeros(2, 1)).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, target) = load_data();
+
+        let mut model = Hyperparameters::new(data.cols())
+            .n_estimators(10)
+            .one_vs_rest();
+        model.fit(&data, &target).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: OneVsRestWrapper<GradientBoosting> = bincode::deserialize(&encoded).unwrap();
+
+        assert!(
+            model.decision_function(&data).unwrap() == decoded.decision_function(&data).unwrap()
+        );
+    }
+}
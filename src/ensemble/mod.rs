@@ -1,3 +1,7 @@
 //! Ensemble models.
 
+pub mod bagging;
+pub mod gradient_boosting;
 pub mod random_forest;
+pub mod random_forest_regressor;
+pub mod voting;
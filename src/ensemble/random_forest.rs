@@ -28,25 +28,225 @@
 //!
 //! let prediction = model.predict(&data).unwrap();
 //! ```
+//!
+//! # Out-of-bag accuracy
+//!
+//! Each tree is fit on a bootstrap sample, leaving out roughly a third of the
+//! training data. With [`Hyperparameters::oob_score`](struct.Hyperparameters.html#method.oob_score),
+//! the accuracy of every tree on its left-out (out-of-bag) samples is recorded
+//! during fitting. Setting [`Hyperparameters::weighted_vote`](struct.Hyperparameters.html#method.weighted_vote)
+//! as well weights each tree's prediction by its normalized out-of-bag accuracy,
+//! instead of averaging all trees uniformly. Weights reflecting prior knowledge
+//! can be given explicitly using
+//! [`RandomForest::set_tree_weights`](struct.RandomForest.html#method.set_tree_weights).
+//!
+//! # Permutation importances
+//!
+//! With [`Hyperparameters::oob_importance`](struct.Hyperparameters.html#method.oob_importance),
+//! the values of each feature are also permuted among the out-of-bag samples of every
+//! tree during fitting, recording the resulting decrease in the tree's accuracy.
+//! Averaged over the trees by [`RandomForest::oob_permutation_importance`](struct.RandomForest.html#method.oob_permutation_importance),
+//! these reflect how much the forest's predictions depend on each feature.
+//!
+//! # Stratified bootstrapping
+//!
+//! On imbalanced data, a bootstrap sample may contain no samples of the minority
+//! class at all. With [`Hyperparameters::stratified_bootstrap`](struct.Hyperparameters.html#method.stratified_bootstrap),
+//! every tree's bootstrap sample is instead drawn separately within each class, in
+//! proportion to the class frequencies, so that every class is represented.
+//!
+//! # Class weights
+//!
+//! The samples of the minority class can also be weighted more heavily when fitting
+//! the trees, using [`Hyperparameters::class_weight`](struct.Hyperparameters.html#method.class_weight).
+//! With [`ClassWeight::Balanced`](enum.ClassWeight.html), each class is weighted
+//! inversely to its frequency in the training data; with `ClassWeight::BalancedSubsample`,
+//! the weights are instead computed separately for every tree from the class
+//! frequencies in its bootstrap sample.
+//!
+//! # Reproducibility
+//!
+//! All randomness in a forest is derived from a single `u64` seed, set using
+//! [`Hyperparameters::seed`](struct.Hyperparameters.html#method.seed). The random
+//! number generators used for building and bootstrapping the `i`-th tree are seeded
+//! from a hash of the forest seed and `i` alone. Forests built and fit with the same seed,
+//! hyperparameters, and data are therefore identical across runs and platforms, and the
+//! `i`-th tree does not depend on the total number of trees or the order in which trees are fit.
+//!
+//! # Adding trees
+//!
+//! More trees can be added to a forest without refitting the existing ones using
+//! [`RandomForest::add_trees`](struct.RandomForest.html#method.add_trees). The new
+//! trees continue the seed sequence, so growing a forest to `n` trees yields the same
+//! forest as fitting `n` trees from the start. Forests built with
+//! [`Hyperparameters::warm_start`](struct.Hyperparameters.html#method.warm_start)
+//! also leave already fitted trees unchanged when `fit` is called again.
+//!
+//! # Feature importances
+//!
+//! The impurity-based feature importances of the trees, averaged over the forest,
+//! are available through the [`FeatureImportance`](../../traits/trait.FeatureImportance.html) trait.
+//!
+//! # Streaming
+//!
+//! Forests can also be fit on a stream of rows, such as rows read from a file
+//! too large to load into memory, using
+//! [`RandomForest::fit_stream`](struct.RandomForest.html#method.fit_stream).
+//! The bootstrap sample of every tree is then built in a single pass over the
+//! stream using reservoir sampling.
+//!
+//! # Parallelism
+//!
+//! Trees can be fit and evaluated on several threads. The number of threads
+//! used by `fit` and `decision_function` is set once with
+//! [`Hyperparameters::n_jobs`](struct.Hyperparameters.html#method.n_jobs);
+//! the `fit_parallel` and `decision_function_parallel` methods take the number
+//! of threads as an argument instead, overriding it for a single call. Fitted
+//! forests and their predictions are the same for any number of threads.
+//!
+//! When `rustlearn` is built with the `rayon` feature, the decision functions of
+//! the trees of single-threaded forests are computed in parallel on the global
+//! rayon thread pool. The result is the same for any number of threads.
+//!
+//! To bound memory use when scoring very large arrays, the decision function can
+//! be computed a chunk of rows at a time using
+//! [`ChunkedPredict::decision_function_chunked`](../../traits/trait.ChunkedPredict.html).
 
-use std::usize;
+use std::f32;
+use std::rc::Rc;
+use std::thread;
 
 use prelude::*;
 
+use metrics::accuracy_score;
 use trees::decision_tree;
 
 use multiclass::OneVsRestWrapper;
-use utils::EncodableRng;
+use persistence::{check_model_format_version, model_format_version, MODEL_FORMAT_VERSION};
+use utils::{
+    check_matched_dimensions, check_valid_labels, derive_seed, normalize_model_weights, seeded_rng,
+};
 
+use crossbeam;
 use rand;
 use rand::distributions::{IndependentSample, Range};
-use rand::SeedableRng;
+use rand::{Rng, StdRng};
+
+/// Seed stream used for the decision tree's own random number generator.
+const TREE_STREAM: u64 = 0;
+/// Seed stream used for drawing bootstrap samples.
+const BOOTSTRAP_STREAM: u64 = 1;
+/// Seed stream used for drawing the number of candidate features of a tree.
+const MAX_FEATURES_STREAM: u64 = 2;
+/// Seed stream used for permuting features among out-of-bag samples.
+const PERMUTATION_STREAM: u64 = 3;
+
+/// Out-of-bag accuracy and permutation importances of a fitted tree, if tracked.
+type OobStatistics = (Option<f32>, Option<Vec<f32>>);
+
+fn default_n_jobs() -> usize {
+    1
+}
+
+/// Seed of forests serialized before seeds were stored, which, like
+/// their random number generators, draw a fresh seed when deserialized.
+fn default_seed() -> u64 {
+    rand::random()
+}
+
+/// How the samples of each class are weighted when fitting the trees of a forest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ClassWeight {
+    /// All samples are weighted equally.
+    Uniform,
+    /// Classes are weighted inversely to their frequency in the training data.
+    Balanced,
+    /// Classes are weighted inversely to their frequency in the
+    /// bootstrap sample of each tree.
+    BalancedSubsample,
+}
+
+fn default_class_weight() -> ClassWeight {
+    ClassWeight::Uniform
+}
+
+/// Count the negative and positive labels in `labels`.
+fn class_counts<'a, I: Iterator<Item = &'a f32>>(labels: I) -> [f32; 2] {
+    let mut counts = [0.0; 2];
+
+    for &label in labels {
+        counts[if label == 1.0 { 1 } else { 0 }] += 1.0;
+    }
+
+    counts
+}
+
+/// Sample weights for `labels`, weighting each class inversely to its entry in
+/// `counts`, so that all classes present carry the same total weight.
+fn balanced_weights(labels: &Array, counts: [f32; 2]) -> Array {
+    let total = counts[0] + counts[1];
+    let num_classes = counts.iter().filter(|&&count| count > 0.0).count() as f32;
+
+    labels.map(|label| total / (num_classes * counts[if label == 1.0 { 1 } else { 0 }]))
+}
+
+/// Resolve an `n_jobs` setting into a number of threads,
+/// using all available cores if it is zero.
+fn resolve_num_threads(n_jobs: usize) -> usize {
+    if n_jobs == 0 {
+        thread::available_parallelism()
+            .map(|num_cores| num_cores.get())
+            .unwrap_or(1)
+    } else {
+        n_jobs
+    }
+}
+
+/// Draw the number of failures before the first success in a sequence of
+/// Bernoulli trials with success probability `p`, capped at `max`.
+fn geometric_skip<R: Rng>(rng: &mut R, p: f64, max: usize) -> usize {
+    if p >= 1.0 {
+        return 0;
+    }
+
+    // Keep the uniform draw in (0, 1] to avoid taking the logarithm of zero
+    let uniform = 1.0 - rng.next_f64();
+    let skip = (uniform.ln() / (1.0 - p).ln()).floor();
+
+    if skip < max as f64 {
+        skip as usize
+    } else {
+        max
+    }
+}
+
+/// Derive the seed of a random number generator used for the `tree_idx`-th tree.
+fn tree_seed(seed: u64, tree_idx: usize, stream: u64) -> u64 {
+    derive_seed(derive_seed(seed, tree_idx as u64), stream)
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Hyperparameters {
     tree_hyperparameters: decision_tree::Hyperparameters,
     num_trees: usize,
-    rng: EncodableRng,
+    #[serde(default = "default_seed")]
+    seed: u64,
+    #[serde(default)]
+    warm_start: bool,
+    #[serde(default)]
+    oob_score: bool,
+    #[serde(default)]
+    weighted_vote: bool,
+    #[serde(default = "default_n_jobs")]
+    n_jobs: usize,
+    #[serde(default)]
+    stratified_bootstrap: bool,
+    #[serde(default)]
+    random_max_features: Option<(usize, usize)>,
+    #[serde(default = "default_class_weight")]
+    class_weight: ClassWeight,
+    #[serde(default)]
+    oob_importance: bool,
 }
 
 impl Hyperparameters {
@@ -59,41 +259,144 @@ impl Hyperparameters {
         Hyperparameters {
             tree_hyperparameters: tree_hyperparameters,
             num_trees: num_trees,
-            rng: EncodableRng::new(),
+            seed: rand::random(),
+            warm_start: false,
+            oob_score: false,
+            weighted_vote: false,
+            n_jobs: 1,
+            stratified_bootstrap: false,
+            random_max_features: None,
+            class_weight: ClassWeight::Uniform,
+            oob_importance: false,
         }
     }
 
-    /// Set the random number generator.
-    pub fn rng(&mut self, rng: rand::StdRng) -> &mut Hyperparameters {
-        self.rng.rng = rng;
+    /// Set the seed from which all randomness in the forest is derived.
+    pub fn seed(&mut self, seed: u64) -> &mut Hyperparameters {
+        self.seed = seed;
         self
     }
 
-    /// Build the random forest model.
-    pub fn build(&self) -> RandomForest {
-        let mut trees = Vec::with_capacity(self.num_trees);
+    /// Set the seed of the forest by drawing it from `rng`.
+    pub fn rng(&mut self, mut rng: rand::StdRng) -> &mut Hyperparameters {
+        self.seed = rng.gen();
+        self
+    }
 
-        let mut rng = self.rng.clone();
+    /// Set whether calling `fit` on an already fitted forest only fits
+    /// trees added since the last fit, leaving the others unchanged.
+    pub fn warm_start(&mut self, warm_start: bool) -> &mut Hyperparameters {
+        self.warm_start = warm_start;
+        self
+    }
 
-        for _ in 0..self.num_trees {
-            // Reseed trees to introduce randomness,
-            // without this they are just copies of each other
-            let range = Range::new(0, usize::MAX);
+    /// Set whether to record the accuracy of every tree on its out-of-bag
+    /// samples: the training samples left out of the tree's bootstrap sample.
+    pub fn oob_score(&mut self, oob_score: bool) -> &mut Hyperparameters {
+        self.oob_score = oob_score;
+        self
+    }
 
-            let mut hyperparams = self.tree_hyperparameters.clone();
-            hyperparams.rng(SeedableRng::from_seed(
-                &(0..10)
-                    .map(|_| range.ind_sample(&mut rng.rng))
-                    .collect::<Vec<_>>()[..],
-            ));
+    /// Set whether to record the permutation importances of every tree: the
+    /// decrease in its out-of-bag accuracy when the values of each feature are
+    /// shuffled among its out-of-bag samples. This requires predicting the
+    /// out-of-bag samples once per feature for every tree.
+    pub fn oob_importance(&mut self, oob_importance: bool) -> &mut Hyperparameters {
+        self.oob_importance = oob_importance;
+        self
+    }
 
-            trees.push(hyperparams.build());
-        }
+    /// Set whether the contribution of each tree to the decision function
+    /// is weighted by its out-of-bag accuracy, rather than uniformly.
+    ///
+    /// Requires `oob_score`: fitting a forest using weighted voting without
+    /// out-of-bag tracking returns an error.
+    pub fn weighted_vote(&mut self, weighted_vote: bool) -> &mut Hyperparameters {
+        self.weighted_vote = weighted_vote;
+        self
+    }
 
-        RandomForest {
-            trees: trees,
-            rng: self.rng.clone(),
-        }
+    /// Set whether bootstrap samples are drawn within each class separately.
+    ///
+    /// When set, the bootstrap sample of every tree holds as many samples of
+    /// each class as the training data, drawn with replacement from the samples
+    /// of that class, so that every class is represented in every tree.
+    /// Otherwise, bootstrap samples are drawn from all samples at once.
+    ///
+    /// Stratified bootstrapping needs the labels of all samples before sampling,
+    /// and is therefore not supported by `fit_stream`.
+    pub fn stratified_bootstrap(&mut self, stratified_bootstrap: bool) -> &mut Hyperparameters {
+        self.stratified_bootstrap = stratified_bootstrap;
+        self
+    }
+
+    /// Set every tree to consider its own number of candidate features when
+    /// finding the best split, drawn uniformly from `[min, max]` when the tree
+    /// is built. The draws are derived from the forest seed, so forests built
+    /// with the same seed have the same per-tree feature counts. This overrides
+    /// the `max_features` setting of the tree hyperparameters, and increases
+    /// the diversity of the trees in the forest.
+    ///
+    /// # Panics
+    /// Panics if `min` is zero, `min` exceeds `max`, or `max` exceeds
+    /// the dimensionality of the data.
+    pub fn random_max_features(&mut self, min: usize, max: usize) -> &mut Hyperparameters {
+        assert!(
+            min > 0 && min <= max,
+            "Feature count bounds must satisfy 0 < min <= max"
+        );
+        assert!(
+            max <= self.tree_hyperparameters.dim(),
+            "Maximum feature count must not exceed the number of features"
+        );
+
+        self.random_max_features = Some((min, max));
+        self
+    }
+
+    /// Set how the samples of each class are weighted when fitting the trees.
+    ///
+    /// Defaults to `ClassWeight::Uniform`. With `ClassWeight::BalancedSubsample`,
+    /// the weights are computed from the bootstrap sample of every tree, so that
+    /// both classes carry the same total weight in every tree.
+    pub fn class_weight(&mut self, class_weight: ClassWeight) -> &mut Hyperparameters {
+        self.class_weight = class_weight;
+        self
+    }
+
+    /// Set the number of threads used for fitting the trees of the forest
+    /// and computing their decision functions. Zero uses all available cores.
+    ///
+    /// Defaults to 1. The number of threads can be overridden for a single call
+    /// using `fit_parallel` or `decision_function_parallel`.
+    pub fn n_jobs(&mut self, n_jobs: usize) -> &mut Hyperparameters {
+        self.n_jobs = n_jobs;
+        self
+    }
+
+    /// Build the random forest model.
+    pub fn build(&self) -> RandomForest {
+        let mut forest = RandomForest {
+            version: MODEL_FORMAT_VERSION,
+            trees: Vec::with_capacity(self.num_trees),
+            seed: self.seed,
+            tree_hyperparameters: Some(self.tree_hyperparameters.clone()),
+            warm_start: self.warm_start,
+            oob_score: self.oob_score,
+            weighted_vote: self.weighted_vote,
+            oob_accuracies: Vec::new(),
+            n_jobs: self.n_jobs,
+            stratified_bootstrap: self.stratified_bootstrap,
+            tree_weights: None,
+            random_max_features: self.random_max_features,
+            class_weight: self.class_weight,
+            oob_importance: self.oob_importance,
+            oob_importances: Vec::new(),
+        };
+
+        forest.push_trees(self.num_trees);
+
+        forest
     }
 
     /// Build a one-vs-rest multiclass random forest.
@@ -106,64 +409,259 @@ impl Hyperparameters {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RandomForest {
+    #[serde(
+        default = "model_format_version",
+        deserialize_with = "check_model_format_version"
+    )]
+    version: u32,
     trees: Vec<decision_tree::DecisionTree>,
-    rng: EncodableRng,
+    #[serde(default = "default_seed")]
+    seed: u64,
+    #[serde(default)]
+    tree_hyperparameters: Option<decision_tree::Hyperparameters>,
+    #[serde(default)]
+    warm_start: bool,
+    #[serde(default)]
+    oob_score: bool,
+    #[serde(default)]
+    weighted_vote: bool,
+    #[serde(default)]
+    oob_accuracies: Vec<f32>,
+    #[serde(default = "default_n_jobs")]
+    n_jobs: usize,
+    #[serde(default)]
+    stratified_bootstrap: bool,
+    #[serde(default)]
+    tree_weights: Option<Vec<f32>>,
+    #[serde(default)]
+    random_max_features: Option<(usize, usize)>,
+    #[serde(default = "default_class_weight")]
+    class_weight: ClassWeight,
+    #[serde(default)]
+    oob_importance: bool,
+    #[serde(default)]
+    oob_importances: Vec<Vec<f32>>,
 }
 
 impl<'a> SupervisedModel<&'a Array> for RandomForest {
-    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), &'static str> {
-        let mut rng = self.rng.clone();
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        let num_threads = resolve_num_threads(self.n_jobs);
+        self.fit_parallel(X, y, num_threads)
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.combine_trees(X.rows(), resolve_num_threads(self.n_jobs), |tree| {
+            tree.decision_function(X)
+        })
+    }
+}
 
-        for tree in &mut self.trees {
-            let indices = RandomForest::bootstrap_indices(X.rows(), &mut rng.rng);
-            try!(tree.fit(&X.get_rows(&indices), &y.get_rows(&indices)));
+impl<'a> ParallelSupervisedModel<&'a Array> for RandomForest {
+    /// Fit the forest, fitting up to `num_threads` trees at a time.
+    /// Zero uses all available cores.
+    fn fit_parallel(
+        &mut self,
+        X: &Array,
+        y: &Array,
+        num_threads: usize,
+    ) -> Result<(), RustlearnError> {
+        let (seed, oob_score, oob_importance) = (self.seed, self.oob_score, self.oob_importance);
+        let stratified = self.stratified_bootstrap;
+        let class_weight = self.class_weight;
+
+        if stratified || class_weight != ClassWeight::Uniform {
+            try!(check_matched_dimensions(X, y));
+            try!(check_valid_labels(y));
         }
 
-        self.rng = rng;
+        let counts = class_counts(y.data().iter());
+
+        self.fit_trees(resolve_num_threads(num_threads), |tree, tree_idx| {
+            let indices = if stratified {
+                RandomForest::stratified_bootstrap_indices(y, seed, tree_idx)
+            } else {
+                RandomForest::bootstrap_indices(X.rows(), seed, tree_idx)
+            };
+            let y_sample = y.get_rows(&indices);
+            let sample_weight = RandomForest::sample_weights(&y_sample, class_weight, counts);
+            try!(tree.fit_weighted(&X.get_rows(&indices), &y_sample, &sample_weight));
+
+            if !(oob_score || oob_importance) {
+                return Ok((None, None));
+            }
+
+            let oob_indices = RandomForest::oob_indices(X.rows(), &indices);
+
+            RandomForest::oob_statistics(
+                X.get_rows(&oob_indices),
+                &y.get_rows(&oob_indices),
+                oob_score,
+                oob_importance,
+                &mut seeded_rng(tree_seed(seed, tree_idx, PERMUTATION_STREAM)),
+                |x| tree.predict(x),
+            )
+        })
+    }
+}
 
-        Ok(())
+impl<'a> ParallelPredict<&'a Array> for RandomForest {
+    /// Compute the decision function, evaluating up to `num_threads`
+    /// trees at a time. Zero uses all available cores.
+    fn decision_function_parallel(
+        &self,
+        X: &Array,
+        num_threads: usize,
+    ) -> Result<Array, RustlearnError> {
+        self.combine_trees(X.rows(), resolve_num_threads(num_threads), |tree| {
+            tree.decision_function(X)
+        })
     }
+}
 
-    fn decision_function(&self, X: &Array) -> Result<Array, &'static str> {
-        let mut df = Array::zeros(X.rows(), 1);
+impl<'a> SupervisedModel<&'a SparseRowArray> for RandomForest {
+    fn fit(&mut self, X: &SparseRowArray, y: &Array) -> Result<(), RustlearnError> {
+        let num_threads = resolve_num_threads(self.n_jobs);
+        self.fit_parallel(X, y, num_threads)
+    }
 
-        for tree in &self.trees {
-            df.add_inplace(&try!(tree.decision_function(X)));
+    fn decision_function(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        let num_threads = resolve_num_threads(self.n_jobs);
+        self.decision_function_parallel(X, num_threads)
+    }
+}
+
+impl<'a> ParallelSupervisedModel<&'a SparseRowArray> for RandomForest {
+    /// Fit the forest, fitting up to `num_threads` trees at a time.
+    /// Zero uses all available cores.
+    fn fit_parallel(
+        &mut self,
+        X: &SparseRowArray,
+        y: &Array,
+        num_threads: usize,
+    ) -> Result<(), RustlearnError> {
+        let (seed, oob_score, oob_importance) = (self.seed, self.oob_score, self.oob_importance);
+        let stratified = self.stratified_bootstrap;
+        let class_weight = self.class_weight;
+
+        if stratified || class_weight != ClassWeight::Uniform {
+            try!(check_matched_dimensions(X, y));
+            try!(check_valid_labels(y));
         }
 
-        df.div_inplace(self.trees.len() as f32);
+        let counts = class_counts(y.data().iter());
 
-        Ok(df)
+        self.fit_trees(resolve_num_threads(num_threads), |tree, tree_idx| {
+            let indices = if stratified {
+                RandomForest::stratified_bootstrap_indices(y, seed, tree_idx)
+            } else {
+                RandomForest::bootstrap_indices(X.rows(), seed, tree_idx)
+            };
+            let x = SparseColumnArray::from(&X.get_rows(&indices));
+            let y_sample = y.get_rows(&indices);
+            let sample_weight = RandomForest::sample_weights(&y_sample, class_weight, counts);
+            try!(tree.fit_weighted(&x, &y_sample, &sample_weight));
+
+            if !(oob_score || oob_importance) {
+                return Ok((None, None));
+            }
+
+            // Permuting features is done on the dense out-of-bag samples
+            let oob_indices = RandomForest::oob_indices(X.rows(), &indices);
+
+            RandomForest::oob_statistics(
+                X.get_rows(&oob_indices).todense(),
+                &y.get_rows(&oob_indices),
+                oob_score,
+                oob_importance,
+                &mut seeded_rng(tree_seed(seed, tree_idx, PERMUTATION_STREAM)),
+                |x| tree.predict(&SparseColumnArray::from(&SparseRowArray::from(x))),
+            )
+        })
     }
 }
 
-impl<'a> SupervisedModel<&'a SparseRowArray> for RandomForest {
-    fn fit(&mut self, X: &SparseRowArray, y: &Array) -> Result<(), &'static str> {
-        let mut rng = self.rng.clone();
+impl<'a> ParallelPredict<&'a SparseRowArray> for RandomForest {
+    /// Compute the decision function, evaluating up to `num_threads`
+    /// trees at a time. Zero uses all available cores.
+    fn decision_function_parallel(
+        &self,
+        X: &SparseRowArray,
+        num_threads: usize,
+    ) -> Result<Array, RustlearnError> {
+        let x = SparseColumnArray::from(X);
 
-        for tree in &mut self.trees {
-            let indices = RandomForest::bootstrap_indices(X.rows(), &mut rng.rng);
-            let x = SparseColumnArray::from(&X.get_rows(&indices));
-            try!(tree.fit(&x, &y.get_rows(&indices)));
+        self.combine_trees(X.rows(), resolve_num_threads(num_threads), |tree| {
+            tree.decision_function(&x)
+        })
+    }
+}
+
+impl FeatureImportance for RandomForest {
+    /// Return the impurity-based feature importances averaged over all trees.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError> {
+        try!(self.check_fitted());
+
+        let mut importances = Vec::new();
+
+        for tree in &self.trees {
+            let tree_importances = try!(tree.feature_importances());
+
+            if importances.is_empty() {
+                importances = vec![0.0; tree_importances.len()];
+            }
+
+            for (importance, tree_importance) in importances.iter_mut().zip(tree_importances) {
+                *importance += tree_importance / self.trees.len() as f32;
+            }
         }
 
-        self.rng = rng;
+        Ok(importances)
+    }
+}
 
-        Ok(())
+impl SeedableModel for RandomForest {
+    /// Set the seed of the forest, reseeding its trees as if
+    /// the forest had been built with this seed.
+    ///
+    /// With `random_max_features`, the number of candidate features of
+    /// every tree is drawn again, replacing the trees with unfitted ones.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+
+        if self.random_max_features.is_some() {
+            self.trees = (0..self.trees.len())
+                .map(|tree_idx| self.build_tree(tree_idx))
+                .collect();
+        } else {
+            for (tree_idx, tree) in self.trees.iter_mut().enumerate() {
+                tree.set_seed(tree_seed(seed, tree_idx, TREE_STREAM));
+            }
+        }
     }
+}
 
-    fn decision_function(&self, X: &SparseRowArray) -> Result<Array, &'static str> {
-        let mut df = Array::zeros(X.rows(), 1);
+impl RowPredict for RandomForest {
+    fn decision_function_row(&self, row: &[f32]) -> Result<f32, RustlearnError> {
+        try!(self.check_fitted());
 
-        let x = SparseColumnArray::from(X);
+        let mut df = 0.0;
 
-        for tree in &self.trees {
-            df.add_inplace(&try!(tree.decision_function(&x)));
-        }
+        match try!(self.vote_weights()) {
+            Some(weights) => {
+                for (tree, &weight) in self.trees.iter().zip(weights.iter()) {
+                    df += try!(tree.decision_function_row(row)) * weight;
+                }
 
-        df.div_inplace(self.trees.len() as f32);
+                Ok(df)
+            }
+            None => {
+                for tree in &self.trees {
+                    df += try!(tree.decision_function_row(row));
+                }
 
-        Ok(df)
+                Ok(df / self.trees.len() as f32)
+            }
+        }
     }
 }
 
@@ -173,13 +671,619 @@ impl RandomForest {
         &self.trees
     }
 
-    fn bootstrap_indices(num_indices: usize, rng: &mut rand::StdRng) -> Vec<usize> {
+    /// Return the seed from which all randomness in the forest is derived.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Return the number of threads used by `fit` and `decision_function`,
+    /// as set by [`Hyperparameters::n_jobs`](struct.Hyperparameters.html#method.n_jobs).
+    pub fn n_jobs(&self) -> usize {
+        self.n_jobs
+    }
+
+    /// Return the accuracy of each tree on its out-of-bag samples, recorded
+    /// during fitting if enabled by
+    /// [`Hyperparameters::oob_score`](struct.Hyperparameters.html#method.oob_score).
+    /// The accuracy is `NaN` for trees whose bootstrap sample contains every training sample.
+    pub fn oob_accuracies(&self) -> &[f32] {
+        &self.oob_accuracies
+    }
+
+    /// Return the mean decrease in out-of-bag accuracy of the trees when the values
+    /// of each feature are permuted among their out-of-bag samples, one value per feature.
+    /// Larger values indicate features the predictions depend on more strongly.
+    ///
+    /// The decreases are recorded during fitting if enabled by
+    /// [`Hyperparameters::oob_importance`](struct.Hyperparameters.html#method.oob_importance),
+    /// and trees without out-of-bag samples are left out of the average.
+    ///
+    /// Returns an error if the forest has not been fit with importances enabled,
+    /// or if no tree has any out-of-bag samples. The latter is always the case
+    /// after `fit_stream`, which cannot revisit the stream.
+    pub fn oob_permutation_importance(&self) -> Result<Vec<f32>, RustlearnError> {
+        try!(self.check_fitted());
+
+        if !self.oob_importance || self.oob_importances.len() != self.trees.len() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Out-of-bag importances must be enabled when fitting the forest.",
+            ));
+        }
+
+        let mut importances = vec![0.0; self.oob_importances[0].len()];
+        let mut num_trees = 0;
+
+        for tree_importances in &self.oob_importances {
+            if tree_importances.iter().any(|x| !x.is_finite()) {
+                continue;
+            }
+
+            for (importance, tree_importance) in importances.iter_mut().zip(tree_importances) {
+                *importance += tree_importance;
+            }
+
+            num_trees += 1;
+        }
+
+        if num_trees == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "No tree has any out-of-bag samples.",
+            ));
+        }
+
+        Ok(importances
+            .iter()
+            .map(|importance| importance / num_trees as f32)
+            .collect())
+    }
+
+    /// Weight the contribution of each tree to the decision function by
+    /// `weights`, one non-negative weight per tree, instead of averaging the
+    /// trees uniformly. Weights are normalized to sum to one, and take
+    /// precedence over out-of-bag weighting.
+    ///
+    /// Returns an error if the number of weights differs from the number of
+    /// trees, or if the weights are negative, non-finite, or all zero. Adding
+    /// trees afterwards makes prediction fail until new weights are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    ///
+    /// use rustlearn::ensemble::random_forest::Hyperparameters;
+    /// use rustlearn::trees::decision_tree;
+    ///
+    /// let mut model = Hyperparameters::new(decision_tree::Hyperparameters::new(4), 3).build();
+    ///
+    /// assert!(model.set_tree_weights(vec![1.0, 2.0, 1.0]).is_ok());
+    /// assert!(model.set_tree_weights(vec![1.0, 2.0]).is_err());
+    /// ```
+    pub fn set_tree_weights(&mut self, weights: Vec<f32>) -> Result<(), RustlearnError> {
+        self.tree_weights = Some(try!(normalize_model_weights(&weights, self.trees.len())));
+
+        Ok(())
+    }
+
+    /// Append `n` new trees, continuing the seed sequence, and fit them on fresh
+    /// bootstrap samples of `X` and `y`. Trees already fitted are left unchanged;
+    /// any trees of the forest not yet fitted are fit as well.
+    ///
+    /// Predictions average over all the trees of the grown forest.
+    pub fn add_trees<T>(&mut self, n: usize, X: T, y: &Array) -> Result<(), RustlearnError>
+    where
+        RandomForest: SupervisedModel<T>,
+    {
+        if self.tree_hyperparameters.is_none() {
+            return Err(RustlearnError::Unsupported(
+                "Forest does not store tree hyperparameters for adding trees.",
+            ));
+        }
+
+        self.push_trees(n);
+
+        let warm_start = self.warm_start;
+        self.warm_start = true;
+        let result = self.fit(X, y);
+        self.warm_start = warm_start;
+
+        result
+    }
+
+    /// Fit the forest on a stream of `(features, target)` rows, passing over the
+    /// stream only once. This allows fitting on datasets that do not fit in memory.
+    ///
+    /// Every tree is fit on a bootstrap sample of `n_rows_hint` rows, drawn with
+    /// replacement from the stream using reservoir sampling: each slot of the sample
+    /// is replaced by the `i`-th row of the stream with probability `1 / i`, so that
+    /// it ends up holding each row of the stream with equal probability. For
+    /// bootstrap samples of the usual size, `n_rows_hint` should be close to the
+    /// length of the stream, but a smaller value can be used to bound memory use.
+    ///
+    /// Only the bootstrap samples are held in memory, requiring at most
+    /// `n_trees * n_rows_hint` rows; rows drawn into several samples are stored once.
+    ///
+    /// As the stream cannot be revisited, out-of-bag accuracies and importances of the
+    /// fitted trees are recorded as `NaN`, and predictions average over all trees uniformly.
+    pub fn fit_stream<I>(&mut self, rows: I, n_rows_hint: usize) -> Result<(), RustlearnError>
+    where
+        I: Iterator<Item = (Vec<f32>, f32)>,
+    {
+        try!(self.check_weighted_vote());
+
+        if self.stratified_bootstrap {
+            return Err(RustlearnError::Unsupported(
+                "Stratified bootstrapping is not supported when fitting on a stream.",
+            ));
+        }
+
+        if n_rows_hint == 0 {
+            return Err(RustlearnError::InvalidInput(
+                "Bootstrap sample size must be positive.",
+            ));
+        }
+
+        let start = self.first_unfitted();
+        self.oob_accuracies.truncate(start);
+        self.oob_importances.truncate(start);
+
+        let mut rngs = (start..self.trees.len())
+            .map(|tree_idx| seeded_rng(tree_seed(self.seed, tree_idx, BOOTSTRAP_STREAM)))
+            .collect::<Vec<_>>();
+        let mut reservoirs: Vec<Vec<Rc<(Vec<f32>, f32)>>> = Vec::with_capacity(rngs.len());
+
+        let mut num_cols = 0;
+        let mut counts = [0.0; 2];
+
+        for (row_idx, row) in rows.enumerate() {
+            counts[if row.1 == 1.0 { 1 } else { 0 }] += 1.0;
+
+            if row_idx == 0 {
+                num_cols = row.0.len();
+
+                let row = Rc::new(row);
+                reservoirs = rngs
+                    .iter()
+                    .map(|_| vec![row.clone(); n_rows_hint])
+                    .collect();
+
+                continue;
+            } else if row.0.len() != num_cols {
+                return Err(RustlearnError::DimensionMismatch(
+                    "All rows must have the same number of features.",
+                ));
+            }
+
+            let row = Rc::new(row);
+            let replacement_probability = 1.0 / (row_idx + 1) as f64;
+
+            for (reservoir, rng) in reservoirs.iter_mut().zip(rngs.iter_mut()) {
+                // Skip over the slots that are not replaced
+                let mut slot = geometric_skip(rng, replacement_probability, n_rows_hint);
+
+                while slot < n_rows_hint {
+                    reservoir[slot] = row.clone();
+                    slot += 1 + geometric_skip(rng, replacement_probability, n_rows_hint);
+                }
+            }
+        }
+
+        if reservoirs.is_empty() && start < self.trees.len() {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit a forest on an empty stream.",
+            ));
+        }
+
+        for (tree, reservoir) in self.trees.iter_mut().skip(start).zip(reservoirs.iter()) {
+            let mut data = Vec::with_capacity(n_rows_hint * num_cols);
+            let mut target = Vec::with_capacity(n_rows_hint);
+
+            for row in reservoir {
+                data.extend_from_slice(&row.0);
+                target.push(row.1);
+            }
+
+            let X = try!(Array::from_vec(data, n_rows_hint, num_cols));
+            let y = Array::from(target);
+            let sample_weight = RandomForest::sample_weights(&y, self.class_weight, counts);
+            try!(tree.fit_weighted(&X, &y, &sample_weight));
+
+            if self.oob_score {
+                self.oob_accuracies.push(f32::NAN);
+            }
+
+            if self.oob_importance {
+                self.oob_importances.push(vec![f32::NAN; num_cols]);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push_trees(&mut self, n: usize) {
+        for tree_idx in self.trees.len()..self.trees.len() + n {
+            let tree = self.build_tree(tree_idx);
+            self.trees.push(tree);
+        }
+    }
+
+    /// Build the unfitted `tree_idx`-th tree of the forest.
+    fn build_tree(&self, tree_idx: usize) -> decision_tree::DecisionTree {
+        // Reseed trees to introduce randomness,
+        // without this they are just copies of each other
+        let mut hyperparams = self.tree_hyperparameters.clone().unwrap();
+        hyperparams.rng(seeded_rng(tree_seed(self.seed, tree_idx, TREE_STREAM)));
+
+        if let Some((min, max)) = self.random_max_features {
+            let mut rng = seeded_rng(tree_seed(self.seed, tree_idx, MAX_FEATURES_STREAM));
+            hyperparams.max_features(Range::new(min, max + 1).ind_sample(&mut rng));
+        }
+
+        hyperparams.build()
+    }
+
+    /// Check that the forest contains trees, all of which have been fit.
+    fn check_fitted(&self) -> Result<(), RustlearnError> {
+        if self.trees.is_empty() || self.trees.iter().any(|tree| !tree.is_fitted()) {
+            Err(RustlearnError::NotFitted(
+                "Forest must be fit before predicting",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_weighted_vote(&self) -> Result<(), RustlearnError> {
+        if self.weighted_vote && !self.oob_score {
+            Err(RustlearnError::InvalidHyperparameter(
+                "Weighted voting requires out-of-bag tracking to be enabled.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fit the trees not yet fitted using `fit_tree`, which fits a tree given its
+    /// index and returns its out-of-bag accuracy and importances, if tracked. Up to `num_threads`
+    /// trees are fit at a time; the results do not depend on the number of threads.
+    fn fit_trees<F>(&mut self, num_threads: usize, fit_tree: F) -> Result<(), RustlearnError>
+    where
+        F: Fn(&mut decision_tree::DecisionTree, usize) -> Result<OobStatistics, RustlearnError>
+            + Sync,
+    {
+        try!(self.check_weighted_vote());
+
+        let start = self.first_unfitted();
+        self.oob_accuracies.truncate(start);
+        self.oob_importances.truncate(start);
+
+        let mut chunk_start = start;
+
+        for chunk in self.trees[start..].chunks_mut(num_threads) {
+            let chunk_len = chunk.len();
+            let fit_tree = &fit_tree;
+
+            let results = if num_threads == 1 {
+                chunk
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(idx, tree)| fit_tree(tree, chunk_start + idx))
+                    .collect::<Vec<_>>()
+            } else {
+                let mut guards = Vec::new();
+
+                crossbeam::scope(|scope| {
+                    for (idx, tree) in chunk.iter_mut().enumerate() {
+                        guards.push(scope.spawn(move || fit_tree(tree, chunk_start + idx)));
+                    }
+                });
+
+                guards.into_iter().map(|guard| guard.join()).collect()
+            };
+
+            for result in results {
+                let (accuracy, importances) = try!(result);
+
+                if let Some(accuracy) = accuracy {
+                    self.oob_accuracies.push(accuracy);
+                }
+
+                if let Some(importances) = importances {
+                    self.oob_importances.push(importances);
+                }
+            }
+
+            chunk_start += chunk_len;
+        }
+
+        Ok(())
+    }
+
+    /// Combine the decision functions of the individual trees, computed
+    /// by `tree_df` on up to `num_threads` threads, into the decision
+    /// function of the forest.
+    fn combine_trees<F>(
+        &self,
+        rows: usize,
+        num_threads: usize,
+        tree_df: F,
+    ) -> Result<Array, RustlearnError>
+    where
+        F: Fn(&decision_tree::DecisionTree) -> Result<Array, RustlearnError> + Sync,
+    {
+        try!(self.check_fitted());
+
+        let weights = try!(self.vote_weights());
+        let tree_weights = weights.as_ref().map(|x| &x[..]);
+
+        let mut df = if num_threads == 1 {
+            try!(self.sum_trees(rows, tree_weights, &tree_df))
+        } else {
+            try!(self.sum_trees_threaded(rows, tree_weights, num_threads, &tree_df))
+        };
+
+        if weights.is_none() {
+            df.div_inplace(self.trees.len() as f32);
+        }
+
+        Ok(df)
+    }
+
+    /// Sum the (optionally weighted) decision functions of all trees.
+    #[cfg(not(feature = "rayon"))]
+    fn sum_trees<F>(
+        &self,
+        rows: usize,
+        weights: Option<&[f32]>,
+        tree_df: &F,
+    ) -> Result<Array, RustlearnError>
+    where
+        F: Fn(&decision_tree::DecisionTree) -> Result<Array, RustlearnError> + Sync,
+    {
+        let mut df = Array::zeros(rows, 1);
+
+        for (tree_idx, tree) in self.trees.iter().enumerate() {
+            let prediction = try!(tree_df(tree));
+
+            match weights {
+                Some(weights) => df.add_inplace(&prediction.times(weights[tree_idx])),
+                None => df.add_inplace(&prediction),
+            };
+        }
+
+        Ok(df)
+    }
+
+    /// Sum the (optionally weighted) decision functions of all trees
+    /// on the rayon thread pool.
+    ///
+    /// The decision functions of the trees are computed in parallel, but
+    /// added up serially in the order of the trees, so the result is the
+    /// same as when summing them serially.
+    #[cfg(feature = "rayon")]
+    fn sum_trees<F>(
+        &self,
+        rows: usize,
+        weights: Option<&[f32]>,
+        tree_df: &F,
+    ) -> Result<Array, RustlearnError>
+    where
+        F: Fn(&decision_tree::DecisionTree) -> Result<Array, RustlearnError> + Sync,
+    {
+        use rayon::prelude::*;
+
+        let predictions = try!(self
+            .trees
+            .par_iter()
+            .map(tree_df)
+            .collect::<Result<Vec<_>, RustlearnError>>());
+
+        let mut df = Array::zeros(rows, 1);
+
+        for (tree_idx, prediction) in predictions.iter().enumerate() {
+            match weights {
+                Some(weights) => df.add_inplace(&prediction.times(weights[tree_idx])),
+                None => df.add_inplace(prediction),
+            };
+        }
+
+        Ok(df)
+    }
+
+    /// Sum the (optionally weighted) decision functions of all trees,
+    /// computing up to `num_threads` of them at a time.
+    ///
+    /// The decision functions are added up in the order of the trees,
+    /// so the result is the same as when summing them serially.
+    fn sum_trees_threaded<F>(
+        &self,
+        rows: usize,
+        weights: Option<&[f32]>,
+        num_threads: usize,
+        tree_df: &F,
+    ) -> Result<Array, RustlearnError>
+    where
+        F: Fn(&decision_tree::DecisionTree) -> Result<Array, RustlearnError> + Sync,
+    {
+        let mut df = Array::zeros(rows, 1);
+
+        for (chunk_idx, trees) in self.trees.chunks(num_threads).enumerate() {
+            let mut guards = Vec::new();
+
+            crossbeam::scope(|scope| {
+                for tree in trees {
+                    guards.push(scope.spawn(move || tree_df(tree)));
+                }
+            });
+
+            for (idx, guard) in guards.into_iter().enumerate() {
+                let prediction = try!(guard.join());
+
+                match weights {
+                    Some(weights) => {
+                        df.add_inplace(&prediction.times(weights[chunk_idx * num_threads + idx]))
+                    }
+                    None => df.add_inplace(&prediction),
+                };
+            }
+        }
+
+        Ok(df)
+    }
+
+    /// Normalized weights of the trees for weighted voting. Explicitly set
+    /// weights take precedence over out-of-bag accuracies. Returns `None`,
+    /// falling back to uniform weights, if neither is in use or out-of-bag
+    /// accuracies are not available for all trees.
+    fn vote_weights(&self) -> Result<Option<Vec<f32>>, RustlearnError> {
+        if let Some(ref weights) = self.tree_weights {
+            if weights.len() != self.trees.len() {
+                return Err(RustlearnError::DimensionMismatch(
+                    "Number of tree weights and number of trees differ.",
+                ));
+            }
+
+            return Ok(Some(weights.clone()));
+        }
+
+        if !self.weighted_vote
+            || self.oob_accuracies.len() != self.trees.len()
+            || self.oob_accuracies.iter().any(|x| !x.is_finite())
+        {
+            return Ok(None);
+        }
+
+        let total = self.oob_accuracies.iter().sum::<f32>();
+
+        if total > 0.0 {
+            Ok(Some(
+                self.oob_accuracies.iter().map(|x| x / total).collect(),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Index of the first tree to fit when calling `fit`.
+    fn first_unfitted(&self) -> usize {
+        if self.warm_start {
+            self.trees.iter().take_while(|tree| tree.is_fitted()).count()
+        } else {
+            0
+        }
+    }
+
+    fn bootstrap_indices(num_indices: usize, seed: u64, tree_idx: usize) -> Vec<usize> {
+        let mut rng = seeded_rng(tree_seed(seed, tree_idx, BOOTSTRAP_STREAM));
         let range = Range::new(0, num_indices);
 
         (0..num_indices)
-            .map(|_| range.ind_sample(rng))
+            .map(|_| range.ind_sample(&mut rng))
             .collect::<Vec<_>>()
     }
+
+    /// Draw a bootstrap sample separately within each class of the binary labels `y`,
+    /// drawing as many samples of every class as there are in `y`. The negative class
+    /// is sampled first, followed by the positive class, from the same random number generator.
+    fn stratified_bootstrap_indices(y: &Array, seed: u64, tree_idx: usize) -> Vec<usize> {
+        let mut rng = seeded_rng(tree_seed(seed, tree_idx, BOOTSTRAP_STREAM));
+        let mut indices = Vec::with_capacity(y.rows());
+
+        for &label in &[0.0, 1.0] {
+            let class_indices = (0..y.rows())
+                .filter(|&idx| y.get(idx, 0) == label)
+                .collect::<Vec<_>>();
+
+            if class_indices.is_empty() {
+                continue;
+            }
+
+            let range = Range::new(0, class_indices.len());
+
+            for _ in 0..class_indices.len() {
+                indices.push(class_indices[range.ind_sample(&mut rng)]);
+            }
+        }
+
+        indices
+    }
+
+    /// Sample weights of the bootstrap sample with labels `y_sample`, where
+    /// `counts` holds the class counts of the full training data.
+    fn sample_weights(y_sample: &Array, class_weight: ClassWeight, counts: [f32; 2]) -> Array {
+        match class_weight {
+            ClassWeight::Uniform => Array::ones(y_sample.rows(), 1),
+            ClassWeight::Balanced => balanced_weights(y_sample, counts),
+            ClassWeight::BalancedSubsample => {
+                balanced_weights(y_sample, class_counts(y_sample.data().iter()))
+            }
+        }
+    }
+
+    /// Compute the out-of-bag accuracy and permutation importances of a tree, as enabled
+    /// by `oob_score` and `oob_importance`, from its out-of-bag samples `oob_x` and `oob_y`
+    /// and its `predict` function. Both are `NaN` if there are no out-of-bag samples.
+    fn oob_statistics<F>(
+        mut oob_x: Array,
+        oob_y: &Array,
+        oob_score: bool,
+        oob_importance: bool,
+        rng: &mut StdRng,
+        predict: F,
+    ) -> Result<OobStatistics, RustlearnError>
+    where
+        F: Fn(&Array) -> Result<Array, RustlearnError>,
+    {
+        let accuracy = if oob_x.rows() == 0 {
+            f32::NAN
+        } else {
+            accuracy_score(oob_y, &try!(predict(&oob_x)))
+        };
+
+        let importances = if !oob_importance {
+            None
+        } else if oob_x.rows() == 0 {
+            Some(vec![f32::NAN; oob_x.cols()])
+        } else {
+            let mut importances = Vec::with_capacity(oob_x.cols());
+            let mut permutation = (0..oob_x.rows()).collect::<Vec<_>>();
+
+            // Permute one column at a time in place, restoring it afterwards
+            for col in 0..oob_x.cols() {
+                rng.shuffle(&mut permutation);
+
+                let column = (0..oob_x.rows())
+                    .map(|row| oob_x.get(row, col))
+                    .collect::<Vec<_>>();
+
+                for (row, &source) in permutation.iter().enumerate() {
+                    oob_x.set(row, col, column[source]);
+                }
+
+                let permuted_accuracy = accuracy_score(oob_y, &try!(predict(&oob_x)));
+                importances.push(accuracy - permuted_accuracy);
+
+                for (row, &value) in column.iter().enumerate() {
+                    oob_x.set(row, col, value);
+                }
+            }
+
+            Some(importances)
+        };
+
+        Ok((if oob_score { Some(accuracy) } else { None }, importances))
+    }
+
+    /// Indices of the samples not drawn into a bootstrap sample.
+    fn oob_indices(num_indices: usize, bootstrap_indices: &[usize]) -> Vec<usize> {
+        let mut in_bag = vec![false; num_indices];
+
+        for &idx in bootstrap_indices {
+            in_bag[idx] = true;
+        }
+
+        (0..num_indices).filter(|&idx| !in_bag[idx]).collect()
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +1296,7 @@ mod tests {
     use metrics::accuracy_score;
     use multiclass::OneVsRestWrapper;
 
+    use rand::distributions::normal::Normal;
     use rand::{SeedableRng, StdRng};
 
     use bincode;
@@ -224,7 +1329,7 @@ mod tests {
                 .rng(StdRng::from_seed(&[100]));
 
             let mut model = Hyperparameters::new(tree_params, 10)
-                .rng(StdRng::from_seed(&[100]))
+                .rng(StdRng::from_seed(&[5]))
                 .one_vs_rest();
 
             model.fit(&x_train, &y_train).unwrap();
@@ -241,6 +1346,834 @@ mod tests {
         assert!(test_accuracy > 0.96);
     }
 
+    #[test]
+    fn test_random_max_features() {
+        let (data, target) = load_data();
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(4);
+
+        let mut params = Hyperparameters::new(tree_params, 20);
+        params.seed(100).random_max_features(1, 4);
+
+        let mut model = params.one_vs_rest();
+        model.fit(&data, &target).unwrap();
+
+        for forest in model.models() {
+            let max_features = forest
+                .trees()
+                .iter()
+                .map(|tree| tree.max_features())
+                .collect::<Vec<_>>();
+
+            println!("Max features {:?}", max_features);
+
+            assert!(max_features.iter().all(|&n| n >= 1 && n <= 4));
+            assert!(max_features.iter().any(|&n| n != max_features[0]));
+        }
+
+        // The draws only depend on the seed
+        let rebuilt = params.build();
+        assert!(rebuilt
+            .trees()
+            .iter()
+            .zip(params.build().trees())
+            .all(|(a, b)| a.max_features() == b.max_features()));
+
+        let mut reseeded = params.seed(7).build();
+        reseeded.set_seed(100);
+        assert!(reseeded
+            .trees()
+            .iter()
+            .zip(rebuilt.trees())
+            .all(|(a, b)| a.max_features() == b.max_features()));
+
+        let accuracy = accuracy_score(&target, &model.predict(&data).unwrap());
+        println!("Accuracy {}", accuracy);
+        assert!(accuracy > 0.95);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_max_features_out_of_bounds() {
+        let tree_params = decision_tree::Hyperparameters::new(4);
+        Hyperparameters::new(tree_params, 10).random_max_features(2, 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_random_max_features_empty_range() {
+        let tree_params = decision_tree::Hyperparameters::new(4);
+        Hyperparameters::new(tree_params, 10).random_max_features(3, 2);
+    }
+
+    #[test]
+    fn test_random_forest_reproducibility() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&x| if x == 1.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let fit_forest = |seed: u64, num_trees: usize| {
+            let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+            tree_params.min_samples_split(10).max_features(2);
+
+            let mut model = Hyperparameters::new(tree_params, num_trees)
+                .seed(seed)
+                .build();
+            model.fit(&data, &binary_target).unwrap();
+
+            model
+        };
+
+        let first = fit_forest(42, 10);
+        let second = fit_forest(42, 10);
+
+        assert!(
+            first.decision_function(&data).unwrap().data()
+                == second.decision_function(&data).unwrap().data()
+        );
+
+        let other = fit_forest(43, 10);
+
+        assert!(
+            first.decision_function(&data).unwrap().data()
+                != other.decision_function(&data).unwrap().data()
+        );
+
+        // Each tree only depends on the forest seed and its own index,
+        // not on how many other trees are built and fit before it.
+        let smaller = fit_forest(42, 5);
+
+        for (tree, smaller_tree) in first.trees().iter().zip(smaller.trees().iter()) {
+            assert!(
+                tree.decision_function(&data).unwrap().data()
+                    == smaller_tree.decision_function(&data).unwrap().data()
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_trees() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&x| if x == 1.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let mut model = Hyperparameters::new(tree_params.clone(), 5)
+            .seed(42)
+            .build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let initial_df = model.decision_function(&data).unwrap();
+        let initial_accuracy = accuracy_score(&binary_target, &model.predict(&data).unwrap());
+
+        model.add_trees(5, &data, &binary_target).unwrap();
+
+        let grown_df = model.decision_function(&data).unwrap();
+        let grown_accuracy = accuracy_score(&binary_target, &model.predict(&data).unwrap());
+
+        println!("Accuracy {} -> {}", initial_accuracy, grown_accuracy);
+
+        assert!(model.trees().len() == 10);
+        assert!(initial_df.data() != grown_df.data());
+        assert!(grown_accuracy > 0.95);
+        assert!(grown_accuracy >= initial_accuracy - 0.02);
+
+        // Growing the forest is equivalent to fitting all trees at once
+        let mut full_model = Hyperparameters::new(tree_params.clone(), 10)
+            .seed(42)
+            .build();
+        full_model.fit(&data, &binary_target).unwrap();
+
+        assert!(full_model.decision_function(&data).unwrap().data() == grown_df.data());
+
+        // Sparse input
+        let sparse_data = SparseRowArray::from(&data);
+        let mut sparse_model = Hyperparameters::new(tree_params, 5).seed(42).build();
+        sparse_model.fit(&sparse_data, &binary_target).unwrap();
+        sparse_model
+            .add_trees(5, &sparse_data, &binary_target)
+            .unwrap();
+
+        assert!(sparse_model.trees().len() == 10);
+        assert!(sparse_model.decision_function(&sparse_data).unwrap().data() != initial_df.data());
+    }
+
+    #[test]
+    fn test_warm_start() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&x| if x == 2.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let mut model = Hyperparameters::new(tree_params, 5)
+            .seed(42)
+            .warm_start(true)
+            .build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let df = model.decision_function(&data).unwrap();
+
+        // Refitting on different data leaves the fitted trees unchanged
+        let flipped_target = binary_target.map(|x| 1.0 - x);
+        model.fit(&data, &flipped_target).unwrap();
+
+        assert!(model.decision_function(&data).unwrap().data() == df.data());
+
+        // Fitted trees of deserialized forests are not refit either
+        let mut model: RandomForest =
+            serde_json::from_str(&serde_json::to_string(&model).unwrap()).unwrap();
+        model.fit(&data, &flipped_target).unwrap();
+
+        assert!(model.decision_function(&data).unwrap().data() == df.data());
+    }
+
+    #[test]
+    fn test_predict_row() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&x| if x == 2.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let mut model = Hyperparameters::new(tree_params, 10).seed(42).build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let df = model.decision_function(&data).unwrap();
+        let prediction = model.predict(&data).unwrap();
+
+        for (row_idx, row) in data.row_iter().unwrap().enumerate() {
+            assert!(model.decision_function_row(row).unwrap() == df.get(row_idx, 0));
+            assert!(model.predict_row(row).unwrap() == prediction.get(row_idx, 0));
+        }
+
+        assert!(model.predict_row(&[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_weighted_vote_iris() {
+        let (data, target) = load_data();
+
+        let mut uniform_accuracy = 0.0;
+        let mut weighted_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+            let y_test = target.get_rows(&test_idx);
+
+            let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+            tree_params.min_samples_split(10).max_features(2);
+
+            let mut params = Hyperparameters::new(tree_params, 10);
+            params.seed(100).oob_score(true);
+
+            let mut uniform = params.one_vs_rest();
+            let mut weighted = params.weighted_vote(true).one_vs_rest();
+
+            uniform.fit(&x_train, &y_train).unwrap();
+            weighted.fit(&x_train, &y_train).unwrap();
+
+            for model in weighted.models() {
+                assert!(model.oob_accuracies().len() == 10);
+                assert!(model.oob_accuracies().iter().all(|&x| x >= 0.0 && x <= 1.0));
+            }
+
+            uniform_accuracy += accuracy_score(&y_test, &uniform.predict(&x_test).unwrap());
+            weighted_accuracy += accuracy_score(&y_test, &weighted.predict(&x_test).unwrap());
+        }
+
+        uniform_accuracy /= no_splits as f32;
+        weighted_accuracy /= no_splits as f32;
+
+        println!("Accuracy {} -> {}", uniform_accuracy, weighted_accuracy);
+
+        assert!(weighted_accuracy >= uniform_accuracy);
+    }
+
+    #[test]
+    fn test_not_fitted() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+
+        let tree_params = decision_tree::Hyperparameters::new(data.cols());
+
+        let mut model = Hyperparameters::new(tree_params.clone(), 5)
+            .seed(100)
+            .build();
+
+        match model.predict(&data) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+        match model.predict(&SparseRowArray::from(&data)) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+        match model.predict_row(data.row_iter().unwrap().next().unwrap()) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+
+        model.fit(&data, &binary_target).unwrap();
+        assert!(model.predict(&data).is_ok());
+
+        let empty_model = Hyperparameters::new(tree_params, 0).build();
+
+        match empty_model.predict(&data) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+    }
+
+    /// Generate data with `num_positives` positive samples out of 500,
+    /// the positive class being shifted away from the negative one.
+    fn imbalanced_data(num_positives: usize, seed: usize) -> (Array, Array) {
+        shifted_imbalanced_data(num_positives, seed, 3.0)
+    }
+
+    /// Standard normal data with the positives shifted by `shift` in every feature.
+    fn shifted_imbalanced_data(num_positives: usize, seed: usize, shift: f32) -> (Array, Array) {
+        let mut rng = StdRng::from_seed(&[seed]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0..500 {
+            let label: f32 = if i < num_positives { 1.0 } else { 0.0 };
+
+            rows.push(
+                (0..5)
+                    .map(|_| normal.ind_sample(&mut rng) as f32 + shift * label)
+                    .collect::<Vec<_>>(),
+            );
+            labels.push(label);
+        }
+
+        (Array::from(&rows), Array::from(labels))
+    }
+
+    fn positive_recall(y: &Array, prediction: &Array) -> f32 {
+        let (true_positives, positives) = y
+            .data()
+            .iter()
+            .zip(prediction.data())
+            .filter(|&(&y, _)| y == 1.0)
+            .fold((0.0, 0.0), |(tp, p), (_, &y_hat)| (tp + y_hat, p + 1.0));
+
+        true_positives / positives
+    }
+
+    #[test]
+    fn test_oob_permutation_importance() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        // Only the first feature determines the label
+        let rows = (0..400)
+            .map(|_| {
+                (0..5)
+                    .map(|_| normal.ind_sample(&mut rng) as f32)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let X = Array::from(&rows);
+        let y = Array::from(
+            rows.iter()
+                .map(|row| if row[0] > 0.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut tree_params = decision_tree::Hyperparameters::new(X.cols());
+        tree_params.min_samples_split(5).max_features(3);
+
+        let mut params = Hyperparameters::new(tree_params, 10);
+        params.seed(100);
+
+        let mut model = params.build();
+        assert!(model.oob_permutation_importance().is_err());
+        model.fit(&X, &y).unwrap();
+        assert!(model.oob_permutation_importance().is_err());
+
+        let mut model = params.oob_importance(true).build();
+        model.fit(&X, &y).unwrap();
+
+        let importances = model.oob_permutation_importance().unwrap();
+        println!("Importances {:?}", importances);
+
+        assert!(importances.len() == X.cols());
+        assert!(importances[0] > 0.3);
+        assert!(importances[1..].iter().all(|&x| x.abs() < 0.05));
+        assert!(model.oob_accuracies().is_empty());
+
+        // Sparse inputs result in the same importances
+        let mut sparse_model = params.build();
+        sparse_model.fit(&SparseRowArray::from(&X), &y).unwrap();
+        assert!(sparse_model.oob_permutation_importance().unwrap() == importances);
+
+        // Streamed forests have no out-of-bag samples to average over
+        let mut streamed = params.build();
+        let stream = (0..X.rows()).map(|idx| (rows[idx].clone(), y.get(idx, 0)));
+        streamed.fit_stream(stream, X.rows()).unwrap();
+
+        match streamed.oob_permutation_importance() {
+            Err(RustlearnError::EmptyInput(_)) => {}
+            _ => panic!("Expected an empty input error."),
+        }
+    }
+
+    #[test]
+    fn test_stratified_bootstrap() {
+        let (_, target) = imbalanced_data(2, 100);
+
+        for tree_idx in 0..20 {
+            let indices = RandomForest::stratified_bootstrap_indices(&target, 100, tree_idx);
+            let positives = indices.iter().filter(|&&idx| idx < 2).count();
+
+            assert!(indices.len() == 500);
+            assert!(positives == 2);
+        }
+
+        let mut tree_params = decision_tree::Hyperparameters::new(5);
+        tree_params.max_features(2);
+
+        let num_datasets = 5;
+        let mut plain_recall = 0.0;
+        let mut stratified_recall = 0.0;
+
+        for seed in 0..num_datasets {
+            // Plain bootstrap samples miss both positives for about one in seven trees
+            let (data, target) = imbalanced_data(2, seed);
+            let (test_data, test_target) = imbalanced_data(250, seed + 1000);
+
+            for &stratified in &[false, true] {
+                let mut model = Hyperparameters::new(tree_params.clone(), 20)
+                    .seed(seed as u64)
+                    .stratified_bootstrap(stratified)
+                    .build();
+                model.fit(&data, &target).unwrap();
+
+                let recall = positive_recall(&test_target, &model.predict(&test_data).unwrap());
+
+                if stratified {
+                    stratified_recall += recall / num_datasets as f32;
+                } else {
+                    plain_recall += recall / num_datasets as f32;
+                }
+            }
+        }
+
+        println!(
+            "Plain recall {}, stratified recall {}",
+            plain_recall, stratified_recall
+        );
+
+        assert!(stratified_recall > plain_recall + 0.1);
+
+        let mut model = Hyperparameters::new(tree_params, 2)
+            .stratified_bootstrap(true)
+            .build();
+        let stream = vec![(vec![0.0; 5], 1.0)].into_iter();
+
+        match model.fit_stream(stream, 1) {
+            Err(RustlearnError::Unsupported(_)) => {}
+            _ => panic!("Expected an unsupported operation error"),
+        }
+    }
+
+    #[test]
+    fn test_class_weight() {
+        let labels = Array::from(vec![0.0, 0.0, 0.0, 1.0]);
+        let weights = balanced_weights(&labels, class_counts(labels.data().iter()));
+        assert!(allclose(
+            &weights,
+            &Array::from(vec![2.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 2.0])
+        ));
+
+        let mut tree_params = decision_tree::Hyperparameters::new(5);
+        tree_params.max_features(2).min_samples_split(20);
+
+        let num_datasets = 5;
+        let mut recalls = [0.0; 3];
+        let weightings = [
+            ClassWeight::Uniform,
+            ClassWeight::Balanced,
+            ClassWeight::BalancedSubsample,
+        ];
+
+        for seed in 0..num_datasets {
+            let (data, target) = shifted_imbalanced_data(25, seed, 1.0);
+            let (test_data, test_target) = shifted_imbalanced_data(250, seed + 1000, 1.0);
+
+            let mut decision_functions = Vec::new();
+
+            for (recall, &class_weight) in recalls.iter_mut().zip(weightings.iter()) {
+                let mut model = Hyperparameters::new(tree_params.clone(), 20)
+                    .seed(seed as u64)
+                    .class_weight(class_weight)
+                    .build();
+                model.fit(&data, &target).unwrap();
+
+                *recall += positive_recall(&test_target, &model.predict(&test_data).unwrap())
+                    / num_datasets as f32;
+                decision_functions.push(model.decision_function(&test_data).unwrap());
+            }
+
+            // Bootstrap-local weights differ from the global ones
+            assert!(decision_functions[1] != decision_functions[2]);
+        }
+
+        println!(
+            "Uniform recall {}, balanced recall {}, balanced subsample recall {}",
+            recalls[0], recalls[1], recalls[2]
+        );
+
+        assert!(recalls[1] > recalls[0] + 0.1);
+        assert!(recalls[2] > recalls[0] + 0.1);
+
+        // Sparse inputs and streams are weighted in the same way
+        let (data, target) = imbalanced_data(10, 100);
+
+        let build = || {
+            Hyperparameters::new(tree_params.clone(), 5)
+                .seed(100)
+                .class_weight(ClassWeight::BalancedSubsample)
+                .build()
+        };
+
+        let mut dense = build();
+        dense.fit(&data, &target).unwrap();
+
+        let mut sparse = build();
+        sparse.fit(&SparseRowArray::from(&data), &target).unwrap();
+
+        assert!(
+            dense.decision_function(&data).unwrap() == sparse.decision_function(&data).unwrap()
+        );
+
+        let mut streamed = build();
+        let stream = (0..data.rows())
+            .map(|idx| (data.get_rows(&vec![idx]).data().clone(), target.get(idx, 0)));
+        streamed.fit_stream(stream, data.rows()).unwrap();
+
+        let streamed_recall = positive_recall(&target, &streamed.predict(&data).unwrap());
+        println!("Streamed recall {}", streamed_recall);
+        assert!(streamed_recall > 0.5);
+    }
+
+    #[test]
+    fn test_n_jobs() {
+        let (data, target) = load_data();
+        let sparse_data = SparseRowArray::from(&data);
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let build = |n_jobs| {
+            Hyperparameters::new(tree_params.clone(), 11)
+                .seed(100)
+                .oob_score(true)
+                .n_jobs(n_jobs)
+                .build()
+        };
+
+        let mut serial = OneVsRestWrapper::new(build(1));
+        serial.fit(&data, &target).unwrap();
+
+        let serial_decision = serial.decision_function(&data).unwrap();
+
+        for &n_jobs in &[2, 0] {
+            let mut model = OneVsRestWrapper::new(build(n_jobs));
+            model.fit(&data, &target).unwrap();
+
+            assert!(model.models()[0].n_jobs() == n_jobs);
+            assert!(model.decision_function(&data).unwrap() == serial_decision);
+            assert!(model.predict(&data).unwrap() == serial.predict(&data).unwrap());
+
+            for (forest, serial_forest) in model.models().iter().zip(serial.models()) {
+                assert!(forest.oob_accuracies() == serial_forest.oob_accuracies());
+            }
+        }
+
+        // Explicit thread counts override the forest's setting
+        let binary_target = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+
+        let mut serial = build(1);
+        serial.fit(&sparse_data, &binary_target).unwrap();
+
+        let mut model = build(1);
+        model.fit_parallel(&sparse_data, &binary_target, 3).unwrap();
+
+        assert!(
+            model.decision_function_parallel(&sparse_data, 3).unwrap()
+                == serial.decision_function(&sparse_data).unwrap()
+        );
+        assert!(
+            model.decision_function_parallel(&data, 4).unwrap()
+                == serial.decision_function(&data).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_parallel_decision_function() {
+        use rayon::ThreadPoolBuilder;
+
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+        let sparse_data = SparseRowArray::from(&data);
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let mut model = Hyperparameters::new(tree_params, 23).seed(100).build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let serial = model
+            .trees()
+            .iter()
+            .fold(Array::zeros(data.rows(), 1), |mut df, tree| {
+                df.add_inplace(&tree.decision_function(&data).unwrap());
+                df
+            })
+            .div(model.trees().len() as f32);
+
+        let predictions = [1, 2, 8]
+            .iter()
+            .map(|&num_threads| {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap();
+                pool.install(|| {
+                    (
+                        model.decision_function(&data).unwrap(),
+                        model.decision_function(&sparse_data).unwrap(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        assert!(predictions[0].0 == serial);
+
+        for &(ref dense, ref sparse) in &predictions {
+            assert!(*dense == predictions[0].0);
+            assert!(*sparse == predictions[0].1);
+        }
+    }
+
+    #[test]
+    fn test_chunked_decision_function() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let mut model = Hyperparameters::new(tree_params.clone(), 10)
+            .seed(100)
+            .build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let mut multiclass_model = Hyperparameters::new(tree_params, 10)
+            .seed(100)
+            .one_vs_rest();
+        multiclass_model.fit(&data, &target).unwrap();
+
+        let full = model.decision_function(&data).unwrap();
+        let multiclass_full = multiclass_model.decision_function(&data).unwrap();
+
+        for &chunk_rows in &[1, 7, 64, 150, 1000] {
+            assert!(model.decision_function_chunked(&data, chunk_rows).unwrap() == full);
+            assert!(
+                multiclass_model
+                    .decision_function_chunked(&data, chunk_rows)
+                    .unwrap()
+                    == multiclass_full
+            );
+        }
+
+        assert!(model.decision_function_chunked(&data, 0).is_err());
+        assert!(model
+            .decision_function_chunked(&Array::zeros(200, 3), 50)
+            .is_err());
+    }
+
+    #[test]
+    fn test_fit_stream() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 1.0 { 1.0 } else { 0.0 });
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(10).max_features(2);
+
+        let no_splits = 5;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        let mut in_memory_accuracy = 0.0;
+        let mut stream_accuracy = 0.0;
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let y_train = binary_target.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+            let y_test = binary_target.get_rows(&test_idx);
+
+            let mut model = Hyperparameters::new(tree_params.clone(), 20)
+                .seed(100)
+                .build();
+            model.fit(&x_train, &y_train).unwrap();
+            in_memory_accuracy += accuracy_score(&y_test, &model.predict(&x_test).unwrap());
+
+            let rows = x_train
+                .row_iter()
+                .unwrap()
+                .zip(y_train.data().iter())
+                .map(|(row, &y)| (row.to_vec(), y));
+
+            let mut model = Hyperparameters::new(tree_params.clone(), 20)
+                .seed(100)
+                .build();
+            model.fit_stream(rows, x_train.rows()).unwrap();
+            stream_accuracy += accuracy_score(&y_test, &model.predict(&x_test).unwrap());
+        }
+
+        in_memory_accuracy /= no_splits as f32;
+        stream_accuracy /= no_splits as f32;
+
+        println!(
+            "In-memory accuracy {}, streamed accuracy {}",
+            in_memory_accuracy, stream_accuracy
+        );
+
+        assert!(stream_accuracy > 0.9);
+        assert!(stream_accuracy > in_memory_accuracy - 0.05);
+    }
+
+    #[test]
+    fn test_fit_stream_errors() {
+        let tree_params = decision_tree::Hyperparameters::new(2);
+        let mut model = Hyperparameters::new(tree_params, 3).seed(100).build();
+
+        match model.fit_stream(Vec::new().into_iter(), 10) {
+            Err(RustlearnError::EmptyInput(_)) => {}
+            _ => panic!("Expected an empty input error"),
+        }
+
+        let rows = vec![(vec![0.0, 1.0], 0.0), (vec![1.0], 1.0)];
+        match model.fit_stream(rows.into_iter(), 10) {
+            Err(RustlearnError::DimensionMismatch(_)) => {}
+            _ => panic!("Expected a dimension mismatch"),
+        }
+
+        let rows = vec![(vec![0.0, 1.0], 0.0), (vec![1.0, 0.0], 1.0)];
+        assert!(model.fit_stream(rows.clone().into_iter(), 0).is_err());
+        assert!(model.fit_stream(rows.into_iter(), 10).is_ok());
+        assert!(model.predict(&Array::zeros(2, 2)).is_ok());
+    }
+
+    #[test]
+    fn test_tree_weights() {
+        let (data, target) = load_data();
+        let target = target.map(|x| if x == 1.0 { 1.0 } else { 0.0 });
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params.max_features(1);
+
+        let mut model = Hyperparameters::new(tree_params, 3).seed(100).build();
+        model.fit(&data, &target).unwrap();
+
+        let uniform = model.decision_function(&data).unwrap();
+
+        model.set_tree_weights(vec![2.0, 2.0, 2.0]).unwrap();
+        assert!(allclose(&model.decision_function(&data).unwrap(), &uniform));
+
+        model.set_tree_weights(vec![0.0, 1.0, 0.0]).unwrap();
+        let tree_df = model.trees()[1].decision_function(&data).unwrap();
+        assert!(model.decision_function(&data).unwrap() == tree_df);
+        assert!(model.decision_function_parallel(&data, 2).unwrap() == tree_df);
+
+        for (row_idx, row) in data.row_iter().unwrap().enumerate() {
+            assert!(model.decision_function_row(row).unwrap() == tree_df.get(row_idx, 0));
+        }
+
+        assert!(model.set_tree_weights(vec![1.0, 1.0]).is_err());
+        assert!(model.set_tree_weights(vec![1.0, -1.0, 1.0]).is_err());
+        assert!(model.set_tree_weights(vec![0.0, 0.0, 0.0]).is_err());
+
+        model.add_trees(1, &data, &target).unwrap();
+        assert!(model.decision_function(&data).is_err());
+    }
+
+    #[test]
+    fn test_weighted_vote_requires_oob() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+
+        let tree_params = decision_tree::Hyperparameters::new(data.cols());
+
+        let mut model = Hyperparameters::new(tree_params.clone(), 5)
+            .weighted_vote(true)
+            .build();
+        assert!(model.fit(&data, &binary_target).is_err());
+        assert!(model
+            .fit(&SparseRowArray::from(&data), &binary_target)
+            .is_err());
+
+        let mut model = Hyperparameters::new(tree_params, 5)
+            .seed(100)
+            .oob_score(true)
+            .weighted_vote(true)
+            .build();
+        model.fit(&data, &binary_target).unwrap();
+
+        assert!(model.oob_accuracies().len() == 5);
+
+        model
+            .fit(&SparseRowArray::from(&data), &binary_target)
+            .unwrap();
+        assert!(model.oob_accuracies().len() == 5);
+
+        assert!(model
+            .predict_row(data.row_iter().unwrap().next().unwrap())
+            .is_ok());
+    }
+
     #[test]
     fn test_random_forest_iris_parallel() {
         let (data, target) = load_data();
@@ -265,7 +2198,7 @@ mod tests {
                 .rng(StdRng::from_seed(&[100]));
 
             let mut model = Hyperparameters::new(tree_params, 10)
-                .rng(StdRng::from_seed(&[100]))
+                .rng(StdRng::from_seed(&[5]))
                 .one_vs_rest();
 
             model.fit_parallel(&x_train, &y_train, 2).unwrap();
@@ -306,7 +2239,7 @@ mod tests {
                 .rng(StdRng::from_seed(&[100]));
 
             let mut model = Hyperparameters::new(tree_params, 10)
-                .rng(StdRng::from_seed(&[100]))
+                .rng(StdRng::from_seed(&[5]))
                 .one_vs_rest();
 
             model.fit(&x_train, &y_train).unwrap();
@@ -396,7 +2329,7 @@ mod tests {
                 .rng(StdRng::from_seed(&[100]));
 
             let mut model = Hyperparameters::new(tree_params, 10)
-                .rng(StdRng::from_seed(&[100]))
+                .rng(StdRng::from_seed(&[5]))
                 .one_vs_rest();
 
             model.fit(&x_train, &y_train).unwrap();
@@ -424,4 +2357,27 @@ mod tests {
         assert!(test_accuracy > 0.96);
     }
 
+    #[test]
+    fn deserialize_unseeded() {
+        // A forest serialized before seeds were stored, holding
+        // an (unserializable) random number generator instead.
+        let json = concat!(
+            r#"{"trees":["#,
+            r#"{"dim":2,"max_features":2,"max_depth":18446744073709551615,"min_samples_split":2,"#,
+            r#""root":{"Interior":{"feature":0,"threshold":1.0,"children":"#,
+            r#"[{"Leaf":{"probability":0.0}},{"Leaf":{"probability":1.0}}]}},"#,
+            r#""feature_types":["Continuous","Continuous"],"rng":{}},"#,
+            r#"{"dim":2,"max_features":2,"max_depth":18446744073709551615,"min_samples_split":2,"#,
+            r#""root":{"Interior":{"feature":0,"threshold":2.0,"children":"#,
+            r#"[{"Leaf":{"probability":0.0}},{"Leaf":{"probability":1.0}}]}},"#,
+            r#""feature_types":["Continuous","Continuous"],"rng":{}}],"#,
+            r#""rng":{}}"#
+        );
+
+        let model: RandomForest = serde_json::from_str(json).unwrap();
+
+        let X = Array::from(&vec![vec![0.0, 1.0], vec![2.0, 1.5], vec![4.0, 2.0]]);
+
+        assert!(model.decision_function(&X).unwrap().data() == &vec![0.0, 0.5, 1.0]);
+    }
 }
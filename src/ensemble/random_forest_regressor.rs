@@ -0,0 +1,326 @@
+//! Random forest regression.
+//!
+//! Fits a forest of regression trees using bootstrap samples of the
+//! training data, and predicts the average of the predictions of the
+//! individual trees. Regression trees consider all features at every
+//! split, so the trees of the forest differ only in their bootstrap samples.
+//!
+//! Besides the mean prediction, [`predict_with_std`](struct.RandomForestRegressor.html#method.predict_with_std)
+//! returns the standard deviation of the predictions of the individual trees,
+//! which is larger for inputs the trees disagree on, such as inputs far from
+//! the training data. Unlike single regression trees, forests are fit on a
+//! single target column.
+//!
+//! As for [classification forests](../random_forest/index.html), all randomness
+//! is derived from a single `u64` seed, and the bootstrap sample of the `i`-th tree
+//! depends only on the forest seed and `i`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//!
+//! use rustlearn::datasets::boston;
+//! use rustlearn::ensemble::random_forest_regressor::Hyperparameters;
+//! use rustlearn::trees::regression_tree;
+//!
+//! let (data, target) = boston::load_data();
+//!
+//! let mut tree_params = regression_tree::Hyperparameters::new(data.cols());
+//! tree_params.min_samples_split(5);
+//!
+//! let mut model = Hyperparameters::new(tree_params, 10).seed(100).build();
+//!
+//! model.fit(&data, &target).unwrap();
+//!
+//! let (mean, std) = model.predict_with_std(&data).unwrap();
+//! ```
+
+use prelude::*;
+
+use trees::regression_tree::{self, RegressionTree};
+
+use utils::{check_matched_dimensions, derive_seed, seeded_rng};
+
+use rand;
+use rand::distributions::{IndependentSample, Range};
+use rand::Rng;
+
+/// Hyperparameters for a `RandomForestRegressor` model.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hyperparameters {
+    tree_hyperparameters: regression_tree::Hyperparameters,
+    num_trees: usize,
+    seed: u64,
+}
+
+impl Hyperparameters {
+    /// Create a new instance of Hyperparameters, using the Hyperparameters
+    /// for a `RegressionTree` and the number of trees to build.
+    pub fn new(
+        tree_hyperparameters: regression_tree::Hyperparameters,
+        num_trees: usize,
+    ) -> Hyperparameters {
+        Hyperparameters {
+            tree_hyperparameters: tree_hyperparameters,
+            num_trees: num_trees,
+            seed: rand::random(),
+        }
+    }
+
+    /// Set the seed from which all randomness in the forest is derived.
+    pub fn seed(&mut self, seed: u64) -> &mut Hyperparameters {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the seed of the forest by drawing it from `rng`.
+    pub fn rng(&mut self, mut rng: rand::StdRng) -> &mut Hyperparameters {
+        self.seed = rng.gen();
+        self
+    }
+
+    /// Build the random forest regression model.
+    pub fn build(&self) -> RandomForestRegressor {
+        RandomForestRegressor {
+            trees: (0..self.num_trees)
+                .map(|_| self.tree_hyperparameters.build())
+                .collect(),
+            seed: self.seed,
+        }
+    }
+}
+
+/// A random forest of regression trees.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RandomForestRegressor {
+    trees: Vec<RegressionTree>,
+    seed: u64,
+}
+
+impl<'a> SupervisedModel<&'a Array> for RandomForestRegressor {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+
+        if y.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Target array has more than one column.",
+            ));
+        }
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit a forest on an empty array.",
+            ));
+        }
+
+        for (tree_idx, tree) in self.trees.iter_mut().enumerate() {
+            let indices = RandomForestRegressor::bootstrap_indices(X.rows(), self.seed, tree_idx);
+            try!(tree.fit(&X.get_rows(&indices), &y.get_rows(&indices)));
+        }
+
+        Ok(())
+    }
+
+    /// Return the average of the predictions of the individual trees.
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let predictions = try!(self.tree_predictions(X));
+
+        Ok(RandomForestRegressor::mean_prediction(&predictions))
+    }
+
+    /// Predict the target values; this is the same as the decision function.
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.decision_function(X)
+    }
+}
+
+impl RandomForestRegressor {
+    /// Return a reference to the forest's individual trees.
+    pub fn trees(&self) -> &Vec<RegressionTree> {
+        &self.trees
+    }
+
+    /// Return the mean and the standard deviation of the predictions
+    /// of the individual trees for each row of `X`.
+    ///
+    /// The standard deviation uses the sample formula, dividing by the
+    /// number of trees minus one; it is zero for forests of a single tree.
+    pub fn predict_with_std(&self, X: &Array) -> Result<(Array, Array), RustlearnError> {
+        let predictions = try!(self.tree_predictions(X));
+        let mean = RandomForestRegressor::mean_prediction(&predictions);
+
+        let num_trees = predictions.len() as f32;
+
+        let std = (0..X.rows())
+            .map(|row_idx| {
+                if predictions.len() < 2 {
+                    return 0.0;
+                }
+
+                let row_mean = mean.get(row_idx, 0);
+                let squared_deviations = predictions
+                    .iter()
+                    .map(|prediction| (prediction.get(row_idx, 0) - row_mean).powi(2))
+                    .sum::<f32>();
+
+                (squared_deviations / (num_trees - 1.0)).sqrt()
+            })
+            .collect::<Vec<_>>();
+
+        Ok((mean, Array::from(std)))
+    }
+
+    fn tree_predictions(&self, X: &Array) -> Result<Vec<Array>, RustlearnError> {
+        if self.trees.is_empty() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Forest must contain at least one tree.",
+            ));
+        }
+
+        let mut predictions = Vec::with_capacity(self.trees.len());
+
+        for tree in &self.trees {
+            predictions.push(try!(tree.predict(X)));
+        }
+
+        Ok(predictions)
+    }
+
+    /// Average the non-empty list of per-tree `predictions`.
+    fn mean_prediction(predictions: &[Array]) -> Array {
+        let mut mean = Array::zeros(predictions[0].rows(), 1);
+
+        for prediction in predictions {
+            mean.add_inplace(prediction);
+        }
+
+        mean.div_inplace(predictions.len() as f32);
+
+        mean
+    }
+
+    fn bootstrap_indices(num_indices: usize, seed: u64, tree_idx: usize) -> Vec<usize> {
+        let mut rng = seeded_rng(derive_seed(seed, tree_idx as u64));
+        let range = Range::new(0, num_indices);
+
+        (0..num_indices)
+            .map(|_| range.ind_sample(&mut rng))
+            .collect::<Vec<_>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use datasets::boston::load_data;
+    use metrics::mean_squared_error;
+    use trees::regression_tree;
+
+    use bincode;
+
+    use super::*;
+
+    fn fitted_forest(num_trees: usize) -> RandomForestRegressor {
+        let (data, target) = load_data();
+
+        let mut tree_params = regression_tree::Hyperparameters::new(data.cols());
+        tree_params.min_samples_split(5);
+
+        let mut model = Hyperparameters::new(tree_params, num_trees)
+            .seed(100)
+            .build();
+        model.fit(&data, &target).unwrap();
+
+        model
+    }
+
+    fn mean(x: &Array) -> f32 {
+        x.data().iter().sum::<f32>() / x.rows() as f32
+    }
+
+    #[test]
+    fn test_forest_boston() {
+        let (data, target) = load_data();
+
+        let forest = fitted_forest(20);
+        let single_tree = fitted_forest(1);
+
+        let forest_error = mean_squared_error(&target, &forest.predict(&data).unwrap());
+        let tree_error = mean_squared_error(&target, &single_tree.predict(&data).unwrap());
+
+        println!("Forest error {}, tree error {}", forest_error, tree_error);
+
+        assert!(forest.trees().len() == 20);
+        assert!(forest_error < tree_error);
+    }
+
+    #[test]
+    fn test_predict_with_std() {
+        let (data, _) = load_data();
+
+        let model = fitted_forest(20);
+
+        let (mean_prediction, std) = model.predict_with_std(&data).unwrap();
+        assert!(mean_prediction == model.predict(&data).unwrap());
+        assert!(std.rows() == data.rows() && std.cols() == 1);
+
+        let extrapolated = data.map(|x| x * 5.0 + 100.0);
+        let (_, extrapolated_std) = model.predict_with_std(&extrapolated).unwrap();
+
+        println!(
+            "Std on training data {}, on extrapolated data {}",
+            mean(&std),
+            mean(&extrapolated_std)
+        );
+
+        assert!(mean(&std) < mean(&extrapolated_std));
+
+        // A single tree does not disagree with itself
+        let (_, single_tree_std) = fitted_forest(1).predict_with_std(&data).unwrap();
+        assert!(single_tree_std.data().iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn test_fit_errors() {
+        let mut model = Hyperparameters::new(regression_tree::Hyperparameters::new(2), 0).build();
+
+        assert!(model.fit(&Array::zeros(0, 2), &Array::zeros(0, 1)).is_err());
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+
+        // Multi-output targets are supported by single trees, but not by forests
+        let X = Array::from(&vec![
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![2.0, 1.0],
+            vec![3.0, 0.0],
+        ]);
+        let y = Array::from(&vec![
+            vec![0.0, 1.0],
+            vec![1.0, 2.0],
+            vec![2.0, 3.0],
+            vec![3.0, 4.0],
+        ]);
+
+        let mut model = Hyperparameters::new(regression_tree::Hyperparameters::new(2), 3).build();
+
+        match model.fit(&X, &y) {
+            Err(RustlearnError::DimensionMismatch(_)) => {}
+            _ => panic!("Expected a dimension mismatch error."),
+        }
+        assert!(model.predict(&X).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, _) = load_data();
+
+        let model = fitted_forest(5);
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: RandomForestRegressor = bincode::deserialize(&encoded).unwrap();
+
+        assert!(model.predict_with_std(&data).unwrap() == decoded.predict_with_std(&data).unwrap());
+    }
+}
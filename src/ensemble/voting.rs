@@ -0,0 +1,438 @@
+//! Voting ensembles over heterogeneous models.
+//!
+//! A `VotingClassifier` combines the predictions of several, possibly
+//! different, models fit on the same data. With
+//! [hard voting](enum.Voting.html), every model casts a vote for its predicted
+//! class, and the class with the most votes wins (ties go to the smaller class label).
+//! With soft voting, the decision functions of the models are averaged, and
+//! the class with the highest average is predicted; this works best when all
+//! models output comparable scores, such as probabilities. Two-class models
+//! predict the positive class when the average exceeds 0.5, a threshold that can
+//! be changed using [`VotingClassifier::set_threshold`](struct.VotingClassifier.html#method.set_threshold).
+//!
+//! By default, all models count equally. Models can be given more or less
+//! influence using [`VotingClassifier::set_weights`](struct.VotingClassifier.html#method.set_weights):
+//! hard votes are then counted with the models' weights, and soft votes
+//! use the weighted average of the decision functions. Models with a weight
+//! of zero are ignored entirely.
+//!
+//! Both two-class models and one-vs-rest multiclass models are supported,
+//! but all models in an ensemble must be of the same kind.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//!
+//! use rustlearn::datasets::iris;
+//! use rustlearn::ensemble::random_forest;
+//! use rustlearn::ensemble::voting::{Voting, VotingClassifier};
+//! use rustlearn::linear_models::sgdclassifier;
+//! use rustlearn::trees::decision_tree;
+//!
+//! let (data, target) = iris::load_data();
+//!
+//! let tree_params = decision_tree::Hyperparameters::new(data.cols());
+//! let forest = random_forest::Hyperparameters::new(tree_params, 10).one_vs_rest();
+//! let sgd = sgdclassifier::Hyperparameters::new(data.cols()).one_vs_rest();
+//!
+//! let mut model = VotingClassifier::new(vec![Box::new(forest), Box::new(sgd)], Voting::Soft);
+//!
+//! model.fit(&data, &target).unwrap();
+//!
+//! let prediction = model.predict(&data).unwrap();
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::{check_matched_dimensions, normalize_model_weights, predict_from_decision};
+
+/// How the predictions of the models in a `VotingClassifier` are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Voting {
+    /// Majority vote over the predicted classes.
+    Hard,
+    /// Argmax over the averaged decision functions.
+    Soft,
+}
+
+/// An ensemble predicting by voting over its constituent models.
+pub struct VotingClassifier {
+    models: Vec<Box<dyn for<'a> SupervisedModel<&'a Array>>>,
+    voting: Voting,
+    weights: Vec<f32>,
+    class_labels: Vec<f32>,
+    threshold: f32,
+}
+
+impl VotingClassifier {
+    /// Create a new voting ensemble from a list of unfitted models.
+    ///
+    /// # Panics
+    /// Panics if `models` is empty.
+    pub fn new(
+        models: Vec<Box<dyn for<'a> SupervisedModel<&'a Array>>>,
+        voting: Voting,
+    ) -> VotingClassifier {
+        assert!(
+            !models.is_empty(),
+            "Ensemble must contain at least one model"
+        );
+
+        let weights = vec![1.0 / models.len() as f32; models.len()];
+
+        VotingClassifier {
+            models: models,
+            voting: voting,
+            weights: weights,
+            class_labels: Vec::new(),
+            threshold: 0.5,
+        }
+    }
+
+    /// Return a reference to the constituent models.
+    pub fn models(&self) -> &Vec<Box<dyn for<'a> SupervisedModel<&'a Array>>> {
+        &self.models
+    }
+
+    /// Return the voting strategy.
+    pub fn voting(&self) -> Voting {
+        self.voting
+    }
+
+    /// Set the threshold above which the averaged single-column decision function
+    /// of two-class models predicts the positive class under soft voting.
+    ///
+    /// Defaults to 0.5, suitable for models outputting probabilities. Models
+    /// outputting margins or log-odds, such as hinge-loss linear models, need a
+    /// threshold of 0.0 instead.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Return the normalized weights of the constituent models.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Weight the votes of the constituent models by `weights`, one
+    /// non-negative weight per model. Weights are normalized to sum to one;
+    /// equal weights give the same predictions as uniform voting.
+    ///
+    /// Returns an error if the number of weights differs from the number of
+    /// models, or if the weights are negative, non-finite, or all zero.
+    pub fn set_weights(&mut self, weights: Vec<f32>) -> Result<(), RustlearnError> {
+        self.weights = try!(normalize_model_weights(&weights, self.models.len()));
+
+        Ok(())
+    }
+
+    fn predict_hard(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let mut predictions = Vec::with_capacity(self.models.len());
+
+        for (model, &weight) in self.models.iter().zip(self.weights.iter()) {
+            if weight > 0.0 {
+                predictions.push((try!(model.predict(X)), weight));
+            }
+        }
+
+        let mut votes = Vec::new();
+
+        Ok(Array::from(
+            (0..X.rows())
+                .map(|row_idx| {
+                    votes.clear();
+
+                    for &(ref prediction, weight) in &predictions {
+                        let label = prediction.get(row_idx, 0);
+
+                        match votes
+                            .iter()
+                            .position(|&(vote_label, _)| vote_label == label)
+                        {
+                            Some(idx) => votes[idx].1 += weight,
+                            None => votes.push((label, weight)),
+                        }
+                    }
+
+                    votes
+                        .iter()
+                        .max_by(|a, b| {
+                            a.1.partial_cmp(&b.1)
+                                .unwrap_or(Ordering::Equal)
+                                .then(b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal))
+                        })
+                        .unwrap()
+                        .0
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+
+    fn predict_soft(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let decision = try!(self.decision_function(X));
+
+        predict_from_decision(&decision, &self.class_labels, self.threshold)
+    }
+}
+
+impl<'a> SupervisedModel<&'a Array> for VotingClassifier {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+
+        for model in &mut self.models {
+            try!(model.fit(X, y));
+        }
+
+        let mut class_labels = y.data().clone();
+        class_labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        class_labels.dedup();
+
+        self.class_labels = class_labels;
+
+        Ok(())
+    }
+
+    /// Return the weighted average of the decision functions of the constituent models.
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        let mut decision: Option<Array> = None;
+
+        for (model, &weight) in self.models.iter().zip(self.weights.iter()) {
+            if weight == 0.0 {
+                continue;
+            }
+
+            let mut model_decision = try!(model.decision_function(X));
+            model_decision.times_inplace(weight);
+
+            decision = match decision {
+                None => Some(model_decision),
+                Some(mut decision) => {
+                    if model_decision.cols() != decision.cols() {
+                        return Err(RustlearnError::DimensionMismatch(
+                            "Models disagree on the number of classes.",
+                        ));
+                    }
+
+                    decision.add_inplace(&model_decision);
+                    Some(decision)
+                }
+            };
+        }
+
+        // Weights are validated to not all be zero
+        Ok(decision.unwrap())
+    }
+
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        match self.voting {
+            Voting::Hard => self.predict_hard(X),
+            Voting::Soft => self.predict_soft(X),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use ensemble::random_forest;
+    use linear_models::sgdclassifier;
+    use metrics::accuracy_score;
+    use trees::decision_tree;
+
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    fn build_forest(cols: usize, seed: u64) -> Box<dyn for<'a> SupervisedModel<&'a Array>> {
+        let mut tree_params = decision_tree::Hyperparameters::new(cols);
+        tree_params.min_samples_split(10).max_features(1);
+
+        Box::new(
+            random_forest::Hyperparameters::new(tree_params, 3)
+                .seed(seed)
+                .one_vs_rest(),
+        )
+    }
+
+    fn cross_validated_accuracy<F>(build_model: F) -> f32
+    where
+        F: Fn(usize) -> Box<dyn for<'a> SupervisedModel<&'a Array>>,
+    {
+        let (data, target) = load_data();
+
+        let mut test_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+
+            let mut model = build_model(data.cols());
+
+            model.fit(&x_train, &y_train).unwrap();
+
+            let test_prediction = model.predict(&x_test).unwrap();
+
+            test_accuracy += accuracy_score(&target.get_rows(&test_idx), &test_prediction);
+        }
+
+        test_accuracy / no_splits as f32
+    }
+
+    #[test]
+    fn test_voting_iris() {
+        let first_accuracy = cross_validated_accuracy(|cols| build_forest(cols, 1));
+        let second_accuracy = cross_validated_accuracy(|cols| build_forest(cols, 2));
+
+        let weakest_accuracy = first_accuracy.min(second_accuracy);
+
+        for &voting in &[Voting::Hard, Voting::Soft] {
+            let accuracy = cross_validated_accuracy(|cols| {
+                Box::new(VotingClassifier::new(
+                    vec![build_forest(cols, 1), build_forest(cols, 2)],
+                    voting,
+                ))
+            });
+
+            println!(
+                "Members {} {}, {:?} voting {}",
+                first_accuracy, second_accuracy, voting, accuracy
+            );
+
+            assert!(accuracy >= weakest_accuracy);
+        }
+    }
+
+    #[test]
+    fn test_hard_voting_majority() {
+        let (data, target) = load_data();
+
+        let sgd = |learning_rate| -> Box<dyn for<'a> SupervisedModel<&'a Array>> {
+            Box::new(
+                sgdclassifier::Hyperparameters::new(data.cols())
+                    .learning_rate(learning_rate)
+                    .one_vs_rest(),
+            )
+        };
+
+        let mut model = VotingClassifier::new(
+            vec![build_forest(data.cols(), 1), sgd(0.5), sgd(1.0)],
+            Voting::Hard,
+        );
+        model.fit(&data, &target).unwrap();
+
+        let predictions = model
+            .models()
+            .iter()
+            .map(|member| member.predict(&data).unwrap())
+            .collect::<Vec<_>>();
+        let prediction = model.predict(&data).unwrap();
+
+        for row_idx in 0..data.rows() {
+            let label = prediction.get(row_idx, 0);
+            let votes = predictions
+                .iter()
+                .filter(|member_prediction| member_prediction.get(row_idx, 0) == label)
+                .count();
+
+            // With three voters, the winner either has a majority
+            // or all three disagree and the smallest label wins.
+            assert!(
+                votes >= 2
+                    || predictions
+                        .iter()
+                        .all(|member_prediction| member_prediction.get(row_idx, 0) >= label)
+            );
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        let (data, target) = load_data();
+
+        let mut member = build_forest(data.cols(), 1);
+        member.fit(&data, &target).unwrap();
+
+        for &voting in &[Voting::Hard, Voting::Soft] {
+            let mut model = VotingClassifier::new(
+                vec![
+                    build_forest(data.cols(), 1),
+                    build_forest(data.cols(), 2),
+                    build_forest(data.cols(), 3),
+                ],
+                voting,
+            );
+            model.fit(&data, &target).unwrap();
+
+            let uniform = model.predict(&data).unwrap();
+
+            model.set_weights(vec![2.0, 2.0, 2.0]).unwrap();
+            assert!(model.predict(&data).unwrap() == uniform);
+
+            model.set_weights(vec![1.0, 0.0, 0.0]).unwrap();
+            assert!(model.weights() == &[1.0, 0.0, 0.0]);
+            assert!(
+                model.decision_function(&data).unwrap() == member.decision_function(&data).unwrap()
+            );
+
+            if voting == Voting::Hard {
+                assert!(model.predict(&data).unwrap() == member.predict(&data).unwrap());
+            }
+
+            assert!(model.set_weights(vec![1.0, 1.0]).is_err());
+            assert!(model.set_weights(vec![1.0, -1.0, 1.0]).is_err());
+            assert!(model.set_weights(vec![0.0, 0.0, 0.0]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_zero_weight_mismatched_model() {
+        let (data, target) = load_data();
+
+        let binary_target = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+
+        let mut binary = sgdclassifier::Hyperparameters::new(data.cols()).build();
+        binary.fit(&data, &binary_target).unwrap();
+
+        let mut multiclass = sgdclassifier::Hyperparameters::new(data.cols()).one_vs_rest();
+        multiclass.fit(&data, &target).unwrap();
+
+        let expected = multiclass.decision_function(&data).unwrap();
+
+        let mut model =
+            VotingClassifier::new(vec![Box::new(binary), Box::new(multiclass)], Voting::Soft);
+        model.set_weights(vec![0.0, 1.0]).unwrap();
+
+        assert!(model.decision_function(&data).unwrap() == expected);
+    }
+
+    #[test]
+    fn test_mismatched_models() {
+        let (data, target) = load_data();
+
+        let binary_target = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+
+        let mut binary = sgdclassifier::Hyperparameters::new(data.cols()).build();
+        binary.fit(&data, &binary_target).unwrap();
+
+        let mut multiclass = sgdclassifier::Hyperparameters::new(data.cols()).one_vs_rest();
+        multiclass.fit(&data, &target).unwrap();
+
+        let model =
+            VotingClassifier::new(vec![Box::new(binary), Box::new(multiclass)], Voting::Soft);
+
+        assert!(model.predict(&data).is_err());
+    }
+}
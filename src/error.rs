@@ -0,0 +1,125 @@
+//! Error types.
+//!
+//! All fallible `rustlearn` functions and model methods return a
+//! `RustlearnError`. Its variants describe the kind of failure, allowing
+//! callers to react to specific errors, and carry a human-readable message
+//! describing the particular failure.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//!
+//! let X = Array::zeros(10, 5);
+//! let y = Array::zeros(8, 1);
+//!
+//! let mut model = Hyperparameters::new(5).build();
+//!
+//! match model.fit(&X, &y) {
+//!     Err(RustlearnError::DimensionMismatch(message)) => println!("Bad shapes: {}", message),
+//!     Err(error) => println!("Other error: {}", error),
+//!     Ok(()) => {}
+//! }
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+/// The kinds of errors returned by `rustlearn`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RustlearnError {
+    /// The shapes of the inputs are inconsistent with each other or with the model,
+    /// such as a data matrix whose number of columns differs from the model's dimensionality.
+    DimensionMismatch(&'static str),
+    /// An input contains no samples.
+    EmptyInput(&'static str),
+    /// The values of an input are invalid, such as labels other than 0 and 1
+    /// for a two-class model, or negative sample weights.
+    InvalidInput(&'static str),
+    /// A hyperparameter or configuration setting is invalid.
+    InvalidHyperparameter(&'static str),
+    /// A model or transformer was used before being fit.
+    NotFitted(&'static str),
+    /// The operation is not supported by the model.
+    Unsupported(&'static str),
+}
+
+impl RustlearnError {
+    /// Return the message describing the error.
+    pub fn message(&self) -> &'static str {
+        match *self {
+            RustlearnError::DimensionMismatch(message)
+            | RustlearnError::EmptyInput(message)
+            | RustlearnError::InvalidInput(message)
+            | RustlearnError::InvalidHyperparameter(message)
+            | RustlearnError::NotFitted(message)
+            | RustlearnError::Unsupported(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for RustlearnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl Error for RustlearnError {}
+
+/// Convert the string errors used by previous versions of `rustlearn`,
+/// easing the migration of models implementing the `rustlearn` traits.
+/// Strings are converted into `InvalidInput` errors.
+impl From<&'static str> for RustlearnError {
+    fn from(message: &'static str) -> RustlearnError {
+        RustlearnError::InvalidInput(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prelude::*;
+
+    use linear_models::sgdclassifier::Hyperparameters;
+    use trees::decision_tree;
+
+    #[test]
+    fn test_fit_error_variants() {
+        let mut model = Hyperparameters::new(2).build();
+
+        match model.fit(&Array::zeros(4, 2), &Array::zeros(3, 1)) {
+            Err(RustlearnError::DimensionMismatch(_)) => {}
+            _ => panic!("Expected a dimension mismatch"),
+        }
+
+        match model.fit(&Array::zeros(4, 3), &Array::zeros(4, 1)) {
+            Err(RustlearnError::DimensionMismatch(_)) => {}
+            _ => panic!("Expected a dimension mismatch"),
+        }
+
+        match model.fit(&Array::zeros(4, 2), &Array::ones(4, 1).add(1.0)) {
+            Err(RustlearnError::InvalidInput(_)) => {}
+            _ => panic!("Expected invalid labels"),
+        }
+
+        let tree = decision_tree::Hyperparameters::new(2).build();
+
+        match tree.predict(&Array::zeros(4, 2)) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+    }
+
+    #[test]
+    fn test_display_and_conversion() {
+        let error = RustlearnError::EmptyInput("Cannot fit on an empty array.");
+
+        assert!(error.to_string() == "Cannot fit on an empty array.");
+        assert!(error.message() == "Cannot fit on an empty array.");
+
+        let converted: RustlearnError = "Legacy error".into();
+        assert!(converted == RustlearnError::InvalidInput("Legacy error"));
+    }
+}
@@ -15,6 +15,12 @@
 //! The model supports multithreaded model fitting via asynchronous stochastic
 //! gradient descent (Hogwild).
 //!
+//! # Monitoring convergence
+//!
+//! Setting [`Hyperparameters::record_loss`](struct.Hyperparameters.html#method.record_loss)
+//! records the mean logistic loss on the training data after every epoch (call to `fit`),
+//! available from [`FactorizationMachine::loss_history`](struct.FactorizationMachine.html#method.loss_history).
+//!
 //! # Examples
 //!
 //! ```
@@ -34,16 +40,19 @@
 #![allow(non_snake_case)]
 
 use std::cmp;
+use std::f32;
 
 use prelude::*;
 
 use multiclass::OneVsRestWrapper;
 use utils::{
-    check_data_dimensionality, check_matched_dimensions, check_valid_labels, EncodableRng,
+    assume_fitted, check_data_dimensionality, check_fitted, check_matched_dimensions,
+    check_valid_labels, EncodableRng,
 };
 
 use rand;
 use rand::distributions::IndependentSample;
+use rand::Rng;
 
 use crossbeam;
 
@@ -55,6 +64,10 @@ fn logistic_loss(y: f32, y_hat: f32) -> f32 {
     y_hat - y
 }
 
+fn default_batch_size() -> usize {
+    1
+}
+
 macro_rules! max {
     ($x:expr, $y:expr) => {{
         match $x > $y {
@@ -73,6 +86,12 @@ macro_rules! min {
     }};
 }
 
+/// Logistic loss of a sample with target `y` and raw model output `margin`.
+fn logistic_loss_value(y: f32, margin: f32) -> f32 {
+    // Computed as softplus(margin) - y * margin to avoid overflow
+    max!(margin, 0.0) + (-margin.abs()).exp().ln_1p() - y * margin
+}
+
 /// Hyperparameters for a FactorizationMachine
 #[derive(Serialize, Deserialize)]
 pub struct Hyperparameters {
@@ -82,6 +101,10 @@ pub struct Hyperparameters {
     learning_rate: f32,
     l2_penalty: f32,
     l1_penalty: f32,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    #[serde(default)]
+    record_loss: bool,
     rng: EncodableRng,
 }
 
@@ -98,6 +121,8 @@ impl Hyperparameters {
             learning_rate: 0.05,
             l2_penalty: 0.0,
             l1_penalty: 0.0,
+            batch_size: 1,
+            record_loss: false,
             rng: EncodableRng::new(),
         }
     }
@@ -123,6 +148,35 @@ impl Hyperparameters {
         self
     }
 
+    /// Set the number of samples per minibatch.
+    ///
+    /// With a batch size larger than one, gradients are averaged over
+    /// minibatches of samples before the coefficients and latent factors
+    /// are updated, and the samples are visited in a random order, drawn
+    /// from the model's random number generator, on every call to `fit`.
+    ///
+    /// Defaults to 1, updating the model after every sample in the
+    /// order in which samples are given.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` is zero.
+    pub fn batch_size(&mut self, batch_size: usize) -> &mut Hyperparameters {
+        assert!(batch_size > 0, "Batch size must be positive");
+
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Set whether to record the mean training loss after every epoch
+    /// (call to `fit`). Defaults to `false`.
+    ///
+    /// Recording takes an additional pass over the training data after each
+    /// epoch, but does not affect the fitted model.
+    pub fn record_loss(&mut self, record_loss: bool) -> &mut Hyperparameters {
+        self.record_loss = record_loss;
+        self
+    }
+
     pub fn rng(&mut self, rng: rand::StdRng) -> &mut Hyperparameters {
         self.rng.rng = rng;
         self
@@ -139,6 +193,8 @@ impl Hyperparameters {
             learning_rate: self.learning_rate,
             l2_penalty: self.l2_penalty,
             l1_penalty: self.l1_penalty,
+            batch_size: self.batch_size,
+            record_loss: self.record_loss,
 
             coefficients: Array::zeros(self.dim, 1),
             latent_factors: self.init_latent_factors_array(&mut rng),
@@ -151,7 +207,10 @@ impl Hyperparameters {
             accumulated_l2: 1.0,
             accumulated_l1: 0.0,
 
+            loss_history: Vec::new(),
+
             rng: rng,
+            fitted: false,
         }
     }
 
@@ -189,6 +248,10 @@ pub struct FactorizationMachine {
     learning_rate: f32,
     l2_penalty: f32,
     l1_penalty: f32,
+    #[serde(default = "default_batch_size")]
+    batch_size: usize,
+    #[serde(default)]
+    record_loss: bool,
 
     coefficients: Array,
     latent_factors: Array,
@@ -200,8 +263,12 @@ pub struct FactorizationMachine {
     latent_applied_l1: Array,
     accumulated_l2: f32,
     accumulated_l1: f32,
+    #[serde(default)]
+    loss_history: Vec<f32>,
 
     rng: EncodableRng,
+    #[serde(default = "assume_fitted")]
+    fitted: bool,
 }
 
 impl FactorizationMachine {
@@ -262,26 +329,43 @@ impl FactorizationMachine {
         *applied_l1 += l1_actual_update;
     }
 
+    /// Take an adaptive gradient step on a single parameter, then apply
+    /// any regularization accumulated since it was last updated.
+    fn gradient_step(
+        parameter_value: &mut f32,
+        gradsq: &mut f32,
+        applied_l2: &mut f32,
+        applied_l1: &mut f32,
+        gradient: f32,
+        learning_rate: f32,
+        accumulated_l2: f32,
+        accumulated_l1: f32,
+    ) {
+        let local_learning_rate = learning_rate / gradsq.sqrt();
+
+        *parameter_value -= local_learning_rate * gradient;
+        *gradsq += gradient.powi(2);
+
+        FactorizationMachine::apply_regularization(
+            parameter_value,
+            applied_l2,
+            applied_l1,
+            local_learning_rate,
+            accumulated_l2,
+            accumulated_l1,
+        );
+    }
+
     fn update<T: NonzeroIterable>(&mut self, row: T, loss: f32, component_sum: &[f32]) {
         for (feature_idx, feature_value) in (&row).iter_nonzero() {
             // Update coefficients
-            let gradsq = self.gradsq.get_mut(feature_idx, 0);
-            let local_learning_rate = self.learning_rate / gradsq.sqrt();
-            let coefficient_value = self.coefficients.get_mut(feature_idx, 0);
-
-            let applied_l2 = self.applied_l2.get_mut(feature_idx, 0);
-            let applied_l1 = self.applied_l1.get_mut(feature_idx, 0);
-
-            let gradient = loss * feature_value;
-
-            *coefficient_value -= local_learning_rate * gradient;
-            *gradsq += gradient.powi(2);
-
-            FactorizationMachine::apply_regularization(
-                coefficient_value,
-                applied_l2,
-                applied_l1,
-                local_learning_rate,
+            FactorizationMachine::gradient_step(
+                self.coefficients.get_mut(feature_idx, 0),
+                self.gradsq.get_mut(feature_idx, 0),
+                self.applied_l2.get_mut(feature_idx, 0),
+                self.applied_l1.get_mut(feature_idx, 0),
+                loss * feature_value,
+                self.learning_rate,
                 self.accumulated_l2,
                 self.accumulated_l1,
             );
@@ -306,21 +390,62 @@ impl FactorizationMachine {
                             .zip(applied_l1_row.iter_mut().zip(component_sum.iter())),
                     ),
                 ) {
-                let local_learning_rate = self.learning_rate / gradsq.sqrt();
                 let update = loss * ((component_sum_value * feature_value)
                     - (*component_value * feature_value.powi(2)));
 
-                *component_value -= local_learning_rate * update;
-                *gradsq += update.powi(2);
-
-                FactorizationMachine::apply_regularization(
+                FactorizationMachine::gradient_step(
                     component_value,
+                    gradsq,
                     applied_l2,
                     applied_l1,
-                    local_learning_rate,
+                    update,
+                    self.learning_rate,
+                    self.accumulated_l2,
+                    self.accumulated_l1,
+                );
+            }
+        }
+    }
+
+    /// Apply the averaged gradients of a minibatch to the coefficients and
+    /// latent factors of the features in `features`, resetting the gradients.
+    fn update_batch(
+        &mut self,
+        features: &[usize],
+        batch_size: usize,
+        coefficient_gradients: &mut [f32],
+        latent_gradients: &mut [f32],
+    ) {
+        let num_components = self.num_components;
+
+        for &feature_idx in features {
+            FactorizationMachine::gradient_step(
+                self.coefficients.get_mut(feature_idx, 0),
+                self.gradsq.get_mut(feature_idx, 0),
+                self.applied_l2.get_mut(feature_idx, 0),
+                self.applied_l1.get_mut(feature_idx, 0),
+                coefficient_gradients[feature_idx] / batch_size as f32,
+                self.learning_rate,
+                self.accumulated_l2,
+                self.accumulated_l1,
+            );
+            coefficient_gradients[feature_idx] = 0.0;
+
+            let slice_start = feature_idx * num_components;
+            let slice_stop = slice_start + num_components;
+
+            for idx in slice_start..slice_stop {
+                FactorizationMachine::gradient_step(
+                    &mut self.latent_factors.as_mut_slice()[idx],
+                    &mut self.latent_gradsq.as_mut_slice()[idx],
+                    &mut self.latent_applied_l2.as_mut_slice()[idx],
+                    &mut self.latent_applied_l1.as_mut_slice()[idx],
+                    latent_gradients[idx] / batch_size as f32,
+                    self.learning_rate,
                     self.accumulated_l2,
                     self.accumulated_l1,
                 );
+                latent_gradients[idx] = 0.0;
             }
         }
     }
@@ -330,11 +455,15 @@ impl FactorizationMachine {
         self.accumulated_l1 += self.l1_penalty;
     }
 
-    fn fit_sigmoid<'a, T>(&mut self, X: &'a T, y: &Array) -> Result<(), &'static str>
+    fn fit_sigmoid<'a, T>(&mut self, X: &'a T, y: &Array) -> Result<(), RustlearnError>
     where
         T: IndexableMatrix,
         &'a T: RowIterable,
     {
+        if self.batch_size > 1 {
+            return self.fit_sigmoid_minibatch(X, y);
+        }
+
         let mut component_sum = &mut vec![0.0; self.num_components][..];
 
         for (row, &true_y) in X.iter_rows().zip(y.data().iter()) {
@@ -348,10 +477,101 @@ impl FactorizationMachine {
         }
 
         self.regularize_all();
+        self.fitted = true;
 
         Ok(())
     }
 
+    fn fit_sigmoid_minibatch<'a, T>(&mut self, X: &'a T, y: &Array) -> Result<(), RustlearnError>
+    where
+        T: IndexableMatrix,
+        &'a T: RowIterable,
+    {
+        let num_components = self.num_components;
+
+        let mut component_sum = vec![0.0; num_components];
+        let mut coefficient_gradients = vec![0.0; self.dim];
+        let mut latent_gradients = vec![0.0; self.dim * num_components];
+
+        let mut is_batch_feature = vec![false; self.dim];
+        let mut batch_features = Vec::new();
+
+        let mut row_order = (0..X.rows()).collect::<Vec<_>>();
+        self.rng.rng.shuffle(&mut row_order);
+
+        for batch in row_order.chunks(self.batch_size) {
+            // Gradients are computed using the parameters from before the batch
+            for &row_idx in batch {
+                let row = X.view_row(row_idx);
+
+                let y_hat = sigmoid(self.compute_prediction(&row, &mut component_sum[..]));
+                let loss = logistic_loss(y.get(row_idx, 0), y_hat);
+
+                for (feature_idx, feature_value) in (&row).iter_nonzero() {
+                    coefficient_gradients[feature_idx] += loss * feature_value;
+
+                    let slice_start = feature_idx * num_components;
+
+                    for (component_idx, component_sum_value) in component_sum.iter().enumerate() {
+                        let component_value =
+                            self.latent_factors.get(feature_idx, component_idx);
+
+                        latent_gradients[slice_start + component_idx] += loss
+                            * ((component_sum_value * feature_value)
+                                - (component_value * feature_value.powi(2)));
+                    }
+
+                    if !is_batch_feature[feature_idx] {
+                        is_batch_feature[feature_idx] = true;
+                        batch_features.push(feature_idx);
+                    }
+                }
+            }
+
+            self.update_batch(
+                &batch_features,
+                batch.len(),
+                &mut coefficient_gradients,
+                &mut latent_gradients,
+            );
+
+            for &feature_idx in &batch_features {
+                is_batch_feature[feature_idx] = false;
+            }
+            batch_features.clear();
+
+            for _ in batch {
+                self.accumulate_regularization();
+            }
+        }
+
+        self.regularize_all();
+        self.fitted = true;
+
+        Ok(())
+    }
+
+    /// Append the mean logistic loss over the rows of `X` to the
+    /// loss history, if recording is enabled.
+    fn record_epoch_loss<'a, T>(&mut self, X: &'a T, y: &Array)
+    where
+        &'a T: RowIterable,
+    {
+        if !self.record_loss {
+            return;
+        }
+
+        let mut component_sum = &mut vec![0.0; self.num_components][..];
+        let mut total_loss = 0.0;
+
+        for (row, &true_y) in X.iter_rows().zip(y.data().iter()) {
+            let margin = self.compute_prediction(&row, component_sum);
+            total_loss += logistic_loss_value(true_y, margin);
+        }
+
+        self.loss_history.push(total_loss / y.rows() as f32);
+    }
+
     /// Perform a dummy update pass over all features to force regularization to be applied.
     fn regularize_all(&mut self) {
         if self.l1_penalty == 0.0 && self.l2_penalty == 0.0 {
@@ -378,6 +598,30 @@ impl FactorizationMachine {
     pub fn get_latent_factors(&self) -> &Array {
         &self.latent_factors
     }
+
+    /// Return the mean logistic loss on the training data after every epoch,
+    /// if recording is enabled by [`Hyperparameters::record_loss`](struct.Hyperparameters.html#method.record_loss).
+    /// Returns an empty slice otherwise.
+    pub fn loss_history(&self) -> &[f32] {
+        &self.loss_history
+    }
+
+    /// Return the predicted probabilities of the positive class as an `(n, 1)` array.
+    ///
+    /// The model is fit using the logistic loss, and its decision function
+    /// already applies the logistic sigmoid to the raw model output. The
+    /// probabilities are the values of the decision function, clipped to
+    /// lie strictly within `(0, 1)` even for very large raw outputs.
+    pub fn predict_proba<'a, T>(&self, X: &'a T) -> Result<Array, RustlearnError>
+    where
+        &'a T: RowIterable,
+        T: IndexableMatrix,
+    {
+        let mut probabilities = try!(self.decision_function(X));
+        probabilities.clip_inplace(f32::EPSILON, 1.0 - f32::EPSILON);
+
+        Ok(probabilities)
+    }
 }
 
 impl<'a, T> SupervisedModel<&'a T> for FactorizationMachine
@@ -385,15 +629,19 @@ where
     &'a T: RowIterable,
     T: IndexableMatrix,
 {
-    fn fit(&mut self, X: &'a T, y: &Array) -> Result<(), &'static str> {
+    fn fit(&mut self, X: &'a T, y: &Array) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
         try!(check_valid_labels(y));
 
-        self.fit_sigmoid(X, y)
+        try!(self.fit_sigmoid(X, y));
+        self.record_epoch_loss(X, y);
+
+        Ok(())
     }
 
-    fn decision_function(&self, X: &'a T) -> Result<Array, &'static str> {
+    fn decision_function(&self, X: &'a T) -> Result<Array, RustlearnError> {
+        try!(check_fitted(self.fitted));
         try!(check_data_dimensionality(self.dim, X));
 
         let mut data = Vec::with_capacity(X.rows());
@@ -409,6 +657,14 @@ where
     }
 }
 
+impl SeedableModel for FactorizationMachine {
+    /// Reseed the generator used for ordering the rows when fitting.
+    /// The initial latent factors drawn when the model was built are unchanged.
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = EncodableRng::from_seed(seed);
+    }
+}
+
 impl<'a, T> ParallelSupervisedModel<&'a T> for FactorizationMachine
 where
     &'a T: RowIterable,
@@ -419,7 +675,7 @@ where
         X: &'a T,
         y: &Array,
         num_threads: usize,
-    ) -> Result<(), &'static str> {
+    ) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
         try!(check_valid_labels(y));
@@ -456,6 +712,8 @@ where
         });
 
         self.regularize_all();
+        self.record_epoch_loss(X, y);
+        self.fitted = true;
 
         Ok(())
     }
@@ -509,6 +767,73 @@ mod tests {
         assert!(model.coefficients.data()[1] == -0.0009981153);
     }
 
+    #[test]
+    fn test_predict_proba() {
+        let X = Array::from(&vec![vec![1.0, -0.5], vec![0.0, 2.0], vec![-1.0, 0.0]]);
+        let y = Array::from(vec![1.0, 0.0, 1.0]);
+
+        let mut model = Hyperparameters::new(2, 2)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        model.fit(&X, &y).unwrap();
+
+        let probabilities = model.predict_proba(&X).unwrap();
+        assert!(probabilities.rows() == 3 && probabilities.cols() == 1);
+
+        let mut component_sum = vec![0.0; 2];
+        for (row_idx, row) in X.iter_rows().enumerate() {
+            let expected = sigmoid(model.compute_prediction(&row, &mut component_sum[..]));
+            assert!(probabilities.get(row_idx, 0) == expected);
+        }
+
+        // Probabilities stay strictly within (0, 1) for extreme inputs
+        let extreme = Array::from(&vec![vec![1000.0, 1000.0], vec![-1000.0, -1000.0]]);
+        for &probability in model.predict_proba(&extreme).unwrap().data() {
+            assert!(probability > 0.0 && probability < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_loss_history() {
+        let X = Array::from(&vec![
+            vec![1.0, 0.2],
+            vec![0.8, -0.1],
+            vec![1.2, 0.3],
+            vec![-1.0, 0.1],
+            vec![-0.7, -0.2],
+            vec![-1.1, 0.2],
+        ]);
+        let y = Array::from(vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+
+        let mut plain = Hyperparameters::new(2, 2)
+            .learning_rate(0.5)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        let mut recording = Hyperparameters::new(2, 2)
+            .learning_rate(0.5)
+            .rng(StdRng::from_seed(&[100]))
+            .record_loss(true)
+            .build();
+
+        for _ in 0..20 {
+            plain.fit(&X, &y).unwrap();
+            recording.fit(&X, &y).unwrap();
+        }
+
+        let history = recording.loss_history();
+
+        println!("Loss history {:?}", history);
+
+        assert!(plain.loss_history().is_empty());
+        assert!(history.len() == 20);
+        assert!(history.windows(2).all(|x| x[1] <= x[0]));
+        assert!(history[19] < history[0]);
+
+        // Recording does not change the fitted model
+        assert!(plain.get_coefficients() == recording.get_coefficients());
+        assert!(plain.get_latent_factors() == recording.get_latent_factors());
+    }
+
     #[test]
     fn test_basic_l1() {
         let mut model = Hyperparameters::new(2, 2)
@@ -541,6 +866,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_not_fitted() {
+        let (data, target) = load_data();
+        let binary_target = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+
+        let mut model = Hyperparameters::new(data.cols(), 2).build();
+
+        match model.predict(&data) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+
+        model.fit(&data, &binary_target).unwrap();
+
+        assert!(model.predict(&data).is_ok());
+    }
+
     #[test]
     fn test_iris() {
         let (data, target) = load_data();
@@ -585,6 +927,57 @@ mod tests {
         assert!(test_accuracy > 0.94);
     }
 
+    #[test]
+    fn test_iris_minibatch() {
+        let (data, target) = load_data();
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        let mut online_accuracy = 0.0;
+        let mut minibatch_accuracy = 0.0;
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+            let y_test = target.get_rows(&test_idx);
+
+            for &batch_size in &[1, 10] {
+                let mut model = Hyperparameters::new(data.cols(), 5)
+                    .learning_rate(0.05)
+                    .batch_size(batch_size)
+                    .rng(StdRng::from_seed(&[100]))
+                    .one_vs_rest();
+
+                for _ in 0..20 {
+                    model.fit(&x_train, &y_train).unwrap();
+                }
+
+                let accuracy = accuracy_score(&y_test, &model.predict(&x_test).unwrap());
+
+                if batch_size == 1 {
+                    online_accuracy += accuracy;
+                } else {
+                    minibatch_accuracy += accuracy;
+                }
+            }
+        }
+
+        online_accuracy /= no_splits as f32;
+        minibatch_accuracy /= no_splits as f32;
+
+        println!(
+            "Online accuracy {}, minibatch accuracy {}",
+            online_accuracy, minibatch_accuracy
+        );
+
+        assert!(minibatch_accuracy > online_accuracy - 0.05);
+    }
+
     #[test]
     fn test_iris_parallel() {
         let (data, target) = load_data();
@@ -0,0 +1,677 @@
+//! Univariate feature selection.
+//!
+//! Features are scored individually by the strength of their association
+//! with a discrete class label, without fitting a model:
+//!
+//! - [`chi2`](fn.chi2.html) computes the chi-squared statistic between each
+//!   non-negative feature (such as counts or frequencies) and the class labels;
+//! - [`mutual_info_classif`](fn.mutual_info_classif.html) estimates the mutual
+//!   information between each feature, discretized into equal-width bins,
+//!   and the class labels.
+//!
+//! Higher scores indicate stronger association. [`SelectKBest`](struct.SelectKBest.html)
+//! scores the features of a training set and keeps the `k` highest-scoring columns.
+//!
+//! Alternatively, [`RFE`](struct.RFE.html) (recursive feature elimination) selects
+//! features using a model's own [feature importances](../traits/trait.FeatureImportance.html),
+//! such as the impurity-based importances of random forests or the absolute coefficients
+//! of linear models. The model is repeatedly fit on the remaining features, and the least
+//! important ones are dropped, until the requested number of features remains.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::synthetic::make_classification;
+//! use rustlearn::feature_selection::{ScoreFunction, SelectKBest};
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let mut rng = StdRng::from_seed(&[100]);
+//! let (X, y) = make_classification(200, 10, 3, 2, &mut rng);
+//!
+//! let mut selector = SelectKBest::new(2);
+//! selector.score_function(ScoreFunction::MutualInfo { n_bins: 10 });
+//!
+//! let X_selected = selector.fit_transform(&X, &y).unwrap();
+//!
+//! assert!(X_selected.cols() == 2);
+//! assert!(selector.support() == &[0, 1]);
+//! # }
+//! ```
+
+use std::cmp::{min, Ordering};
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, check_matched_dimensions};
+
+/// Return the sorted distinct class labels of `y`, together with
+/// the index of the class of every row.
+fn class_indices(y: &Array) -> (Vec<f32>, Vec<usize>) {
+    let mut class_labels = y.data().clone();
+    class_labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    class_labels.dedup();
+
+    let indices = y
+        .data()
+        .iter()
+        .map(|label| {
+            class_labels
+                .iter()
+                .position(|class_label| class_label == label)
+                .unwrap()
+        })
+        .collect();
+
+    (class_labels, indices)
+}
+
+fn check_inputs(X: &Array, y: &Array) -> Result<(), RustlearnError> {
+    try!(check_matched_dimensions(X, y));
+
+    if y.cols() != 1 {
+        return Err(RustlearnError::DimensionMismatch(
+            "Target array has more than one column.",
+        ));
+    }
+
+    if X.rows() == 0 {
+        return Err(RustlearnError::EmptyInput(
+            "Cannot score features of an empty array.",
+        ));
+    }
+
+    if X.data()
+        .iter()
+        .chain(y.data().iter())
+        .any(|x| !x.is_finite())
+    {
+        return Err(RustlearnError::InvalidInput(
+            "Features and labels must be finite.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Compute the chi-squared statistic between every feature of `X` and the
+/// class labels `y`.
+///
+/// For every feature, the sum of its values within each class is compared
+/// with the sum expected if the feature were independent of the class.
+/// Features must be non-negative, such as counts or term frequencies.
+/// Features that are zero for every sample score zero.
+pub fn chi2(X: &Array, y: &Array) -> Result<Vec<f32>, RustlearnError> {
+    try!(check_inputs(X, y));
+
+    if X.data().iter().any(|&x| x < 0.0) {
+        return Err(RustlearnError::InvalidInput(
+            "Feature values must be non-negative.",
+        ));
+    }
+
+    let (class_labels, class_indices) = class_indices(y);
+
+    let mut class_counts = vec![0.0; class_labels.len()];
+    let mut observed = Array::zeros(class_labels.len(), X.cols());
+
+    for (row_idx, &class_idx) in class_indices.iter().enumerate() {
+        class_counts[class_idx] += 1.0;
+
+        for feature_idx in 0..X.cols() {
+            *observed.get_mut(class_idx, feature_idx) += X.get(row_idx, feature_idx);
+        }
+    }
+
+    let num_samples = X.rows() as f32;
+
+    Ok((0..X.cols())
+        .map(|feature_idx| {
+            let feature_total = (0..class_labels.len())
+                .map(|class_idx| observed.get(class_idx, feature_idx))
+                .sum::<f32>();
+
+            class_counts
+                .iter()
+                .enumerate()
+                .map(|(class_idx, &class_count)| {
+                    let expected = class_count / num_samples * feature_total;
+
+                    if expected > 0.0 {
+                        (observed.get(class_idx, feature_idx) - expected).powi(2) / expected
+                    } else {
+                        0.0
+                    }
+                })
+                .sum::<f32>()
+        })
+        .collect())
+}
+
+/// Estimate the mutual information between every feature of `X` and the
+/// class labels `y`, in nats.
+///
+/// Each feature is discretized into `n_bins` equal-width bins spanning its
+/// range, and the mutual information between the bin and the class is
+/// computed from their joint frequencies. Constant features score zero.
+///
+/// # Panics
+/// Panics if `n_bins` is zero.
+pub fn mutual_info_classif(
+    X: &Array,
+    y: &Array,
+    n_bins: usize,
+) -> Result<Vec<f32>, RustlearnError> {
+    assert!(n_bins > 0, "Number of bins must be positive");
+
+    try!(check_inputs(X, y));
+
+    let (class_labels, class_indices) = class_indices(y);
+
+    let num_samples = X.rows() as f32;
+
+    let mut class_counts = vec![0.0; class_labels.len()];
+    for &class_idx in &class_indices {
+        class_counts[class_idx] += 1.0;
+    }
+
+    Ok((0..X.cols())
+        .map(|feature_idx| {
+            let (min, max) = (0..X.rows())
+                .map(|row_idx| X.get(row_idx, feature_idx))
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| {
+                    (min.min(x), max.max(x))
+                });
+            let width = (max - min) / n_bins as f32;
+
+            let mut joint_counts = Array::zeros(n_bins, class_labels.len());
+            let mut bin_counts = vec![0.0; n_bins];
+
+            for (row_idx, &class_idx) in class_indices.iter().enumerate() {
+                let bin = if width > 0.0 {
+                    (((X.get(row_idx, feature_idx) - min) / width) as usize).min(n_bins - 1)
+                } else {
+                    0
+                };
+
+                *joint_counts.get_mut(bin, class_idx) += 1.0;
+                bin_counts[bin] += 1.0;
+            }
+
+            let mut mutual_information = 0.0;
+
+            for (bin, &bin_count) in bin_counts.iter().enumerate() {
+                for (class_idx, &class_count) in class_counts.iter().enumerate() {
+                    let joint_count = joint_counts.get(bin, class_idx);
+
+                    if joint_count > 0.0 {
+                        mutual_information += joint_count / num_samples
+                            * (joint_count * num_samples / (bin_count * class_count)).ln();
+                    }
+                }
+            }
+
+            mutual_information.max(0.0)
+        })
+        .collect())
+}
+
+/// The univariate scoring function used by [`SelectKBest`](struct.SelectKBest.html).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ScoreFunction {
+    /// The [chi-squared statistic](fn.chi2.html), for non-negative features.
+    Chi2,
+    /// The [mutual information](fn.mutual_info_classif.html) of each
+    /// feature discretized into `n_bins` bins.
+    MutualInfo {
+        /// The number of equal-width bins per feature.
+        n_bins: usize,
+    },
+}
+
+/// Selects the `k` features with the highest univariate scores.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SelectKBest {
+    k: usize,
+    score_function: ScoreFunction,
+
+    scores: Vec<f32>,
+    support: Vec<usize>,
+}
+
+impl SelectKBest {
+    /// Create a new, unfitted selector keeping `k` features,
+    /// scored using the chi-squared statistic.
+    ///
+    /// # Panics
+    /// Panics if `k` is zero.
+    pub fn new(k: usize) -> SelectKBest {
+        assert!(k > 0, "Must select at least one feature");
+
+        SelectKBest {
+            k: k,
+            score_function: ScoreFunction::Chi2,
+            scores: Vec::new(),
+            support: Vec::new(),
+        }
+    }
+
+    /// Set the function used to score features. Defaults to `ScoreFunction::Chi2`.
+    ///
+    /// # Panics
+    /// Panics if the number of bins of `ScoreFunction::MutualInfo` is zero.
+    pub fn score_function(&mut self, score_function: ScoreFunction) -> &mut SelectKBest {
+        if let ScoreFunction::MutualInfo { n_bins } = score_function {
+            assert!(n_bins > 0, "Number of bins must be positive");
+        }
+
+        self.score_function = score_function;
+        self
+    }
+
+    /// The score of every feature, computed during fitting.
+    pub fn scores(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// The indices of the selected features, in ascending order.
+    pub fn support(&self) -> &[usize] {
+        &self.support
+    }
+
+    /// Score the features of `X` against the class labels `y`
+    /// and select the `k` highest-scoring ones.
+    ///
+    /// Ties are broken in favour of the lower feature index.
+    pub fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        if self.k > X.cols() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Cannot select more features than there are columns.",
+            ));
+        }
+
+        let scores = try!(match self.score_function {
+            ScoreFunction::Chi2 => chi2(X, y),
+            ScoreFunction::MutualInfo { n_bins } => mutual_info_classif(X, y, n_bins),
+        });
+
+        let mut ranking = (0..X.cols()).collect::<Vec<_>>();
+        ranking.sort_by(|&a, &b| {
+            scores[b]
+                .partial_cmp(&scores[a])
+                .unwrap_or(Ordering::Equal)
+                .then(a.cmp(&b))
+        });
+
+        let mut support = ranking[..self.k].to_owned();
+        support.sort();
+
+        self.scores = scores;
+        self.support = support;
+
+        Ok(())
+    }
+
+    /// Return the selected columns of `X`.
+    pub fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        if self.support.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Selector must be fit before transforming",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.scores.len(), X));
+
+        Ok(select_columns(X, &self.support))
+    }
+
+    /// Fit the selector on `X` and `y` and return the selected columns of `X`.
+    pub fn fit_transform(&mut self, X: &Array, y: &Array) -> Result<Array, RustlearnError> {
+        try!(self.fit(X, y));
+        self.transform(X)
+    }
+}
+
+/// Recursive feature elimination.
+///
+/// Selects features by repeatedly fitting a model built by `model_builder`,
+/// which is given the number of remaining features, and dropping the `step`
+/// features with the lowest importance until `n_features_to_select` remain.
+///
+/// # Examples
+///
+/// ```
+/// extern crate rand;
+/// extern crate rustlearn;
+///
+/// use rustlearn::prelude::*;
+/// use rustlearn::datasets::synthetic::make_regression;
+/// use rustlearn::feature_selection::RFE;
+/// use rustlearn::linear_models::Ridge;
+///
+/// use rand::{SeedableRng, StdRng};
+///
+/// # fn main() {
+/// let mut rng = StdRng::from_seed(&[100]);
+/// let (X, y) = make_regression(100, 6, 2, 0.1, &mut rng);
+///
+/// let mut selector = RFE::new(|_| Ridge::new(0.1), 2, 1);
+/// let X_selected = selector.fit_transform(&X, &y).unwrap();
+///
+/// assert!(X_selected.cols() == 2);
+/// assert!(selector.support() == &[0, 1]);
+/// # }
+/// ```
+pub struct RFE<F> {
+    model_builder: F,
+    n_features_to_select: usize,
+    step: usize,
+
+    dim: usize,
+    support: Vec<usize>,
+    ranking: Vec<usize>,
+}
+
+impl<F> RFE<F> {
+    /// Create a new, unfitted selector keeping `n_features_to_select` features
+    /// and dropping `step` features at every iteration.
+    ///
+    /// # Panics
+    /// Panics if `n_features_to_select` or `step` is zero.
+    pub fn new(model_builder: F, n_features_to_select: usize, step: usize) -> RFE<F> {
+        assert!(n_features_to_select > 0, "Must select at least one feature");
+        assert!(step > 0, "Must eliminate at least one feature per step");
+
+        RFE {
+            model_builder: model_builder,
+            n_features_to_select: n_features_to_select,
+            step: step,
+            dim: 0,
+            support: Vec::new(),
+            ranking: Vec::new(),
+        }
+    }
+
+    /// The indices of the selected features, in ascending order.
+    pub fn support(&self) -> &[usize] {
+        &self.support
+    }
+
+    /// The rank of every feature: selected features have rank 1, and
+    /// features eliminated earlier have higher ranks.
+    pub fn ranking(&self) -> &[usize] {
+        &self.ranking
+    }
+
+    /// Select features of `X` by recursively eliminating the least important
+    /// features of models fit on `X` and `y`.
+    ///
+    /// Among features of equal importance, those with higher indices are
+    /// eliminated first.
+    pub fn fit<M>(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError>
+    where
+        F: Fn(usize) -> M,
+        M: for<'a> SupervisedModel<&'a Array> + FeatureImportance,
+    {
+        if self.n_features_to_select > X.cols() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Cannot select more features than there are columns.",
+            ));
+        }
+
+        let mut remaining = (0..X.cols()).collect::<Vec<_>>();
+        let mut eliminated = Vec::new();
+
+        while remaining.len() > self.n_features_to_select {
+            let mut model = (self.model_builder)(remaining.len());
+            try!(model.fit(&select_columns(X, &remaining), y));
+
+            let importances = try!(model.feature_importances());
+
+            if importances.len() != remaining.len() {
+                return Err(RustlearnError::DimensionMismatch(
+                    "Model returned the wrong number of feature importances.",
+                ));
+            }
+
+            let mut order = (0..remaining.len()).collect::<Vec<_>>();
+            order.sort_by(|&a, &b| {
+                importances[a]
+                    .partial_cmp(&importances[b])
+                    .unwrap_or(Ordering::Equal)
+                    .then(b.cmp(&a))
+            });
+
+            let num_dropped = min(self.step, remaining.len() - self.n_features_to_select);
+            let dropped = order[..num_dropped]
+                .iter()
+                .map(|&idx| remaining[idx])
+                .collect::<Vec<_>>();
+
+            remaining.retain(|feature_idx| !dropped.contains(feature_idx));
+            eliminated.push(dropped);
+        }
+
+        let mut ranking = vec![1; X.cols()];
+
+        for (rank, dropped) in eliminated.iter().rev().enumerate() {
+            for &feature_idx in dropped {
+                ranking[feature_idx] = rank + 2;
+            }
+        }
+
+        self.dim = X.cols();
+        self.support = remaining;
+        self.ranking = ranking;
+
+        Ok(())
+    }
+
+    /// Return the selected columns of `X`.
+    pub fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        if self.support.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Selector must be fit before transforming",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.dim, X));
+
+        Ok(select_columns(X, &self.support))
+    }
+
+    /// Fit the selector on `X` and `y` and return the selected columns of `X`.
+    pub fn fit_transform<M>(&mut self, X: &Array, y: &Array) -> Result<Array, RustlearnError>
+    where
+        F: Fn(usize) -> M,
+        M: for<'a> SupervisedModel<&'a Array> + FeatureImportance,
+    {
+        try!(self.fit(X, y));
+        self.transform(X)
+    }
+}
+
+/// Return the columns of `X` with the given indices, in order.
+fn select_columns(X: &Array, columns: &[usize]) -> Array {
+    let mut selected = Array::zeros(X.rows(), columns.len());
+
+    for row_idx in 0..X.rows() {
+        for (col_idx, &feature_idx) in columns.iter().enumerate() {
+            selected.set(row_idx, col_idx, X.get(row_idx, feature_idx));
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{SeedableRng, StdRng};
+
+    use datasets::synthetic::{make_classification, make_regression};
+    use ensemble::random_forest;
+    use linear_models::{Lasso, Ridge};
+    use trees::decision_tree;
+
+    use super::*;
+
+    #[test]
+    fn chi2_counts() {
+        let X = Array::from(&vec![
+            vec![3.0, 1.0, 0.0],
+            vec![2.0, 1.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+        ]);
+        let y = Array::from(vec![1.0, 1.0, 0.0, 0.0]);
+
+        let scores = chi2(&X, &y).unwrap();
+
+        // Observed per-class sums (0, 5) against expected (2.5, 2.5)
+        assert!(scores[0] == 5.0);
+        assert!(scores[1] == 0.0);
+        assert!(scores[2] == 0.0);
+
+        assert!(chi2(&Array::from(vec![-1.0, 1.0]), &Array::from(vec![0.0, 1.0])).is_err());
+        assert!(chi2(&X, &Array::from(vec![0.0, 1.0])).is_err());
+    }
+
+    #[test]
+    fn mutual_information() {
+        let X = Array::from(&vec![
+            vec![0.0, 0.0, 5.0],
+            vec![0.1, 1.0, 5.0],
+            vec![0.9, 0.0, 5.0],
+            vec![1.0, 1.0, 5.0],
+        ]);
+        let y = Array::from(vec![0.0, 0.0, 1.0, 1.0]);
+
+        let scores = mutual_info_classif(&X, &y, 2).unwrap();
+
+        // The first feature determines the class, the others carry no information
+        assert!((scores[0] - 2.0f32.ln()).abs() < 1e-6);
+        assert!(scores[1].abs() < 1e-6);
+        assert!(scores[2] == 0.0);
+    }
+
+    #[test]
+    fn informative_features_score_higher() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (mut X, y) = make_classification(500, 10, 3, 3, &mut rng);
+
+        // Shift all features to be non-negative for the chi-squared statistic
+        let min = X.data().iter().cloned().fold(f32::INFINITY, f32::min);
+        X.sub_inplace(min);
+
+        let chi2_scores = chi2(&X, &y).unwrap();
+        let mi_scores = mutual_info_classif(&X, &y, 10).unwrap();
+
+        println!("Chi2 {:?}", chi2_scores);
+        println!("Mutual information {:?}", mi_scores);
+
+        for scores in &[chi2_scores, mi_scores] {
+            let worst_informative = scores[..3].iter().cloned().fold(f32::INFINITY, f32::min);
+            let best_noise = scores[3..].iter().cloned().fold(0.0, f32::max);
+
+            assert!(worst_informative > 3.0 * best_noise);
+        }
+
+        for &score_function in &[
+            ScoreFunction::Chi2,
+            ScoreFunction::MutualInfo { n_bins: 10 },
+        ] {
+            let mut selector = SelectKBest::new(3);
+            selector.score_function(score_function);
+
+            let selected = selector.fit_transform(&X, &y).unwrap();
+
+            assert!(selector.support() == &[0, 1, 2]);
+            assert!(selected == X.submatrix(0..X.rows(), 0..3));
+        }
+    }
+
+    #[test]
+    fn select_k_best_errors() {
+        let X = Array::ones(4, 3);
+        let y = Array::from(vec![0.0, 1.0, 0.0, 1.0]);
+
+        let mut selector = SelectKBest::new(4);
+        assert!(selector.transform(&X).is_err());
+        assert!(selector.fit(&X, &y).is_err());
+
+        let mut selector = SelectKBest::new(2);
+        selector.fit(&X, &y).unwrap();
+
+        // Ties are broken by feature index
+        assert!(selector.support() == &[0, 1]);
+        assert!(selector.transform(&Array::ones(4, 2)).is_err());
+    }
+
+    #[test]
+    fn rfe_random_forest() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_classification(300, 8, 2, 3, &mut rng);
+
+        let mut selector = RFE::new(
+            |dim| {
+                random_forest::Hyperparameters::new(decision_tree::Hyperparameters::new(dim), 20)
+                    .seed(100)
+                    .build()
+            },
+            3,
+            2,
+        );
+
+        let selected = selector.fit_transform(&X, &y).unwrap();
+
+        println!("Ranking {:?}", selector.ranking());
+
+        assert!(selector.support() == &[0, 1, 2]);
+        assert!(selected == X.submatrix(0..X.rows(), 0..3));
+
+        // Five features are eliminated in steps of two, two and one
+        let mut ranks = selector.ranking().to_owned();
+        ranks.sort();
+        assert!(ranks == vec![1, 1, 1, 2, 3, 3, 4, 4]);
+    }
+
+    #[test]
+    fn rfe_linear_models() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_regression(200, 8, 3, 0.1, &mut rng);
+
+        let mut ridge = RFE::new(|_| Ridge::new(0.1), 3, 1);
+        ridge.fit(&X, &y).unwrap();
+
+        let mut lasso = RFE::new(|_| Lasso::new(0.1), 3, 1);
+        lasso.fit(&X, &y).unwrap();
+
+        assert!(ridge.support() == &[0, 1, 2]);
+        assert!(lasso.support() == &[0, 1, 2]);
+        assert!(ridge.transform(&Array::zeros(2, 7)).is_err());
+    }
+
+    #[test]
+    fn rfe_errors() {
+        let X = Array::ones(4, 3);
+        let y = Array::from(vec![0.0, 1.0, 2.0, 3.0]);
+
+        let mut selector = RFE::new(|_| Ridge::new(0.1), 4, 1);
+        assert!(selector.transform(&X).is_err());
+        assert!(selector.fit(&X, &y).is_err());
+
+        let mut selector = RFE::new(|_| Ridge::new(-1.0), 2, 1);
+        assert!(selector.fit(&X, &y).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_zero_features() {
+        SelectKBest::new(0);
+    }
+}
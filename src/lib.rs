@@ -19,35 +19,98 @@
 //! ## Models
 //!
 //! - [logistic regression](linear_models/sgdclassifier/index.html) using stochastic gradient descent,
+//!   which also fits linear support vector machines and robust (Huber) regression,
+//! - [ridge and lasso regression](linear_models/linear_regression/index.html),
 //! - [support vector machines](svm/libsvm/svc/index.html) using the `libsvm` library,
-//! - [decision trees](trees/decision_tree/index.html) using the CART algorithm,
-//! - [random forests](ensemble/random_forest/index.html) using CART decision trees, and
+//! - [decision trees](trees/decision_tree/index.html) and
+//!   [regression trees](trees/regression_tree/index.html) using the CART algorithm,
+//! - [random forests](ensemble/random_forest/index.html) using CART decision trees,
+//! - [random forest regression](ensemble/random_forest_regressor/index.html) with per-row prediction uncertainty,
+//! - [gradient boosted trees](ensemble/gradient_boosting/index.html) using CART regression trees,
+//! - [voting ensembles](ensemble/voting/index.html) over heterogeneous models,
+//! - [bagging ensembles](ensemble/bagging/index.html) over arbitrary base models,
+//! - [k-nearest neighbors](neighbors/kneighbors_classifier/index.html),
+//! - [multinomial naive Bayes](naive_bayes/multinomial_nb/index.html), and
 //! - [factorization machines](factorization/factorization_machines/index.html).
 //!
 //! All the models support fitting and prediction on both dense and sparse data, and the implementations
 //! should be roughly competitive with Python `sklearn` implementations, both in accuracy and performance.
 //!
+//! ## Clustering
+//!
+//! - [k-means](cluster/k_means/index.html) with k-means++ initialization
+//!
+//! ## Preprocessing
+//!
+//! - [feature standardization](preprocessing/standard_scaler/index.html)
+//! - [min-max feature scaling](preprocessing/min_max_scaler/index.html)
+//! - [feature discretization](preprocessing/kbins_discretizer/index.html) into uniform or quantile bins
+//! - [one-hot encoding](preprocessing/one_hot_encoder/index.html) of categorical features
+//! - [principal component analysis](decomposition/pca/index.html)
+//! - [univariate feature selection](feature_selection/index.html) using chi-squared or mutual information scores,
+//!   and [recursive feature elimination](feature_selection/struct.RFE.html)
+//! - [pipelines](pipeline/index.html) chaining a transformation with a model
+//!
 //! ## Cross-validation
 //!
 //! - [k-fold cross-validation](cross_validation/cross_validation/index.html)
+//! - [cross-validated scores](cross_validation/cross_validation/fn.cross_val_score.html)
 //! - [shuffle split](cross_validation/shuffle_split/index.html)
+//! - [grid search](model_selection/grid_search/index.html) over hyperparameters
+//! - [learning curves](model_selection/learning_curve/index.html) of training and validation scores
 //!
 //! ## Metrics
 //!
 //! - [accuracy](metrics/fn.accuracy_score.html)
+//! - [accuracy of predicted probabilities](metrics/fn.accuracy_from_proba.html)
+//! - [balanced accuracy](metrics/fn.balanced_accuracy_score.html)
+//! - [geometric mean score](metrics/fn.geometric_mean_score.html) and [per-class recall](metrics/fn.recall_per_class.html)
+//! - [Cohen's kappa](metrics/fn.cohen_kappa_score.html)
+//! - [Matthews correlation coefficient](metrics/fn.matthews_corrcoef.html)
 //! - [mean_absolute_error](metrics/fn.mean_absolute_error.html)
 //! - [mean_squared_error](metrics/fn.mean_squared_error.html)
 //! - [ROC AUC score](metrics/ranking/fn.roc_auc_score.html)
+//! - [approximate ROC AUC score](metrics/ranking/fn.roc_auc_score_approx.html) without sorting
+//! - [multiclass ROC AUC score](metrics/ranking/fn.roc_auc_score_multiclass.html)
+//! - [top-k accuracy](metrics/ranking/fn.top_k_accuracy_score.html) of multiclass scores
+//! - [ROC curve](metrics/ranking/fn.roc_curve.html)
+//! - [precision-recall curve](metrics/ranking/fn.precision_recall_curve.html)
+//! - [average precision](metrics/ranking/fn.average_precision_score.html)
+//! - [cumulative gain curve](metrics/ranking/fn.cumulative_gain_curve.html) and [lift](metrics/ranking/fn.lift_at.html)
 //! - [dcg_score](metrics/ranking/fn.dcg_score.html)
 //! - [ndcg_score](metrics/ranking/fn.ndcg_score.html)
 //!
+//! ## Probability calibration
+//! - [Platt scaling](calibration/index.html) of classifier scores
+//! - [isotonic regression](calibration/isotonic/index.html) calibration of classifier scores
+//!
+//! ## Model inspection
+//!
+//! - [permutation feature importance](model_inspection/fn.permutation_importance.html)
+//! - [model-based feature importances](traits/trait.FeatureImportance.html) of trees, forests and linear models
+//!
 //! ## Parallelization
 //!
 //! A number of models support both parallel model fitting and prediction.
 //!
+//! Building `rustlearn` with the optional `rayon` feature additionally parallelizes
+//! [random forest](ensemble/random_forest/index.html) prediction across trees
+//! using rayon's work-stealing thread pool.
+//!
+//! ## Compressed datasets
+//!
+//! Building `rustlearn` with the optional `flate2` feature allows the
+//! [dataset loaders](datasets/io/index.html) to read gzip-compressed files.
+//!
 //! ## Model serialization
 //!
-//! Model serialization is supported via `serde`.
+//! Model serialization is supported via `serde`. The [persistence](persistence/index.html)
+//! module provides helpers for saving models to and loading them from files.
+//!
+//! ## Error handling
+//!
+//! Fallible functions and model methods return a [`RustlearnError`](error/enum.RustlearnError.html),
+//! whose variants describe the kind of failure.
 //!
 //! # Using `rustlearn`
 //! Usage should be straightforward.
@@ -147,30 +210,42 @@
 #[cfg(feature = "bench")]
 extern crate test;
 
-#[cfg(test)]
-extern crate bincode;
-
 #[cfg(test)]
 extern crate csv;
 
-#[cfg(test)]
-extern crate serde_json;
-
+extern crate bincode;
 extern crate crossbeam;
+#[cfg(feature = "flate2")]
+extern crate flate2;
 extern crate rand;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_json;
 
 pub mod array;
+pub mod calibration;
+pub mod cluster;
 pub mod cross_validation;
 pub mod datasets;
+pub mod decomposition;
 pub mod ensemble;
+pub mod error;
 pub mod factorization;
 pub mod feature_extraction;
+pub mod feature_selection;
 pub mod linear_models;
 pub mod metrics;
+pub mod model_inspection;
+pub mod model_selection;
 pub mod multiclass;
+pub mod naive_bayes;
+pub mod neighbors;
+pub mod persistence;
+pub mod pipeline;
+pub mod preprocessing;
 pub mod svm;
 pub mod traits;
 pub mod trees;
@@ -180,5 +255,6 @@ pub mod utils;
 pub mod prelude {
     //! Basic data structures and traits used throughout `rustlearn`.
     pub use array::prelude::*;
+    pub use error::RustlearnError;
     pub use traits::*;
 }
@@ -0,0 +1,616 @@
+//! Regularized linear regression.
+//!
+//! Both models fit a linear function of the features, plus an unpenalized
+//! intercept, to continuous targets:
+//!
+//! - `Ridge` minimizes the squared error plus `alpha` times the squared L2 norm
+//!   of the coefficients. The coefficients are found in closed form, by solving
+//!   `(X^T X + alpha I) w = X^T y` on centered data. With `alpha = 0` this is
+//!   ordinary least squares.
+//! - `Lasso` minimizes the mean squared error (halved) plus `alpha` times the
+//!   L1 norm of the coefficients, using cyclic coordinate descent. The L1 penalty
+//!   sets the coefficients of irrelevant features to exactly zero.
+//!
+//! Only dense input is supported. The decision function and predictions
+//! of both models are the predicted target values.
+//!
+//! For data that is already centered, fitting the intercept can be disabled
+//! using `fit_intercept`; the intercept is then fixed at zero.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::synthetic::make_regression;
+//! use rustlearn::linear_models::{Lasso, Ridge};
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let mut rng = StdRng::from_seed(&[100]);
+//! let (X, y) = make_regression(100, 5, 3, 0.1, &mut rng);
+//!
+//! let mut ridge = Ridge::new(1.0);
+//! ridge.fit(&X, &y).unwrap();
+//!
+//! let mut lasso = Lasso::new(0.5);
+//! lasso.fit(&X, &y).unwrap();
+//!
+//! let prediction = lasso.predict(&X).unwrap();
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, check_matched_dimensions};
+
+/// Maximum number of passes over the coefficients of coordinate descent.
+const MAX_ITER: usize = 1000;
+/// Coordinate descent stops once no coefficient changes by more than this
+/// fraction of the largest coefficient during a pass.
+const TOLERANCE: f64 = 1e-6;
+
+fn default_fit_intercept() -> bool {
+    true
+}
+
+/// Linear regression with an L2 penalty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Ridge {
+    alpha: f32,
+    #[serde(default = "default_fit_intercept")]
+    fit_intercept: bool,
+
+    coefficients: Array,
+    intercept: f32,
+}
+
+/// Linear regression with an L1 penalty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Lasso {
+    alpha: f32,
+    #[serde(default = "default_fit_intercept")]
+    fit_intercept: bool,
+
+    coefficients: Array,
+    intercept: f32,
+}
+
+impl Ridge {
+    /// Create a new, unfitted model with L2 penalty `alpha`.
+    pub fn new(alpha: f32) -> Ridge {
+        Ridge {
+            alpha: alpha,
+            fit_intercept: true,
+            coefficients: Array::zeros(0, 0),
+            intercept: 0.0,
+        }
+    }
+
+    /// Set whether to fit an intercept. Defaults to `true`; if `false`,
+    /// the data is not centered and the intercept is fixed at zero.
+    pub fn fit_intercept(&mut self, fit_intercept: bool) -> &mut Ridge {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// The estimated coefficients, as a `(n_features, 1)` array.
+    pub fn coefficients(&self) -> &Array {
+        &self.coefficients
+    }
+
+    /// The estimated intercept.
+    pub fn intercept(&self) -> f32 {
+        self.intercept
+    }
+}
+
+impl Lasso {
+    /// Create a new, unfitted model with L1 penalty `alpha`.
+    pub fn new(alpha: f32) -> Lasso {
+        Lasso {
+            alpha: alpha,
+            fit_intercept: true,
+            coefficients: Array::zeros(0, 0),
+            intercept: 0.0,
+        }
+    }
+
+    /// Set whether to fit an intercept. Defaults to `true`; if `false`,
+    /// the data is not centered and the intercept is fixed at zero.
+    pub fn fit_intercept(&mut self, fit_intercept: bool) -> &mut Lasso {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// The estimated coefficients, as a `(n_features, 1)` array.
+    pub fn coefficients(&self) -> &Array {
+        &self.coefficients
+    }
+
+    /// The estimated intercept.
+    pub fn intercept(&self) -> f32 {
+        self.intercept
+    }
+}
+
+impl<'a> SupervisedModel<&'a Array> for Ridge {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        if self.alpha < 0.0 {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Regularization strength must be non-negative.",
+            ));
+        }
+
+        let data = try!(CenteredData::new(X, y, self.fit_intercept));
+        let alpha = self.alpha as f64;
+
+        let mut gram = (0..data.columns.len())
+            .map(|j| {
+                (0..data.columns.len())
+                    .map(|k| dot(&data.columns[j], &data.columns[k]))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        for (j, row) in gram.iter_mut().enumerate() {
+            row[j] += alpha;
+        }
+
+        let moments = data
+            .columns
+            .iter()
+            .map(|column| dot(column, &data.y))
+            .collect::<Vec<_>>();
+
+        let weights = try!(solve(gram, moments));
+
+        self.intercept = data.intercept(&weights);
+        self.coefficients = to_array(&weights);
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        linear_prediction(&self.coefficients, self.intercept, X)
+    }
+
+    /// Predict the target values; this is the same as the decision function.
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.decision_function(X)
+    }
+}
+
+impl FeatureImportance for Ridge {
+    /// Return the absolute values of the coefficients.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError> {
+        absolute_coefficients(&self.coefficients)
+    }
+}
+
+impl<'a> SupervisedModel<&'a Array> for Lasso {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        if self.alpha < 0.0 {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Regularization strength must be non-negative.",
+            ));
+        }
+
+        let data = try!(CenteredData::new(X, y, self.fit_intercept));
+        let num_samples = data.y.len() as f64;
+        let penalty = self.alpha as f64 * num_samples;
+
+        let squared_norms = data
+            .columns
+            .iter()
+            .map(|column| dot(column, column))
+            .collect::<Vec<_>>();
+
+        let mut weights = vec![0.0; data.columns.len()];
+        let mut residuals = data.y.clone();
+
+        for _ in 0..MAX_ITER {
+            let mut max_change: f64 = 0.0;
+            let mut max_weight: f64 = 0.0;
+
+            for (j, column) in data.columns.iter().enumerate() {
+                if squared_norms[j] == 0.0 {
+                    continue;
+                }
+
+                let old_weight = weights[j];
+                let correlation = dot(column, &residuals) + squared_norms[j] * old_weight;
+                let new_weight = soft_threshold(correlation, penalty) / squared_norms[j];
+
+                if new_weight != old_weight {
+                    let change = new_weight - old_weight;
+
+                    for (residual, &x) in residuals.iter_mut().zip(column.iter()) {
+                        *residual -= change * x;
+                    }
+
+                    weights[j] = new_weight;
+                    max_change = max_change.max(change.abs());
+                }
+
+                max_weight = max_weight.max(new_weight.abs());
+            }
+
+            if max_change <= TOLERANCE * max_weight {
+                break;
+            }
+        }
+
+        self.intercept = data.intercept(&weights);
+        self.coefficients = to_array(&weights);
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        linear_prediction(&self.coefficients, self.intercept, X)
+    }
+
+    /// Predict the target values; this is the same as the decision function.
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.decision_function(X)
+    }
+}
+
+impl FeatureImportance for Lasso {
+    /// Return the absolute values of the coefficients.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError> {
+        absolute_coefficients(&self.coefficients)
+    }
+}
+
+/// Features and targets with their means subtracted,
+/// so that the intercept can be fit separately. Without
+/// centering, all means are zero, and so is the intercept.
+struct CenteredData {
+    columns: Vec<Vec<f64>>,
+    y: Vec<f64>,
+    column_means: Vec<f64>,
+    y_mean: f64,
+}
+
+impl CenteredData {
+    fn new(X: &Array, y: &Array, centered: bool) -> Result<CenteredData, RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+
+        if y.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Target array has more than one column.",
+            ));
+        }
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit on an empty array."));
+        }
+
+        let (columns, column_means): (Vec<_>, Vec<_>) = (0..X.cols())
+            .map(|col_idx| {
+                center(
+                    (0..X.rows())
+                        .map(|row_idx| X.get(row_idx, col_idx) as f64)
+                        .collect(),
+                    centered,
+                )
+            })
+            .unzip();
+        let (y, y_mean) = center(y.data().iter().map(|&x| x as f64).collect(), centered);
+
+        Ok(CenteredData {
+            columns: columns,
+            y: y,
+            column_means: column_means,
+            y_mean: y_mean,
+        })
+    }
+
+    /// The intercept of the uncentered data given the coefficients.
+    fn intercept(&self, weights: &[f64]) -> f32 {
+        (self.y_mean - dot(&self.column_means, weights)) as f32
+    }
+}
+
+fn center(mut values: Vec<f64>, centered: bool) -> (Vec<f64>, f64) {
+    if !centered {
+        return (values, 0.0);
+    }
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+    for value in &mut values {
+        *value -= mean;
+    }
+
+    (values, mean)
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y.iter()).map(|(a, b)| a * b).sum()
+}
+
+fn soft_threshold(x: f64, threshold: f64) -> f64 {
+    if x > threshold {
+        x - threshold
+    } else if x < -threshold {
+        x + threshold
+    } else {
+        0.0
+    }
+}
+
+fn to_array(weights: &[f64]) -> Array {
+    Array::from(weights.iter().map(|&x| x as f32).collect::<Vec<_>>())
+}
+
+fn absolute_coefficients(coefficients: &Array) -> Result<Vec<f32>, RustlearnError> {
+    if coefficients.rows() == 0 {
+        return Err(RustlearnError::NotFitted(
+            "Model must be fit before computing feature importances",
+        ));
+    }
+
+    Ok(coefficients.data().iter().map(|x| x.abs()).collect())
+}
+
+fn linear_prediction(
+    coefficients: &Array,
+    intercept: f32,
+    X: &Array,
+) -> Result<Array, RustlearnError> {
+    if coefficients.rows() == 0 {
+        return Err(RustlearnError::NotFitted(
+            "Model must be fit before predicting",
+        ));
+    }
+
+    try!(check_data_dimensionality(coefficients.rows(), X));
+
+    let mut prediction = X.dot(coefficients);
+    prediction.add_inplace(intercept);
+
+    Ok(prediction)
+}
+
+/// Solve the linear system `a x = b` using Gaussian elimination with partial pivoting.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Result<Vec<f64>, RustlearnError> {
+    let n = b.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| {
+                a[i][col]
+                    .abs()
+                    .partial_cmp(&a[j][col].abs())
+                    .unwrap_or(Ordering::Equal)
+            })
+            .unwrap();
+
+        if a[pivot][col].abs() < 1e-12 {
+            return Err(RustlearnError::InvalidInput(
+                "Linear system is singular; try a positive regularization strength.",
+            ));
+        }
+
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+
+            for k in col..n {
+                a[row][k] -= factor * a[col][k];
+            }
+
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+
+    for row in (0..n).rev() {
+        let sum = (row + 1..n).map(|k| a[row][k] * x[k]).sum::<f64>();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use datasets::synthetic::make_regression_with_coefficients;
+    use metrics::mean_squared_error;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    #[test]
+    fn test_ridge_least_squares() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, coefficients) = make_regression_with_coefficients(100, 4, 4, 0.0, &mut rng);
+        let y = y.map(|x| x + 3.0);
+
+        let mut model = Ridge::new(0.0);
+        model.fit(&X, &y).unwrap();
+
+        println!("Coefficients {:?}", model.coefficients().data());
+
+        // Without noise or regularization, the fit is exact
+        assert!(model.coefficients().approx_eq(&coefficients, 1e-3));
+        assert!((model.intercept() - 3.0).abs() < 1e-3);
+        assert!(mean_squared_error(&y, &model.predict(&X).unwrap()) < 1e-6);
+    }
+
+    #[test]
+    fn test_ridge_shrinkage() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, coefficients) = make_regression_with_coefficients(200, 5, 3, 1.0, &mut rng);
+
+        let norm = |model: &Ridge| {
+            model
+                .coefficients()
+                .data()
+                .iter()
+                .map(|x| x * x)
+                .sum::<f32>()
+        };
+
+        let mut unregularized = Ridge::new(0.0);
+        unregularized.fit(&X, &y).unwrap();
+
+        assert!(unregularized.coefficients().approx_eq(&coefficients, 0.5));
+
+        let mut regularized = Ridge::new(100.0);
+        regularized.fit(&X, &y).unwrap();
+
+        assert!(norm(&regularized) < norm(&unregularized));
+    }
+
+    #[test]
+    fn test_lasso_sparsity() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, coefficients) = make_regression_with_coefficients(200, 10, 3, 1.0, &mut rng);
+
+        let mut model = Lasso::new(1.0);
+        model.fit(&X, &y).unwrap();
+
+        println!("Coefficients {:?}", model.coefficients().data());
+
+        for (&estimate, &truth) in model
+            .coefficients()
+            .data()
+            .iter()
+            .zip(coefficients.data().iter())
+        {
+            if truth == 0.0 {
+                assert!(estimate == 0.0);
+            } else {
+                // Shrunk towards zero by roughly `alpha`
+                assert!((estimate - truth).abs() < 2.0);
+            }
+        }
+
+        // Without regularization, lasso matches least squares
+        let mut lasso = Lasso::new(0.0);
+        lasso.fit(&X, &y).unwrap();
+
+        let mut ridge = Ridge::new(0.0);
+        ridge.fit(&X, &y).unwrap();
+
+        assert!(lasso.coefficients().approx_eq(ridge.coefficients(), 1e-3));
+        assert!((lasso.intercept() - ridge.intercept()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_fit_intercept() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, coefficients) = make_regression_with_coefficients(200, 4, 4, 0.1, &mut rng);
+
+        let mut ridge = Ridge::new(0.0);
+        ridge.fit(&X, &y).unwrap();
+
+        let mut lasso = Lasso::new(0.0);
+        lasso.fit(&X, &y).unwrap();
+
+        // Center the data, so that no intercept is needed
+        let mut X_centered = X.clone();
+        for col_idx in 0..X.cols() {
+            let mean = (0..X.rows())
+                .map(|row_idx| X.get(row_idx, col_idx))
+                .sum::<f32>()
+                / X.rows() as f32;
+
+            for row_idx in 0..X.rows() {
+                X_centered.set(row_idx, col_idx, X.get(row_idx, col_idx) - mean);
+            }
+        }
+        let y_mean = y.mean();
+        let y_centered = y.map(|x| x - y_mean);
+
+        let mut centered_ridge = Ridge::new(0.0);
+        centered_ridge
+            .fit_intercept(false)
+            .fit(&X_centered, &y_centered)
+            .unwrap();
+
+        let mut centered_lasso = Lasso::new(0.0);
+        centered_lasso
+            .fit_intercept(false)
+            .fit(&X_centered, &y_centered)
+            .unwrap();
+
+        for model in &[
+            &centered_ridge as &dyn SupervisedModel<&Array>,
+            &centered_lasso,
+        ] {
+            let prediction = model.predict(&X_centered).unwrap();
+            assert!(mean_squared_error(&y_centered, &prediction) < 0.02);
+        }
+
+        assert!(centered_ridge.intercept() == 0.0);
+        assert!(centered_lasso.intercept() == 0.0);
+        assert!(centered_ridge
+            .coefficients()
+            .approx_eq(ridge.coefficients(), 1e-3));
+        assert!(centered_lasso
+            .coefficients()
+            .approx_eq(lasso.coefficients(), 1e-3));
+        assert!(centered_ridge.coefficients().approx_eq(&coefficients, 0.1));
+
+        // Without an intercept, shifted targets cannot be fit
+        let y_shifted = y_centered.map(|x| x + 10.0);
+        centered_ridge.fit(&X_centered, &y_shifted).unwrap();
+
+        assert!(centered_ridge.intercept() == 0.0);
+        assert!(
+            mean_squared_error(&y_shifted, &centered_ridge.predict(&X_centered).unwrap()) > 50.0
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let X = Array::from(&vec![vec![1.0, 2.0], vec![2.0, 4.0], vec![3.0, 6.0]]);
+        let y = Array::from(vec![1.0, 2.0, 3.0]);
+
+        assert!(Ridge::new(1.0).predict(&X).is_err());
+        assert!(Lasso::new(1.0).predict(&X).is_err());
+
+        // Collinear features make least squares singular
+        assert!(Ridge::new(0.0).fit(&X, &y).is_err());
+        assert!(Ridge::new(1.0).fit(&X, &y).is_ok());
+
+        assert!(Ridge::new(-1.0).fit(&X, &y).is_err());
+        assert!(Lasso::new(1.0).fit(&X, &Array::zeros(2, 1)).is_err());
+
+        let mut model = Lasso::new(0.1);
+        model.fit(&X, &y).unwrap();
+        assert!(model.predict(&Array::zeros(2, 3)).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y, _) = make_regression_with_coefficients(50, 3, 2, 0.1, &mut rng);
+
+        let mut model = Ridge::new(1.0);
+        model.fit(&X, &y).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: Ridge = bincode::deserialize(&encoded).unwrap();
+
+        assert!(model.predict(&X).unwrap() == decoded.predict(&X).unwrap());
+    }
+}
@@ -1,3 +1,6 @@
 //! Linear models.
 
+pub mod linear_regression;
 pub mod sgdclassifier;
+
+pub use self::linear_regression::{Lasso, Ridge};
@@ -8,6 +8,28 @@
 //! Repeated calls to the `fit` function are equivalent to running
 //! multiple epochs of training.
 //!
+//! Samples can be weighted by fitting with `fit_weighted`, which scales
+//! the gradient of each sample's loss by its weight.
+//!
+//! To monitor convergence, the mean training loss after every call to `fit`
+//! can be recorded by setting [`Hyperparameters::record_loss`](struct.Hyperparameters.html#method.record_loss),
+//! and retrieved using [`SGDClassifier::loss_history`](struct.SGDClassifier.html#method.loss_history).
+//!
+//! By default, the model fits no intercept, so that the decision boundary passes
+//! through the origin; either center the data, add a constant feature, or set
+//! [`Hyperparameters::fit_intercept`](struct.Hyperparameters.html#method.fit_intercept)
+//! to fit an unpenalized intercept as well.
+//!
+//! # Loss functions
+//!
+//! By default, the model minimizes the logistic loss, and its decision function
+//! gives the probability of the positive class. Other [loss functions](enum.Loss.html)
+//! can be chosen using [`Hyperparameters::loss`](struct.Hyperparameters.html#method.loss):
+//! the hinge and squared hinge losses give a linear support vector machine,
+//! whose decision function is the signed distance from the separating hyperplane,
+//! and the Huber loss gives a linear regression model robust to outliers, whose
+//! decision function and predictions are the estimated target values.
+//!
 //! # Examples
 //!
 //! Fitting the model on the iris dataset is straightforward:
@@ -62,7 +84,93 @@ use crossbeam;
 use prelude::*;
 
 use multiclass::OneVsRestWrapper;
-use utils::{check_data_dimensionality, check_matched_dimensions, check_valid_labels};
+use persistence::{check_model_format_version, model_format_version, MODEL_FORMAT_VERSION};
+use utils::{
+    assume_fitted, check_data_dimensionality, check_fitted, check_matched_dimensions,
+    check_row_dimensionality, check_sample_weights, check_valid_labels,
+};
+
+/// The loss function minimized by a `SGDClassifier` model.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Loss {
+    /// The logistic loss, giving logistic regression.
+    Log,
+    /// The hinge loss `max(0, 1 - y * f(x))` for labels `y` of -1 and 1,
+    /// giving a linear support vector machine.
+    Hinge,
+    /// The square of the hinge loss, which is smooth but
+    /// more sensitive to misclassified samples.
+    SquaredHinge,
+    /// The Huber loss for regression: the squared error for residuals of at most
+    /// `epsilon` in absolute value, and linear in the residual beyond that.
+    /// With an infinite `epsilon`, this is the squared loss.
+    Huber { epsilon: f32 },
+}
+
+impl Loss {
+    /// Value of the loss for a sample with target `y` and linear prediction `margin`.
+    fn value(&self, y: f32, margin: f32) -> f32 {
+        match *self {
+            // Computed as softplus(margin) - y * margin to avoid overflow
+            Loss::Log => margin.max(0.0) + (-margin.abs()).exp().ln_1p() - y * margin,
+            Loss::Hinge => (1.0 - (2.0 * y - 1.0) * margin).max(0.0),
+            Loss::SquaredHinge => (1.0 - (2.0 * y - 1.0) * margin).max(0.0).powi(2),
+            Loss::Huber { epsilon } => {
+                let residual = (margin - y).abs();
+
+                if residual <= epsilon {
+                    0.5 * residual.powi(2)
+                } else {
+                    epsilon * (residual - 0.5 * epsilon)
+                }
+            }
+        }
+    }
+
+    /// Derivative of the loss with respect to the linear prediction `margin`
+    /// for a sample with target `y`.
+    fn gradient(&self, y: f32, margin: f32) -> f32 {
+        match *self {
+            Loss::Log => sigmoid(margin) - y,
+            Loss::Hinge | Loss::SquaredHinge => {
+                let label = 2.0 * y - 1.0;
+                let slack = 1.0 - label * margin;
+
+                if slack <= 0.0 {
+                    0.0
+                } else if *self == Loss::Hinge {
+                    -label
+                } else {
+                    -2.0 * label * slack
+                }
+            }
+            Loss::Huber { epsilon } => {
+                let residual = margin - y;
+
+                if residual.abs() <= epsilon {
+                    residual
+                } else {
+                    epsilon * residual.signum()
+                }
+            }
+        }
+    }
+
+    fn is_classification(&self) -> bool {
+        match *self {
+            Loss::Huber { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+fn default_loss() -> Loss {
+    Loss::Log
+}
+
+fn default_intercept_gradsq() -> f32 {
+    1.0
+}
 
 /// Hyperparameters for a `SGDClassifier` model.
 #[derive(Serialize, Deserialize)]
@@ -72,6 +180,12 @@ pub struct Hyperparameters {
     learning_rate: f32,
     l2_penalty: f32,
     l1_penalty: f32,
+    #[serde(default = "default_loss")]
+    loss: Loss,
+    #[serde(default)]
+    fit_intercept: bool,
+    #[serde(default)]
+    record_loss: bool,
 }
 
 impl Hyperparameters {
@@ -96,6 +210,9 @@ impl Hyperparameters {
             learning_rate: 0.05,
             l2_penalty: 0.0,
             l1_penalty: 0.0,
+            loss: Loss::Log,
+            fit_intercept: false,
+            record_loss: false,
         }
     }
     /// Set the initial learning rate.
@@ -123,19 +240,63 @@ impl Hyperparameters {
         self
     }
 
+    /// Set the loss function. Defaults to `Loss::Log`.
+    ///
+    /// Classification losses require labels of 0 and 1; the Huber loss
+    /// accepts any finite target values.
+    ///
+    /// # Panics
+    /// Panics if the `epsilon` of a Huber loss is not positive.
+    pub fn loss(&mut self, loss: Loss) -> &mut Hyperparameters {
+        if let Loss::Huber { epsilon } = loss {
+            assert!(epsilon > 0.0, "Huber epsilon must be positive");
+        }
+
+        self.loss = loss;
+        self
+    }
+
+    /// Set whether to fit an intercept. Defaults to `false`, fixing the
+    /// intercept at zero.
+    ///
+    /// The intercept is updated like the coefficient of a constant feature,
+    /// with its own adaptive learning rate, but is not regularized.
+    pub fn fit_intercept(&mut self, fit_intercept: bool) -> &mut Hyperparameters {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Set whether to record the mean training loss after every epoch
+    /// (call to `fit`). Defaults to `false`.
+    ///
+    /// Recording takes an additional pass over the training data after each
+    /// epoch, but does not affect the fitted model.
+    pub fn record_loss(&mut self, record_loss: bool) -> &mut Hyperparameters {
+        self.record_loss = record_loss;
+        self
+    }
+
     /// Build a two-class model.
     pub fn build(&self) -> SGDClassifier {
         SGDClassifier {
+            version: MODEL_FORMAT_VERSION,
             dim: self.dim,
             learning_rate: self.learning_rate,
             l2_penalty: self.l2_penalty,
             l1_penalty: self.l1_penalty,
+            loss: self.loss,
+            fit_intercept: self.fit_intercept,
+            record_loss: self.record_loss,
             coefficients: Array::zeros(self.dim, 1),
             gradsq: Array::ones(self.dim, 1),
             applied_l1: Array::zeros(self.dim, 1),
             applied_l2: Array::ones(self.dim, 1),
             accumulated_l1: 0.0,
             accumulated_l2: 1.0,
+            intercept: 0.0,
+            intercept_gradsq: 1.0,
+            loss_history: Vec::new(),
+            fitted: false,
         }
     }
 
@@ -150,11 +311,22 @@ impl Hyperparameters {
 /// A two-class linear regression classifier implemented using stochastic gradient descent.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SGDClassifier {
+    #[serde(
+        default = "model_format_version",
+        deserialize_with = "check_model_format_version"
+    )]
+    version: u32,
     dim: usize,
 
     learning_rate: f32,
     l2_penalty: f32,
     l1_penalty: f32,
+    #[serde(default = "default_loss")]
+    loss: Loss,
+    #[serde(default)]
+    fit_intercept: bool,
+    #[serde(default)]
+    record_loss: bool,
 
     coefficients: Array,
     gradsq: Array,
@@ -162,16 +334,21 @@ pub struct SGDClassifier {
     applied_l2: Array,
     accumulated_l1: f32,
     accumulated_l2: f32,
+    #[serde(default)]
+    intercept: f32,
+    #[serde(default = "default_intercept_gradsq")]
+    intercept_gradsq: f32,
+    #[serde(default)]
+    loss_history: Vec<f32>,
+
+    #[serde(default = "assume_fitted")]
+    fitted: bool,
 }
 
 fn sigmoid(x: f32) -> f32 {
     1.0 / (1.0 + (-x).exp())
 }
 
-fn logistic_loss(y: f32, y_hat: f32) -> f32 {
-    y_hat - y
-}
-
 macro_rules! adagrad_updates {
     ($coefficients:expr, $x:expr, $gradsq:expr) => {{
         $coefficients
@@ -202,25 +379,46 @@ macro_rules! min {
 }
 
 impl<'a> SupervisedModel<&'a Array> for SGDClassifier {
-    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), &'static str> {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        self.fit_weighted(X, y, &Array::ones(y.rows(), 1))
+    }
+
+    fn fit_weighted(
+        &mut self,
+        X: &Array,
+        y: &Array,
+        sample_weight: &Array,
+    ) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
-        try!(check_valid_labels(y));
-
-        for (row, &true_y) in X.iter_rows().zip(y.data().iter()) {
-            let y_hat = self.compute_prediction(&row);
-            let loss = logistic_loss(true_y, y_hat);
-            self.update(&row, loss);
+        try!(self.check_targets(y));
+        try!(check_sample_weights(y, sample_weight));
+
+        for ((row, &true_y), &weight) in X
+            .iter_rows()
+            .zip(y.data().iter())
+            .zip(sample_weight.data().iter())
+        {
+            let gradient = self.loss.gradient(true_y, self.compute_margin(&row));
+            self.update(&row, weight * gradient);
         }
 
         for idx in 0..self.dim {
             self.apply_regularization(idx);
         }
 
+        if self.record_loss {
+            let loss = self.mean_loss(X, y, sample_weight);
+            self.loss_history.push(loss);
+        }
+
+        self.fitted = true;
+
         Ok(())
     }
 
-    fn decision_function(&self, X: &Array) -> Result<Array, &'static str> {
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(check_fitted(self.fitted));
         try!(check_data_dimensionality(self.dim, X));
 
         let mut data = Vec::with_capacity(X.rows());
@@ -231,6 +429,10 @@ impl<'a> SupervisedModel<&'a Array> for SGDClassifier {
 
         Ok(Array::from(data))
     }
+
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        Ok(try!(self.decision_function(X)).map(|x| self.label(x)))
+    }
 }
 
 impl<'a> ParallelPredict<&'a Array> for SGDClassifier {
@@ -238,7 +440,8 @@ impl<'a> ParallelPredict<&'a Array> for SGDClassifier {
         &self,
         X: &Array,
         num_threads: usize,
-    ) -> Result<Array, &'static str> {
+    ) -> Result<Array, RustlearnError> {
+        try!(check_fitted(self.fitted));
         try!(check_data_dimensionality(self.dim, X));
 
         let mut data = Vec::with_capacity(X.rows());
@@ -265,28 +468,53 @@ impl<'a> ParallelPredict<&'a Array> for SGDClassifier {
 
         Ok(Array::from(data))
     }
+
+    fn predict_parallel(&self, X: &Array, num_threads: usize) -> Result<Array, RustlearnError> {
+        Ok(try!(self.decision_function_parallel(X, num_threads)).map(|x| self.label(x)))
+    }
 }
 
 impl<'a> SupervisedModel<&'a SparseRowArray> for SGDClassifier {
-    fn fit(&mut self, X: &SparseRowArray, y: &Array) -> Result<(), &'static str> {
+    fn fit(&mut self, X: &SparseRowArray, y: &Array) -> Result<(), RustlearnError> {
+        self.fit_weighted(X, y, &Array::ones(y.rows(), 1))
+    }
+
+    fn fit_weighted(
+        &mut self,
+        X: &SparseRowArray,
+        y: &Array,
+        sample_weight: &Array,
+    ) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
-        try!(check_valid_labels(y));
-
-        for (row, &true_y) in X.iter_rows().zip(y.data().iter()) {
-            let y_hat = self.compute_prediction(&row);
-            let loss = logistic_loss(true_y, y_hat);
-            self.update(&row, loss);
+        try!(self.check_targets(y));
+        try!(check_sample_weights(y, sample_weight));
+
+        for ((row, &true_y), &weight) in X
+            .iter_rows()
+            .zip(y.data().iter())
+            .zip(sample_weight.data().iter())
+        {
+            let gradient = self.loss.gradient(true_y, self.compute_margin(&row));
+            self.update(&row, weight * gradient);
         }
 
         for idx in 0..self.dim {
             self.apply_regularization(idx);
         }
 
+        if self.record_loss {
+            let loss = self.mean_loss(X, y, sample_weight);
+            self.loss_history.push(loss);
+        }
+
+        self.fitted = true;
+
         Ok(())
     }
 
-    fn decision_function(&self, X: &SparseRowArray) -> Result<Array, &'static str> {
+    fn decision_function(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        try!(check_fitted(self.fitted));
         try!(check_data_dimensionality(self.dim, X));
 
         let mut data = Vec::with_capacity(X.rows());
@@ -297,6 +525,35 @@ impl<'a> SupervisedModel<&'a SparseRowArray> for SGDClassifier {
 
         Ok(Array::from(data))
     }
+
+    fn predict(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        Ok(try!(self.decision_function(X)).map(|x| self.label(x)))
+    }
+}
+
+impl RowPredict for SGDClassifier {
+    fn decision_function_row(&self, row: &[f32]) -> Result<f32, RustlearnError> {
+        try!(check_fitted(self.fitted));
+        try!(check_row_dimensionality(self.dim, row));
+
+        Ok(self.compute_prediction(&row))
+    }
+
+    fn predict_row(&self, row: &[f32]) -> Result<f32, RustlearnError> {
+        Ok(self.label(try!(self.decision_function_row(row))))
+    }
+}
+
+impl SeedableModel for SGDClassifier {}
+
+impl FeatureImportance for SGDClassifier {
+    /// Return the absolute values of the coefficients. These are only comparable
+    /// across features when the features are on similar scales.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError> {
+        try!(check_fitted(self.fitted));
+
+        Ok(self.coefficients.data().iter().map(|x| x.abs()).collect())
+    }
 }
 
 impl SGDClassifier {
@@ -305,6 +562,60 @@ impl SGDClassifier {
         &self.coefficients
     }
 
+    /// Returns the estimated intercept; zero unless the model fits an intercept.
+    pub fn intercept(&self) -> f32 {
+        self.intercept
+    }
+
+    /// Return the mean training loss after every epoch, if recording
+    /// is enabled by [`Hyperparameters::record_loss`](struct.Hyperparameters.html#method.record_loss).
+    /// Returns an empty slice otherwise.
+    ///
+    /// Sample weights given to `fit_weighted` weight the mean;
+    /// regularization penalties are not included.
+    pub fn loss_history(&self) -> &[f32] {
+        &self.loss_history
+    }
+
+    /// Return the loss function minimized by the model.
+    pub fn loss(&self) -> Loss {
+        self.loss
+    }
+
+    /// Check that `y` holds binary labels for classification
+    /// losses, or finite target values for regression losses.
+    fn check_targets(&self, y: &Array) -> Result<(), RustlearnError> {
+        if self.loss.is_classification() {
+            check_valid_labels(y)
+        } else if y.cols() != 1 {
+            Err(RustlearnError::DimensionMismatch(
+                "Target array has more than one column.",
+            ))
+        } else if y.data().iter().any(|x| !x.is_finite()) {
+            Err(RustlearnError::InvalidInput(
+                "Target values must be finite.",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Turn a value of the decision function into a prediction: a class
+    /// label for classification losses, or the value itself for regression.
+    fn label(&self, decision: f32) -> f32 {
+        let threshold = match self.loss {
+            Loss::Log => 0.5,
+            Loss::Hinge | Loss::SquaredHinge => 0.0,
+            Loss::Huber { .. } => return decision,
+        };
+
+        if decision > threshold {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
     fn update_at_idx(&mut self, idx: usize, update: f32) {
         let gradsq = self.gradsq.get(idx, 0);
 
@@ -320,6 +631,13 @@ impl SGDClassifier {
             self.apply_regularization(idx);
         }
 
+        if self.fit_intercept {
+            let local_learning_rate = self.learning_rate / self.intercept_gradsq.sqrt();
+
+            self.intercept -= local_learning_rate * loss;
+            self.intercept_gradsq += loss.powi(2);
+        }
+
         self.accumulate_regularization();
     }
 
@@ -359,19 +677,52 @@ impl SGDClassifier {
         *applied_l1 += l1_actual_update;
     }
 
-    fn compute_prediction<T: NonzeroIterable>(&self, row: &T) -> f32 {
-        let mut prediction = 0.0;
+    /// Weighted mean of the loss over the rows of `X`.
+    fn mean_loss<'a, T>(&self, X: &'a T, y: &Array, sample_weight: &Array) -> f32
+    where
+        &'a T: RowIterable,
+    {
+        let (total_loss, total_weight) = X
+            .iter_rows()
+            .zip(y.data().iter())
+            .zip(sample_weight.data().iter())
+            .fold(
+                (0.0, 0.0),
+                |(total_loss, total_weight), ((row, &y), &weight)| {
+                    let loss = self.loss.value(y, self.compute_margin(&row));
+                    (total_loss + weight * loss, total_weight + weight)
+                },
+            );
+
+        total_loss / total_weight
+    }
+
+    fn compute_margin<T: NonzeroIterable>(&self, row: &T) -> f32 {
+        let mut margin = self.intercept;
 
         for (idx, value) in row.iter_nonzero() {
-            prediction += self.coefficients.get(idx, 0) * value;
+            margin += self.coefficients.get(idx, 0) * value;
         }
 
-        sigmoid(prediction)
+        margin
+    }
+
+    fn compute_prediction<T: NonzeroIterable>(&self, row: &T) -> f32 {
+        let margin = self.compute_margin(row);
+
+        match self.loss {
+            Loss::Log => sigmoid(margin),
+            _ => margin,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::f32;
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
     use rand::{SeedableRng, StdRng};
 
     use prelude::*;
@@ -523,6 +874,412 @@ mod tests {
         assert!(test_accuracy > 0.9);
     }
 
+    #[test]
+    fn test_equal_weights() {
+        let (data, target) = load_data();
+        let sparse_data = SparseRowArray::from(&data);
+        let weights = Array::ones(data.rows(), 1);
+
+        let mut params = Hyperparameters::new(data.cols());
+        params
+            .learning_rate(0.5)
+            .l2_penalty(0.0001)
+            .l1_penalty(0.0001);
+
+        let mut model = params.one_vs_rest();
+        let mut weighted_model = params.one_vs_rest();
+
+        model.fit(&data, &target).unwrap();
+        weighted_model
+            .fit_weighted(&data, &target, &weights)
+            .unwrap();
+
+        for (model, weighted_model) in model.models().iter().zip(weighted_model.models()) {
+            assert!(model.get_coefficients() == weighted_model.get_coefficients());
+        }
+
+        let mut model = Hyperparameters::new(data.cols()).build();
+        let mut weighted_model = model.clone();
+
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&y| if y == 0.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        model.fit(&sparse_data, &binary_target).unwrap();
+        weighted_model
+            .fit_weighted(&sparse_data, &binary_target, &weights)
+            .unwrap();
+
+        assert!(model.get_coefficients() == weighted_model.get_coefficients());
+    }
+
+    #[test]
+    fn test_sample_weights_recall() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        // 180 negative and 20 overlapping positive
+        // samples, with a constant intercept feature.
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0..200 {
+            let (center, label) = if i < 180 { (0.0, 0.0) } else { (1.5, 1.0) };
+
+            rows.push(vec![
+                center + normal.ind_sample(&mut rng) as f32,
+                center + normal.ind_sample(&mut rng) as f32,
+                1.0,
+            ]);
+            labels.push(label);
+        }
+
+        let X = Array::from(&rows);
+        let y = Array::from(labels);
+        let weights = Array::from(
+            y.data()
+                .iter()
+                .map(|&y| if y == 1.0 { 9.0 } else { 1.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let recall = |model: &SGDClassifier| {
+            let prediction = model.predict(&X).unwrap();
+
+            y.data()
+                .iter()
+                .zip(prediction.data().iter())
+                .filter(|&(&y, &y_hat)| y == 1.0 && y_hat == 1.0)
+                .count() as f32
+                / 20.0
+        };
+
+        let mut model = Hyperparameters::new(3)
+            .learning_rate(0.1)
+            .l2_penalty(0.0)
+            .l1_penalty(0.0)
+            .build();
+        let mut weighted_model = model.clone();
+
+        for _ in 0..20 {
+            model.fit(&X, &y).unwrap();
+            weighted_model.fit_weighted(&X, &y, &weights).unwrap();
+        }
+
+        println!(
+            "Recall {}, weighted {}",
+            recall(&model),
+            recall(&weighted_model)
+        );
+
+        assert!(recall(&weighted_model) > recall(&model) + 0.2);
+    }
+
+    #[test]
+    fn test_predict_row() {
+        let (data, target) = load_data();
+        let binary_target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&y| if y == 0.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut model = Hyperparameters::new(data.cols())
+            .learning_rate(0.5)
+            .l2_penalty(0.0001)
+            .build();
+        model.fit(&data, &binary_target).unwrap();
+
+        let df = model.decision_function(&data).unwrap();
+        let prediction = model.predict(&data).unwrap();
+
+        for (row_idx, row) in data.row_iter().unwrap().enumerate() {
+            assert!(model.decision_function_row(row).unwrap() == df.get(row_idx, 0));
+            assert!(model.predict_row(row).unwrap() == prediction.get(row_idx, 0));
+        }
+
+        assert!(model.predict_row(&[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_hinge_separable() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        // Points at least 0.5 away from the hyperplane x_0 = x_1
+        while rows.len() < 200 {
+            let row = vec![
+                normal.ind_sample(&mut rng) as f32,
+                normal.ind_sample(&mut rng) as f32,
+            ];
+            let margin = row[0] - row[1];
+
+            if margin.abs() > 0.5 {
+                labels.push(if margin > 0.0 { 1.0 } else { 0.0 });
+                rows.push(row);
+            }
+        }
+
+        let X = Array::from(&rows);
+        let y = Array::from(labels);
+
+        for &loss in &[Loss::Hinge, Loss::SquaredHinge] {
+            let mut model = Hyperparameters::new(2)
+                .learning_rate(0.5)
+                .loss(loss)
+                .build();
+
+            for _ in 0..20 {
+                model.fit(&X, &y).unwrap();
+            }
+
+            let prediction = model.predict(&X).unwrap();
+            let df = model.decision_function(&X).unwrap();
+
+            println!("{:?} accuracy {}", loss, accuracy_score(&y, &prediction));
+
+            assert!(model.loss() == loss);
+            assert!(accuracy_score(&y, &prediction) == 1.0);
+
+            for (row_idx, row) in X.row_iter().unwrap().enumerate() {
+                assert!(model.predict_row(row).unwrap() == prediction.get(row_idx, 0));
+                assert!((df.get(row_idx, 0) > 0.0) == (prediction.get(row_idx, 0) == 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_huber_outlier() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let coefficients = [2.0, -1.0];
+
+        let mut rows = Vec::new();
+        let mut targets = Vec::new();
+
+        for _ in 0..200 {
+            let row = vec![
+                normal.ind_sample(&mut rng) as f32,
+                normal.ind_sample(&mut rng) as f32,
+            ];
+            let noise = 0.1 * normal.ind_sample(&mut rng) as f32;
+
+            targets.push(coefficients[0] * row[0] + coefficients[1] * row[1] + noise);
+            rows.push(row);
+        }
+
+        // A single grossly corrupted target
+        rows.push(vec![1.0, 1.0]);
+        targets.push(1000.0);
+
+        let X = Array::from(&rows);
+        let y = Array::from(targets);
+
+        let coefficient_error = |epsilon: f32| {
+            let mut model = Hyperparameters::new(2)
+                .learning_rate(0.1)
+                .loss(Loss::Huber { epsilon: epsilon })
+                .build();
+
+            for _ in 0..50 {
+                model.fit(&X, &y).unwrap();
+            }
+
+            assert!(model.predict(&X).unwrap() == model.decision_function(&X).unwrap());
+
+            model
+                .get_coefficients()
+                .data()
+                .iter()
+                .zip(coefficients.iter())
+                .map(|(estimate, truth)| (estimate - truth).abs())
+                .fold(0.0, f32::max)
+        };
+
+        let huber_error = coefficient_error(1.0);
+        let squared_error = coefficient_error(f32::INFINITY);
+
+        println!(
+            "Huber error {}, squared error {}",
+            huber_error, squared_error
+        );
+
+        assert!(huber_error < 0.1);
+        assert!(huber_error < squared_error);
+    }
+
+    #[test]
+    fn test_fit_intercept() {
+        let (data, target) = load_data();
+        let target = target.map(|y| if y == 0.0 { 1.0 } else { 0.0 });
+
+        let mut means = data.sum_axis(0).unwrap();
+        means.div_inplace(data.rows() as f32);
+        let mut centered = data.clone();
+        for row_idx in 0..centered.rows() {
+            for col_idx in 0..centered.cols() {
+                let value = centered.get(row_idx, col_idx) - means.get(0, col_idx);
+                centered.set(row_idx, col_idx, value);
+            }
+        }
+
+        let fit = |X: &Array, fit_intercept: bool| {
+            let mut model = Hyperparameters::new(X.cols())
+                .learning_rate(0.5)
+                .fit_intercept(fit_intercept)
+                .build();
+
+            for _ in 0..10 {
+                model.fit(X, &target).unwrap();
+            }
+
+            let accuracy = accuracy_score(&target, &model.predict(X).unwrap());
+
+            (model, accuracy)
+        };
+
+        let (with_intercept, intercept_accuracy) = fit(&centered, true);
+        let (without_intercept, no_intercept_accuracy) = fit(&centered, false);
+
+        println!(
+            "Accuracy {} with intercept, {} without",
+            intercept_accuracy, no_intercept_accuracy
+        );
+
+        assert!(with_intercept.intercept() != 0.0);
+        assert!(without_intercept.intercept() == 0.0);
+        assert!(no_intercept_accuracy >= intercept_accuracy - 0.01);
+
+        // A threshold away from the origin needs an intercept
+        let X = Array::from(
+            (0..20)
+                .map(|x| if x < 10 { 2.0 } else { 5.0 } + x as f32 / 100.0)
+                .collect::<Vec<_>>(),
+        );
+        let y = Array::from(
+            (0..20)
+                .map(|x| if x < 10 { 0.0 } else { 1.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        for &fit_intercept in &[false, true] {
+            let mut model = Hyperparameters::new(1)
+                .learning_rate(0.5)
+                .fit_intercept(fit_intercept)
+                .build();
+
+            for _ in 0..100 {
+                model.fit(&X, &y).unwrap();
+            }
+
+            let accuracy = accuracy_score(&y, &model.predict(&X).unwrap());
+
+            assert!((accuracy == 1.0) == fit_intercept);
+        }
+    }
+
+    #[test]
+    fn test_loss_history() {
+        let X = Array::from(&vec![
+            vec![1.0, 0.2],
+            vec![0.8, -0.1],
+            vec![1.2, 0.3],
+            vec![-1.0, 0.1],
+            vec![-0.7, -0.2],
+            vec![-1.1, 0.2],
+        ]);
+        let y = Array::from(vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+
+        let mut plain = Hyperparameters::new(2).learning_rate(0.5).build();
+        let mut recording = Hyperparameters::new(2)
+            .learning_rate(0.5)
+            .record_loss(true)
+            .build();
+
+        for _ in 0..20 {
+            plain.fit(&X, &y).unwrap();
+            recording.fit(&X, &y).unwrap();
+        }
+
+        let history = recording.loss_history();
+
+        println!("Loss history {:?}", history);
+
+        assert!(plain.loss_history().is_empty());
+        assert!(history.len() == 20);
+        assert!(history.windows(2).all(|x| x[1] <= x[0]));
+        assert!(history[19] < history[0]);
+
+        // Recording does not change the fitted model
+        assert!(plain.get_coefficients() == recording.get_coefficients());
+
+        let sparse_X = SparseRowArray::from(&X);
+        let mut sparse = Hyperparameters::new(2)
+            .learning_rate(0.5)
+            .record_loss(true)
+            .build();
+        sparse.fit(&sparse_X, &y).unwrap();
+
+        assert!(sparse.loss_history() == &history[..1]);
+    }
+
+    #[test]
+    fn test_loss_targets() {
+        let X = Array::from(&vec![vec![1.0], vec![2.0]]);
+        let y = Array::from(vec![0.5, 2.0]);
+
+        let mut classifier = Hyperparameters::new(1).loss(Loss::Hinge).build();
+        assert!(classifier.fit(&X, &y).is_err());
+
+        let mut regressor = Hyperparameters::new(1)
+            .loss(Loss::Huber { epsilon: 1.0 })
+            .build();
+        assert!(regressor.fit(&X, &y).is_ok());
+        assert!(regressor
+            .fit(&X, &Array::from(vec![0.5, f32::NAN]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_not_fitted() {
+        let (data, target) = load_data();
+        let sparse_data = SparseRowArray::from(&data);
+        let binary_target = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+
+        let mut model = Hyperparameters::new(data.cols()).build();
+
+        match model.predict(&data) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+        match model.decision_function(&sparse_data) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+        match model.predict_row(data.row_iter().unwrap().next().unwrap()) {
+            Err(RustlearnError::NotFitted(_)) => {}
+            _ => panic!("Expected an unfitted model error"),
+        }
+
+        model.fit(&data, &binary_target).unwrap();
+
+        assert!(model.predict(&data).is_ok());
+        assert!(model.decision_function(&sparse_data).is_ok());
+        assert!(model
+            .predict_row(data.row_iter().unwrap().next().unwrap())
+            .is_ok());
+    }
+
     #[test]
     fn serialization() {
         let (data, target) = load_data();
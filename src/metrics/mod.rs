@@ -1,12 +1,16 @@
 //! Accuracy metrics.
 
+use std::cmp::Ordering;
+
 use array::prelude::*;
 
 mod ranking;
 
 mod test;
 
-pub use self::ranking::{roc_auc_score, dcg_score, ndcg_score};
+pub use self::ranking::{roc_auc_score, roc_auc_score_approx, roc_auc_score_multiclass, roc_curve,
+                        dcg_score, ndcg_score, precision_recall_curve, average_precision_score,
+                        top_k_accuracy_score, cumulative_gain_curve, lift_at, Average};
 
 
 /// Measure classifier accuracy
@@ -28,6 +32,212 @@ pub fn accuracy_score(y_true: &Array, y_hat: &Array) -> f32 {
     accuracy / (y_true.rows() as f32)
 }
 
+/// Measure classifier accuracy of predicted probabilities.
+///
+/// A single column of positive class probabilities is converted into
+/// labels of 1.0 where the probability is at least `threshold`, and 0.0
+/// elsewhere. For a matrix with one column of probabilities per class,
+/// the predicted label is the index of the most probable column, and
+/// `threshold` is ignored.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length, or if any
+/// probability lies outside `[0, 1]`.
+pub fn accuracy_from_proba(y_true: &Array, y_prob: &Array, threshold: f32) -> f32 {
+    assert!(y_prob.rows() == y_true.rows(),
+            "Probabilities and labels must have the same number of rows");
+    assert!(y_prob.data().iter().all(|&p| p >= 0.0 && p <= 1.0),
+            "Probabilities must lie in [0, 1]");
+
+    let y_hat = if y_prob.cols() == 1 {
+        y_prob.map(|p| if p >= threshold { 1.0 } else { 0.0 })
+    } else {
+        let labels = (0..y_prob.rows())
+            .map(|row| {
+                let mut best = 0;
+                for col in 1..y_prob.cols() {
+                    if y_prob.get(row, col) > y_prob.get(row, best) {
+                        best = col;
+                    }
+                }
+                best as f32
+            })
+            .collect::<Vec<_>>();
+
+        Array::from(labels)
+    };
+
+    accuracy_score(y_true, &y_hat)
+}
+
+/// Measure classifier accuracy as the mean of the per-class recalls,
+/// averaged over the classes present in `y_true`.
+///
+/// Unlike plain accuracy, this is not inflated by performing
+/// well on the majority class of an imbalanced dataset.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length.
+pub fn balanced_accuracy_score(y_true: &Array, y_hat: &Array) -> f32 {
+    assert!(y_true.rows() == y_hat.rows());
+
+    let (labels, confusion) = confusion_counts(y_true, y_hat);
+
+    let recalls = (0..labels.len())
+        .filter(|&true_idx| confusion[true_idx].iter().sum::<f32>() > 0.0)
+        .map(|true_idx| {
+            confusion[true_idx][true_idx] / confusion[true_idx].iter().sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+
+    recalls.iter().sum::<f32>() / recalls.len() as f32
+}
+
+/// Compute the recall of every class: the fraction of samples of that class
+/// in `y_true` that are predicted correctly.
+///
+/// Returns the recalls together with the corresponding labels, sorted in
+/// ascending order. Labels occurring only in `y_hat` have no support and
+/// a recall of zero.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length.
+pub fn recall_per_class(y_true: &Array, y_hat: &Array) -> (Vec<f32>, Vec<f32>) {
+    assert!(y_true.rows() == y_hat.rows());
+
+    let (labels, confusion) = confusion_counts(y_true, y_hat);
+
+    let recalls = (0..labels.len())
+        .map(|true_idx| {
+            let support = confusion[true_idx].iter().sum::<f32>();
+
+            if support > 0.0 {
+                confusion[true_idx][true_idx] / support
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    (recalls, labels)
+}
+
+/// Measure classifier performance as the geometric mean of the per-class
+/// recalls, over the classes present in `y_true`.
+///
+/// Like balanced accuracy, this is robust to class imbalance, but it
+/// is zero whenever any class is never predicted correctly.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length.
+pub fn geometric_mean_score(y_true: &Array, y_hat: &Array) -> f32 {
+    assert!(y_true.rows() == y_hat.rows());
+
+    let (labels, confusion) = confusion_counts(y_true, y_hat);
+
+    let recalls = (0..labels.len())
+        .filter(|&true_idx| confusion[true_idx].iter().sum::<f32>() > 0.0)
+        .map(|true_idx| {
+            confusion[true_idx][true_idx] / confusion[true_idx].iter().sum::<f32>()
+        })
+        .collect::<Vec<_>>();
+
+    recalls.iter().product::<f32>().powf(1.0 / recalls.len() as f32)
+}
+
+/// Measure the agreement between true and predicted labels using Cohen's kappa,
+/// the accuracy corrected for the agreement expected by chance.
+///
+/// A value of 1 means perfect agreement, and 0 means agreement no better than chance.
+/// When both inputs contain only the same single class, chance agreement is perfect
+/// and the score is defined to be 1.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length.
+pub fn cohen_kappa_score(y_true: &Array, y_hat: &Array) -> f32 {
+    assert!(y_true.rows() == y_hat.rows());
+
+    let (labels, confusion) = confusion_counts(y_true, y_hat);
+    let num_samples = y_true.rows() as f32;
+
+    let observed = (0..labels.len())
+        .map(|idx| confusion[idx][idx])
+        .sum::<f32>()
+        / num_samples;
+    let expected = (0..labels.len())
+        .map(|idx| {
+            let true_count = confusion[idx].iter().sum::<f32>();
+            let predicted_count = confusion.iter().map(|row| row[idx]).sum::<f32>();
+
+            true_count * predicted_count
+        })
+        .sum::<f32>()
+        / num_samples.powi(2);
+
+    if expected >= 1.0 {
+        1.0
+    } else {
+        (observed - expected) / (1.0 - expected)
+    }
+}
+
+/// Measure the quality of binary predictions using the Matthews correlation
+/// coefficient, computed from the confusion counts of the positive class
+/// (label 1) and the negative class (label 0).
+///
+/// A value of 1 means perfect prediction, 0 prediction no better than chance,
+/// and -1 total disagreement. If any of the four marginal counts is zero, the
+/// coefficient is undefined and 0 is returned.
+///
+/// # Panics
+/// Will panic if inputs are of unequal length.
+pub fn matthews_corrcoef(y_true: &Array, y_hat: &Array) -> f32 {
+    assert!(y_true.rows() == y_hat.rows());
+
+    let (mut tp, mut tn, mut fp, mut fn_) = (0.0f64, 0.0f64, 0.0f64, 0.0f64);
+
+    for (&yt, &yh) in y_true.data().iter().zip(y_hat.data().iter()) {
+        match (yt == 1.0, yh == 1.0) {
+            (true, true) => tp += 1.0,
+            (false, false) => tn += 1.0,
+            (false, true) => fp += 1.0,
+            (true, false) => fn_ += 1.0,
+        }
+    }
+
+    let denominator = (tp + fp) * (tp + fn_) * (tn + fp) * (tn + fn_);
+
+    if denominator == 0.0 {
+        0.0
+    } else {
+        ((tp * tn - fp * fn_) / denominator.sqrt()) as f32
+    }
+}
+
+/// Return the sorted labels occurring in either input, and the
+/// confusion matrix indexed by true and predicted label positions.
+fn confusion_counts(y_true: &Array, y_hat: &Array) -> (Vec<f32>, Vec<Vec<f32>>) {
+    let mut labels = y_true
+        .data()
+        .iter()
+        .chain(y_hat.data().iter())
+        .cloned()
+        .collect::<Vec<_>>();
+    labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    labels.dedup();
+
+    let mut confusion = vec![vec![0.0; labels.len()]; labels.len()];
+
+    for (yt, yh) in y_true.data().iter().zip(y_hat.data().iter()) {
+        let true_idx = labels.iter().position(|x| x == yt).unwrap();
+        let hat_idx = labels.iter().position(|x| x == yh).unwrap();
+
+        confusion[true_idx][hat_idx] += 1.0;
+    }
+
+    (labels, confusion)
+}
+
 /// Measure Regressor Performance
 ///
 /// # Panics
@@ -62,7 +272,9 @@ mod tests {
 
     use prelude::*;
 
-    use super::{mean_absolute_error, mean_squared_error};
+    use super::{accuracy_from_proba, accuracy_score, balanced_accuracy_score, cohen_kappa_score,
+                geometric_mean_score, matthews_corrcoef, mean_absolute_error, mean_squared_error,
+                recall_per_class};
 
     #[test]
     fn basic() {
@@ -75,4 +287,152 @@ mod tests {
 
     }
 
+    #[test]
+    fn imbalanced_multiclass() {
+        // Confusion matrix (rows true, columns predicted):
+        // class 0: [8, 1, 1]
+        // class 1: [1, 1, 0]
+        // class 2: [2, 0, 0]
+        let mut y_true = Vec::new();
+        let mut y_hat = Vec::new();
+
+        for &(true_label, predicted_label, count) in &[(0.0, 0.0, 8),
+                                                       (0.0, 1.0, 1),
+                                                       (0.0, 2.0, 1),
+                                                       (1.0, 0.0, 1),
+                                                       (1.0, 1.0, 1),
+                                                       (2.0, 0.0, 2)] {
+            for _ in 0..count {
+                y_true.push(true_label);
+                y_hat.push(predicted_label);
+            }
+        }
+
+        let y_true = Array::from(y_true);
+        let y_hat = Array::from(y_hat);
+
+        let accuracy = accuracy_score(&y_true, &y_hat);
+        let balanced_accuracy = balanced_accuracy_score(&y_true, &y_hat);
+
+        assert!((accuracy - 9.0 / 14.0).abs() < 1e-6);
+        assert!((balanced_accuracy - (0.8 + 0.5 + 0.0) / 3.0).abs() < 1e-6);
+        assert!(accuracy - balanced_accuracy > 0.2);
+
+        // Chance agreement is (10 * 11 + 2 * 2 + 2 * 1) / 14^2
+        let expected = 116.0 / 196.0;
+        let kappa = cohen_kappa_score(&y_true, &y_hat);
+
+        assert!((kappa - (accuracy - expected) / (1.0 - expected)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn accuracy_of_probabilities() {
+        // Eight negatives and two positives, with the positives
+        // scored above most, but not all, of the negatives.
+        let y_true = Array::from(vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0]);
+        let y_prob = Array::from(vec![0.05, 0.1, 0.1, 0.2, 0.2, 0.3, 0.35, 0.6, 0.4, 0.7]);
+
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.5) == 0.8);
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.38) == 0.9);
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.65) == 0.9);
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.0) == 0.2);
+        assert!(accuracy_from_proba(&y_true, &y_prob, 1.0) == 0.8);
+
+        // Thresholding matches thresholding by hand
+        let thresholded = y_prob.map(|p| if p >= 0.5 { 1.0 } else { 0.0 });
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.5) ==
+                accuracy_score(&y_true, &thresholded));
+
+        let y_true = Array::from(vec![0.0, 1.0, 2.0, 2.0]);
+        let y_prob = Array::from(&vec![vec![0.6, 0.3, 0.1],
+                                       vec![0.2, 0.5, 0.3],
+                                       vec![0.1, 0.1, 0.8],
+                                       vec![0.4, 0.35, 0.25]]);
+
+        assert!(accuracy_from_proba(&y_true, &y_prob, 0.5) == 0.75);
+    }
+
+    #[test]
+    #[should_panic(expected = "same number of rows")]
+    fn accuracy_of_mismatched_probabilities() {
+        let y_true = Array::from(vec![0.0, 1.0, 1.0]);
+        let y_prob = Array::from(vec![0.2, 0.8]);
+
+        accuracy_from_proba(&y_true, &y_prob, 0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn accuracy_of_invalid_probabilities() {
+        let y_true = Array::from(vec![0.0, 1.0]);
+        let y_prob = Array::from(vec![-0.5, 1.5]);
+
+        accuracy_from_proba(&y_true, &y_prob, 0.5);
+    }
+
+    #[test]
+    fn per_class_recall() {
+        // Confusion matrix (rows true, columns predicted):
+        // class 1: [3, 1, 0, 0]
+        // class 2: [1, 1, 0, 0]
+        // class 3: [0, 1, 1, 1]
+        // class 4: never occurs in y_true
+        let y_true = Array::from(vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 3.0]);
+        let y_hat = Array::from(vec![1.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0, 3.0, 4.0]);
+
+        let (recalls, labels) = recall_per_class(&y_true, &y_hat);
+
+        assert!(labels == vec![1.0, 2.0, 3.0, 4.0]);
+        assert!(recalls == vec![0.75, 0.5, 1.0 / 3.0, 0.0]);
+
+        // The class without support is left out
+        let expected = (0.75f32 * 0.5 / 3.0).powf(1.0 / 3.0);
+        assert!((geometric_mean_score(&y_true, &y_hat) - expected).abs() < 1e-6);
+
+        assert!(geometric_mean_score(&y_true, &y_true) == 1.0);
+
+        let y_never = Array::from(vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(geometric_mean_score(&y_true, &y_never) == 0.0);
+    }
+
+    #[test]
+    fn classification_edge_cases() {
+        let y = Array::from(vec![1.0, 2.0, 3.0, 1.0]);
+
+        assert!(balanced_accuracy_score(&y, &y) == 1.0);
+        assert!((cohen_kappa_score(&y, &y) - 1.0).abs() < 1e-6);
+
+        let single = Array::from(vec![1.0, 1.0, 1.0]);
+
+        assert!(balanced_accuracy_score(&single, &single) == 1.0);
+        assert!(cohen_kappa_score(&single, &single) == 1.0);
+
+        let predicted = Array::from(vec![1.0, 0.0, 1.0]);
+        let kappa = cohen_kappa_score(&single, &predicted);
+
+        assert!((balanced_accuracy_score(&single, &predicted) - 2.0 / 3.0).abs() < 1e-6);
+        assert!(kappa == 0.0);
+    }
+
+    #[test]
+    fn matthews_correlation() {
+        // TP = 6, TN = 3, FP = 1, FN = 2
+        let y_true = Array::from(vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0,
+                                      0.0, 0.0, 0.0, 0.0]);
+        let y_hat = Array::from(vec![1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0,
+                                     0.0, 0.0, 0.0, 1.0]);
+
+        let expected = (6.0 * 3.0 - 1.0 * 2.0) / (7.0f32 * 8.0 * 4.0 * 5.0).sqrt();
+
+        assert!((matthews_corrcoef(&y_true, &y_hat) - expected).abs() < 1e-6);
+        assert!(matthews_corrcoef(&y_true, &y_true) == 1.0);
+
+        let inverted = y_true.map(|y| 1.0 - y);
+        assert!(matthews_corrcoef(&y_true, &inverted) == -1.0);
+
+        // Constant predictions leave the coefficient undefined
+        let constant = Array::from(vec![1.0; 12]);
+        assert!(matthews_corrcoef(&y_true, &constant) == 0.0);
+    }
+
 }
@@ -4,6 +4,27 @@ use std::f32;
 use std::cmp::{min, Ordering};
 
 use array::prelude::*;
+use error::RustlearnError;
+
+
+/// Order (index, score) pairs by descending score, placing NaN scores last
+/// and breaking ties by ascending index, so that sorting is deterministic.
+fn descending_score_order(a: (usize, f32), b: (usize, f32)) -> Ordering {
+    let by_score = match (a.1.is_nan(), b.1.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => b.1.partial_cmp(&a.1).unwrap(),
+    };
+
+    by_score.then(a.0.cmp(&b.0))
+}
+
+
+/// Whether two scores are tied, treating all NaN scores as equal.
+fn same_score(a: f32, b: f32) -> bool {
+    a == b || (a.is_nan() && b.is_nan())
+}
 
 
 /// Discounted Cumulative Gain
@@ -12,8 +33,8 @@ use array::prelude::*;
 /// Will panic if inputs are of unequal length.
 pub fn dcg_score(y_true: &Array, y_hat: &Array, k: i32) -> f32 {
     assert!(y_true.rows() == y_hat.rows());
-    let mut pairs: Vec<_> = y_hat.data().iter().enumerate().collect();
-    pairs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+    let mut pairs: Vec<_> = y_hat.data().iter().cloned().enumerate().collect();
+    pairs.sort_by(|&a, &b| descending_score_order(a, b));
     let mut out: f32 = 0.0;
     let last = min(k as usize, y_true.rows());
     for i in 0..last {
@@ -36,27 +57,30 @@ pub fn ndcg_score(y_true: &Array, y_hat: &Array, k: i32) -> f32 {
     actual / best
 }
 
-/// Return (nondecreasing) counts of true positives and false positives.
-fn counts_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>) {
+/// Return (nondecreasing) counts of true positives and false positives,
+/// together with the (decreasing) score thresholds at which they are attained.
+/// NaN scores are ranked below all other scores, and tied with each other.
+fn counts_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
 
-    // vector of pairs (score, label) - the order is switched with respect to function arguments
-    let mut pairs: Vec<_> = y_hat.iter().cloned().zip(y_true.iter().cloned()).collect();
+    // Sort indices by scores in a descending order
+    let mut order: Vec<_> = (0..y_hat.len()).collect();
+    order.sort_by(|&a, &b| descending_score_order((a, y_hat[a]), (b, y_hat[b])));
 
-    // Sort by scores in a descending order
-    pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
-
-    let mut score_prev = ::std::f32::NAN;
+    let mut score_prev: Option<f32> = None;
     // tp .. true positives, fp .. false positives
     let (mut tp, mut fp) = (0.0f32, 0.0f32);
-    let (mut tps, mut fps) = (vec![], vec![]);
-    for (score, label) in pairs {
+    let (mut tps, mut fps, mut thresholds) = (vec![], vec![], vec![]);
+    for idx in order {
+        let (score, label) = (y_hat[idx], y_true[idx]);
         // `tp` and `fp` from the previous iteration are pushed onto the ROC curve only if
-        // the `score` changed. This avoids errors due to arbitrary classification of points with
-        // identical scores
-        if score != score_prev {
+        // the `score` changed. All points with identical scores are thus classified together,
+        // and contribute the average of their possible orderings to the AUC.
+        if !score_prev.map_or(false, |prev| same_score(prev, score)) {
             tps.push(tp);
             fps.push(fp);
-            score_prev = score;
+            // No points are predicted positive at the initial (0,0) point
+            thresholds.push(score_prev.unwrap_or(f32::INFINITY));
+            score_prev = Some(score);
         }
         tp += label;
         fp += 1.0 - label;
@@ -64,16 +88,18 @@ fn counts_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>) {
     // Push the final point corresponding to the (1,1) ROC coordinates
     tps.push(tp);
     fps.push(fp);
+    thresholds.push(score_prev.unwrap_or(f32::NAN));
 
-    (tps, fps)
+    (tps, fps, thresholds)
 }
 
 
-/// Calculate true positive and false positive rates.
-/// Both vectors are nondecreasing.
-fn rates_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>) {
+/// Calculate true positive and false positive rates, and the corresponding thresholds.
+/// Both rate vectors are nondecreasing.
+fn rates_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>, Vec<f32>) {
 
-    let (mut true_positive_count, mut false_positive_count) = counts_at_score(y_true, y_hat);
+    let (mut true_positive_count, mut false_positive_count, thresholds) =
+        counts_at_score(y_true, y_hat);
 
     let true_positives = true_positive_count[true_positive_count.len() - 1];
     let false_positives = false_positive_count[false_positive_count.len() - 1];
@@ -84,7 +110,7 @@ fn rates_at_score(y_true: &[f32], y_hat: &[f32]) -> (Vec<f32>, Vec<f32>) {
         *fp /= false_positives;
     }
 
-    (true_positive_count, false_positive_count)
+    (true_positive_count, false_positive_count, thresholds)
 }
 
 
@@ -108,18 +134,18 @@ fn trapezoidal(x: &[f32], y: &[f32]) -> f32 {
 }
 
 
-fn check_roc_auc_inputs(y_true: &Array, y_hat: &Array) -> Result<(), &'static str> {
+fn check_roc_auc_inputs(y_true: &Array, y_hat: &Array) -> Result<(), RustlearnError> {
 
     if y_true.cols() != 1 || y_hat.cols() != 1 {
-        return Err("Input array has more than one column.");
+        return Err(RustlearnError::DimensionMismatch("Input array has more than one column."));
     }
 
     if y_true.rows() != y_hat.rows() {
-        return Err("Unequal number of rows");
+        return Err(RustlearnError::DimensionMismatch("Unequal number of rows"));
     }
 
     if y_true.rows() < 1 {
-        return Err("Inputs are empty.");
+        return Err(RustlearnError::EmptyInput("Inputs are empty."));
     }
 
     let mut pos_present = false;
@@ -133,12 +159,15 @@ fn check_roc_auc_inputs(y_true: &Array, y_hat: &Array) -> Result<(), &'static st
             1.0 => {
                 pos_present = true;
             }
-            _ => return Err("Invalid labels: target data is not either 0.0 or 1.0"),
+            _ => {
+                return Err(RustlearnError::InvalidInput("Invalid labels: target data is not \
+                                                         either 0.0 or 1.0"))
+            }
         }
     }
 
     if !pos_present || !neg_present {
-        return Err("Both classes must be present.");
+        return Err(RustlearnError::InvalidInput("Both classes must be present."));
     }
 
     Ok(())
@@ -147,36 +176,446 @@ fn check_roc_auc_inputs(y_true: &Array, y_hat: &Array) -> Result<(), &'static st
 
 /// Compute the ROC AUC score for a binary classification problem.
 ///
+/// Tied scores are handled as in the Mann-Whitney U statistic: a positive-negative
+/// pair with equal scores counts as half a correctly ranked pair. NaN scores are
+/// ranked below all other scores. The result does not depend on the order of the
+/// inputs.
+///
 /// # Failures
 /// Will fail if inputs are illegal:
 ///
 /// - inputs are of unequal length
 /// - both classes are not represented in the input
 /// - inputs are empty
-pub fn roc_auc_score(y_true: &Array, y_hat: &Array) -> Result<f32, &'static str> {
+pub fn roc_auc_score(y_true: &Array, y_hat: &Array) -> Result<f32, RustlearnError> {
 
     try!(check_roc_auc_inputs(y_true, y_hat));
 
-    let (tpr, fpr) = rates_at_score(y_true.data(), y_hat.data());
+    let (tpr, fpr, _) = rates_at_score(y_true.data(), y_hat.data());
 
     Ok(trapezoidal(&fpr, &tpr))
 }
 
 
+/// Compute an approximate ROC AUC score for a binary classification problem
+/// without sorting the scores.
+///
+/// The range of the scores is split into `n_bins` equal-width bins, and the
+/// AUC is computed from per-class histograms of the scores. Scores falling in
+/// the same bin are treated as ties. This takes two linear passes over the
+/// inputs (one to find the range of the scores, one to fill the histograms)
+/// and `O(n_bins)` additional memory.
+///
+/// The absolute error is at most half the fraction of positive-negative pairs
+/// whose scores share a bin; for scores spread over their range, this is
+/// on the order of `1 / n_bins`.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+/// - `n_bins` is zero
+pub fn roc_auc_score_approx(y_true: &Array,
+                            y_hat: &Array,
+                            n_bins: usize)
+                            -> Result<f32, RustlearnError> {
+
+    try!(check_roc_auc_inputs(y_true, y_hat));
+
+    if n_bins == 0 {
+        return Err(RustlearnError::InvalidInput("Number of bins must be positive."));
+    }
+
+    let (min_score, max_score) = y_hat.data()
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY),
+              |(min_score, max_score), &x| (min_score.min(x), max_score.max(x)));
+
+    let mut positive_counts = vec![0u64; n_bins];
+    let mut negative_counts = vec![0u64; n_bins];
+
+    let bin_width = (max_score - min_score) / n_bins as f32;
+
+    for (&y, &score) in y_true.data().iter().zip(y_hat.data().iter()) {
+        let bin = if bin_width > 0.0 {
+            min(((score - min_score) / bin_width) as usize, n_bins - 1)
+        } else {
+            0
+        };
+
+        if y == 1.0 {
+            positive_counts[bin] += 1;
+        } else {
+            negative_counts[bin] += 1;
+        }
+    }
+
+    let num_positives = positive_counts.iter().sum::<u64>() as f64;
+    let num_negatives = negative_counts.iter().sum::<u64>() as f64;
+
+    // Count the positive-negative pairs ranked correctly,
+    // with pairs in the same bin counting as half.
+    let mut negatives_below = 0u64;
+    let mut correct_pairs = 0.0f64;
+
+    for (&positives, &negatives) in positive_counts.iter().zip(negative_counts.iter()) {
+        correct_pairs += positives as f64 * (negatives_below as f64 + negatives as f64 / 2.0);
+        negatives_below += negatives;
+    }
+
+    Ok((correct_pairs / (num_positives * num_negatives)) as f32)
+}
+
+
+/// Compute the ROC curve for a binary classification problem.
+///
+/// Returns a tuple of (false positive rates, true positive rates, thresholds),
+/// ordered by decreasing threshold. The point at index `i` is obtained by
+/// predicting as positive all examples with a score of at least `thresholds[i]`;
+/// examples with tied scores are always classified together.
+/// The curve starts at `(0, 0)`, with an infinite threshold, and ends at `(1, 1)`.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+pub fn roc_curve(y_true: &Array,
+                 y_hat: &Array)
+                 -> Result<(Vec<f32>, Vec<f32>, Vec<f32>), RustlearnError> {
+
+    try!(check_roc_auc_inputs(y_true, y_hat));
+
+    let (tpr, fpr, thresholds) = rates_at_score(y_true.data(), y_hat.data());
+
+    Ok((fpr, tpr, thresholds))
+}
+
+
+/// Compute the cumulative gain curve for a binary classification problem.
+///
+/// Returns a tuple of (fractions of the population, fractions of positives captured),
+/// obtained by targeting examples in the order of decreasing score. The point at index
+/// `i` gives the fraction of all positives found among the fraction of the population
+/// with the highest scores. Examples with tied scores are always targeted together, so
+/// that the curve does not depend on the order of the inputs; NaN scores are targeted last.
+/// The curve starts at `(0, 0)` and ends at `(1, 1)`.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+pub fn cumulative_gain_curve(y_true: &Array,
+                             y_hat: &Array)
+                             -> Result<(Vec<f32>, Vec<f32>), RustlearnError> {
+
+    try!(check_roc_auc_inputs(y_true, y_hat));
+
+    let (tps, fps, _) = counts_at_score(y_true.data(), y_hat.data());
+
+    let num_samples = y_true.rows() as f32;
+    let num_positives = tps[tps.len() - 1];
+
+    let population = tps.iter()
+        .zip(fps.iter())
+        .map(|(tp, fp)| (tp + fp) / num_samples)
+        .collect::<Vec<_>>();
+    let gains = tps.iter().map(|tp| tp / num_positives).collect::<Vec<_>>();
+
+    Ok((population, gains))
+}
+
+
+/// Compute the lift of the `fraction` of the population with the highest scores:
+/// the fraction of positives in that part of the population, divided by the fraction
+/// of positives in the whole population.
+///
+/// The captured fraction of positives is read off the
+/// [cumulative gain curve](fn.cumulative_gain_curve.html), interpolating linearly
+/// between its points; when `fraction` splits a group of examples with tied scores,
+/// the group counts as evenly mixed.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+/// - `fraction` is not in `(0, 1]`
+pub fn lift_at(y_true: &Array, y_hat: &Array, fraction: f32) -> Result<f32, RustlearnError> {
+
+    if !(fraction > 0.0 && fraction <= 1.0) {
+        return Err(RustlearnError::InvalidInput("Fraction must be in (0, 1]."));
+    }
+
+    let (population, gains) = try!(cumulative_gain_curve(y_true, y_hat));
+
+    // The first point past `fraction`; the last point is at a population of 1
+    let idx = population.iter().position(|&x| x >= fraction).unwrap();
+
+    let gain = if population[idx] == fraction {
+        gains[idx]
+    } else {
+        let weight = (fraction - population[idx - 1]) / (population[idx] - population[idx - 1]);
+        gains[idx - 1] + weight * (gains[idx] - gains[idx - 1])
+    };
+
+    Ok(gain / fraction)
+}
+
+
+/// Averaging strategy for multiclass ROC AUC scores.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Average {
+    /// Compute the one-vs-rest AUC of each class separately,
+    /// and take their unweighted mean.
+    Macro,
+    /// Pool the one-vs-rest indicators and scores of all classes,
+    /// and compute a single AUC.
+    Micro,
+}
+
+
+/// Compute the one-vs-rest ROC AUC score for a multiclass classification problem.
+///
+/// Class labels in `y_true` must take values `0.0` to `k - 1`, where `k` is the number
+/// of columns of `y_hat`; the `j`-th column of `y_hat` holds the scores for class `j`,
+/// as in the `decision_function` output of a `OneVsRestWrapper` fit on such labels.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - labels are not valid column indices of `y_hat`
+/// - for macro averaging, any class is not represented in the input
+/// - inputs are empty
+pub fn roc_auc_score_multiclass(y_true: &Array,
+                                y_hat: &Array,
+                                average: Average)
+                                -> Result<f32, RustlearnError> {
+
+    if y_true.cols() != 1 {
+        return Err(RustlearnError::DimensionMismatch("Input array has more than one column."));
+    }
+
+    if y_true.rows() != y_hat.rows() {
+        return Err(RustlearnError::DimensionMismatch("Unequal number of rows"));
+    }
+
+    let num_classes = y_hat.cols();
+
+    for &y in y_true.data() {
+        if y < 0.0 || y.fract() != 0.0 || y as usize >= num_classes {
+            return Err(RustlearnError::InvalidInput("Invalid labels: target data is not a \
+                                                     valid class index."));
+        }
+    }
+
+    let mut indicators = Vec::with_capacity(num_classes);
+    let mut scores = Vec::with_capacity(num_classes);
+
+    for class in 0..num_classes {
+        indicators.push(Array::from(y_true.data()
+            .iter()
+            .map(|&y| if y as usize == class { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>()));
+        scores.push(Array::from((0..y_hat.rows())
+            .map(|row| y_hat.get(row, class))
+            .collect::<Vec<_>>()));
+    }
+
+    match average {
+        Average::Macro => {
+            let mut score = 0.0;
+
+            for (class_true, class_hat) in indicators.iter().zip(scores.iter()) {
+                score += try!(roc_auc_score(class_true, class_hat));
+            }
+
+            Ok(score / num_classes as f32)
+        }
+        Average::Micro => {
+            let pooled_true = indicators.iter()
+                .flat_map(|x| x.data().iter().cloned())
+                .collect::<Vec<_>>();
+            let pooled_hat = scores.iter()
+                .flat_map(|x| x.data().iter().cloned())
+                .collect::<Vec<_>>();
+
+            roc_auc_score(&Array::from(pooled_true), &Array::from(pooled_hat))
+        }
+    }
+}
+
+
+/// Compute the top-k accuracy of multiclass scores: the fraction of rows for which
+/// the true class is among the `k` classes with the highest scores.
+///
+/// Class labels in `y_true` must take values `0.0` to `c - 1`, where `c` is the number
+/// of columns of `y_hat`; the `j`-th column of `y_hat` holds the scores (such as
+/// probabilities) of class `j`. Classes with tied scores are ranked by ascending
+/// class index, and NaN scores are ranked last, so that a tie at the boundary of
+/// the top `k` is resolved deterministically.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - labels are not valid column indices of `y_hat`
+/// - `k` is zero or larger than the number of classes
+/// - inputs are empty
+pub fn top_k_accuracy_score(y_true: &Array,
+                            y_hat: &Array,
+                            k: usize)
+                            -> Result<f32, RustlearnError> {
+
+    if y_true.cols() != 1 {
+        return Err(RustlearnError::DimensionMismatch("Input array has more than one column."));
+    }
+
+    if y_true.rows() != y_hat.rows() {
+        return Err(RustlearnError::DimensionMismatch("Unequal number of rows"));
+    }
+
+    if y_true.rows() == 0 {
+        return Err(RustlearnError::EmptyInput("Inputs are empty."));
+    }
+
+    let num_classes = y_hat.cols();
+
+    if k == 0 || k > num_classes {
+        return Err(RustlearnError::InvalidInput("k must be between one and the number of \
+                                                 classes."));
+    }
+
+    let mut correct = 0;
+
+    for (row, &y) in y_true.data().iter().enumerate() {
+        if y < 0.0 || y.fract() != 0.0 || y as usize >= num_classes {
+            return Err(RustlearnError::InvalidInput("Invalid labels: target data is not a \
+                                                     valid class index."));
+        }
+
+        let true_class = (y as usize, y_hat.get(row, y as usize));
+
+        // The true class is in the top k if fewer than k classes are ranked ahead of it
+        let num_ahead = (0..num_classes)
+            .filter(|&class| {
+                descending_score_order((class, y_hat.get(row, class)), true_class) ==
+                Ordering::Less
+            })
+            .count();
+
+        if num_ahead < k {
+            correct += 1;
+        }
+    }
+
+    Ok(correct as f32 / y_true.rows() as f32)
+}
+
+
+/// Compute the precision-recall curve for a binary classification problem.
+///
+/// Returns a tuple of (precisions, recalls, thresholds), ordered by decreasing
+/// threshold, so that recall is nondecreasing. The point at index `i` is obtained
+/// by predicting as positive all examples with a score of at least `thresholds[i]`;
+/// examples with tied scores are always classified together.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+pub fn precision_recall_curve(y_true: &Array,
+                              y_hat: &Array)
+                              -> Result<(Vec<f32>, Vec<f32>, Vec<f32>), RustlearnError> {
+
+    try!(check_roc_auc_inputs(y_true, y_hat));
+
+    let (true_positive_count, false_positive_count, thresholds) =
+        counts_at_score(y_true.data(), y_hat.data());
+
+    let true_positives = true_positive_count[true_positive_count.len() - 1];
+
+    // Skip the initial point, where nothing is predicted positive
+    // and precision is undefined.
+    let precision = true_positive_count.iter()
+        .zip(false_positive_count.iter())
+        .skip(1)
+        .map(|(tp, fp)| tp / (tp + fp))
+        .collect();
+    let recall = true_positive_count.iter()
+        .skip(1)
+        .map(|tp| tp / true_positives)
+        .collect();
+
+    Ok((precision, recall, thresholds[1..].to_vec()))
+}
+
+
+/// Compute the average precision score for a binary classification problem.
+///
+/// This is the mean of the precisions along the
+/// [precision-recall curve](fn.precision_recall_curve.html),
+/// each weighted by the increase in recall from the previous threshold.
+///
+/// # Failures
+/// Will fail if inputs are illegal:
+///
+/// - inputs are of unequal length
+/// - both classes are not represented in the input
+/// - inputs are empty
+pub fn average_precision_score(y_true: &Array, y_hat: &Array) -> Result<f32, RustlearnError> {
+
+    let (precision, recall, _) = try!(precision_recall_curve(y_true, y_hat));
+
+    let mut prev_recall = 0.0;
+    let mut score = 0.0;
+
+    for (&p, &r) in precision.iter().zip(recall.iter()) {
+        score += (r - prev_recall) * p;
+        prev_recall = r;
+    }
+
+    Ok(score)
+}
+
+
 
 #[cfg(test)]
 mod tests {
 
     use prelude::*;
 
-    use super::{counts_at_score, roc_auc_score, dcg_score, ndcg_score};
+    use std::f32;
+
+    use super::{counts_at_score, roc_auc_score, roc_curve, trapezoidal, dcg_score, ndcg_score,
+                precision_recall_curve, average_precision_score, roc_auc_score_multiclass,
+                roc_auc_score_approx, top_k_accuracy_score, cumulative_gain_curve, lift_at,
+                Average};
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
+
+    use datasets::iris;
+    use ensemble::random_forest::Hyperparameters;
+    use trees::decision_tree;
+
+    use rand::{SeedableRng, StdRng};
 
     #[test]
     fn basic() {
         let y_true = vec![1.0, 1.0, 0.0, 0.0];
         let y_hat = vec![0.5, 0.2, 0.3, -1.0];
 
-        let (x, y) = counts_at_score(&y_true, &y_hat);
+        let (x, y, _) = counts_at_score(&y_true, &y_hat);
 
         let x_expected: Vec<f32> = vec![0.0, 1.0, 1.0, 2.0, 2.0];
         let y_expected: Vec<f32> = vec![0.0, 0.0, 1.0, 1.0, 2.0];
@@ -188,6 +627,156 @@ mod tests {
                       roc_auc_score(&Array::from(y_true), &Array::from(y_hat)).unwrap()));
     }
 
+    #[test]
+    fn test_roc_curve() {
+        let y_true = Array::from(vec![0.0, 1.0, 1.0, 0.0, 1.0]);
+        let y_hat = Array::from(vec![0.1, 0.8, 0.4, 0.4, 0.9]);
+
+        let (fpr, tpr, thresholds) = roc_curve(&y_true, &y_hat).unwrap();
+
+        // The tied 0.4 scores move the curve diagonally in a single step.
+        let fpr_expected = vec![0.0, 0.0, 0.0, 0.5, 1.0];
+        let tpr_expected = vec![0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0, 1.0];
+        let thresholds_expected = vec![f32::INFINITY, 0.9, 0.8, 0.4, 0.1];
+
+        assert!(allclose(&Array::from(fpr.clone()), &Array::from(fpr_expected)));
+        assert!(allclose(&Array::from(tpr.clone()), &Array::from(tpr_expected)));
+        assert!(thresholds == thresholds_expected);
+
+        assert!(close(trapezoidal(&fpr, &tpr),
+                      roc_auc_score(&y_true, &y_hat).unwrap()));
+        assert!(close(0.916666, trapezoidal(&fpr, &tpr)));
+    }
+
+    #[test]
+    fn test_roc_auc_score_approx() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let mut y_true = Vec::new();
+        let mut y_hat = Vec::new();
+
+        for i in 0..5000 {
+            let label = if i % 3 == 0 { 1.0 } else { 0.0 };
+
+            y_true.push(label);
+            y_hat.push(label + normal.ind_sample(&mut rng) as f32);
+        }
+
+        let y_true = Array::from(y_true);
+        let y_hat = Array::from(y_hat);
+
+        let exact = roc_auc_score(&y_true, &y_hat).unwrap();
+
+        for &n_bins in &[10, 100, 1000] {
+            let approx = roc_auc_score_approx(&y_true, &y_hat, n_bins).unwrap();
+
+            println!("Exact AUC {}, approximate with {} bins {}", exact, n_bins, approx);
+
+            assert!((exact - approx).abs() <= 1.0 / n_bins as f32);
+        }
+
+        // Constant scores are all tied
+        let constant = Array::from(vec![0.3; 5000]);
+        assert!(close(0.5, roc_auc_score_approx(&y_true, &constant, 10).unwrap()));
+
+        assert!(roc_auc_score_approx(&y_true, &y_hat, 0).is_err());
+        assert!(roc_auc_score_approx(&Array::ones(5000, 1), &y_hat, 10).is_err());
+    }
+
+    #[test]
+    fn test_roc_curve_invalid() {
+        let y_true = Array::from(vec![1.0, 1.0]);
+        let y_hat = Array::from(vec![0.5, 0.2]);
+
+        assert!(roc_curve(&y_true, &y_hat).is_err());
+    }
+
+    #[test]
+    fn test_precision_recall_curve() {
+        let y_true = Array::from(vec![1.0, 0.0, 1.0, 1.0, 0.0]);
+        let y_hat = Array::from(vec![0.9, 0.8, 0.7, 0.6, 0.5]);
+
+        let (precision, recall, thresholds) = precision_recall_curve(&y_true, &y_hat).unwrap();
+
+        let precision_expected = vec![1.0, 0.5, 2.0 / 3.0, 0.75, 0.6];
+        let recall_expected = vec![1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 1.0, 1.0];
+
+        assert!(allclose(&Array::from(precision), &Array::from(precision_expected)));
+        assert!(allclose(&Array::from(recall.clone()), &Array::from(recall_expected)));
+        assert!(thresholds == vec![0.9, 0.8, 0.7, 0.6, 0.5]);
+
+        for idx in 1..recall.len() {
+            assert!(recall[idx] >= recall[idx - 1]);
+        }
+
+        // (1/3) * 1 + (1/3) * (2/3) + (1/3) * (3/4)
+        assert!(close(0.8055556, average_precision_score(&y_true, &y_hat).unwrap()));
+    }
+
+    #[test]
+    fn test_precision_recall_curve_ties() {
+        let y_true = Array::from(vec![1.0, 0.0, 1.0, 0.0]);
+        let y_hat = Array::from(vec![0.5, 0.5, 0.5, 0.1]);
+
+        let (precision, recall, thresholds) = precision_recall_curve(&y_true, &y_hat).unwrap();
+
+        assert!(allclose(&Array::from(precision), &Array::from(vec![2.0 / 3.0, 0.5])));
+        assert!(allclose(&Array::from(recall), &Array::from(vec![1.0, 1.0])));
+        assert!(thresholds == vec![0.5, 0.1]);
+
+        assert!(close(2.0 / 3.0, average_precision_score(&y_true, &y_hat).unwrap()));
+    }
+
+    #[test]
+    fn test_roc_auc_multiclass() {
+        let y_true = Array::from(vec![0.0, 1.0, 2.0, 0.0, 1.0, 2.0]);
+        let y_hat = Array::from(&vec![vec![0.8, 0.1, 0.1],
+                                      vec![0.2, 0.7, 0.1],
+                                      vec![0.1, 0.2, 0.7],
+                                      vec![0.3, 0.4, 0.3],
+                                      vec![0.1, 0.6, 0.3],
+                                      vec![0.3, 0.3, 0.4]]);
+
+        let macro_auc = roc_auc_score_multiclass(&y_true, &y_hat, Average::Macro).unwrap();
+        let micro_auc = roc_auc_score_multiclass(&y_true, &y_hat, Average::Micro).unwrap();
+
+        // Class 0 has one tied pair out of eight; classes 1 and 2 are perfectly ranked.
+        assert!(close((0.9375 + 1.0 + 1.0) / 3.0, macro_auc));
+        assert!(micro_auc > 0.9 && micro_auc <= 1.0);
+
+        assert!(roc_auc_score_multiclass(&y_true, &Array::zeros(6, 2), Average::Macro)
+            .is_err());
+        assert!(roc_auc_score_multiclass(&y_true, &Array::zeros(5, 3), Average::Micro)
+            .is_err());
+    }
+
+    #[test]
+    fn test_roc_auc_multiclass_iris() {
+        let (X, y) = iris::load_data();
+
+        let mut tree_params = decision_tree::Hyperparameters::new(X.cols());
+        tree_params.min_samples_split(10)
+            .max_features(2)
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut model = Hyperparameters::new(tree_params, 10)
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+
+        model.fit(&X, &y).unwrap();
+
+        let decision = model.decision_function(&X).unwrap();
+
+        let macro_auc = roc_auc_score_multiclass(&y, &decision, Average::Macro).unwrap();
+        let micro_auc = roc_auc_score_multiclass(&y, &decision, Average::Micro).unwrap();
+
+        println!("Macro AUC {}, micro AUC {}", macro_auc, micro_auc);
+
+        assert!(macro_auc > 0.9);
+        assert!(micro_auc > 0.9);
+    }
+
     #[test]
     fn test_dcg_basic() {
         // From: https://gist.github.com/mblondel/7337391#file-letor_metrics-py-L211
@@ -220,6 +809,35 @@ mod tests {
         assert!(close(1.0, ndcg_score(r3, r4, 10)));
     }
 
+    #[test]
+    fn test_roc_auc_ties_deterministic() {
+        let y_true = vec![1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0];
+        let y_hat = vec![0.7, 0.7, 0.3, 0.3, 0.3, 0.1, f32::NAN, f32::NAN];
+
+        // Positives at 0.7, 0.3, 0.3 and NaN against negatives at 0.7, 0.3, 0.1 and NaN:
+        // (0.5 + 1 + 1 + 1) + 2 * (0 + 0.5 + 1 + 1) + (0 + 0 + 0 + 0.5) = 9 of 16 pairs.
+        let expected = 9.0 / 16.0;
+
+        let auc = roc_auc_score(&Array::from(y_true.clone()), &Array::from(y_hat.clone()))
+            .unwrap();
+        assert!(close(expected, auc));
+
+        for _ in 0..10 {
+            assert!(auc ==
+                    roc_auc_score(&Array::from(y_true.clone()), &Array::from(y_hat.clone()))
+                .unwrap());
+        }
+
+        // Permuting the inputs does not change the result
+        let permutation = [7, 5, 3, 1, 6, 4, 2, 0];
+        let permuted_true = permutation.iter().map(|&i| y_true[i]).collect::<Vec<_>>();
+        let permuted_hat = permutation.iter().map(|&i| y_hat[i]).collect::<Vec<_>>();
+
+        assert!(auc ==
+                roc_auc_score(&Array::from(permuted_true), &Array::from(permuted_hat))
+            .unwrap());
+    }
+
     #[test]
     fn basic_repeated() {
         let y_true = vec![1.0, 1.0, 0.0, 0.0];
@@ -228,4 +846,68 @@ mod tests {
         assert!(close(0.75,
                       roc_auc_score(&Array::from(y_true), &Array::from(y_hat)).unwrap()));
     }
+
+    #[test]
+    fn top_k_accuracy() {
+        // The true class is mostly the runner-up
+        let y_true = Array::from(vec![0.0, 1.0, 2.0, 2.0]);
+        let y_hat = Array::from(&vec![vec![0.3, 0.6, 0.1],
+                                      vec![0.5, 0.4, 0.1],
+                                      vec![0.2, 0.3, 0.5],
+                                      vec![0.7, 0.1, 0.2]]);
+
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 1).unwrap() == 0.25);
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 2).unwrap() == 1.0);
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 3).unwrap() == 1.0);
+
+        // Ties at the boundary go to the smaller class index
+        let y_true = Array::from(vec![1.0, 2.0]);
+        let y_hat = Array::from(&vec![vec![0.2, 0.4, 0.4], vec![0.2, 0.4, 0.4]]);
+
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 1).unwrap() == 0.5);
+
+        // NaN scores rank last
+        let y_true = Array::from(vec![0.0]);
+        let y_hat = Array::from(&vec![vec![f32::NAN, 0.1, 0.2]]);
+
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 2).unwrap() == 0.0);
+
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 0).is_err());
+        assert!(top_k_accuracy_score(&y_true, &y_hat, 4).is_err());
+        assert!(top_k_accuracy_score(&Array::from(vec![3.0]), &y_hat, 1).is_err());
+        assert!(top_k_accuracy_score(&Array::from(vec![0.0, 1.0]), &y_hat, 1).is_err());
+    }
+
+    #[test]
+    fn cumulative_gain_and_lift() {
+        // Positives at ranks 1, 3, 4 and 8 of 10
+        let y_true = Array::from(vec![0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0]);
+        let y_hat = Array::from(vec![0.1, 0.3, 0.7, 0.2, 0.8, 0.4, 0.95, 0.9, 0.5, 0.05]);
+
+        let (population, gains) = cumulative_gain_curve(&y_true, &y_hat).unwrap();
+
+        assert!(population.len() == 11);
+        assert!(population[0] == 0.0 && gains[0] == 0.0);
+        assert!(population[10] == 1.0 && gains[10] == 1.0);
+        assert!(gains[2] == 0.5);
+        assert!(gains.windows(2).all(|x| x[0] <= x[1]));
+
+        // The top 20% holds 2 of the 4 positives, against a baseline of 40% positives
+        assert!((lift_at(&y_true, &y_hat, 0.2).unwrap() - 2.5).abs() < 1e-6);
+        assert!((lift_at(&y_true, &y_hat, 1.0).unwrap() - 1.0).abs() < 1e-6);
+
+        // Ties are targeted together, and split evenly at the boundary
+        let y_true = Array::from(vec![1.0, 0.0, 0.0, 1.0]);
+        let y_hat = Array::from(vec![0.5, 0.5, 0.5, 0.5]);
+
+        let (population, gains) = cumulative_gain_curve(&y_true, &y_hat).unwrap();
+
+        assert!(population == vec![0.0, 1.0]);
+        assert!(gains == vec![0.0, 1.0]);
+        assert!((lift_at(&y_true, &y_hat, 0.25).unwrap() - 1.0).abs() < 1e-6);
+
+        assert!(lift_at(&y_true, &y_hat, 0.0).is_err());
+        assert!(lift_at(&y_true, &y_hat, 1.5).is_err());
+        assert!(cumulative_gain_curve(&Array::from(vec![0.0, 0.0]), &Array::zeros(2, 1)).is_err());
+    }
 }
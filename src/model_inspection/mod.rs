@@ -0,0 +1,186 @@
+//! Model-agnostic inspection utilities.
+//!
+//! Permutation importance measures how much a fitted model relies on each
+//! feature: the values of one feature are shuffled across rows, breaking its
+//! relationship with the target, and the resulting drop in the model's score is
+//! recorded. Shuffling is repeated `n_repeats` times per feature, and the mean and
+//! standard deviation of the score drops are reported.
+//!
+//! Because it only uses `predict`, this works for any supervised model, making
+//! importances comparable across model types. The scoring function should be
+//! higher-is-better (such as [accuracy](../metrics/fn.accuracy_score.html)).
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::ensemble::random_forest::Hyperparameters;
+//! use rustlearn::metrics::accuracy_score;
+//! use rustlearn::model_inspection::permutation_importance;
+//! use rustlearn::trees::decision_tree;
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let (X, y) = iris::load_data();
+//!
+//! let tree_params = decision_tree::Hyperparameters::new(X.cols());
+//! let mut model = Hyperparameters::new(tree_params, 10).one_vs_rest();
+//! model.fit(&X, &y).unwrap();
+//!
+//! let mut rng = StdRng::from_seed(&[100]);
+//! let importances = permutation_importance(&model, &X, &y, accuracy_score, 5, &mut rng).unwrap();
+//!
+//! assert!(importances.len() == X.cols());
+//! # }
+//! ```
+
+use rand::{Rng, StdRng};
+
+use prelude::*;
+
+use utils::check_matched_dimensions;
+
+/// Compute the permutation importance of every feature of `X` for a fitted `model`.
+///
+/// Returns a `(mean, standard deviation)` pair of the decrease in `scorer(y, prediction)`
+/// over `n_repeats` shuffles for each feature.
+pub fn permutation_importance<M, S>(
+    model: &M,
+    X: &Array,
+    y: &Array,
+    scorer: S,
+    n_repeats: usize,
+    rng: &mut StdRng,
+) -> Result<Vec<(f32, f32)>, RustlearnError>
+where
+    M: for<'a> SupervisedModel<&'a Array>,
+    S: Fn(&Array, &Array) -> f32,
+{
+    try!(check_matched_dimensions(X, y));
+
+    if n_repeats == 0 {
+        return Err(RustlearnError::InvalidInput(
+            "Number of repeats must be positive.",
+        ));
+    }
+
+    let baseline_score = scorer(y, &try!(model.predict(X)));
+
+    let mut permuted = X.clone();
+    let mut importances = Vec::with_capacity(X.cols());
+
+    for col_idx in 0..X.cols() {
+        let mut column = (0..X.rows())
+            .map(|row_idx| X.get(row_idx, col_idx))
+            .collect::<Vec<_>>();
+        let mut score_drops = Vec::with_capacity(n_repeats);
+
+        for _ in 0..n_repeats {
+            rng.shuffle(&mut column);
+
+            for (row_idx, &value) in column.iter().enumerate() {
+                permuted.set(row_idx, col_idx, value);
+            }
+
+            score_drops.push(baseline_score - scorer(y, &try!(model.predict(&permuted))));
+        }
+
+        // Restore the original column before moving on to the next one
+        for row_idx in 0..X.rows() {
+            permuted.set(row_idx, col_idx, X.get(row_idx, col_idx));
+        }
+
+        let mean = score_drops.iter().sum::<f32>() / n_repeats as f32;
+        let variance =
+            score_drops.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / n_repeats as f32;
+
+        importances.push((mean, variance.sqrt()));
+    }
+
+    Ok(importances)
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use metrics::accuracy_score;
+    use trees::decision_tree;
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_informative_feature_importance() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        // Only the first feature determines the target
+        let rows = (0..200)
+            .map(|_| {
+                vec![
+                    normal.ind_sample(&mut rng) as f32,
+                    normal.ind_sample(&mut rng) as f32,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let X = Array::from(&rows);
+        let y = Array::from(
+            rows.iter()
+                .map(|row| if row[0] > 0.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut tree_params = decision_tree::Hyperparameters::new(X.cols());
+        tree_params
+            .max_features(2)
+            .max_depth(3)
+            .rng(StdRng::from_seed(&[100]));
+        let mut model = tree_params.build();
+        model.fit(&X, &y).unwrap();
+
+        let importances =
+            permutation_importance(&model, &X, &y, accuracy_score, 5, &mut rng).unwrap();
+
+        println!("Importances {:?}", importances);
+
+        let (informative, _) = importances[0];
+        let (noise, _) = importances[1];
+
+        assert!(informative > 0.3);
+        assert!(informative > 10.0 * noise.abs());
+    }
+
+    #[test]
+    fn test_permutation_importance_errors() {
+        let model = decision_tree::Hyperparameters::new(2).build();
+        let mut rng = StdRng::from_seed(&[100]);
+
+        assert!(permutation_importance(
+            &model,
+            &Array::zeros(3, 2),
+            &Array::zeros(2, 1),
+            accuracy_score,
+            5,
+            &mut rng
+        )
+        .is_err());
+        assert!(permutation_importance(
+            &model,
+            &Array::zeros(3, 2),
+            &Array::zeros(3, 1),
+            accuracy_score,
+            5,
+            &mut rng
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Exhaustive hyperparameter search using k-fold cross-validation.
+//!
+//! Every hyperparameter combination in the grid is evaluated on the same
+//! k-fold split of the data. For each combination and fold, a fresh model is
+//! built by a user-supplied closure, fitted on the training part, and scored
+//! on the validation part. The combination with the highest mean validation
+//! score is selected, so the scoring function should be higher-is-better
+//! (such as [accuracy](../../metrics/fn.accuracy_score.html)).
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//! use rustlearn::metrics::accuracy_score;
+//! use rustlearn::model_selection::GridSearchCV;
+//!
+//! let (X, y) = iris::load_data();
+//!
+//! let mut search = GridSearchCV::new(vec![0.1, 0.5, 1.0], 5);
+//!
+//! let (best_learning_rate, best_score) = search
+//!     .fit(
+//!         &X,
+//!         &y,
+//!         |&learning_rate| {
+//!             Hyperparameters::new(X.cols())
+//!                 .learning_rate(learning_rate)
+//!                 .one_vs_rest()
+//!         },
+//!         accuracy_score,
+//!     )
+//!     .unwrap();
+//!
+//! assert!(search.scores().len() == 3);
+//! ```
+
+use rand;
+use rand::StdRng;
+
+use prelude::*;
+
+use cross_validation::CrossValidation;
+use utils::check_matched_dimensions;
+
+/// Grid search over a list of hyperparameter combinations.
+pub struct GridSearchCV<P> {
+    param_grid: Vec<P>,
+    n_folds: usize,
+    scores: Vec<f32>,
+    rng: StdRng,
+}
+
+impl<P: Clone> GridSearchCV<P> {
+    /// Create a new grid search over `param_grid` using
+    /// `n_folds`-fold cross-validation.
+    ///
+    /// # Panics
+    /// Panics if `param_grid` is empty or `n_folds <= 1`.
+    pub fn new(param_grid: Vec<P>, n_folds: usize) -> GridSearchCV<P> {
+        assert!(!param_grid.is_empty(), "Parameter grid must not be empty");
+        assert!(n_folds > 1, "Number of folds must be greater than one");
+
+        GridSearchCV {
+            param_grid: param_grid,
+            n_folds: n_folds,
+            scores: Vec::new(),
+            rng: rand::StdRng::new().unwrap(),
+        }
+    }
+
+    /// Fix the random number generator used for splitting the data.
+    pub fn set_rng(&mut self, rng: StdRng) {
+        self.rng = rng;
+    }
+
+    /// Mean cross-validated scores from the last call to `fit`,
+    /// in the order of the parameter grid.
+    pub fn scores(&self) -> &[f32] {
+        &self.scores
+    }
+
+    /// Evaluate every hyperparameter combination, returning the best
+    /// combination and its mean cross-validated score.
+    ///
+    /// `build_model` constructs an unfitted model from a hyperparameter
+    /// combination; `scorer` takes the true and predicted targets.
+    pub fn fit<M, B, S>(
+        &mut self,
+        X: &Array,
+        y: &Array,
+        build_model: B,
+        scorer: S,
+    ) -> Result<(P, f32), RustlearnError>
+    where
+        M: for<'a> SupervisedModel<&'a Array>,
+        B: Fn(&P) -> M,
+        S: Fn(&Array, &Array) -> f32,
+    {
+        try!(check_matched_dimensions(X, y));
+
+        if self.n_folds >= X.rows() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Number of folds must be smaller than number of samples.",
+            ));
+        }
+
+        let mut cv = CrossValidation::new(X.rows(), self.n_folds);
+        cv.set_rng(self.rng.clone());
+
+        let folds = cv
+            .map(|(train_idx, test_idx)| {
+                (
+                    X.get_rows(&train_idx),
+                    y.get_rows(&train_idx),
+                    X.get_rows(&test_idx),
+                    y.get_rows(&test_idx),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.scores = Vec::with_capacity(self.param_grid.len());
+
+        for params in &self.param_grid {
+            let mut score = 0.0;
+
+            for &(ref X_train, ref y_train, ref X_test, ref y_test) in &folds {
+                let mut model = build_model(params);
+
+                try!(model.fit(X_train, y_train));
+                let prediction = try!(model.predict(X_test));
+
+                score += scorer(y_test, &prediction);
+            }
+
+            self.scores.push(score / folds.len() as f32);
+        }
+
+        let mut best_idx = 0;
+
+        for (idx, &score) in self.scores.iter().enumerate() {
+            if score > self.scores[best_idx] {
+                best_idx = idx;
+            }
+        }
+
+        Ok((self.param_grid[best_idx].clone(), self.scores[best_idx]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use datasets::iris::load_data;
+    use ensemble::random_forest::Hyperparameters;
+    use metrics::accuracy_score;
+    use trees::decision_tree;
+
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_grid_search_random_forest_iris() {
+        let (data, target) = load_data();
+
+        let mut search = GridSearchCV::new(vec![1, 10], 5);
+        search.set_rng(StdRng::from_seed(&[100]));
+
+        let (num_trees, score) = search
+            .fit(
+                &data,
+                &target,
+                |&num_trees| {
+                    let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+                    tree_params
+                        .min_samples_split(10)
+                        .max_features(4)
+                        .rng(StdRng::from_seed(&[100]));
+
+                    Hyperparameters::new(tree_params, num_trees)
+                        .rng(StdRng::from_seed(&[100]))
+                        .one_vs_rest()
+                },
+                accuracy_score,
+            )
+            .unwrap();
+
+        println!("Best num_trees {} accuracy {}", num_trees, score);
+
+        assert!(search.scores().len() == 2);
+        assert!(search.scores().iter().all(|&x| x <= score));
+        assert!(score > 0.9);
+    }
+
+    #[test]
+    fn test_grid_search_mismatched_dimensions() {
+        let mut search = GridSearchCV::new(vec![1, 10], 5);
+
+        let result = search.fit(
+            &Array::zeros(20, 2),
+            &Array::zeros(10, 1),
+            |&num_trees| {
+                let tree_params = decision_tree::Hyperparameters::new(2);
+                Hyperparameters::new(tree_params, num_trees).build()
+            },
+            accuracy_score,
+        );
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Learning curves: training and validation scores as a function
+//! of the size of the training set.
+//!
+//! Learning curves help decide whether collecting more data is worthwhile:
+//! if the validation score is still increasing at the largest training set
+//! size, more data is likely to help. A large gap between the training and
+//! validation scores indicates overfitting.
+//!
+//! The data is split into k folds. For every fold and every training set size,
+//! a fresh model is fitted on a fraction of the training part of the fold, and
+//! scored both on the data it was fitted on and on the validation part.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::metrics::accuracy_score;
+//! use rustlearn::model_selection::learning_curve;
+//! use rustlearn::trees::decision_tree::Hyperparameters;
+//!
+//! let (X, y) = iris::load_data();
+//!
+//! let (train_scores, validation_scores) = learning_curve(
+//!     || Hyperparameters::new(X.cols()).one_vs_rest(),
+//!     &X,
+//!     &y,
+//!     &[0.25, 0.5, 1.0],
+//!     accuracy_score,
+//!     5,
+//!     rand::StdRng::new().unwrap(),
+//! )
+//! .unwrap();
+//!
+//! assert!(train_scores.len() == 3 && validation_scores.len() == 3);
+//! ```
+
+use rand::StdRng;
+
+use prelude::*;
+
+use cross_validation::CrossValidation;
+use utils::check_matched_dimensions;
+
+/// Compute the mean training and validation scores of models fitted on
+/// fractions `train_sizes` of the training data, using `n_folds`-fold
+/// cross-validation.
+///
+/// `build_model` constructs an unfitted model; `scorer` takes the true and
+/// predicted targets. Each element of `train_sizes` must lie in `(0, 1]`, and is
+/// the fraction of the training part of each fold used for fitting; at least
+/// one sample is always used. The folds, and the samples making up each
+/// fraction, are determined by `rng`. Smaller fractions are subsets of
+/// larger ones.
+///
+/// Returns a tuple of (mean training scores, mean validation scores),
+/// in the order of `train_sizes`.
+pub fn learning_curve<M, B, S>(
+    build_model: B,
+    X: &Array,
+    y: &Array,
+    train_sizes: &[f32],
+    scorer: S,
+    n_folds: usize,
+    rng: StdRng,
+) -> Result<(Vec<f32>, Vec<f32>), RustlearnError>
+where
+    M: for<'a> SupervisedModel<&'a Array>,
+    B: Fn() -> M,
+    S: Fn(&Array, &Array) -> f32,
+{
+    try!(check_matched_dimensions(X, y));
+
+    if train_sizes.is_empty() {
+        return Err(RustlearnError::InvalidInput(
+            "At least one training set size is required.",
+        ));
+    }
+
+    if train_sizes.iter().any(|&size| !(size > 0.0 && size <= 1.0)) {
+        return Err(RustlearnError::InvalidInput(
+            "Training set sizes must be in the interval (0, 1].",
+        ));
+    }
+
+    if n_folds <= 1 || n_folds >= X.rows() {
+        return Err(RustlearnError::InvalidHyperparameter(
+            "Number of folds must be greater than one \
+             and smaller than number of samples.",
+        ));
+    }
+
+    let mut cv = CrossValidation::new(X.rows(), n_folds);
+    cv.set_rng(rng);
+
+    let mut train_scores = vec![0.0; train_sizes.len()];
+    let mut validation_scores = vec![0.0; train_sizes.len()];
+
+    for (train_idx, test_idx) in cv {
+        let X_test = X.get_rows(&test_idx);
+        let y_test = y.get_rows(&test_idx);
+
+        for (size_idx, &size) in train_sizes.iter().enumerate() {
+            // Training indices are shuffled, so any prefix is a random subset
+            let num_train = ((size * train_idx.len() as f32).round() as usize)
+                .max(1)
+                .min(train_idx.len());
+            let subset_idx = train_idx[..num_train].to_vec();
+
+            let X_train = X.get_rows(&subset_idx);
+            let y_train = y.get_rows(&subset_idx);
+
+            let mut model = build_model();
+            try!(model.fit(&X_train, &y_train));
+
+            train_scores[size_idx] += scorer(&y_train, &try!(model.predict(&X_train)));
+            validation_scores[size_idx] += scorer(&y_test, &try!(model.predict(&X_test)));
+        }
+    }
+
+    for score in train_scores.iter_mut().chain(validation_scores.iter_mut()) {
+        *score /= n_folds as f32;
+    }
+
+    Ok((train_scores, validation_scores))
+}
+
+#[cfg(test)]
+mod tests {
+    use datasets::iris::load_data;
+    use metrics::accuracy_score;
+    use trees::decision_tree::Hyperparameters;
+
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_learning_curve_iris() {
+        let (data, target) = load_data();
+
+        let train_sizes = [0.05, 0.1, 0.3, 0.6, 1.0];
+
+        let (train_scores, validation_scores) = learning_curve(
+            || {
+                Hyperparameters::new(data.cols())
+                    .max_features(4)
+                    .rng(StdRng::from_seed(&[100]))
+                    .one_vs_rest()
+            },
+            &data,
+            &target,
+            &train_sizes,
+            accuracy_score,
+            5,
+            StdRng::from_seed(&[100]),
+        )
+        .unwrap();
+
+        println!(
+            "Train scores {:?}, validation scores {:?}",
+            train_scores, validation_scores
+        );
+
+        assert!(train_scores.len() == train_sizes.len());
+        assert!(validation_scores.len() == train_sizes.len());
+
+        for window in validation_scores.windows(2) {
+            assert!(window[1] >= window[0] - 0.05);
+        }
+
+        assert!(validation_scores[train_sizes.len() - 1] > validation_scores[0]);
+    }
+
+    #[test]
+    fn test_learning_curve_errors() {
+        let (data, target) = load_data();
+
+        for sizes in &[vec![], vec![0.0, 0.5], vec![0.5, 1.5], vec![-0.5]] {
+            let result = learning_curve(
+                || Hyperparameters::new(data.cols()).one_vs_rest(),
+                &data,
+                &target,
+                sizes,
+                accuracy_score,
+                5,
+                StdRng::from_seed(&[100]),
+            );
+
+            match result {
+                Err(RustlearnError::InvalidInput(_)) => {}
+                _ => panic!("Expected an invalid input error"),
+            }
+        }
+    }
+}
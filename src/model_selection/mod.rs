@@ -0,0 +1,7 @@
+//! Model selection utilities.
+
+pub mod grid_search;
+pub mod learning_curve;
+
+pub use self::grid_search::GridSearchCV;
+pub use self::learning_curve::learning_curve;
@@ -1,4 +1,17 @@
 //! Utilities for mutliclass classifiers.
+//!
+//! The one-vs-rest wrapper fits one binary model per class, each a copy of the
+//! same base model. For randomized base models, such as random forests,
+//! [`OneVsRestWrapper::set_rng`](struct.OneVsRestWrapper.html#method.set_rng)
+//! gives every per-class model its own seed, derived from the wrapper's seed and
+//! the class label. This makes the whole model reproducible while keeping the
+//! per-class models independent of each other. The wrapper's seed is kept when
+//! the model is serialized.
+//!
+//! Fitting a wrapper reseeds the per-class models it creates, so base models
+//! must implement [`SeedableModel`](../traits/trait.SeedableModel.html). Models
+//! without any randomness, including model types defined outside `rustlearn`,
+//! implement it with an empty `impl SeedableModel for MyModel {}`.
 
 use std::cmp::{Ordering, PartialOrd};
 use std::f32;
@@ -8,7 +21,12 @@ use array::dense::*;
 use array::sparse::*;
 use array::traits::*;
 
+use error::RustlearnError;
+use persistence::{check_model_format_version, model_format_version, MODEL_FORMAT_VERSION};
 use traits::*;
+use utils::derive_seed;
+
+use rand::{Rng, StdRng};
 
 use crossbeam;
 
@@ -79,20 +97,129 @@ impl<'a> Iterator for OneVsRest<'a> {
 /// Wraps simple two-class classifiers to implement one-vs-rest strategies.
 #[derive(Serialize, Deserialize)]
 pub struct OneVsRestWrapper<T> {
+    #[serde(
+        default = "model_format_version",
+        deserialize_with = "check_model_format_version"
+    )]
+    version: u32,
     base_model: T,
     models: Vec<T>,
     class_labels: Vec<f32>,
+    #[serde(default)]
+    thresholds: Vec<f32>,
+    #[serde(default)]
+    seed: Option<u64>,
 }
 
 impl<T: Clone> OneVsRestWrapper<T> {
     pub fn new(base_model: T) -> OneVsRestWrapper<T> {
         OneVsRestWrapper {
+            version: MODEL_FORMAT_VERSION,
             base_model: base_model,
             models: Vec::new(),
             class_labels: Vec::new(),
+            thresholds: Vec::new(),
+            seed: None,
+        }
+    }
+
+    pub fn models(&self) -> &Vec<T> {
+        &self.models
+    }
+
+    pub fn class_labels(&self) -> &Vec<f32> {
+        &self.class_labels
+    }
+
+    /// Convert a predicted `value` into an integer class label, failing
+    /// unless it is one of the wrapper's class labels and a non-negative
+    /// whole number.
+    fn class_label_index(&self, value: f32) -> Result<usize, RustlearnError> {
+        let label = value.round();
+
+        if self
+            .class_labels
+            .iter()
+            .any(|&class_label| class_label == value)
+            && label >= 0.0
+            && (value - label).abs() <= 1e-3
+        {
+            Ok(label as usize)
+        } else {
+            Err(RustlearnError::InvalidInput(
+                "Predicted value is not a valid class label.",
+            ))
         }
     }
 
+    /// Set per-class decision thresholds, in the order of `class_labels()`.
+    ///
+    /// When predicting, each class's threshold is subtracted from the
+    /// decision function of its binary classifier, and the class with the
+    /// highest adjusted margin is chosen. Lowering the threshold of a class
+    /// makes it more likely to be predicted, which can improve the recall
+    /// of minority classes. All-zero thresholds, the default, leave the
+    /// predictions unchanged.
+    ///
+    /// Predicting fails if the number of thresholds differs from the number of classes.
+    pub fn set_thresholds(&mut self, thresholds: &[f32]) {
+        self.thresholds = thresholds.to_vec();
+    }
+
+    /// Return the per-class decision thresholds. Empty if no thresholds have been set.
+    pub fn thresholds(&self) -> &[f32] {
+        &self.thresholds
+    }
+
+    /// Pick the class with the highest threshold-adjusted decision function for every row.
+    fn predict_from_decision(&self, decision: &Array) -> Result<Array, RustlearnError> {
+        if !self.thresholds.is_empty() && self.thresholds.len() != self.class_labels.len() {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Number of thresholds must match the number of classes.",
+            ));
+        }
+
+        let mut predictions = Vec::with_capacity(decision.rows());
+
+        for row in decision.iter_rows() {
+            let mut max_value = f32::NEG_INFINITY;
+            let mut max_class = 0;
+
+            for (class_idx, val) in row.iter_nonzero() {
+                let val = val - self.thresholds.get(class_idx).cloned().unwrap_or(0.0);
+
+                if val > max_value {
+                    max_value = val;
+                    max_class = class_idx;
+                }
+            }
+
+            predictions.push(self.class_labels[max_class]);
+        }
+
+        Ok(Array::from(predictions))
+    }
+}
+
+impl<T: Clone + SeedableModel> OneVsRestWrapper<T> {
+    /// Seed the wrapper by drawing a seed from `rng`. See
+    /// [`set_seed`](#method.set_seed) for how the seed is used.
+    pub fn set_rng(&mut self, mut rng: StdRng) {
+        self.set_seed(rng.gen());
+    }
+
+    /// Return a fresh copy of the base model for `class_label`,
+    /// seeded from the wrapper's seed if one has been set.
+    fn new_model(&self, class_label: f32) -> T {
+        let mut model = self.base_model.clone();
+
+        if let Some(seed) = self.seed {
+            model.set_seed(class_seed(seed, class_label));
+        }
+
+        model
+    }
+
     fn get_model(&mut self, class_label: f32) -> &mut T {
         for (idx, label) in self.class_labels.iter().enumerate() {
             if let Some(Ordering::Equal) = class_label.partial_cmp(label) {
@@ -100,8 +227,10 @@ impl<T: Clone> OneVsRestWrapper<T> {
             }
         }
 
+        let model = self.new_model(class_label);
+
         self.class_labels.push(class_label);
-        self.models.push(self.base_model.clone());
+        self.models.push(model);
 
         &mut self.models[self.class_labels.len() - 1]
     }
@@ -120,24 +249,35 @@ impl<T: Clone> OneVsRestWrapper<T> {
             return self.models.remove(idx);
         }
 
-        self.base_model.clone()
+        self.new_model(class_label)
     }
+}
 
-    pub fn models(&self) -> &Vec<T> {
-        &self.models
-    }
+impl<T: Clone + SeedableModel> SeedableModel for OneVsRestWrapper<T> {
+    /// Set the seed from which the seeds of all per-class models are derived.
+    ///
+    /// Every per-class model, including those already created by an earlier
+    /// fit, is given a distinct seed derived from `seed` and its class label.
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
 
-    pub fn class_labels(&self) -> &Vec<f32> {
-        &self.class_labels
+        for (model, &class_label) in self.models.iter_mut().zip(self.class_labels.iter()) {
+            model.set_seed(class_seed(seed, class_label));
+        }
     }
 }
 
+/// Derive the seed of the model for `class_label` from the wrapper's seed.
+fn class_seed(seed: u64, class_label: f32) -> u64 {
+    derive_seed(seed, class_label.to_bits() as u64)
+}
+
 macro_rules! impl_multiclass_supervised_model {
     ($t:ty) => {
-        impl<'a, T: SupervisedModel<&'a $t> + Clone> SupervisedModel<&'a $t>
+        impl<'a, T: SupervisedModel<&'a $t> + Clone + SeedableModel> SupervisedModel<&'a $t>
             for OneVsRestWrapper<T>
         {
-            fn fit(&mut self, X: &'a $t, y: &Array) -> Result<(), &'static str> {
+            fn fit(&mut self, X: &'a $t, y: &Array) -> Result<(), RustlearnError> {
                 for (class_label, binary_target) in OneVsRest::split(y) {
                     let model = self.get_model(class_label);
                     try!(model.fit(X, &binary_target));
@@ -145,7 +285,20 @@ macro_rules! impl_multiclass_supervised_model {
                 Ok(())
             }
 
-            fn decision_function(&self, X: &'a $t) -> Result<Array, &'static str> {
+            fn fit_weighted(
+                &mut self,
+                X: &'a $t,
+                y: &Array,
+                sample_weight: &Array,
+            ) -> Result<(), RustlearnError> {
+                for (class_label, binary_target) in OneVsRest::split(y) {
+                    let model = self.get_model(class_label);
+                    try!(model.fit_weighted(X, &binary_target, sample_weight));
+                }
+                Ok(())
+            }
+
+            fn decision_function(&self, X: &'a $t) -> Result<Array, RustlearnError> {
                 let mut out = Array::zeros(X.rows(), self.class_labels.len());
 
                 for (col_idx, model) in self.models.iter().enumerate() {
@@ -158,25 +311,22 @@ macro_rules! impl_multiclass_supervised_model {
                 Ok(out)
             }
 
-            fn predict(&self, X: &'a $t) -> Result<Array, &'static str> {
+            fn predict(&self, X: &'a $t) -> Result<Array, RustlearnError> {
                 let decision = try!(self.decision_function(X));
-                let mut predictions = Vec::with_capacity(X.rows());
-
-                for row in decision.iter_rows() {
-                    let mut max_value = f32::NEG_INFINITY;
-                    let mut max_class = 0;
 
-                    for (class_idx, val) in row.iter_nonzero() {
-                        if val > max_value {
-                            max_value = val;
-                            max_class = class_idx;
-                        }
-                    }
+                self.predict_from_decision(&decision)
+            }
 
-                    predictions.push(self.class_labels[max_class]);
-                }
+            /// Predict integer class labels, checking every predicted label
+            /// against the wrapper's class labels.
+            fn predict_labels(&self, X: &'a $t) -> Result<Vec<usize>, RustlearnError> {
+                let prediction = try!(self.predict(X));
 
-                Ok(Array::from(predictions))
+                prediction
+                    .data()
+                    .iter()
+                    .map(|&value| self.class_label_index(value))
+                    .collect()
             }
         }
     };
@@ -191,7 +341,7 @@ macro_rules! impl_multiclass_parallel_predict {
                 &self,
                 X: &'a $t,
                 num_threads: usize,
-            ) -> Result<Array, &'static str> {
+            ) -> Result<Array, RustlearnError> {
                 let mut out = Array::zeros(X.rows(), self.class_labels.len());
 
                 let numbered_models = self.models.iter().enumerate().collect::<Vec<_>>();
@@ -224,25 +374,10 @@ macro_rules! impl_multiclass_parallel_predict {
                 &self,
                 X: &'a $t,
                 num_threads: usize,
-            ) -> Result<Array, &'static str> {
+            ) -> Result<Array, RustlearnError> {
                 let decision = try!(self.decision_function_parallel(X, num_threads));
-                let mut predictions = Vec::with_capacity(X.rows());
 
-                for row in decision.iter_rows() {
-                    let mut max_value = f32::NEG_INFINITY;
-                    let mut max_class = 0;
-
-                    for (class_idx, val) in row.iter_nonzero() {
-                        if val > max_value {
-                            max_value = val;
-                            max_class = class_idx;
-                        }
-                    }
-
-                    predictions.push(self.class_labels[max_class]);
-                }
-
-                Ok(Array::from(predictions))
+                self.predict_from_decision(&decision)
             }
         }
     };
@@ -250,15 +385,15 @@ macro_rules! impl_multiclass_parallel_predict {
 
 macro_rules! impl_multiclass_parallel_supervised {
     ($t:ty) => {
-        impl<'a, T: SupervisedModel<&'a $t> + Clone + Sync + Send> ParallelSupervisedModel<&'a $t>
-            for OneVsRestWrapper<T>
+        impl<'a, T: SupervisedModel<&'a $t> + Clone + SeedableModel + Sync + Send>
+            ParallelSupervisedModel<&'a $t> for OneVsRestWrapper<T>
         {
             fn fit_parallel(
                 &mut self,
                 X: &'a $t,
                 y: &Array,
                 num_threads: usize,
-            ) -> Result<(), &'static str> {
+            ) -> Result<(), RustlearnError> {
                 let mut ovr = OneVsRest::split(y);
 
                 loop {
@@ -309,3 +444,185 @@ impl_multiclass_parallel_predict!(SparseColumnArray);
 impl_multiclass_parallel_supervised!(Array);
 impl_multiclass_parallel_supervised!(SparseRowArray);
 impl_multiclass_parallel_supervised!(SparseColumnArray);
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use super::OneVsRestWrapper;
+
+    use datasets::iris::load_data;
+    use datasets::synthetic::make_classification;
+    use ensemble::random_forest;
+    use linear_models::sgdclassifier::Hyperparameters;
+    use trees::decision_tree;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+    use serde_json;
+
+    fn recall(y: &Array, y_hat: &Array, class_label: f32) -> f32 {
+        let (hits, total) = y
+            .data()
+            .iter()
+            .zip(y_hat.data().iter())
+            .filter(|&(&y, _)| y == class_label)
+            .fold((0, 0), |(hits, total), (_, &y_hat)| {
+                (hits + (y_hat == class_label) as usize, total + 1)
+            });
+
+        hits as f32 / total as f32
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_classification(3000, 5, 3, 2, &mut rng);
+
+        // Keep only one in twenty rows of the minority class
+        let mut minority_count = 0;
+        let indices = (0..X.rows())
+            .filter(|&idx| {
+                if y.get(idx, 0) != 2.0 {
+                    return true;
+                }
+                minority_count += 1;
+                minority_count % 20 == 0
+            })
+            .collect::<Vec<_>>();
+        let (X, y) = (X.get_rows(&indices), y.get_rows(&indices));
+
+        let mut model = Hyperparameters::new(X.cols())
+            .learning_rate(0.05)
+            .one_vs_rest();
+
+        for _ in 0..5 {
+            model.fit(&X, &y).unwrap();
+        }
+
+        let default_prediction = model.predict(&X).unwrap();
+
+        model.set_thresholds(&[0.0, 0.0, 0.0]);
+        assert!(model.predict(&X).unwrap() == default_prediction);
+
+        model.set_thresholds(&[0.0, 0.0, -0.3]);
+        let adjusted_prediction = model.predict(&X).unwrap();
+
+        let default_recall = recall(&y, &default_prediction, 2.0);
+        let adjusted_recall = recall(&y, &adjusted_prediction, 2.0);
+
+        println!(
+            "Minority recall {} with default thresholds, {} with adjusted thresholds",
+            default_recall, adjusted_recall
+        );
+
+        assert!(adjusted_recall > default_recall);
+
+        model.set_thresholds(&[0.0, 0.0]);
+        match model.predict(&X) {
+            Err(RustlearnError::InvalidHyperparameter(_)) => {}
+            _ => panic!("Expected an invalid hyperparameter error"),
+        }
+    }
+
+    #[test]
+    fn test_predict_labels() {
+        let (X, y) = load_data();
+
+        let mut model = decision_tree::Hyperparameters::new(X.cols())
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        model.fit(&X, &y).unwrap();
+
+        let prediction = model.predict(&X).unwrap();
+        let labels = model.predict_labels(&X).unwrap();
+
+        assert!(labels.len() == X.rows());
+        assert!(labels
+            .iter()
+            .zip(prediction.data())
+            .all(|(&label, &value)| label == value.round() as usize));
+        assert!((0..3).all(|class| labels.contains(&class)));
+
+        // Labels that are not class indices cannot be converted
+        for &offset in &[-1.0, 0.5] {
+            let mut model = decision_tree::Hyperparameters::new(X.cols())
+                .rng(StdRng::from_seed(&[100]))
+                .one_vs_rest();
+            model.fit(&X, &y.map(|label| label + offset)).unwrap();
+
+            match model.predict_labels(&X) {
+                Err(RustlearnError::InvalidInput(_)) => {}
+                _ => panic!("Expected an invalid input error"),
+            }
+        }
+
+        // Whole numbers other than the class labels are rejected
+        assert!(model.class_label_index(2.0) == Ok(2));
+        assert!(model.class_label_index(3.0).is_err());
+    }
+
+    #[test]
+    fn test_seed_propagation() {
+        let (X, y) = load_data();
+
+        let build = || {
+            random_forest::Hyperparameters::new(decision_tree::Hyperparameters::new(X.cols()), 5)
+                .seed(100)
+                .one_vs_rest()
+        };
+
+        let mut first = build();
+        first.set_rng(StdRng::from_seed(&[100]));
+        first.fit(&X, &y).unwrap();
+
+        let mut second = build();
+        second.set_rng(StdRng::from_seed(&[100]));
+        second.fit_parallel(&X, &y, 2).unwrap();
+
+        assert!(first.decision_function(&X).unwrap() == second.decision_function(&X).unwrap());
+
+        let seeds = first
+            .models()
+            .iter()
+            .map(|model| model.seed())
+            .collect::<Vec<_>>();
+
+        assert!(seeds.len() == 3);
+        assert!(seeds[0] != seeds[1] && seeds[1] != seeds[2] && seeds[0] != seeds[2]);
+
+        // Without a seed, every per-class forest is a copy of the base forest
+        let mut unseeded = build();
+        unseeded.fit(&X, &y).unwrap();
+        assert!(unseeded.models().iter().all(|model| model.seed() == 100));
+
+        // Seeding after fitting reseeds the existing per-class forests
+        unseeded.set_rng(StdRng::from_seed(&[100]));
+        unseeded.fit(&X, &y).unwrap();
+        assert!(unseeded.decision_function(&X).unwrap() == first.decision_function(&X).unwrap());
+
+        let mut reseeded = build();
+        reseeded.set_rng(StdRng::from_seed(&[200]));
+        reseeded.fit(&X, &y).unwrap();
+        assert!(reseeded.decision_function(&X).unwrap() != first.decision_function(&X).unwrap());
+
+        // The wrapper seed survives serialization
+        let mut seeded = build();
+        seeded.set_rng(StdRng::from_seed(&[100]));
+
+        let mut encoded = bincode::deserialize::<OneVsRestWrapper<random_forest::RandomForest>>(
+            &bincode::serialize(&seeded).unwrap(),
+        )
+        .unwrap();
+        encoded.fit(&X, &y).unwrap();
+        assert!(encoded.decision_function(&X).unwrap() == first.decision_function(&X).unwrap());
+
+        let mut json = serde_json::from_str::<OneVsRestWrapper<random_forest::RandomForest>>(
+            &serde_json::to_string(&seeded).unwrap(),
+        )
+        .unwrap();
+        json.fit(&X, &y).unwrap();
+        assert!(json.decision_function(&X).unwrap() == first.decision_function(&X).unwrap());
+    }
+}
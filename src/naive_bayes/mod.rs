@@ -0,0 +1,5 @@
+//! Naive Bayes models.
+
+pub mod multinomial_nb;
+
+pub use self::multinomial_nb::MultinomialNB;
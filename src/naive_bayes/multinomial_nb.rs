@@ -0,0 +1,380 @@
+//! Multinomial naive Bayes.
+//!
+//! A classifier for count data, such as word counts or tf-idf weights of
+//! text documents. Each class is modelled as a multinomial distribution over
+//! the features, whose probabilities are estimated from the per-class feature
+//! totals with additive (Laplace) smoothing controlled by `alpha`. Samples are
+//! assigned to the class with the highest posterior log-probability, combining
+//! the feature log-probabilities with the class priors observed during fitting.
+//!
+//! The model supports multiclass problems natively. Its decision function
+//! (equal to [`predict_proba`](struct.MultinomialNB.html#method.predict_proba))
+//! has one column per class, holding the posterior class probabilities.
+//! Columns are ordered by class label, as returned by `class_labels`.
+//!
+//! Feature values must be non-negative. Dense input is supported by
+//! conversion to a sparse representation.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::naive_bayes::MultinomialNB;
+//!
+//! let (X, y) = iris::load_data();
+//! let X = SparseRowArray::from(&X);
+//!
+//! let mut model = MultinomialNB::new(1.0);
+//!
+//! model.fit(&X, &y).unwrap();
+//!
+//! let prediction = model.predict(&X).unwrap();
+//! ```
+
+use std::cmp::Ordering;
+use std::f32;
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, check_matched_dimensions};
+
+/// A multinomial naive Bayes classifier.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultinomialNB {
+    alpha: f32,
+
+    class_labels: Vec<f32>,
+    class_log_prior: Vec<f32>,
+    feature_log_prob: Array,
+}
+
+impl MultinomialNB {
+    /// Create a new, unfitted classifier with additive
+    /// smoothing parameter `alpha`.
+    pub fn new(alpha: f32) -> MultinomialNB {
+        MultinomialNB {
+            alpha: alpha,
+            class_labels: Vec::new(),
+            class_log_prior: Vec::new(),
+            feature_log_prob: Array::zeros(0, 0),
+        }
+    }
+
+    /// The class labels seen during fitting, in ascending order.
+    pub fn class_labels(&self) -> &[f32] {
+        &self.class_labels
+    }
+
+    /// The logarithm of the prior probability of each class.
+    pub fn class_log_prior(&self) -> &[f32] {
+        &self.class_log_prior
+    }
+
+    /// The smoothed log-probability of each feature given the class,
+    /// with one row per class and one column per feature.
+    pub fn feature_log_prob(&self) -> &Array {
+        &self.feature_log_prob
+    }
+
+    /// Predict the posterior probability of each class.
+    pub fn predict_proba(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        let mut probabilities = try!(self.joint_log_likelihood(X));
+
+        for row_idx in 0..probabilities.rows() {
+            let max_value = (0..probabilities.cols())
+                .map(|class_idx| probabilities.get(row_idx, class_idx))
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            let mut total = 0.0;
+
+            for class_idx in 0..probabilities.cols() {
+                let value = (probabilities.get(row_idx, class_idx) - max_value).exp();
+                probabilities.set(row_idx, class_idx, value);
+                total += value;
+            }
+
+            for class_idx in 0..probabilities.cols() {
+                *probabilities.get_mut(row_idx, class_idx) /= total;
+            }
+        }
+
+        Ok(probabilities)
+    }
+
+    /// Unnormalized posterior log-probabilities, one column per class.
+    fn joint_log_likelihood(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        if self.class_labels.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.feature_log_prob.cols(), X));
+
+        let mut log_likelihood = Array::zeros(X.rows(), self.class_labels.len());
+
+        for (row_idx, row) in X.iter_rows().enumerate() {
+            for (class_idx, &log_prior) in self.class_log_prior.iter().enumerate() {
+                let mut value = log_prior;
+
+                for (feature_idx, count) in row.iter_nonzero() {
+                    value += count * self.feature_log_prob.get(class_idx, feature_idx);
+                }
+
+                log_likelihood.set(row_idx, class_idx, value);
+            }
+        }
+
+        Ok(log_likelihood)
+    }
+}
+
+impl<'a> SupervisedModel<&'a SparseRowArray> for MultinomialNB {
+    fn fit(&mut self, X: &SparseRowArray, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+
+        if y.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Target array has more than one column.",
+            ));
+        }
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit on an empty array."));
+        }
+
+        let mut class_labels = y.data().clone();
+        class_labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        class_labels.dedup();
+
+        let mut class_counts = vec![0.0; class_labels.len()];
+        let mut feature_counts = Array::zeros(class_labels.len(), X.cols());
+
+        for (row, label) in X.iter_rows().zip(y.data().iter()) {
+            let class_idx = class_labels
+                .iter()
+                .position(|class_label| class_label == label)
+                .unwrap();
+
+            class_counts[class_idx] += 1.0;
+
+            for (feature_idx, count) in row.iter_nonzero() {
+                if count < 0.0 {
+                    return Err(RustlearnError::InvalidInput(
+                        "Feature values must be non-negative.",
+                    ));
+                }
+
+                *feature_counts.get_mut(class_idx, feature_idx) += count;
+            }
+        }
+
+        for class_idx in 0..class_labels.len() {
+            let smoothed_total = (0..X.cols())
+                .map(|feature_idx| feature_counts.get(class_idx, feature_idx))
+                .sum::<f32>()
+                + self.alpha * X.cols() as f32;
+
+            for feature_idx in 0..X.cols() {
+                let smoothed_count = feature_counts.get(class_idx, feature_idx) + self.alpha;
+                feature_counts.set(
+                    class_idx,
+                    feature_idx,
+                    (smoothed_count / smoothed_total).ln(),
+                );
+            }
+        }
+
+        self.class_log_prior = class_counts
+            .iter()
+            .map(|&count| (count / X.rows() as f32).ln())
+            .collect();
+        self.feature_log_prob = feature_counts;
+        self.class_labels = class_labels;
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        self.predict_proba(X)
+    }
+
+    fn predict(&self, X: &SparseRowArray) -> Result<Array, RustlearnError> {
+        let log_likelihood = try!(self.joint_log_likelihood(X));
+
+        Ok(Array::from(
+            (0..log_likelihood.rows())
+                .map(|row_idx| {
+                    let mut max_value = f32::NEG_INFINITY;
+                    let mut max_class = 0;
+
+                    for class_idx in 0..log_likelihood.cols() {
+                        let value = log_likelihood.get(row_idx, class_idx);
+
+                        if value > max_value {
+                            max_value = value;
+                            max_class = class_idx;
+                        }
+                    }
+
+                    self.class_labels[max_class]
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+impl<'a> SupervisedModel<&'a Array> for MultinomialNB {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        self.fit(&SparseRowArray::from(X), y)
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.decision_function(&SparseRowArray::from(X))
+    }
+
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.predict(&SparseRowArray::from(X))
+    }
+}
+
+impl SeedableModel for MultinomialNB {}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use metrics::accuracy_score;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    #[test]
+    fn test_multinomial_nb_counts() {
+        let X = SparseRowArray::from(&Array::from(&vec![
+            vec![2.0, 0.0, 0.0],
+            vec![1.0, 1.0, 0.0],
+            vec![0.0, 0.0, 3.0],
+        ]));
+        let y = Array::from(vec![1.0, 1.0, 4.0]);
+
+        let mut model = MultinomialNB::new(1.0);
+        model.fit(&X, &y).unwrap();
+
+        assert!(model.class_labels() == &[1.0, 4.0]);
+        assert!(allclose(
+            &Array::from(model.class_log_prior().to_vec()),
+            &Array::from(vec![(2.0f32 / 3.0).ln(), (1.0f32 / 3.0).ln()])
+        ));
+
+        // Class 1 has feature totals [3, 1, 0], class 4 has [0, 0, 3]
+        let expected = Array::from(&vec![
+            vec![
+                (4.0f32 / 7.0).ln(),
+                (2.0f32 / 7.0).ln(),
+                (1.0f32 / 7.0).ln(),
+            ],
+            vec![
+                (1.0f32 / 6.0).ln(),
+                (1.0f32 / 6.0).ln(),
+                (4.0f32 / 6.0).ln(),
+            ],
+        ]);
+        assert!(allclose(model.feature_log_prob(), &expected));
+
+        let test = SparseRowArray::from(&Array::from(&vec![
+            vec![3.0, 0.0, 0.0],
+            vec![0.0, 1.0, 2.0],
+        ]));
+
+        assert!(model.predict(&test).unwrap().data() == &[1.0, 4.0]);
+
+        let probabilities = model.predict_proba(&test).unwrap();
+
+        for row_idx in 0..probabilities.rows() {
+            let total = probabilities.get(row_idx, 0) + probabilities.get(row_idx, 1);
+            assert!((total - 1.0).abs() < 1e-6);
+        }
+
+        assert!(probabilities.get(0, 0) > 0.5);
+        assert!(probabilities.get(1, 1) > 0.5);
+    }
+
+    #[test]
+    fn test_multinomial_nb_iris() {
+        let (data, target) = load_data();
+        let data = SparseRowArray::from(&data);
+
+        let mut test_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+
+            let mut model = MultinomialNB::new(1.0);
+
+            model.fit(&x_train, &y_train).unwrap();
+
+            let test_prediction = model.predict(&x_test).unwrap();
+
+            test_accuracy += accuracy_score(&target.get_rows(&test_idx), &test_prediction);
+        }
+
+        test_accuracy /= no_splits as f32;
+
+        println!("Accuracy {}", test_accuracy);
+
+        assert!(test_accuracy > 0.8);
+    }
+
+    #[test]
+    fn test_dense_matches_sparse() {
+        let (data, target) = load_data();
+
+        let mut model = MultinomialNB::new(0.5);
+        model.fit(&data, &target).unwrap();
+
+        assert!(
+            model.predict(&data).unwrap() == model.predict(&SparseRowArray::from(&data)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let model = MultinomialNB::new(1.0);
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+
+        let mut model = MultinomialNB::new(1.0);
+        let X = Array::from(&vec![vec![1.0, -1.0], vec![0.0, 1.0]]);
+        assert!(model.fit(&X, &Array::from(vec![0.0, 1.0])).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, target) = load_data();
+
+        let mut model = MultinomialNB::new(1.0);
+        model.fit(&data, &target).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: MultinomialNB = bincode::deserialize(&encoded).unwrap();
+
+        assert!(
+            model.predict_proba(&SparseRowArray::from(&data)).unwrap()
+                == decoded.predict_proba(&SparseRowArray::from(&data)).unwrap()
+        );
+    }
+}
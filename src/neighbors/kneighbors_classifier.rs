@@ -0,0 +1,300 @@
+//! A k-nearest-neighbors classifier.
+//!
+//! The model simply stores the training data when fit. To classify a sample,
+//! the Euclidean distances to all training samples are computed, and the
+//! `k` nearest training samples vote on the class. If several classes receive
+//! the same number of votes, the class of the nearest of the tied neighbors wins.
+//!
+//! The model supports multiclass problems natively. Its decision function
+//! (equal to [`predict_proba`](struct.KNeighborsClassifier.html#method.predict_proba))
+//! has one column per class, holding the fraction of the neighbors belonging to it.
+//! Columns are ordered by class label, as returned by `class_labels`.
+//!
+//! Prediction time grows linearly with the size of the training set,
+//! so the model is best suited to small datasets.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::neighbors::KNeighborsClassifier;
+//!
+//! let (X, y) = iris::load_data();
+//!
+//! let mut model = KNeighborsClassifier::new(5);
+//!
+//! model.fit(&X, &y).unwrap();
+//!
+//! let prediction = model.predict(&X).unwrap();
+//! ```
+
+use std::cmp::{min, Ordering};
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, check_matched_dimensions};
+
+/// A k-nearest-neighbors classifier.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KNeighborsClassifier {
+    k: usize,
+
+    X: Array,
+    y: Vec<usize>,
+    class_labels: Vec<f32>,
+}
+
+impl KNeighborsClassifier {
+    /// Create a new, unfitted classifier voting among
+    /// the `k` nearest neighbors.
+    ///
+    /// # Panics
+    /// Panics if `k` is zero.
+    pub fn new(k: usize) -> KNeighborsClassifier {
+        assert!(k > 0, "Number of neighbors must be positive");
+
+        KNeighborsClassifier {
+            k: k,
+            X: Array::zeros(0, 0),
+            y: Vec::new(),
+            class_labels: Vec::new(),
+        }
+    }
+
+    /// The class labels seen during fitting, in ascending order.
+    pub fn class_labels(&self) -> &[f32] {
+        &self.class_labels
+    }
+
+    /// Predict class probabilities as the fraction of
+    /// the `k` nearest neighbors belonging to each class.
+    pub fn predict_proba(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(self.check_fitted(X));
+
+        let mut probabilities = Array::zeros(X.rows(), self.class_labels.len());
+
+        for row_idx in 0..X.rows() {
+            let neighbors = self.nearest_neighbors(X, row_idx);
+            let weight = 1.0 / neighbors.len() as f32;
+
+            for &neighbor_idx in &neighbors {
+                *probabilities.get_mut(row_idx, self.y[neighbor_idx]) += weight;
+            }
+        }
+
+        Ok(probabilities)
+    }
+
+    fn check_fitted(&self, X: &Array) -> Result<(), RustlearnError> {
+        if self.X.rows() == 0 {
+            return Err(RustlearnError::NotFitted(
+                "Model must be fit before predicting",
+            ));
+        }
+
+        check_data_dimensionality(self.X.cols(), X)
+    }
+
+    /// Indices of the training samples nearest to the `row_idx`-th row of `X`,
+    /// nearest first.
+    fn nearest_neighbors(&self, X: &Array, row_idx: usize) -> Vec<usize> {
+        let mut distances = (0..self.X.rows())
+            .map(|train_idx| {
+                let distance = (0..X.cols())
+                    .map(|col_idx| {
+                        (X.get(row_idx, col_idx) - self.X.get(train_idx, col_idx)).powi(2)
+                    })
+                    .sum::<f32>();
+
+                (distance, train_idx)
+            })
+            .collect::<Vec<_>>();
+
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        distances
+            .iter()
+            .take(min(self.k, distances.len()))
+            .map(|&(_, train_idx)| train_idx)
+            .collect()
+    }
+}
+
+impl<'a> SupervisedModel<&'a Array> for KNeighborsClassifier {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_matched_dimensions(X, y));
+
+        if y.cols() != 1 {
+            return Err(RustlearnError::DimensionMismatch(
+                "Target array has more than one column.",
+            ));
+        }
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit on an empty array."));
+        }
+
+        let mut class_labels = y.data().clone();
+        class_labels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        class_labels.dedup();
+
+        self.y = y
+            .data()
+            .iter()
+            .map(|label| {
+                class_labels
+                    .iter()
+                    .position(|class_label| class_label == label)
+                    .unwrap()
+            })
+            .collect();
+        self.class_labels = class_labels;
+        self.X = X.clone();
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.predict_proba(X)
+    }
+
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(self.check_fitted(X));
+
+        let mut predictions = Vec::with_capacity(X.rows());
+        let mut votes = vec![0; self.class_labels.len()];
+
+        for row_idx in 0..X.rows() {
+            let neighbors = self.nearest_neighbors(X, row_idx);
+
+            for vote in votes.iter_mut() {
+                *vote = 0;
+            }
+
+            for &neighbor_idx in &neighbors {
+                votes[self.y[neighbor_idx]] += 1;
+            }
+
+            let max_votes = *votes.iter().max().unwrap();
+
+            // Break ties in favour of the nearest neighbor
+            let class_idx = neighbors
+                .iter()
+                .map(|&neighbor_idx| self.y[neighbor_idx])
+                .find(|&class_idx| votes[class_idx] == max_votes)
+                .unwrap();
+
+            predictions.push(self.class_labels[class_idx]);
+        }
+
+        Ok(Array::from(predictions))
+    }
+}
+
+impl SeedableModel for KNeighborsClassifier {}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use metrics::accuracy_score;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    #[test]
+    fn test_knn_iris() {
+        let (data, target) = load_data();
+
+        let mut test_accuracy = 0.0;
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+
+            let mut model = KNeighborsClassifier::new(5);
+
+            model.fit(&x_train, &y_train).unwrap();
+
+            let test_prediction = model.predict(&x_test).unwrap();
+
+            test_accuracy += accuracy_score(&target.get_rows(&test_idx), &test_prediction);
+        }
+
+        test_accuracy /= no_splits as f32;
+
+        println!("Accuracy {}", test_accuracy);
+
+        assert!(test_accuracy > 0.9);
+    }
+
+    #[test]
+    fn test_tie_breaking() {
+        let X = Array::from(vec![0.0, 1.5, 2.0, 3.0]);
+        let y = Array::from(vec![2.0, 1.0, 2.0, 1.0]);
+
+        let mut model = KNeighborsClassifier::new(4);
+        model.fit(&X, &y).unwrap();
+
+        // Two votes each, the nearest neighbor decides
+        let prediction = model.predict(&Array::from(vec![1.4, 0.4])).unwrap();
+
+        assert!(prediction.data() == &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_predict_proba() {
+        let X = Array::from(vec![0.0, 1.0, 2.0, 10.0, 11.0]);
+        let y = Array::from(vec![0.0, 0.0, 3.0, 3.0, 3.0]);
+
+        let mut model = KNeighborsClassifier::new(3);
+        model.fit(&X, &y).unwrap();
+
+        let probabilities = model.predict_proba(&Array::from(vec![0.5, 10.5])).unwrap();
+
+        assert!(model.class_labels() == &[0.0, 3.0]);
+        assert!(allclose(
+            &probabilities,
+            &Array::from(&vec![vec![2.0 / 3.0, 1.0 / 3.0], vec![0.0, 1.0]])
+        ));
+        assert!(model.predict(&Array::from(vec![0.5, 10.5])).unwrap().data() == &[0.0, 3.0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let model = KNeighborsClassifier::new(3);
+
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+
+        let mut model = KNeighborsClassifier::new(3);
+        model.fit(&Array::zeros(5, 2), &Array::zeros(5, 1)).unwrap();
+
+        assert!(model.predict(&Array::zeros(2, 3)).is_err());
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, target) = load_data();
+
+        let mut model = KNeighborsClassifier::new(5);
+        model.fit(&data, &target).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: KNeighborsClassifier = bincode::deserialize(&encoded).unwrap();
+
+        assert!(model.predict(&data).unwrap() == decoded.predict(&data).unwrap());
+    }
+}
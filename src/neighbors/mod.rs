@@ -0,0 +1,5 @@
+//! Nearest-neighbor models.
+
+pub mod kneighbors_classifier;
+
+pub use self::kneighbors_classifier::KNeighborsClassifier;
@@ -0,0 +1,274 @@
+//! Saving and loading models.
+//!
+//! Thin wrappers around the `serde` support of all `rustlearn` models,
+//! writing either a compact `bincode` or a human-readable JSON representation
+//! to a file.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::ensemble::random_forest::{Hyperparameters, RandomForest};
+//! use rustlearn::multiclass::OneVsRestWrapper;
+//! use rustlearn::persistence::{load_bincode, save_bincode};
+//! use rustlearn::trees::decision_tree;
+//!
+//! let (X, y) = iris::load_data();
+//!
+//! let tree_params = decision_tree::Hyperparameters::new(X.cols());
+//! let mut model = Hyperparameters::new(tree_params, 10).one_vs_rest();
+//!
+//! model.fit(&X, &y).unwrap();
+//!
+//! save_bincode(&model, "model.bin").unwrap();
+//! let model: OneVsRestWrapper<RandomForest> = load_bincode("model.bin").unwrap();
+//! ```
+//!
+//! # Format versioning
+//!
+//! Random forests, one-vs-rest wrappers, and SGD classifiers store the
+//! [`MODEL_FORMAT_VERSION`](constant.MODEL_FORMAT_VERSION.html) they were
+//! serialized with. Deserializing a model stored with a different version
+//! fails with an error instead of misinterpreting its fields. JSON payloads
+//! written before versioning was introduced carry no version and are read as
+//! the current version, as their fields are matched by name.
+
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Write};
+
+use bincode;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json;
+
+/// Version of the serialized model format.
+///
+/// Bumped whenever a change to a versioned model struct makes
+/// previously serialized models incompatible.
+pub const MODEL_FORMAT_VERSION: u32 = 6;
+
+/// Default format version of deserialized models that do not store one.
+pub fn model_format_version() -> u32 {
+    MODEL_FORMAT_VERSION
+}
+
+/// Deserialize a stored model format version, failing if it differs
+/// from [`MODEL_FORMAT_VERSION`](constant.MODEL_FORMAT_VERSION.html).
+pub fn check_model_format_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let version = try!(u32::deserialize(deserializer));
+
+    if version == MODEL_FORMAT_VERSION {
+        Ok(version)
+    } else {
+        Err(D::Error::custom(format!(
+            "incompatible model format version {}, expected version {}",
+            version, MODEL_FORMAT_VERSION
+        )))
+    }
+}
+
+fn bincode_to_io_error(error: bincode::Error) -> io::Error {
+    match *error {
+        bincode::ErrorKind::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+/// Serialize `model` to the file at `path` using `bincode`.
+///
+/// The file is created if it does not exist, and truncated if it does.
+pub fn save_bincode<T: Serialize>(model: &T, path: &str) -> io::Result<()> {
+    let mut writer = BufWriter::new(try!(File::create(path)));
+
+    try!(bincode::serialize_into(&mut writer, model).map_err(bincode_to_io_error));
+
+    writer.flush()
+}
+
+/// Deserialize a model previously saved with
+/// [`save_bincode`](fn.save_bincode.html) from the file at `path`.
+pub fn load_bincode<T: DeserializeOwned>(path: &str) -> io::Result<T> {
+    let reader = BufReader::new(try!(File::open(path)));
+
+    bincode::deserialize_from(reader).map_err(bincode_to_io_error)
+}
+
+/// Serialize `model` to the file at `path` as JSON.
+///
+/// The file is created if it does not exist, and truncated if it does.
+pub fn save_json<T: Serialize>(model: &T, path: &str) -> io::Result<()> {
+    let mut writer = BufWriter::new(try!(File::create(path)));
+
+    try!(serde_json::to_writer(&mut writer, model));
+
+    writer.flush()
+}
+
+/// Deserialize a model previously saved with
+/// [`save_json`](fn.save_json.html) from the file at `path`.
+pub fn load_json<T: DeserializeOwned>(path: &str) -> io::Result<T> {
+    let reader = BufReader::new(try!(File::open(path)));
+
+    Ok(try!(serde_json::from_reader(reader)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::remove_file;
+
+    use prelude::*;
+
+    use datasets::iris::load_data;
+    use ensemble::random_forest::{Hyperparameters, RandomForest};
+    use linear_models::sgdclassifier::{self, SGDClassifier};
+    use multiclass::OneVsRestWrapper;
+    use trees::decision_tree;
+
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    fn fit_iris_forest() -> (Array, OneVsRestWrapper<RandomForest>) {
+        let (data, target) = load_data();
+
+        let mut tree_params = decision_tree::Hyperparameters::new(data.cols());
+        tree_params
+            .min_samples_split(10)
+            .max_features(4)
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut model = Hyperparameters::new(tree_params, 10)
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+
+        model.fit(&data, &target).unwrap();
+
+        (data, model)
+    }
+
+    #[test]
+    fn save_load_bincode() {
+        let (data, model) = fit_iris_forest();
+
+        let mut path = env::temp_dir();
+        path.push("rustlearn_save_load_bincode.bin");
+        let path = path.to_str().unwrap();
+
+        save_bincode(&model, path).unwrap();
+        let decoded: OneVsRestWrapper<RandomForest> = load_bincode(path).unwrap();
+        remove_file(path).unwrap();
+
+        let expected = model.predict(&data).unwrap();
+        let actual = decoded.predict(&data).unwrap();
+
+        assert!(expected.data() == actual.data());
+    }
+
+    #[test]
+    fn save_load_json() {
+        let (data, model) = fit_iris_forest();
+
+        let mut path = env::temp_dir();
+        path.push("rustlearn_save_load_json.json");
+        let path = path.to_str().unwrap();
+
+        save_json(&model, path).unwrap();
+        let decoded: OneVsRestWrapper<RandomForest> = load_json(path).unwrap();
+        remove_file(path).unwrap();
+
+        let expected = model.predict(&data).unwrap();
+        let actual = decoded.predict(&data).unwrap();
+
+        assert!(expected.data() == actual.data());
+    }
+
+    #[test]
+    fn save_load_linear_model() {
+        let (data, target) = load_data();
+
+        let mut model = sgdclassifier::Hyperparameters::new(data.cols())
+            .learning_rate(0.5)
+            .l2_penalty(0.0001)
+            .one_vs_rest();
+
+        for _ in 0..5 {
+            model.fit(&data, &target).unwrap();
+        }
+
+        let mut path = env::temp_dir();
+        path.push("rustlearn_save_load_linear_model.bin");
+        let path = path.to_str().unwrap();
+
+        save_bincode(&model, path).unwrap();
+        let from_bincode: OneVsRestWrapper<SGDClassifier> = load_bincode(path).unwrap();
+
+        save_json(&model, path).unwrap();
+        let from_json: OneVsRestWrapper<SGDClassifier> = load_json(path).unwrap();
+        remove_file(path).unwrap();
+
+        let expected = model.decision_function(&data).unwrap();
+
+        assert!(expected.data() == from_bincode.decision_function(&data).unwrap().data());
+        assert!(expected.data() == from_json.decision_function(&data).unwrap().data());
+    }
+
+    #[test]
+    fn incompatible_version() {
+        let (_, model) = fit_iris_forest();
+
+        let mut payload = serde_json::to_value(&model).unwrap();
+        assert!(payload["version"] == MODEL_FORMAT_VERSION);
+        let decoded: serde_json::Result<OneVsRestWrapper<RandomForest>> =
+            serde_json::from_value(payload.clone());
+        assert!(decoded.is_ok());
+
+        payload["base_model"]["version"] = (MODEL_FORMAT_VERSION + 1).into();
+        let error = serde_json::from_value::<OneVsRestWrapper<RandomForest>>(payload)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("incompatible model format version"));
+
+        let model = sgdclassifier::Hyperparameters::new(4).build();
+        let mut encoded = bincode::serialize(&model).unwrap();
+        assert!(bincode::deserialize::<SGDClassifier>(&encoded).is_ok());
+
+        encoded[0] = encoded[0].wrapping_add(1);
+        assert!(bincode::deserialize::<SGDClassifier>(&encoded).is_err());
+    }
+
+    #[test]
+    fn unversioned_json() {
+        let model = sgdclassifier::Hyperparameters::new(4).build();
+
+        let mut payload = serde_json::to_value(&model).unwrap();
+        payload.as_object_mut().unwrap().remove("version");
+
+        assert!(serde_json::from_value::<SGDClassifier>(payload).is_ok());
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let result: io::Result<RandomForest> = load_bincode("rustlearn_does_not_exist.bin");
+
+        assert!(result.err().unwrap().kind() == io::ErrorKind::NotFound);
+    }
+
+    // Writes to /dev/full fail, but small models fit in the write buffer,
+    // so the error only surfaces when the buffer is flushed.
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn save_write_error() {
+        let model = sgdclassifier::Hyperparameters::new(4).build();
+
+        assert!(save_bincode(&model, "/dev/full").is_err());
+        assert!(save_json(&model, "/dev/full").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+//! Chaining a data transformation with a supervised model.
+//!
+//! A `Pipeline` holds a transformer (such as a
+//! [`StandardScaler`](../preprocessing/standard_scaler/struct.StandardScaler.html))
+//! and a supervised model. Fitting the pipeline fits the transformer on the
+//! training data and then fits the model on the transformed data; prediction
+//! transforms the input using the fitted transformer before passing it to the model.
+//!
+//! Because the transformer is refitted on every call to `fit`, repeated calls
+//! on the same data are equivalent to running multiple epochs of model training.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::linear_models::sgdclassifier::Hyperparameters;
+//! use rustlearn::pipeline::Pipeline;
+//! use rustlearn::preprocessing::StandardScaler;
+//!
+//! let (X, y) = iris::load_data();
+//!
+//! let model = Hyperparameters::new(X.cols())
+//!     .learning_rate(0.5)
+//!     .one_vs_rest();
+//!
+//! let mut pipeline = Pipeline::new(StandardScaler::new(), model);
+//!
+//! pipeline.fit(&X, &y).unwrap();
+//!
+//! let prediction = pipeline.predict(&X).unwrap();
+//! ```
+
+use prelude::*;
+
+/// A transformer followed by a supervised model.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Pipeline<T, M> {
+    transformer: T,
+    model: M,
+}
+
+impl<T, M> Pipeline<T, M> {
+    /// Create a new pipeline from a transformer and a model.
+    pub fn new(transformer: T, model: M) -> Pipeline<T, M> {
+        Pipeline {
+            transformer: transformer,
+            model: model,
+        }
+    }
+
+    /// The transformer step of the pipeline.
+    pub fn transformer(&self) -> &T {
+        &self.transformer
+    }
+
+    /// The model step of the pipeline.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+}
+
+impl<'a, T, M> SupervisedModel<&'a Array> for Pipeline<T, M>
+where
+    T: Transformer<&'a Array>,
+    M: for<'b> SupervisedModel<&'b Array>,
+{
+    fn fit(&mut self, X: &'a Array, y: &Array) -> Result<(), RustlearnError> {
+        let transformed = try!(self.transformer.fit_transform(X));
+        self.model.fit(&transformed, y)
+    }
+
+    fn decision_function(&self, X: &'a Array) -> Result<Array, RustlearnError> {
+        let transformed = try!(self.transformer.transform(X));
+        self.model.decision_function(&transformed)
+    }
+
+    fn predict(&self, X: &'a Array) -> Result<Array, RustlearnError> {
+        let transformed = try!(self.transformer.transform(X));
+        self.model.predict(&transformed)
+    }
+}
+
+impl<T, M: SeedableModel> SeedableModel for Pipeline<T, M> {
+    fn set_seed(&mut self, seed: u64) {
+        self.model.set_seed(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use cross_validation::cross_validation::CrossValidation;
+    use datasets::iris::load_data;
+    use linear_models::sgdclassifier::{Hyperparameters, SGDClassifier};
+    use metrics::accuracy_score;
+    use multiclass::OneVsRestWrapper;
+    use preprocessing::StandardScaler;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    fn build_model(dim: usize) -> OneVsRestWrapper<SGDClassifier> {
+        Hyperparameters::new(dim)
+            .learning_rate(0.5)
+            .l2_penalty(0.0)
+            .l1_penalty(0.0)
+            .one_vs_rest()
+    }
+
+    #[test]
+    fn test_pipeline_iris() {
+        let (data, target) = load_data();
+
+        let num_epochs = 5;
+        let no_splits = 10;
+
+        let mut pipeline_accuracy = 0.0;
+        let mut manual_accuracy = 0.0;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+            let y_test = target.get_rows(&test_idx);
+
+            let mut pipeline = Pipeline::new(StandardScaler::new(), build_model(data.cols()));
+
+            for _ in 0..num_epochs {
+                pipeline.fit(&x_train, &y_train).unwrap();
+            }
+
+            let pipeline_prediction = pipeline.predict(&x_test).unwrap();
+            pipeline_accuracy += accuracy_score(&y_test, &pipeline_prediction);
+
+            let mut scaler = StandardScaler::new();
+            let mut model = build_model(data.cols());
+
+            let x_train_scaled = scaler.fit_transform(&x_train).unwrap();
+
+            for _ in 0..num_epochs {
+                model.fit(&x_train_scaled, &y_train).unwrap();
+            }
+
+            let manual_prediction = model
+                .predict(&scaler.transform(&x_test).unwrap())
+                .unwrap();
+            manual_accuracy += accuracy_score(&y_test, &manual_prediction);
+
+            assert!(allclose(&pipeline_prediction, &manual_prediction));
+        }
+
+        pipeline_accuracy /= no_splits as f32;
+        manual_accuracy /= no_splits as f32;
+
+        println!("Accuracy {}", pipeline_accuracy);
+
+        assert!(pipeline_accuracy == manual_accuracy);
+        assert!(pipeline_accuracy > 0.8);
+    }
+
+    #[test]
+    fn serialization() {
+        let (data, target) = load_data();
+
+        let mut pipeline = Pipeline::new(StandardScaler::new(), build_model(data.cols()));
+        pipeline.fit(&data, &target).unwrap();
+
+        let encoded = bincode::serialize(&pipeline).unwrap();
+        let decoded: Pipeline<StandardScaler, OneVsRestWrapper<SGDClassifier>> =
+            bincode::deserialize(&encoded).unwrap();
+
+        assert!(allclose(
+            &pipeline.predict(&data).unwrap(),
+            &decoded.predict(&data).unwrap()
+        ));
+    }
+}
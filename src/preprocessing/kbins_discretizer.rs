@@ -0,0 +1,280 @@
+//! Feature discretization.
+//!
+//! Bins each column of the data into at most `n_bins` intervals, replacing
+//! every value with the index of the bin it falls into (as an `f32`). Bin edges
+//! are learned per column during fitting, using one of two strategies:
+//!
+//! - `Uniform`: all bins of a column have the same width, spanning the range
+//!   of values seen during fitting.
+//! - `Quantile`: bin edges are the empirical percentiles of the column, so that
+//!   all bins hold roughly the same number of samples.
+//!
+//! Columns with fewer distinct values than bins (or, for the quantile strategy,
+//! with heavily repeated values) are given fewer bins: duplicate edges are
+//! removed, and a constant column is mapped to a single bin. Values outside the
+//! range seen during fitting are assigned to the first or last bin.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::preprocessing::{BinningStrategy, KBinsDiscretizer};
+//!
+//! let (X, _) = iris::load_data();
+//!
+//! let mut discretizer = KBinsDiscretizer::new(5, BinningStrategy::Quantile);
+//! let X_binned = discretizer.fit_transform(&X).unwrap();
+//!
+//! assert!(X_binned.data().iter().all(|&x| x >= 0.0 && x < 5.0));
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, percentile};
+
+/// Strategy used for choosing bin edges.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum BinningStrategy {
+    /// Bins of equal width.
+    Uniform,
+    /// Bins holding equal numbers of samples.
+    Quantile,
+}
+
+/// Discretizes continuous features into bins.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KBinsDiscretizer {
+    n_bins: usize,
+    strategy: BinningStrategy,
+
+    bin_edges: Vec<Vec<f32>>,
+}
+
+impl KBinsDiscretizer {
+    /// Create a new, unfitted discretizer using at most `n_bins` bins per column.
+    ///
+    /// # Panics
+    /// Panics if `n_bins` is zero.
+    pub fn new(n_bins: usize, strategy: BinningStrategy) -> KBinsDiscretizer {
+        assert!(n_bins > 0, "Number of bins must be positive");
+
+        KBinsDiscretizer {
+            n_bins: n_bins,
+            strategy: strategy,
+            bin_edges: Vec::new(),
+        }
+    }
+
+    /// The strategy used for choosing bin edges.
+    pub fn strategy(&self) -> BinningStrategy {
+        self.strategy
+    }
+
+    /// The bin edges of each column learned during fitting, in ascending order.
+    /// A column with `k + 1` edges has `k` bins; a constant column has a single edge.
+    pub fn bin_edges(&self) -> &[Vec<f32>] {
+        &self.bin_edges
+    }
+
+    fn bin_index(edges: &[f32], value: f32) -> usize {
+        if edges.len() < 3 {
+            return 0;
+        }
+
+        // Values equal to an inner edge go to the upper bin
+        let inner_edges = &edges[1..edges.len() - 1];
+        inner_edges
+            .iter()
+            .take_while(|&&edge| edge <= value)
+            .count()
+    }
+}
+
+impl<'a> Transformer<&'a Array> for KBinsDiscretizer {
+    /// Learn the bin edges of every column of `X`.
+    fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit discretizer on an empty array.",
+            ));
+        }
+
+        let mut bin_edges = Vec::with_capacity(X.cols());
+
+        for column in X.iter_columns() {
+            let mut values = column.iter().collect::<Vec<_>>();
+
+            if values.iter().any(|x| !x.is_finite()) {
+                return Err(RustlearnError::InvalidInput(
+                    "Feature values must be finite.",
+                ));
+            }
+
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            let mut edges = match self.strategy {
+                BinningStrategy::Uniform => {
+                    let (min_value, max_value) = (values[0], values[values.len() - 1]);
+                    let width = (max_value - min_value) / self.n_bins as f32;
+
+                    (0..self.n_bins + 1)
+                        .map(|bin| min_value + bin as f32 * width)
+                        .collect::<Vec<_>>()
+                }
+                BinningStrategy::Quantile => (0..self.n_bins + 1)
+                    .map(|bin| percentile(&values, bin as f32 / self.n_bins as f32))
+                    .collect::<Vec<_>>(),
+            };
+
+            // Guard against rounding errors in the last edge
+            edges[self.n_bins] = values[values.len() - 1];
+            edges.dedup();
+
+            bin_edges.push(edges);
+        }
+
+        self.bin_edges = bin_edges;
+
+        Ok(())
+    }
+
+    /// Replace every value of `X` with the index of its bin.
+    fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        if self.bin_edges.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Discretizer must be fit before transforming",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.bin_edges.len(), X));
+
+        let mut transformed = X.clone();
+
+        for row_idx in 0..X.rows() {
+            for (col_idx, edges) in self.bin_edges.iter().enumerate() {
+                let bin = KBinsDiscretizer::bin_index(edges, X.get(row_idx, col_idx));
+                transformed.set(row_idx, col_idx, bin as f32);
+            }
+        }
+
+        Ok(transformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
+    use rand::{SeedableRng, StdRng};
+
+    use super::*;
+
+    #[test]
+    fn test_uniform_bins() {
+        let X = Array::from(&vec![
+            vec![0.0, 5.0],
+            vec![1.0, 5.0],
+            vec![2.5, 5.0],
+            vec![3.9, 5.0],
+            vec![4.0, 5.0],
+        ]);
+
+        let mut discretizer = KBinsDiscretizer::new(4, BinningStrategy::Uniform);
+        let transformed = discretizer.fit_transform(&X).unwrap();
+
+        assert!(discretizer.bin_edges()[0] == vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        // The constant column gets a single bin
+        assert!(discretizer.bin_edges()[1] == vec![5.0]);
+
+        let expected = Array::from(&vec![
+            vec![0.0, 0.0],
+            vec![1.0, 0.0],
+            vec![2.0, 0.0],
+            vec![3.0, 0.0],
+            vec![3.0, 0.0],
+        ]);
+
+        assert!(transformed == expected);
+
+        // Out-of-range values are clipped to the outermost bins
+        let clipped = discretizer
+            .transform(&Array::from(&vec![vec![-10.0, 0.0], vec![10.0, 10.0]]))
+            .unwrap();
+
+        assert!(clipped == Array::from(&vec![vec![0.0, 0.0], vec![3.0, 0.0]]));
+    }
+
+    #[test]
+    fn test_quantile_bins_skewed() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        // Log-normally distributed, heavily right-skewed
+        let X = Array::from(
+            (0..1000)
+                .map(|_| (2.0 * normal.ind_sample(&mut rng) as f32).exp())
+                .collect::<Vec<_>>(),
+        );
+
+        let mut discretizer = KBinsDiscretizer::new(4, BinningStrategy::Quantile);
+        let transformed = discretizer.fit_transform(&X).unwrap();
+
+        let mut counts = vec![0; 4];
+        for &bin in transformed.data() {
+            counts[bin as usize] += 1;
+        }
+
+        println!("Bin counts {:?}", counts);
+
+        for &count in &counts {
+            assert!(count >= 240 && count <= 260);
+        }
+
+        // Uniform bins put nearly everything in the first bin
+        let mut discretizer = KBinsDiscretizer::new(4, BinningStrategy::Uniform);
+        let transformed = discretizer.fit_transform(&X).unwrap();
+
+        assert!(transformed.data().iter().filter(|&&bin| bin == 0.0).count() > 900);
+    }
+
+    #[test]
+    fn test_quantile_few_distinct_values() {
+        let X = Array::from(vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 2.0]);
+
+        let mut discretizer = KBinsDiscretizer::new(5, BinningStrategy::Quantile);
+        let transformed = discretizer.fit_transform(&X).unwrap();
+
+        let edges = &discretizer.bin_edges()[0];
+        assert!(edges.len() < 6);
+        assert!(edges.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Equal values always share a bin, and order is preserved
+        for (&x, &bin) in X.data().iter().zip(transformed.data()) {
+            for (&other_x, &other_bin) in X.data().iter().zip(transformed.data()) {
+                assert!((x == other_x) == (bin == other_bin));
+                assert!(x < other_x || bin >= other_bin);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let discretizer = KBinsDiscretizer::new(3, BinningStrategy::Uniform);
+        assert!(discretizer.transform(&Array::zeros(2, 2)).is_err());
+
+        let mut discretizer = KBinsDiscretizer::new(3, BinningStrategy::Uniform);
+        assert!(discretizer.fit(&Array::zeros(0, 2)).is_err());
+        assert!(discretizer
+            .fit(&Array::from(vec![1.0, ::std::f32::NAN]))
+            .is_err());
+
+        discretizer.fit(&Array::zeros(3, 2)).unwrap();
+        assert!(discretizer.transform(&Array::zeros(2, 3)).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Feature scaling to the unit interval.
+//!
+//! Scales each column of the data to the `[0, 1]` interval, using the
+//! column minima and maxima estimated during fitting. Columns with a single
+//! value are mapped to zero. Values outside the range seen during fitting
+//! are mapped outside the unit interval.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::preprocessing::MinMaxScaler;
+//!
+//! let (X, _) = iris::load_data();
+//!
+//! let mut scaler = MinMaxScaler::new();
+//! let X_scaled = scaler.fit_transform(&X).unwrap();
+//!
+//! assert!(X_scaled.data().iter().all(|&x| x >= 0.0 && x <= 1.0));
+//! ```
+
+use prelude::*;
+
+use utils::check_data_dimensionality;
+
+/// Scales features to the unit interval.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MinMaxScaler {
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+impl MinMaxScaler {
+    /// Create a new, unfitted scaler.
+    pub fn new() -> MinMaxScaler {
+        MinMaxScaler {
+            min: Vec::new(),
+            max: Vec::new(),
+        }
+    }
+
+    /// Per-column minima estimated during fitting.
+    pub fn data_min(&self) -> &[f32] {
+        &self.min
+    }
+
+    /// Per-column maxima estimated during fitting.
+    pub fn data_max(&self) -> &[f32] {
+        &self.max
+    }
+}
+
+impl<'a> Transformer<&'a Array> for MinMaxScaler {
+    /// Estimate the minimum and maximum of every column of `X`.
+    fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit scaler on an empty array.",
+            ));
+        }
+
+        if X.data().iter().any(|x| !x.is_finite()) {
+            return Err(RustlearnError::InvalidInput(
+                "Cannot fit scaler on non-finite values.",
+            ));
+        }
+
+        self.min = X
+            .iter_columns()
+            .map(|column| column.iter().fold(f32::INFINITY, f32::min))
+            .collect();
+        self.max = X
+            .iter_columns()
+            .map(|column| column.iter().fold(f32::NEG_INFINITY, f32::max))
+            .collect();
+
+        Ok(())
+    }
+
+    /// Scale `X` using the estimated minima and maxima.
+    fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        if self.min.is_empty() {
+            return Err(RustlearnError::NotFitted(
+                "Scaler must be fit before transforming",
+            ));
+        }
+
+        try!(check_data_dimensionality(self.min.len(), X));
+
+        let mut transformed = X.clone();
+
+        for row_idx in 0..X.rows() {
+            for (col_idx, (&min, &max)) in self.min.iter().zip(self.max.iter()).enumerate() {
+                let range = if max > min { max - min } else { 1.0 };
+                let value = (X.get(row_idx, col_idx) - min) / range;
+                transformed.set(row_idx, col_idx, value);
+            }
+        }
+
+        Ok(transformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_scaling() {
+        let X = Array::from(&vec![vec![1.0, 5.0], vec![3.0, 5.0], vec![5.0, 5.0]]);
+
+        let mut scaler = MinMaxScaler::new();
+        let transformed = scaler.fit_transform(&X).unwrap();
+
+        assert!(scaler.data_min() == &[1.0, 5.0]);
+        assert!(scaler.data_max() == &[5.0, 5.0]);
+
+        let expected = Array::from(&vec![vec![0.0, 0.0], vec![0.5, 0.0], vec![1.0, 0.0]]);
+
+        assert!(allclose(&transformed, &expected));
+
+        let unseen = scaler
+            .transform(&Array::from(&vec![vec![-1.0, 6.0]]))
+            .unwrap();
+        assert!(allclose(&unseen, &Array::from(&vec![vec![-0.5, 1.0]])));
+    }
+
+    #[test]
+    fn fit_transform() {
+        let X = Array::from(&vec![vec![1.0, 5.0], vec![3.0, 2.0], vec![4.0, 5.0]]);
+
+        let mut scaler = MinMaxScaler::new();
+        let transformed = scaler.fit_transform(&X).unwrap();
+
+        let mut fitted = MinMaxScaler::new();
+        fitted.fit(&X).unwrap();
+
+        assert!(transformed == fitted.transform(&X).unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        let mut scaler = MinMaxScaler::new();
+
+        assert!(scaler.transform(&Array::zeros(3, 2)).is_err());
+        assert!(scaler.fit(&Array::zeros(0, 2)).is_err());
+        assert!(scaler
+            .fit(&Array::from(vec![1.0, ::std::f32::NAN]))
+            .is_err());
+
+        scaler.fit(&Array::zeros(3, 2)).unwrap();
+        assert!(scaler.transform(&Array::zeros(3, 3)).is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Data preprocessing utilities.
+
+pub mod kbins_discretizer;
+pub mod min_max_scaler;
+pub mod one_hot_encoder;
+pub mod standard_scaler;
+
+pub use self::kbins_discretizer::{BinningStrategy, KBinsDiscretizer};
+pub use self::min_max_scaler::MinMaxScaler;
+pub use self::one_hot_encoder::OneHotEncoder;
+pub use self::standard_scaler::StandardScaler;
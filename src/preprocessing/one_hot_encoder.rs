@@ -0,0 +1,284 @@
+//! One-hot encoding of categorical features.
+//!
+//! Expands integer-coded categorical columns into blocks of binary indicator
+//! columns, one per category seen during fitting. The indicator blocks replace
+//! the encoded columns in place: the output keeps the original column order,
+//! with every selected column replaced by its categories in ascending order.
+//! Columns that are not selected for encoding are passed through unchanged.
+//!
+//! Categories not seen during fitting are encoded as an all-zero block by
+//! default; use `set_error_on_unknown` to report them as errors instead.
+//!
+//! Both dense (`transform`) and sparse (`transform_sparse`) outputs are supported.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::preprocessing::OneHotEncoder;
+//!
+//! let X = Array::from(&vec![vec![0.0, 1.5],
+//!                           vec![2.0, -1.0],
+//!                           vec![1.0, 0.5]]);
+//!
+//! let mut encoder = OneHotEncoder::new(vec![0]);
+//! let X_encoded = encoder.fit_transform(&X).unwrap();
+//!
+//! assert!(X_encoded.cols() == 4);
+//! assert!(X_encoded.get(1, 2) == 1.0 && X_encoded.get(1, 3) == -1.0);
+//! ```
+
+use std::cmp::Ordering;
+
+use prelude::*;
+
+use utils::check_data_dimensionality;
+
+/// Encodes integer-coded categorical columns as one-hot indicators.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OneHotEncoder {
+    columns: Vec<usize>,
+    error_on_unknown: bool,
+
+    dim: usize,
+    categories: Vec<Vec<f32>>,
+}
+
+impl OneHotEncoder {
+    /// Create a new, unfitted encoder for the given column indices.
+    pub fn new(columns: Vec<usize>) -> OneHotEncoder {
+        let mut columns = columns;
+        columns.sort();
+        columns.dedup();
+
+        OneHotEncoder {
+            columns: columns,
+            error_on_unknown: false,
+            dim: 0,
+            categories: Vec::new(),
+        }
+    }
+
+    /// Whether categories not seen during fitting cause `transform`
+    /// to return an error, rather than being encoded as all zeros.
+    pub fn set_error_on_unknown(&mut self, error_on_unknown: bool) {
+        self.error_on_unknown = error_on_unknown;
+    }
+
+    /// The indices of the encoded columns, in ascending order.
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// The sorted categories of each encoded column seen during fitting.
+    pub fn categories(&self) -> &[Vec<f32>] {
+        &self.categories
+    }
+
+    /// The number of columns of the transformed data.
+    pub fn output_dim(&self) -> usize {
+        self.dim - self.columns.len() + self.categories.iter().map(|c| c.len()).sum::<usize>()
+    }
+
+    /// Encode `X` into a sparse array.
+    pub fn transform_sparse(&self, X: &Array) -> Result<SparseRowArray, RustlearnError> {
+        try!(self.check_fitted(X));
+
+        let mut transformed = SparseRowArray::zeros(X.rows(), self.output_dim());
+        let mut entries = Vec::with_capacity(self.dim);
+
+        for row_idx in 0..X.rows() {
+            try!(self.encode_row(X, row_idx, &mut entries));
+
+            for &(col_idx, value) in &entries {
+                transformed.set(row_idx, col_idx, value);
+            }
+        }
+
+        Ok(transformed)
+    }
+
+    fn check_fitted(&self, X: &Array) -> Result<(), RustlearnError> {
+        if self.dim == 0 {
+            return Err(RustlearnError::NotFitted(
+                "Encoder must be fit before transforming",
+            ));
+        }
+
+        check_data_dimensionality(self.dim, X)
+    }
+
+    /// Fill `entries` with the output column indices and values
+    /// of the `row_idx`-th row of `X`, in ascending column order.
+    fn encode_row(
+        &self,
+        X: &Array,
+        row_idx: usize,
+        entries: &mut Vec<(usize, f32)>,
+    ) -> Result<(), RustlearnError> {
+        entries.clear();
+
+        let mut encoded = self.columns.iter().zip(self.categories.iter()).peekable();
+        let mut out_idx = 0;
+
+        for col_idx in 0..X.cols() {
+            let value = X.get(row_idx, col_idx);
+
+            if encoded
+                .peek()
+                .map_or(false, |&(&encoded_idx, _)| encoded_idx == col_idx)
+            {
+                let (_, categories) = encoded.next().unwrap();
+
+                match categories.iter().position(|&category| category == value) {
+                    Some(category_idx) => entries.push((out_idx + category_idx, 1.0)),
+                    None if self.error_on_unknown => {
+                        return Err(RustlearnError::InvalidInput(
+                            "Unknown category encountered.",
+                        ));
+                    }
+                    None => {}
+                }
+
+                out_idx += categories.len();
+            } else {
+                entries.push((out_idx, value));
+                out_idx += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Transformer<&'a Array> for OneHotEncoder {
+    /// Learn the categories of every encoded column of `X`.
+    fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit encoder on an empty array.",
+            ));
+        }
+
+        if self.columns.iter().any(|&col_idx| col_idx >= X.cols()) {
+            return Err(RustlearnError::InvalidHyperparameter(
+                "Encoded column index out of bounds.",
+            ));
+        }
+
+        let mut categories = Vec::with_capacity(self.columns.len());
+
+        for &col_idx in &self.columns {
+            let mut values = (0..X.rows())
+                .map(|row_idx| X.get(row_idx, col_idx))
+                .collect::<Vec<_>>();
+
+            if values.iter().any(|x| !x.is_finite() || x.fract() != 0.0) {
+                return Err(RustlearnError::InvalidInput(
+                    "Categorical values must be integers.",
+                ));
+            }
+
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            values.dedup();
+
+            categories.push(values);
+        }
+
+        self.dim = X.cols();
+        self.categories = categories;
+
+        Ok(())
+    }
+
+    /// Encode `X` into a dense array.
+    fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(self.check_fitted(X));
+
+        let mut transformed = Array::zeros(X.rows(), self.output_dim());
+        let mut entries = Vec::with_capacity(self.dim);
+
+        for row_idx in 0..X.rows() {
+            try!(self.encode_row(X, row_idx, &mut entries));
+
+            for &(col_idx, value) in &entries {
+                transformed.set(row_idx, col_idx, value);
+            }
+        }
+
+        Ok(transformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use super::*;
+
+    fn categorical_data() -> Array {
+        Array::from(&vec![
+            vec![3.0, 0.5, 1.0],
+            vec![1.0, -2.0, 0.0],
+            vec![3.0, 1.5, 2.0],
+            vec![2.0, 0.0, 0.0],
+        ])
+    }
+
+    #[test]
+    fn test_one_hot_encoding() {
+        let X = categorical_data();
+
+        let mut encoder = OneHotEncoder::new(vec![2, 0]);
+        let transformed = encoder.fit_transform(&X).unwrap();
+
+        assert!(encoder.columns() == &[0, 2]);
+        assert!(encoder.categories() == &[vec![1.0, 2.0, 3.0], vec![0.0, 1.0, 2.0]]);
+        assert!(encoder.output_dim() == 7);
+
+        let expected = Array::from(&vec![
+            vec![0.0, 0.0, 1.0, 0.5, 0.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0, -2.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 1.5, 0.0, 0.0, 1.0],
+            vec![0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        ]);
+
+        assert!(transformed == expected);
+        assert!(encoder.transform_sparse(&X).unwrap().todense() == expected);
+    }
+
+    #[test]
+    fn test_unknown_categories() {
+        let X = categorical_data();
+        let query = Array::from(&vec![vec![5.0, 1.0, 2.0]]);
+
+        let mut encoder = OneHotEncoder::new(vec![0, 2]);
+        encoder.fit(&X).unwrap();
+
+        let transformed = encoder.transform(&query).unwrap();
+
+        assert!(transformed == Array::from(&vec![vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0]]));
+
+        encoder.set_error_on_unknown(true);
+
+        assert!(encoder.transform(&query).is_err());
+        assert!(encoder.transform_sparse(&query).is_err());
+        assert!(encoder.transform(&X).is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        let encoder = OneHotEncoder::new(vec![0]);
+        assert!(encoder.transform(&Array::zeros(2, 2)).is_err());
+
+        let mut encoder = OneHotEncoder::new(vec![3]);
+        assert!(encoder.fit(&categorical_data()).is_err());
+
+        let mut encoder = OneHotEncoder::new(vec![1]);
+        assert!(encoder.fit(&categorical_data()).is_err());
+
+        let mut encoder = OneHotEncoder::new(vec![0]);
+        encoder.fit(&categorical_data()).unwrap();
+        assert!(encoder.transform(&Array::zeros(2, 2)).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Feature standardization.
+//!
+//! Scales each column of the data to zero mean and unit variance,
+//! using the column means and standard deviations estimated
+//! during fitting. Columns with zero variance are only centered.
+//!
+//! # Examples
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::datasets::iris;
+//! use rustlearn::preprocessing::StandardScaler;
+//!
+//! let (X, _) = iris::load_data();
+//!
+//! let mut scaler = StandardScaler::new();
+//! let X_scaled = scaler.fit_transform(&X).unwrap();
+//!
+//! assert!(X_scaled.rows() == X.rows() && X_scaled.cols() == X.cols());
+//! ```
+//!
+//! # Streaming
+//!
+//! Data too large to fit in memory at once can be fed to the scaler in chunks
+//! of rows using [`StandardScaler::partial_fit`](struct.StandardScaler.html#method.partial_fit),
+//! which updates running column means and variances using Welford's online
+//! algorithm.
+//!
+//! ```
+//! use rustlearn::prelude::*;
+//! use rustlearn::preprocessing::StandardScaler;
+//!
+//! let mut scaler = StandardScaler::new();
+//!
+//! scaler.partial_fit(&Array::from(&vec![vec![1.0], vec![3.0]])).unwrap();
+//! scaler.partial_fit(&Array::from(&vec![vec![5.0]])).unwrap();
+//!
+//! assert!(scaler.mean() == &[3.0]);
+//! ```
+
+use prelude::*;
+
+use utils::check_data_dimensionality;
+
+/// Scales features to zero mean and unit variance.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct StandardScaler {
+    mean: Vec<f32>,
+    std: Vec<f32>,
+    #[serde(default)]
+    n_samples_seen: usize,
+    #[serde(default)]
+    sum_squared_deviations: Vec<f32>,
+}
+
+impl StandardScaler {
+    /// Create a new, unfitted scaler.
+    pub fn new() -> StandardScaler {
+        StandardScaler {
+            mean: Vec::new(),
+            std: Vec::new(),
+            n_samples_seen: 0,
+            sum_squared_deviations: Vec::new(),
+        }
+    }
+
+    /// Update the column means and standard deviations with a chunk of rows `X`.
+    ///
+    /// The statistics are updated using Welford's online algorithm, so that
+    /// fitting a sequence of chunks yields the same statistics as a single `fit`
+    /// on all chunks concatenated, up to floating point error. A scaler fit with
+    /// `fit` can be updated further with `partial_fit`.
+    ///
+    /// Returns an error if `X` is empty, or if it has a different number of
+    /// columns than the data the scaler has already seen. Scalers fitted and
+    /// serialized by versions without `partial_fit` do not record how many
+    /// samples they were fit on and cannot be updated: calling `partial_fit`
+    /// on them returns an error instead of discarding their statistics.
+    pub fn partial_fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit scaler on an empty array."));
+        }
+
+        if self.n_samples_seen == 0 && !self.mean.is_empty() {
+            return Err(RustlearnError::Unsupported("Scaler does not record the number of \
+                                                    samples it was fit on; refit it with `fit`."));
+        }
+
+        if self.n_samples_seen == 0 {
+            self.mean = vec![0.0; X.cols()];
+            self.sum_squared_deviations = vec![0.0; X.cols()];
+        } else {
+            try!(check_data_dimensionality(self.mean.len(), X));
+        }
+
+        for row_idx in 0..X.rows() {
+            self.n_samples_seen += 1;
+            let num_seen = self.n_samples_seen as f32;
+
+            for col_idx in 0..X.cols() {
+                let value = X.get(row_idx, col_idx);
+                let delta = value - self.mean[col_idx];
+
+                self.mean[col_idx] += delta / num_seen;
+                self.sum_squared_deviations[col_idx] += delta * (value - self.mean[col_idx]);
+            }
+        }
+
+        let num_seen = self.n_samples_seen as f32;
+
+        self.std = self.sum_squared_deviations
+            .iter()
+            .map(|&sum| {
+                let variance = sum / num_seen;
+                if variance > 0.0 { variance.sqrt() } else { 1.0 }
+            })
+            .collect();
+
+        Ok(())
+    }
+
+    /// Per-column means estimated during fitting.
+    pub fn mean(&self) -> &[f32] {
+        &self.mean
+    }
+
+    /// Per-column standard deviations estimated during fitting.
+    pub fn std(&self) -> &[f32] {
+        &self.std
+    }
+}
+
+impl<'a> Transformer<&'a Array> for StandardScaler {
+    /// Estimate the mean and standard deviation of every column of `X`.
+    fn fit(&mut self, X: &Array) -> Result<(), RustlearnError> {
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput("Cannot fit scaler on an empty array."));
+        }
+
+        let num_rows = X.rows() as f32;
+
+        self.mean = vec![0.0; X.cols()];
+        self.std = vec![0.0; X.cols()];
+        self.n_samples_seen = X.rows();
+        self.sum_squared_deviations = vec![0.0; X.cols()];
+
+        for (col_idx, column) in X.iter_columns().enumerate() {
+            let mean = column.iter().sum::<f32>() / num_rows;
+            let variance = column.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / num_rows;
+
+            self.mean[col_idx] = mean;
+            self.sum_squared_deviations[col_idx] = variance * num_rows;
+            self.std[col_idx] = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+        }
+
+        Ok(())
+    }
+
+    /// Standardize `X` using the estimated means and standard deviations.
+    fn transform(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(check_data_dimensionality(self.mean.len(), X));
+
+        let mut transformed = X.clone();
+
+        for row_idx in 0..X.rows() {
+            for col_idx in 0..X.cols() {
+                let value = (X.get(row_idx, col_idx) - self.mean[col_idx]) / self.std[col_idx];
+                transformed.set(row_idx, col_idx, value);
+            }
+        }
+
+        Ok(transformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn standardization() {
+        let X = Array::from(&vec![vec![1.0, 5.0], vec![3.0, 5.0], vec![5.0, 5.0]]);
+
+        let mut scaler = StandardScaler::new();
+        let transformed = scaler.fit_transform(&X).unwrap();
+
+        assert!(allclose(&Array::from(scaler.mean().to_vec()), &Array::from(vec![3.0, 5.0])));
+
+        let expected = Array::from(&vec![
+            vec![-1.2247449, 0.0],
+            vec![0.0, 0.0],
+            vec![1.2247449, 0.0],
+        ]);
+
+        assert!(allclose(&transformed, &expected));
+    }
+
+    #[test]
+    fn fit_transform() {
+        let X = Array::from(&vec![vec![1.0, 5.0], vec![3.0, 5.0], vec![5.0, 4.0]]);
+
+        let mut scaler = StandardScaler::new();
+        let transformed = scaler.fit_transform(&X).unwrap();
+
+        let mut fitted = StandardScaler::new();
+        fitted.fit(&X).unwrap();
+
+        assert!(transformed == fitted.transform(&X).unwrap());
+    }
+
+    #[test]
+    fn partial_fit() {
+        let X = Array::from(&vec![
+            vec![1.0, 5.0, -3.0],
+            vec![3.0, 5.0, 0.5],
+            vec![5.0, 5.0, 12.0],
+            vec![-2.0, 5.0, 7.0],
+            vec![8.0, 5.0, 1.0],
+        ]);
+
+        let mut batch = StandardScaler::new();
+        batch.fit(&X).unwrap();
+
+        let mut streaming = StandardScaler::new();
+        streaming.partial_fit(&X.get_rows(&(0..2))).unwrap();
+        streaming.partial_fit(&X.get_rows(&(2..5))).unwrap();
+
+        assert!(allclose(&Array::from(streaming.mean().to_vec()),
+                         &Array::from(batch.mean().to_vec())));
+        assert!(allclose(&Array::from(streaming.std().to_vec()),
+                         &Array::from(batch.std().to_vec())));
+        assert!(allclose(&streaming.transform(&X).unwrap(),
+                         &batch.transform(&X).unwrap()));
+
+        let mut continued = StandardScaler::new();
+        continued.fit(&X.get_rows(&(0..3))).unwrap();
+        continued.partial_fit(&X.get_rows(&(3..5))).unwrap();
+
+        assert!(allclose(&Array::from(continued.mean().to_vec()),
+                         &Array::from(batch.mean().to_vec())));
+        assert!(allclose(&Array::from(continued.std().to_vec()),
+                         &Array::from(batch.std().to_vec())));
+    }
+
+    #[test]
+    fn partial_fit_dimension_mismatch() {
+        let mut scaler = StandardScaler::new();
+        scaler.partial_fit(&Array::zeros(3, 2)).unwrap();
+
+        assert!(scaler.partial_fit(&Array::zeros(3, 3)).is_err());
+        assert!(scaler.partial_fit(&Array::zeros(0, 2)).is_err());
+        assert!(scaler.partial_fit(&Array::zeros(3, 2)).is_ok());
+    }
+
+    #[test]
+    fn partial_fit_without_sample_count() {
+        let serialized = r#"{"mean":[3.0,5.0],"std":[1.0,2.0]}"#;
+        let mut scaler: StandardScaler = ::serde_json::from_str(serialized).unwrap();
+
+        assert!(scaler.partial_fit(&Array::zeros(3, 2)).is_err());
+        assert!(scaler.mean() == &[3.0, 5.0]);
+        assert!(scaler.std() == &[1.0, 2.0]);
+
+        scaler.fit(&Array::zeros(3, 2)).unwrap();
+        assert!(scaler.partial_fit(&Array::zeros(3, 2)).is_ok());
+    }
+
+    #[test]
+    fn transform_dimension_mismatch() {
+        let mut scaler = StandardScaler::new();
+        scaler.fit_transform(&Array::zeros(3, 2)).unwrap();
+
+        assert!(scaler.transform(&Array::zeros(3, 3)).is_err());
+    }
+}
@@ -381,7 +381,11 @@ fn check(problem: *const LibsvmProblem, param: *const LibsvmParameter) -> Result
 }
 
 /// Fit a `libsvm` model.
-pub fn fit<'a, T>(X: &'a T, y: &Array, parameters: &SvmParameter) -> Result<SvmModel, &'static str>
+pub fn fit<'a, T>(
+    X: &'a T,
+    y: &Array,
+    parameters: &SvmParameter,
+) -> Result<SvmModel, RustlearnError>
 where
     T: IndexableMatrix,
     &'a T: RowIterable,
@@ -401,7 +405,9 @@ where
                 // A bit of a horrible out-of-band error reporting,
                 // we should switch the model traits to String errors
                 println!("Libsvm check error: {}", error_str);
-                return Err("Invalid libsvm parameters.");
+                return Err(RustlearnError::InvalidHyperparameter(
+                    "Invalid libsvm parameters.",
+                ));
             }
         };
         svm_train(
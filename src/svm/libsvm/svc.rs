@@ -106,7 +106,7 @@ pub struct SVC {
 macro_rules! impl_supervised_model {
     ($x_type:ty) => {
         impl<'a> SupervisedModel<&'a $x_type> for SVC {
-            fn fit(&mut self, X: &$x_type, y: &Array) -> Result<(), &'static str> {
+            fn fit(&mut self, X: &$x_type, y: &Array) -> Result<(), RustlearnError> {
                 try!(check_data_dimensionality(self.dim, X));
                 try!(check_matched_dimensions(X, y));
 
@@ -117,7 +117,7 @@ macro_rules! impl_supervised_model {
                 Ok(())
             }
 
-            fn decision_function(&self, X: &$x_type) -> Result<Array, &'static str> {
+            fn decision_function(&self, X: &$x_type) -> Result<Array, RustlearnError> {
                 try!(check_data_dimensionality(self.dim, X));
 
                 match self.model {
@@ -125,17 +125,21 @@ macro_rules! impl_supervised_model {
                         let (decision_function, _) = ffi::predict(model, X);
                         Ok(decision_function)
                     }
-                    None => Err("Model must be fit before predicting."),
+                    None => Err(RustlearnError::NotFitted(
+                        "Model must be fit before predicting.",
+                    )),
                 }
             }
 
-            fn predict(&self, X: &$x_type) -> Result<Array, &'static str> {
+            fn predict(&self, X: &$x_type) -> Result<Array, RustlearnError> {
                 match self.model {
                     Some(ref model) => {
                         let (_, predicted_class) = ffi::predict(model, X);
                         Ok(predicted_class)
                     }
-                    None => Err("Model must be fit before predicting."),
+                    None => Err(RustlearnError::NotFitted(
+                        "Model must be fit before predicting.",
+                    )),
                 }
             }
         }
@@ -145,6 +149,8 @@ macro_rules! impl_supervised_model {
 impl_supervised_model!(Array);
 impl_supervised_model!(SparseRowArray);
 
+impl SeedableModel for SVC {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,15 +1,30 @@
 //! Common rustlearn traits.
 
+use std::cmp;
 use std::cmp::Ordering;
 
 use array::dense::*;
+use array::traits::IndexableMatrix;
+use error::RustlearnError;
 
 
 /// Trait describing supervised models.
 pub trait SupervisedModel<T> {
-    fn fit(&mut self, X: T, y: &Array) -> Result<(), &'static str>;
-    fn decision_function(&self, X: T) -> Result<Array, &'static str>;
-    fn predict(&self, x: T) -> Result<Array, &'static str> {
+    fn fit(&mut self, X: T, y: &Array) -> Result<(), RustlearnError>;
+    /// Fit the model, scaling the contribution of each sample by the
+    /// corresponding entry of the `(n_samples, 1)` array `sample_weight`.
+    ///
+    /// Returns an error for models that do not support sample weights.
+    #[allow(unused_variables)]
+    fn fit_weighted(&mut self,
+                    X: T,
+                    y: &Array,
+                    sample_weight: &Array)
+                    -> Result<(), RustlearnError> {
+        Err(RustlearnError::Unsupported("Sample weights are not supported by this model."))
+    }
+    fn decision_function(&self, X: T) -> Result<Array, RustlearnError>;
+    fn predict(&self, x: T) -> Result<Array, RustlearnError> {
 
         let decision_func = try!(self.decision_function(x));
 
@@ -23,13 +38,53 @@ pub trait SupervisedModel<T> {
             })
             .collect::<Vec<f32>>()))
     }
+    /// Predict integer class labels, such as the labels of a multiclass
+    /// `OneVsRestWrapper`, rather than an array of floating point labels.
+    ///
+    /// Returns an error if any prediction is not a non-negative whole number.
+    /// `OneVsRestWrapper` additionally rejects predictions other than its class labels.
+    fn predict_labels(&self, x: T) -> Result<Vec<usize>, RustlearnError> {
+        let prediction = try!(self.predict(x));
+
+        prediction.data()
+            .iter()
+            .map(|&v| {
+                let label = v.round();
+
+                if label >= 0.0 && (v - label).abs() <= 1e-3 {
+                    Ok(label as usize)
+                } else {
+                    Err(RustlearnError::InvalidInput("Predicted value is not a valid class label."))
+                }
+            })
+            .collect()
+    }
+}
+
+
+/// Applies to models capable of scoring a single row of features
+/// given as a slice, without building an `Array`.
+pub trait RowPredict {
+    fn decision_function_row(&self, row: &[f32]) -> Result<f32, RustlearnError>;
+    fn predict_row(&self, row: &[f32]) -> Result<f32, RustlearnError> {
+
+        let decision_func = try!(self.decision_function_row(row));
+
+        match decision_func.partial_cmp(&0.5) {
+            Some(Ordering::Greater) => Ok(1.0),
+            _ => Ok(0.0),
+        }
+    }
 }
 
 
 /// Applies to models capable of making predictions in a parallel fashion.
 pub trait ParallelPredict<T> {
-    fn decision_function_parallel(&self, X: T, num_threads: usize) -> Result<Array, &'static str>;
-    fn predict_parallel(&self, X: T, num_threads: usize) -> Result<Array, &'static str> {
+    fn decision_function_parallel(&self,
+                                  X: T,
+                                  num_threads: usize)
+                                  -> Result<Array, RustlearnError>;
+    fn predict_parallel(&self, X: T, num_threads: usize) -> Result<Array, RustlearnError> {
 
         let decision_func = try!(self.decision_function_parallel(X, num_threads));
 
@@ -46,7 +101,91 @@ pub trait ParallelPredict<T> {
 }
 
 
+/// Applies to models that can compute their decision function on a dense array
+/// a chunk of rows at a time.
+///
+/// This bounds the memory used by intermediate results when scoring very large
+/// arrays to that needed for `chunk_rows` rows; only the output is allocated in
+/// full. The result is the same as that of `decision_function`. All models
+/// supporting dense input implement this trait.
+pub trait ChunkedPredict {
+    fn decision_function_chunked(&self,
+                                 X: &Array,
+                                 chunk_rows: usize)
+                                 -> Result<Array, RustlearnError>;
+}
+
+
+impl<M> ChunkedPredict for M
+    where M: for<'a> SupervisedModel<&'a Array>
+{
+    fn decision_function_chunked(&self,
+                                 X: &Array,
+                                 chunk_rows: usize)
+                                 -> Result<Array, RustlearnError> {
+
+        if chunk_rows == 0 {
+            return Err(RustlearnError::InvalidInput("Chunk size must be positive."));
+        }
+
+        if X.rows() <= chunk_rows || X.cols() == 0 {
+            return self.decision_function(X);
+        }
+
+        let mut output: Option<Array> = None;
+
+        for start in (0..X.rows()).step_by(chunk_rows) {
+            let stop = cmp::min(start + chunk_rows, X.rows());
+            let chunk = try!(self.decision_function(&X.submatrix(start..stop, 0..X.cols())));
+
+            let output = output.get_or_insert_with(|| Array::zeros(X.rows(), chunk.cols()));
+
+            for row_idx in 0..chunk.rows() {
+                for col_idx in 0..chunk.cols() {
+                    output.set(start + row_idx, col_idx, chunk.get(row_idx, col_idx));
+                }
+            }
+        }
+
+        Ok(output.unwrap())
+    }
+}
+
+
 /// Applies to models capable of being trained in a parallel fashion.
 pub trait ParallelSupervisedModel<T> {
-    fn fit_parallel(&mut self, X: T, y: &Array, num_threads: usize) -> Result<(), &'static str>;
+    fn fit_parallel(&mut self, X: T, y: &Array, num_threads: usize) -> Result<(), RustlearnError>;
+}
+
+
+/// Applies to fitted models that can score how much they rely on each input feature.
+pub trait FeatureImportance {
+    /// Return a non-negative importance score for every feature,
+    /// with higher scores for more important features.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError>;
+}
+
+
+/// Applies to models whose randomness can be derived from a single integer seed.
+///
+/// Base models of one-vs-rest wrappers must implement the trait, so that the
+/// wrapper can seed its per-class models. Models without any randomness
+/// implement it using the default method, which ignores the seed.
+pub trait SeedableModel {
+    /// Set the seed from which the randomness of subsequent fits is derived.
+    fn set_seed(&mut self, _seed: u64) {}
+}
+
+
+/// Trait describing unsupervised data transformations, such as feature scaling.
+pub trait Transformer<T: Copy> {
+    /// Estimate the parameters of the transformation from `X`.
+    fn fit(&mut self, X: T) -> Result<(), RustlearnError>;
+    /// Transform `X` using the parameters estimated during fitting.
+    fn transform(&self, X: T) -> Result<Array, RustlearnError>;
+    /// Fit the transformation on `X` and return the transformed `X`.
+    fn fit_transform(&mut self, X: T) -> Result<Array, RustlearnError> {
+        try!(self.fit(X));
+        self.transform(X)
+    }
 }
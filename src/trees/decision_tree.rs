@@ -4,9 +4,45 @@
 //! algorithm for both dense and sparse data. The tree is split by
 //! randomly sampling `max_features` candidate features, then choosing
 //! the best split amongst those features using reduction in Gini impurity.
+//! Candidate features are sampled afresh at every node, rather than once
+//! per tree. The number of candidates can be given as an absolute count,
+//! or derived from the number of features using [`MaxFeatures`](enum.MaxFeatures.html).
 //!
-//! Both binary and numeric features are supported; categorical features
-//! without a clear ordering should be one-hot encoded for best results.
+//! Both binary and numeric features are supported. Features declared categorical
+//! using [`Hyperparameters::categorical_features`](struct.Hyperparameters.html#method.categorical_features)
+//! are split on subsets of their values instead of thresholds: samples whose value
+//! is one of a learned set of categories go to the left child. Other categorical
+//! features without a clear ordering should be one-hot encoded for best results.
+//!
+//! Missing feature values may be encoded as `NaN`. When evaluating a split,
+//! samples with a missing value for the split feature are sent to whichever
+//! child yields the lower impurity; this choice is stored in the tree, and
+//! samples with missing values are routed the same way at prediction time.
+//! If no missing values were seen at a node during fitting, they are routed
+//! to the right child. Splits may also separate the samples with missing values
+//! from all others, so features taking a single value besides `NaN` are still used.
+//!
+//! Samples can be weighted by fitting with `fit_weighted`: each sample then
+//! contributes in proportion to its weight to the impurity of candidate splits
+//! and to the positive class probability of the leaf it ends up in. The
+//! `min_samples_split` hyperparameter still refers to the unweighted number of samples.
+//!
+//! Fitted trees report impurity-based feature importances through the
+//! [`FeatureImportance`](../../traits/trait.FeatureImportance.html) trait: the total
+//! decrease in weighted Gini impurity brought about by splits on each feature,
+//! normalized to sum to one.
+//!
+//! Fully grown trees tend to overfit. Setting `min_impurity_decrease` stops
+//! splitting nodes whose best split barely reduces impurity, while setting `ccp_alpha`
+//! prunes the tree after growing it using minimal cost-complexity pruning, trading
+//! off training impurity against the number of leaves.
+//!
+//! On large dense datasets, setting `max_bins` speeds up fitting by discretizing
+//! every feature into quantile bins and only considering splits at bin boundaries.
+//!
+//! By default, trees are grown depth-first. Setting `max_leaf_nodes` instead grows
+//! the tree best-first, splitting the leaf with the largest decrease in weighted
+//! impurity until the leaf limit is reached.
 //!
 //! The model is specified using [hyperparameters](./struct.Hyperparameters.html)
 //!
@@ -32,6 +68,7 @@
 //! ```
 
 use std::cmp::{min, Ordering};
+use std::collections::BinaryHeap;
 use std::f32;
 use std::usize;
 
@@ -39,7 +76,8 @@ use prelude::*;
 
 use multiclass::OneVsRestWrapper;
 use utils::{
-    check_data_dimensionality, check_matched_dimensions, check_valid_labels, EncodableRng,
+    check_data_dimensionality, check_matched_dimensions, check_row_dimensionality,
+    check_sample_weights, check_valid_labels, EncodableRng,
 };
 
 use rand;
@@ -106,47 +144,69 @@ impl FeatureIndices {
     }
 }
 
+/// Feature values of the samples at a node. Counts and label
+/// totals are sums of the sample weights.
 #[derive(Debug)]
 struct FeatureValues {
-    xy_pairs: Vec<(f32, f32)>,
-    zero_count: usize,
+    /// Triples of feature value, weighted label, and weight.
+    xyw_triples: Vec<(f32, f32, f32)>,
+    has_zero: bool,
+    zero_count: f32,
     zero_y: f32,
-    count: usize,
+    nan_count: f32,
+    nan_y: f32,
+    count: f32,
     total_y: f32,
 }
 
 impl FeatureValues {
     fn with_capacity(capacity: usize) -> FeatureValues {
         FeatureValues {
-            xy_pairs: Vec::with_capacity(capacity),
-            zero_count: 0,
+            xyw_triples: Vec::with_capacity(capacity),
+            has_zero: false,
+            zero_count: 0.0,
             zero_y: 0.0,
-            count: 0,
+            nan_count: 0.0,
+            nan_y: 0.0,
+            count: 0.0,
             total_y: 0.0,
         }
     }
 
-    fn push(&mut self, x: f32, y: f32) {
-        self.count += 1;
+    fn push(&mut self, x: f32, y: f32, weight: f32) {
+        self.push_totals(x, weight * y, weight);
+    }
+
+    /// Add samples sharing the feature value `x`, with a total
+    /// weighted label of `y` and a total weight of `weight`.
+    fn push_totals(&mut self, x: f32, y: f32, weight: f32) {
+        self.count += weight;
         self.total_y += y;
 
-        if x == 0.0 {
+        if x.is_nan() {
+            // Missing values do not take part in finding
+            // the split threshold
+            self.nan_count += weight;
+            self.nan_y += y;
+        } else if x == 0.0 {
             // We need that one zero value
             // for later finding the optimal split
-            if self.zero_count == 0 {
-                self.xy_pairs.push((x, 0.0))
+            if !self.has_zero {
+                self.xyw_triples.push((x, 0.0, 0.0));
+                self.has_zero = true;
             }
 
-            self.zero_count += 1;
+            self.zero_count += weight;
             self.zero_y += y;
         } else {
-            self.xy_pairs.push((x, y))
+            self.xyw_triples.push((x, y, weight))
         }
     }
 
-    fn fill_remaining_zeros(&mut self, remaining_zero_count: usize, remaining_positives: f32) {
-        if self.zero_count == 0 {
-            self.xy_pairs.push((0.0, 0.0));
+    fn fill_remaining_zeros(&mut self, remaining_zero_count: f32, remaining_positives: f32) {
+        if !self.has_zero {
+            self.xyw_triples.push((0.0, 0.0, 0.0));
+            self.has_zero = true;
         }
 
         self.zero_count += remaining_zero_count;
@@ -157,21 +217,25 @@ impl FeatureValues {
     }
 
     fn sort(&mut self) {
-        self.xy_pairs
+        self.xyw_triples
             .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
     }
 
     fn value_bounds(&self) -> (f32, f32) {
-        match self.xy_pairs.len() {
+        match self.xyw_triples.len() {
             0 => (0.0, 0.0),
-            _ => (self.xy_pairs[0].0, self.xy_pairs[self.xy_pairs.len() - 1].0),
+            _ => (
+                self.xyw_triples[0].0,
+                self.xyw_triples[self.xyw_triples.len() - 1].0,
+            ),
         }
     }
 
     fn feature_type(&self) -> FeatureType {
         let (min_x, max_x) = self.value_bounds();
 
-        if min_x == max_x {
+        // A single value can still be split from missing values
+        if self.xyw_triples.is_empty() || (min_x == max_x && self.nan_count == 0.0) {
             FeatureType::Constant
         } else if min_x == 0.0 && max_x == 1.0 {
             FeatureType::Binary
@@ -182,12 +246,15 @@ impl FeatureValues {
 
     fn clear(&mut self) {
         unsafe {
-            self.xy_pairs.set_len(0);
+            self.xyw_triples.set_len(0);
         }
-        self.zero_count = 0;
+        self.has_zero = false;
+        self.zero_count = 0.0;
         self.zero_y = 0.0;
+        self.nan_count = 0.0;
+        self.nan_y = 0.0;
         self.total_y = 0.0;
-        self.count = 0;
+        self.count = 0.0;
     }
 }
 
@@ -198,14 +265,198 @@ enum FeatureType {
     Continuous,
 }
 
+/// Bin index marking missing feature values.
+const MISSING_BIN: u32 = u32::MAX;
+
+/// A dense feature array with every feature discretized
+/// into quantile bins, used for histogram-based split finding.
+struct BinnedArray {
+    rows: usize,
+    /// Bin indices of the feature values, stored column-wise.
+    bins: Vec<u32>,
+    /// The upper edge of every bin of every feature. Edges are
+    /// values of the feature, so they serve as split thresholds.
+    edges: Vec<Vec<f32>>,
+}
+
+impl BinnedArray {
+    /// Discretize the features of `X` into up to `max_bins` bins. Categorical
+    /// features get a bin for every category, so that categories are never merged.
+    fn new(X: &Array, max_bins: usize, categorical_features: &[usize]) -> BinnedArray {
+        let mut bins = Vec::with_capacity(X.rows() * X.cols());
+        let mut edges = Vec::with_capacity(X.cols());
+
+        for (feature_idx, col) in X.iter_columns().enumerate() {
+            let mut values = col.iter().filter(|x| !x.is_nan()).collect::<Vec<_>>();
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+            let feature_edges = if categorical_features.contains(&feature_idx) {
+                BinnedArray::bin_edges(&values, usize::MAX)
+            } else {
+                BinnedArray::bin_edges(&values, max_bins)
+            };
+
+            for value in col.iter() {
+                bins.push(if value.is_nan() {
+                    MISSING_BIN
+                } else {
+                    // The bin of a value is the first bin whose upper edge is not below it
+                    match feature_edges.binary_search_by(|edge| {
+                        edge.partial_cmp(&value).unwrap_or(Ordering::Equal)
+                    }) {
+                        Ok(bin) | Err(bin) => bin as u32,
+                    }
+                });
+            }
+
+            edges.push(feature_edges);
+        }
+
+        BinnedArray {
+            rows: X.rows(),
+            bins: bins,
+            edges: edges,
+        }
+    }
+
+    /// Compute the upper edges of up to `max_bins` quantile bins of the sorted
+    /// non-missing `values` of a feature. Features with at most `max_bins`
+    /// distinct values get a bin for every value.
+    fn bin_edges(values: &[f32], max_bins: usize) -> Vec<f32> {
+        let mut distinct = values.to_vec();
+        distinct.dedup();
+
+        if distinct.len() <= max_bins {
+            return distinct;
+        }
+
+        let mut edges = (1..(max_bins + 1))
+            .map(|bin| values[bin * values.len() / max_bins - 1])
+            .collect::<Vec<_>>();
+        edges.dedup();
+
+        edges
+    }
+
+    fn column(&self, feature_idx: usize) -> &[u32] {
+        &self.bins[feature_idx * self.rows..(feature_idx + 1) * self.rows]
+    }
+
+    /// Return the value representing bin `bin` of feature `feature_idx`.
+    fn bin_value(&self, feature_idx: usize, bin: u32) -> f32 {
+        if bin == MISSING_BIN {
+            f32::NAN
+        } else {
+            self.edges[feature_idx][bin as usize]
+        }
+    }
+}
+
+/// The best split found for a node.
+struct Split {
+    feature: usize,
+    threshold: f32,
+    categories: Option<Vec<f32>>,
+    missing_left: bool,
+    impurity: f32,
+    num_left: usize,
+}
+
+/// A node awaiting expansion during best-first tree growth.
+struct Candidate {
+    node_idx: usize,
+    totals: (f32, f32),
+    indices: Vec<usize>,
+    depth: usize,
+    used_features: Vec<usize>,
+    split: Split,
+    impurity_decrease: f32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    /// Order by impurity decrease, breaking ties
+    /// in favour of nodes created earlier.
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        self.impurity_decrease
+            .partial_cmp(&other.impurity_decrease)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node_idx.cmp(&self.node_idx))
+    }
+}
+
+/// Training data statistics of a node, used for cost-complexity pruning.
+struct PruningNode {
+    totals: (f32, f32),
+    children: Option<(usize, usize)>,
+    pruned: bool,
+}
+
+/// Rules for deriving the number of candidate features considered
+/// at each split from the total number of features.
+///
+/// The resulting number is rounded to the nearest integer, and is always at least 1.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum MaxFeatures {
+    /// The square root of the number of features.
+    Sqrt,
+    /// The base-2 logarithm of the number of features.
+    Log2,
+    /// A fraction of the number of features.
+    Fraction(f32),
+}
+
+impl MaxFeatures {
+    /// Return the number of candidate features for data with `dim` features.
+    pub fn num_features(&self, dim: usize) -> usize {
+        let num_features = match *self {
+            MaxFeatures::Sqrt => (dim as f32).sqrt(),
+            MaxFeatures::Log2 => (dim as f32).log2(),
+            MaxFeatures::Fraction(fraction) => fraction * dim as f32,
+        };
+
+        // The logarithm of zero or one is not positive.
+        if num_features > 1.0 {
+            num_features.round() as usize
+        } else {
+            1
+        }
+    }
+}
+
 /// Hyperparameters for a `DecisionTree` model.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Hyperparameters {
     dim: usize,
 
     max_features: usize,
+    #[serde(default)]
+    max_features_mode: Option<MaxFeatures>,
     min_samples_split: usize,
     max_depth: usize,
+    #[serde(default)]
+    max_leaf_nodes: Option<usize>,
+    #[serde(default)]
+    ccp_alpha: f32,
+    #[serde(default)]
+    min_impurity_decrease: f32,
+    #[serde(default)]
+    max_bins: Option<usize>,
+    #[serde(default)]
+    categorical_features: Vec<usize>,
 
     rng: EncodableRng,
 }
@@ -227,8 +478,14 @@ impl Hyperparameters {
         Hyperparameters {
             dim: dim,
             max_features: (dim as f32).sqrt() as usize,
+            max_features_mode: None,
             min_samples_split: 2,
             max_depth: usize::MAX,
+            max_leaf_nodes: None,
+            ccp_alpha: 0.0,
+            min_impurity_decrease: 0.0,
+            max_bins: None,
+            categorical_features: Vec::new(),
             rng: EncodableRng::new(),
         }
     }
@@ -239,6 +496,28 @@ impl Hyperparameters {
     /// Defaults to `sqrt(self.dim)`
     pub fn max_features(&mut self, max_features: usize) -> &mut Hyperparameters {
         self.max_features = max_features;
+        self.max_features_mode = None;
+        self
+    }
+    /// Set the number of features to be considered when finding the
+    /// best split to a fraction of the number of features, computed when
+    /// the tree is fit. The number is rounded, and is at least 1.
+    ///
+    /// # Panics
+    /// Panics if `fraction` is not in the interval `(0, 1]`.
+    pub fn max_features_fraction(&mut self, fraction: f32) -> &mut Hyperparameters {
+        assert!(
+            fraction > 0.0 && fraction <= 1.0,
+            "Feature fraction must be in the interval (0, 1]"
+        );
+
+        self.max_features_mode(MaxFeatures::Fraction(fraction))
+    }
+    /// Set the rule for deriving the number of features to be considered
+    /// when finding the best split from the number of features.
+    /// This overrides any count set using `max_features`.
+    pub fn max_features_mode(&mut self, mode: MaxFeatures) -> &mut Hyperparameters {
+        self.max_features_mode = Some(mode);
         self
     }
     /// Set the minimum number of samples that must be present
@@ -256,21 +535,113 @@ impl Hyperparameters {
         self.max_depth = max_depth;
         self
     }
+    /// Set the maximum number of leaves of the tree.
+    ///
+    /// When set, the tree is grown best-first: the leaf whose split yields
+    /// the largest decrease in weighted impurity is split next, until the tree
+    /// has `max_leaf_nodes` leaves or no leaf can be split further. Otherwise,
+    /// the tree is grown depth-first without a limit on the number of leaves.
+    pub fn max_leaf_nodes(&mut self, max_leaf_nodes: usize) -> &mut Hyperparameters {
+        self.max_leaf_nodes = Some(max_leaf_nodes);
+        self
+    }
+    /// Set the complexity parameter of minimal cost-complexity pruning.
+    ///
+    /// After the tree is grown, the subtree with the smallest increase
+    /// in total weighted impurity per removed leaf (its effective alpha)
+    /// is repeatedly collapsed into a leaf, for as long as its effective
+    /// alpha is smaller than `ccp_alpha`. Impurities are weighted by the
+    /// fraction of the total sample weight reaching each node.
+    ///
+    /// Defaults to 0.0, which disables pruning.
+    pub fn ccp_alpha(&mut self, ccp_alpha: f32) -> &mut Hyperparameters {
+        self.ccp_alpha = ccp_alpha;
+        self
+    }
+    /// Set the minimum decrease in impurity required to split a node.
+    ///
+    /// A node is only split if the decrease in Gini impurity achieved by its
+    /// best split, weighted by the fraction of the total sample weight reaching
+    /// the node, exceeds `min_impurity_decrease`; otherwise it becomes a leaf.
+    /// This applies in addition to the other stopping criteria, such as
+    /// `min_samples_split` and `max_depth`.
+    ///
+    /// Defaults to 0.0, which accepts all splits.
+    pub fn min_impurity_decrease(&mut self, min_impurity_decrease: f32) -> &mut Hyperparameters {
+        self.min_impurity_decrease = min_impurity_decrease;
+        self
+    }
+    /// Find splits using histograms of up to `max_bins` quantile bins per feature.
+    ///
+    /// When fitting on a dense array, the values of every feature are first
+    /// discretized into bins holding roughly equal numbers of samples, and
+    /// splits are only considered at bin boundaries. The label totals of each
+    /// candidate split are accumulated per bin rather than per sample, avoiding
+    /// sorting the samples at every node. This greatly speeds up fitting on
+    /// large datasets, at the cost of slightly coarser thresholds; features
+    /// with at most `max_bins` distinct values are split exactly as without binning.
+    ///
+    /// Fitting on sparse arrays always uses exact split finding.
+    ///
+    /// # Panics
+    /// Panics if `max_bins` is less than 2.
+    pub fn max_bins(&mut self, max_bins: usize) -> &mut Hyperparameters {
+        assert!(max_bins >= 2, "Need at least two bins");
+
+        self.max_bins = Some(max_bins);
+        self
+    }
+    /// Declare the features with indices `categorical_features` categorical.
+    ///
+    /// Categorical features are split on subsets of their values rather than
+    /// thresholds: samples whose value is one of the categories chosen for the
+    /// split go to the left child, and all others to the right child. Values are
+    /// compared for equality, so categories should be encoded as integers.
+    ///
+    /// The best subset is found exactly, by ordering the categories at the node
+    /// by their fraction of positive samples and considering every split of
+    /// that ordering. With `max_bins`, categorical features are never binned.
+    ///
+    /// # Panics
+    /// Panics if any index is not smaller than the dimensionality of the data.
+    pub fn categorical_features(
+        &mut self,
+        categorical_features: Vec<usize>,
+    ) -> &mut Hyperparameters {
+        assert!(
+            categorical_features.iter().all(|&idx| idx < self.dim),
+            "Categorical feature index out of bounds"
+        );
+
+        self.categorical_features = categorical_features;
+        self
+    }
     /// Set the random number generator used for sampling features
     /// to consider at each split.
     pub fn rng(&mut self, rng: rand::StdRng) -> &mut Hyperparameters {
         self.rng.rng = rng;
         self
     }
+    /// Return the dimensionality of the data the tree is built for.
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
     /// Build a binary decision tree model.
     pub fn build(&self) -> DecisionTree {
         DecisionTree {
             dim: self.dim,
             max_features: self.max_features,
+            max_features_mode: self.max_features_mode,
             max_depth: self.max_depth,
             min_samples_split: self.min_samples_split,
+            max_leaf_nodes: self.max_leaf_nodes,
+            ccp_alpha: self.ccp_alpha,
+            min_impurity_decrease: self.min_impurity_decrease,
+            max_bins: self.max_bins,
+            categorical_features: self.categorical_features.clone(),
             root: None,
             feature_types: Vec::new(),
+            feature_importances: Vec::new(),
             rng: self.rng.clone(),
         }
     }
@@ -287,6 +658,10 @@ enum Node {
     Interior {
         feature: usize,
         threshold: f32,
+        #[serde(default)]
+        categories: Option<Vec<f32>>,
+        #[serde(default)]
+        missing_left: bool,
         children: Box<(Node, Node)>,
     },
     Leaf {
@@ -294,48 +669,314 @@ enum Node {
     },
 }
 
+/// A read-only view of a node of a fitted decision tree,
+/// as returned by [`DecisionTree::nodes`](struct.DecisionTree.html#method.nodes).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeNode {
+    /// An interior node. Samples whose value of `feature` is at most `threshold`
+    /// go to the `left` child; for splits of categorical features, `categories`
+    /// holds the sorted values going to the left child instead, and `threshold`
+    /// is unused. Samples with a missing value go to the left child if
+    /// `missing_left` is set, and to the right child otherwise. Children are
+    /// given as indices into the node vector.
+    Split {
+        feature: usize,
+        threshold: f32,
+        categories: Option<Vec<f32>>,
+        missing_left: bool,
+        left: usize,
+        right: usize,
+    },
+    /// A leaf node, predicting the positive class probability `value`.
+    Leaf { value: f32 },
+}
+
 /// A two-class decision tree.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DecisionTree {
     dim: usize,
 
     max_features: usize,
+    #[serde(default)]
+    max_features_mode: Option<MaxFeatures>,
     max_depth: usize,
     min_samples_split: usize,
+    #[serde(default)]
+    max_leaf_nodes: Option<usize>,
+    #[serde(default)]
+    ccp_alpha: f32,
+    #[serde(default)]
+    min_impurity_decrease: f32,
+    #[serde(default)]
+    max_bins: Option<usize>,
+    #[serde(default)]
+    categorical_features: Vec<usize>,
 
     root: Option<Node>,
     feature_types: Vec<FeatureType>,
+    #[serde(default)]
+    feature_importances: Vec<f32>,
     rng: EncodableRng,
 }
 
+impl DecisionTree {
+    /// Return whether the tree has been fit.
+    pub fn is_fitted(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// Return the number of candidate features considered when finding
+    /// the best split. When set using `max_features_mode`, the number is
+    /// only derived from the dimensionality of the data once the tree is fit.
+    pub fn max_features(&self) -> usize {
+        self.max_features
+    }
+
+    /// Return the nodes of the fitted tree in depth-first order, with the root first
+    /// and left subtrees before right subtrees. Returns an empty vector if the tree
+    /// has not been fit.
+    pub fn nodes(&self) -> Vec<TreeNode> {
+        let mut nodes = Vec::new();
+
+        if let Some(ref root) = self.root {
+            DecisionTree::collect_nodes(root, &mut nodes);
+        }
+
+        nodes
+    }
+
+    /// Return the indices of the nodes visited by `row` on its way from the
+    /// root to a leaf, in order. Indices refer to the vector returned by
+    /// [`nodes`](#method.nodes), so the first entry is always zero and the
+    /// last entry is the index of the leaf whose value is predicted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    /// use rustlearn::trees::decision_tree::{Hyperparameters, TreeNode};
+    ///
+    /// let X = Array::from(&vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+    /// let y = Array::from(vec![1.0, 0.0]);
+    ///
+    /// let mut model = Hyperparameters::new(2).build();
+    /// model.fit(&X, &y).unwrap();
+    ///
+    /// let path = model.decision_path(&[0.0, 1.0]).unwrap();
+    ///
+    /// assert!(path[0] == 0);
+    /// assert!(model.nodes()[path[path.len() - 1]] == TreeNode::Leaf { value: 1.0 });
+    /// ```
+    pub fn decision_path(&self, row: &[f32]) -> Result<Vec<usize>, RustlearnError> {
+        try!(check_row_dimensionality(self.dim, row));
+
+        let mut node = match self.root {
+            Some(ref node) => node,
+            None => {
+                return Err(RustlearnError::NotFitted(
+                    "Tree must be built before computing decision paths",
+                ))
+            }
+        };
+
+        let mut node_idx = 0;
+        let mut path = vec![node_idx];
+
+        while let Node::Interior {
+            feature,
+            threshold,
+            ref categories,
+            missing_left,
+            ref children,
+        } = *node
+        {
+            let categories = categories.as_ref().map(|x| &x[..]);
+
+            // Nodes are numbered depth-first, so the right child
+            // comes after the whole left subtree.
+            if DecisionTree::goes_left(row[feature], threshold, categories, missing_left) {
+                node_idx += 1;
+                node = &children.0;
+            } else {
+                node_idx += 1 + DecisionTree::num_nodes(&children.0);
+                node = &children.1;
+            }
+
+            path.push(node_idx);
+        }
+
+        Ok(path)
+    }
+
+    /// Render the fitted tree in the Graphviz DOT format.
+    ///
+    /// Splits are labelled using `feature_names` if given, and using
+    /// the feature index otherwise. Edges to left children are labelled
+    /// `yes` and edges to right children `no`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rustlearn::prelude::*;
+    /// use rustlearn::trees::decision_tree::Hyperparameters;
+    ///
+    /// let X = Array::from(&vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+    /// let y = Array::from(vec![1.0, 0.0]);
+    ///
+    /// let mut model = Hyperparameters::new(2).build();
+    /// model.fit(&X, &y).unwrap();
+    ///
+    /// let dot = model.to_dot(Some(&["width", "height"])).unwrap();
+    ///
+    /// assert!(dot.starts_with("digraph Tree {"));
+    /// ```
+    pub fn to_dot(&self, feature_names: Option<&[&str]>) -> Result<String, RustlearnError> {
+        if self.root.is_none() {
+            return Err(RustlearnError::NotFitted(
+                "Tree must be built before exporting",
+            ));
+        }
+
+        if let Some(names) = feature_names {
+            if names.len() != self.dim {
+                return Err(RustlearnError::DimensionMismatch(
+                    "Number of feature names and model dimensionality differ.",
+                ));
+            }
+        }
+
+        let mut dot = String::from("digraph Tree {\n    node [shape=box];\n");
+
+        for (node_idx, node) in self.nodes().iter().enumerate() {
+            match *node {
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    ref categories,
+                    missing_left,
+                    left,
+                    right,
+                } => {
+                    let name = match feature_names {
+                        Some(names) => names[feature].replace('"', "\\\""),
+                        None => format!("X[{}]", feature),
+                    };
+                    let condition = match *categories {
+                        Some(ref categories) => format!(
+                            "{} in {{{}}}",
+                            name,
+                            categories
+                                .iter()
+                                .map(|x| x.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        None => format!("{} <= {}", name, threshold),
+                    };
+                    let missing = if missing_left { "left" } else { "right" };
+
+                    dot.push_str(&format!(
+                        "    {} [label=\"{}\\nmissing: {}\"];\n",
+                        node_idx, condition, missing
+                    ));
+                    dot.push_str(&format!("    {} -> {} [label=\"yes\"];\n", node_idx, left));
+                    dot.push_str(&format!("    {} -> {} [label=\"no\"];\n", node_idx, right));
+                }
+                TreeNode::Leaf { value } => {
+                    dot.push_str(&format!("    {} [label=\"p = {:.3}\"];\n", node_idx, value));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+
+        Ok(dot)
+    }
+
+    /// Return the number of nodes in the subtree rooted at `node`.
+    fn num_nodes(node: &Node) -> usize {
+        match *node {
+            Node::Interior { ref children, .. } => {
+                1 + DecisionTree::num_nodes(&children.0) + DecisionTree::num_nodes(&children.1)
+            }
+            Node::Leaf { .. } => 1,
+        }
+    }
+
+    /// Append the nodes of the subtree rooted at `node` to `nodes`,
+    /// returning the index of `node`.
+    fn collect_nodes(node: &Node, nodes: &mut Vec<TreeNode>) -> usize {
+        let node_idx = nodes.len();
+
+        match *node {
+            Node::Interior {
+                feature,
+                threshold,
+                ref categories,
+                missing_left,
+                ref children,
+            } => {
+                // Placeholder until the child indices are known
+                nodes.push(TreeNode::Leaf { value: 0.0 });
+
+                let left = DecisionTree::collect_nodes(&children.0, nodes);
+                let right = DecisionTree::collect_nodes(&children.1, nodes);
+
+                nodes[node_idx] = TreeNode::Split {
+                    feature: feature,
+                    threshold: threshold,
+                    categories: categories.clone(),
+                    missing_left: missing_left,
+                    left: left,
+                    right: right,
+                };
+            }
+            Node::Leaf { probability } => nodes.push(TreeNode::Leaf { value: probability }),
+        }
+
+        node_idx
+    }
+}
+
 impl<'a> SupervisedModel<&'a Array> for DecisionTree {
-    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), &'static str> {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        self.fit_weighted(X, y, &Array::ones(y.rows(), 1))
+    }
+
+    fn fit_weighted(
+        &mut self,
+        X: &Array,
+        y: &Array,
+        sample_weight: &Array,
+    ) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
         try!(check_valid_labels(y));
+        try!(check_sample_weights(y, sample_weight));
 
         self.feature_types = DecisionTree::analyze_features(X);
 
-        let mut feature_values = FeatureValues::with_capacity(X.rows());
-        let mut feature_indices = FeatureIndices::new(self.get_nonconstant_feature_indices());
-        let mut candidate_features = Vec::with_capacity(self.max_features);
-
-        self.root = Some(self.build_tree(
-            X,
-            y,
-            &mut (0..X.rows()).collect::<Vec<usize>>()[..],
-            &mut feature_indices,
-            &mut candidate_features,
-            0,
-            &mut feature_values,
-            &DecisionTree::get_values,
-            &DecisionTree::split_indices,
-        ));
+        self.root = Some(match self.max_bins {
+            Some(max_bins) => self.grow_tree(
+                &BinnedArray::new(X, max_bins, &self.categorical_features),
+                y,
+                sample_weight,
+                &DecisionTree::get_values_binned,
+                &DecisionTree::split_indices_binned,
+            ),
+            None => self.grow_tree(
+                X,
+                y,
+                sample_weight,
+                &DecisionTree::get_values,
+                &DecisionTree::split_indices,
+            ),
+        });
 
         Ok(())
     }
 
-    fn decision_function(&self, X: &Array) -> Result<Array, &'static str> {
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
 
         match self.root {
@@ -346,31 +987,35 @@ impl<'a> SupervisedModel<&'a Array> for DecisionTree {
                 }
                 Ok(Array::from(data))
             }
-            None => Err("Tree must be built before predicting"),
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before predicting",
+            )),
         }
     }
 }
 
 impl<'a> SupervisedModel<&'a SparseColumnArray> for DecisionTree {
-    fn fit(&mut self, X: &SparseColumnArray, y: &Array) -> Result<(), &'static str> {
+    fn fit(&mut self, X: &SparseColumnArray, y: &Array) -> Result<(), RustlearnError> {
+        self.fit_weighted(X, y, &Array::ones(y.rows(), 1))
+    }
+
+    fn fit_weighted(
+        &mut self,
+        X: &SparseColumnArray,
+        y: &Array,
+        sample_weight: &Array,
+    ) -> Result<(), RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
         try!(check_matched_dimensions(X, y));
         try!(check_valid_labels(y));
+        try!(check_sample_weights(y, sample_weight));
 
         self.feature_types = DecisionTree::analyze_features_sparse(X);
 
-        let mut feature_values = FeatureValues::with_capacity(X.rows());
-        let mut feature_indices = FeatureIndices::new(self.get_nonconstant_feature_indices());
-        let mut candidate_features = Vec::with_capacity(self.max_features);
-
-        self.root = Some(self.build_tree(
+        self.root = Some(self.grow_tree(
             X,
             y,
-            &mut (0..X.rows()).collect::<Vec<usize>>()[..],
-            &mut feature_indices,
-            &mut candidate_features,
-            0,
-            &mut feature_values,
+            sample_weight,
             &DecisionTree::get_values_sparse,
             &DecisionTree::split_indices_sparse,
         ));
@@ -378,7 +1023,7 @@ impl<'a> SupervisedModel<&'a SparseColumnArray> for DecisionTree {
         Ok(())
     }
 
-    fn decision_function(&self, X: &SparseColumnArray) -> Result<Array, &'static str> {
+    fn decision_function(&self, X: &SparseColumnArray) -> Result<Array, RustlearnError> {
         try!(check_data_dimensionality(self.dim, X));
 
         match self.root {
@@ -389,7 +1034,46 @@ impl<'a> SupervisedModel<&'a SparseColumnArray> for DecisionTree {
                 }
                 Ok(Array::from(data))
             }
-            None => Err("Tree must be built before predicting"),
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before predicting",
+            )),
+        }
+    }
+}
+
+impl FeatureImportance for DecisionTree {
+    /// Return the normalized decrease in Gini impurity due to splits on
+    /// each feature. A tree without splits has all-zero importances.
+    fn feature_importances(&self) -> Result<Vec<f32>, RustlearnError> {
+        match self.root {
+            Some(_) if self.feature_importances.len() == self.dim => {
+                Ok(self.feature_importances.clone())
+            }
+            Some(_) => Err(RustlearnError::Unsupported(
+                "Feature importances are not available for this tree.",
+            )),
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before computing feature importances",
+            )),
+        }
+    }
+}
+
+impl SeedableModel for DecisionTree {
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = EncodableRng::from_seed(seed);
+    }
+}
+
+impl RowPredict for DecisionTree {
+    fn decision_function_row(&self, row: &[f32]) -> Result<f32, RustlearnError> {
+        try!(check_row_dimensionality(self.dim, row));
+
+        match self.root {
+            Some(ref node) => Ok(DecisionTree::query_tree_row(node, row)),
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before predicting",
+            )),
         }
     }
 }
@@ -399,15 +1083,13 @@ impl DecisionTree {
         let mut features = Vec::with_capacity(X.cols());
 
         for col in X.iter_columns() {
-            let mut values = col.iter().collect::<Vec<_>>();
+            let mut values = col.iter().filter(|x| !x.is_nan()).collect::<Vec<_>>();
+            let has_missing = values.len() < X.rows();
+
             values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
             values.dedup();
 
-            features.push(match values.len() {
-                1 => FeatureType::Constant,
-                2 => FeatureType::Binary,
-                _ => FeatureType::Continuous,
-            });
+            features.push(DecisionTree::feature_type(values.len(), has_missing));
         }
 
         features
@@ -417,26 +1099,46 @@ impl DecisionTree {
         let mut features = Vec::with_capacity(X.cols());
 
         for col in X.iter_columns() {
-            let mut values = col.iter_nonzero().map(|(_, val)| val).collect::<Vec<_>>();
+            let nonzero = col.iter_nonzero().map(|(_, val)| val).collect::<Vec<_>>();
+
+            let mut values = nonzero
+                .iter()
+                .cloned()
+                .filter(|x| !x.is_nan())
+                .collect::<Vec<_>>();
+            let has_missing = values.len() < nonzero.len();
 
             // Add zero as a distrinct value
-            if values.len() < X.rows() {
+            if nonzero.len() < X.rows() {
                 values.push(0.0);
             }
 
             values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
             values.dedup();
 
-            features.push(match values.len() {
-                1 => FeatureType::Constant,
-                2 => FeatureType::Binary,
-                _ => FeatureType::Continuous,
-            });
+            features.push(DecisionTree::feature_type(values.len(), has_missing));
         }
 
         features
     }
 
+    /// Classify a feature by its number of distinct non-missing values.
+    /// Missing values count as an additional distinct value, as splitting
+    /// samples with missing values from the others may be informative.
+    fn feature_type(num_values: usize, has_missing: bool) -> FeatureType {
+        let num_values = if has_missing && num_values > 0 {
+            num_values + 1
+        } else {
+            num_values
+        };
+
+        match num_values {
+            0 | 1 => FeatureType::Constant,
+            2 => FeatureType::Binary,
+            _ => FeatureType::Continuous,
+        }
+    }
+
     fn get_nonconstant_feature_indices(&self) -> Vec<usize> {
         self.feature_types
             .iter()
@@ -449,126 +1151,814 @@ impl DecisionTree {
             .collect::<Vec<_>>()
     }
 
-    fn count_positives(y: &Array, indices: &mut [usize]) -> usize {
-        let mut count = 0;
+    /// Return the total weight, and the total weight
+    /// of positive samples, of the samples in `indices`.
+    fn count_positives(y: &Array, sample_weight: &Array, indices: &[usize]) -> (f32, f32) {
+        let mut count = 0.0;
+        let mut positives = 0.0;
 
         let data = y.data();
+        let weights = sample_weight.data();
 
         for &row_idx in indices.iter() {
+            count += weights[row_idx];
+
             if data[row_idx] > 0.0 {
-                count += 1;
+                positives += weights[row_idx];
             }
         }
 
-        count
+        (count, positives)
     }
 
-    fn build_tree<T, F, G>(
+    fn probability(totals: (f32, f32)) -> f32 {
+        match totals {
+            (count, positives) if count > 0.0 => positives / count,
+            _ => 0.0,
+        }
+    }
+
+    fn grow_tree<T, F, G>(
         &mut self,
         X: T,
         y: &Array,
-        indices: &mut [usize],
-        feature_indices: &mut FeatureIndices,
-        candidate_features: &mut Vec<usize>,
-        depth: usize,
-        feature_values: &mut FeatureValues,
+        sample_weight: &Array,
         get_values: &F,
         split_indices: &G,
     ) -> Node
     where
         T: Copy,
-        F: Fn(T, &Array, usize, usize, &[usize], &mut FeatureValues) -> (),
-        G: Fn(T, &mut [usize], usize, f32) -> (&mut [usize], &mut [usize]),
+        F: Fn(T, &Array, &Array, (f32, f32), usize, &[usize], &mut FeatureValues) -> (),
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
     {
-        let num_positives = DecisionTree::count_positives(y, indices);
-        let probability = num_positives as f32 / indices.len() as f32;
-
-        if probability == 0.0
-            || probability == 1.0
-            || depth > self.max_depth
-            || indices.len() < self.min_samples_split
-        {
-            return Node::Leaf {
-                probability: probability,
-            };
+        if let Some(mode) = self.max_features_mode {
+            self.max_features = mode.num_features(self.dim);
         }
 
-        // Multiple attemps to perform a split.
-        for _ in 0..10 {
-            feature_indices.sample_indices(
-                candidate_features,
-                self.max_features,
-                &mut self.rng.rng,
-            );
+        let mut feature_values = FeatureValues::with_capacity(y.rows());
+        let mut candidate_features = Vec::with_capacity(self.max_features);
+        let mut indices = (0..y.rows()).collect::<Vec<usize>>();
+        let totals = DecisionTree::count_positives(y, sample_weight, &indices);
+        let (total_weight, _) = totals;
+
+        self.feature_importances = vec![0.0; self.dim];
+
+        let root = match self.max_leaf_nodes {
+            Some(max_leaf_nodes) => self.build_tree_best_first(
+                X,
+                y,
+                sample_weight,
+                total_weight,
+                totals,
+                indices,
+                max_leaf_nodes,
+                &mut candidate_features,
+                &mut feature_values,
+                get_values,
+                split_indices,
+            ),
+            None => {
+                let mut feature_indices =
+                    FeatureIndices::new(self.get_nonconstant_feature_indices());
+
+                self.build_tree(
+                    X,
+                    y,
+                    sample_weight,
+                    total_weight,
+                    totals,
+                    &mut indices[..],
+                    &mut feature_indices,
+                    &mut candidate_features,
+                    0,
+                    &mut feature_values,
+                    get_values,
+                    split_indices,
+                )
+            }
+        };
 
-            let mut best_feature_idx = 0;
-            let mut best_feature_threshold = 0.0;
-            let mut best_impurity = f32::INFINITY;
+        let root = if self.ccp_alpha > 0.0 {
+            self.prune_tree(root, X, y, sample_weight, split_indices)
+        } else {
+            root
+        };
 
-            for &feature_idx in candidate_features.iter() {
-                get_values(X, y, num_positives, feature_idx, indices, feature_values);
+        let total = self.feature_importances.iter().sum::<f32>();
 
-                if let FeatureType::Constant = feature_values.feature_type() {
-                    feature_indices.mark_as_used(feature_idx);
-                    continue;
-                }
+        if total > 0.0 {
+            for importance in self.feature_importances.iter_mut() {
+                *importance /= total;
+            }
+        }
 
-                let (threshold, impurity) = DecisionTree::calculate_split(feature_values);
+        root
+    }
+
+    /// Perform minimal cost-complexity pruning of a fitted tree.
+    ///
+    /// The feature importances accumulated while growing the tree are
+    /// recomputed from the splits that survive pruning.
+    fn prune_tree<T, G>(
+        &mut self,
+        root: Node,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        split_indices: &G,
+    ) -> Node
+    where
+        T: Copy,
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        let mut nodes = Vec::new();
+        let mut indices = (0..y.rows()).collect::<Vec<usize>>();
+
+        DecisionTree::collect_pruning_nodes(
+            &root,
+            X,
+            y,
+            sample_weight,
+            &mut indices[..],
+            split_indices,
+            &mut nodes,
+        );
+
+        let (total_weight, _) = nodes[0].totals;
+
+        loop {
+            let (_, _, weakest_link) = DecisionTree::weakest_link(&nodes, 0, total_weight);
+
+            match weakest_link {
+                Some((alpha, node_idx)) if alpha < self.ccp_alpha => nodes[node_idx].pruned = true,
+                _ => break,
+            }
+        }
+
+        self.feature_importances = vec![0.0; self.dim];
+
+        DecisionTree::apply_pruning(root, &nodes, 0, &mut self.feature_importances)
+    }
+
+    /// Record the training data statistics of every node of the subtree
+    /// rooted at `node` in depth-first order, returning the index of `node`.
+    fn collect_pruning_nodes<T, G>(
+        node: &Node,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        indices: &mut [usize],
+        split_indices: &G,
+        nodes: &mut Vec<PruningNode>,
+    ) -> usize
+    where
+        T: Copy,
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        let node_idx = nodes.len();
+
+        nodes.push(PruningNode {
+            totals: DecisionTree::count_positives(y, sample_weight, indices),
+            children: None,
+            pruned: false,
+        });
+
+        if let Node::Interior {
+            feature,
+            threshold,
+            ref categories,
+            missing_left,
+            ref children,
+        } = *node
+        {
+            let (left_indices, right_indices) = split_indices(
+                X,
+                indices,
+                feature,
+                threshold,
+                categories.as_ref().map(|x| &x[..]),
+                missing_left,
+            );
+
+            let left = DecisionTree::collect_pruning_nodes(
+                &children.0,
+                X,
+                y,
+                sample_weight,
+                left_indices,
+                split_indices,
+                nodes,
+            );
+            let right = DecisionTree::collect_pruning_nodes(
+                &children.1,
+                X,
+                y,
+                sample_weight,
+                right_indices,
+                split_indices,
+                nodes,
+            );
+
+            nodes[node_idx].children = Some((left, right));
+        }
+
+        node_idx
+    }
 
-                if impurity < best_impurity {
-                    best_feature_idx = feature_idx;
-                    best_feature_threshold = threshold;
-                    best_impurity = impurity;
+    /// Return the cost and number of leaves of the subtree rooted at `node_idx`,
+    /// and the effective alpha and index of its weakest link, if any.
+    fn weakest_link(
+        nodes: &[PruningNode],
+        node_idx: usize,
+        total_weight: f32,
+    ) -> (f32, usize, Option<(f32, usize)>) {
+        let node = &nodes[node_idx];
+
+        let (count, _) = node.totals;
+        let probability = DecisionTree::probability(node.totals);
+        let leaf_cost = count / total_weight * 2.0 * probability * (1.0 - probability);
+
+        match node.children {
+            Some((left, right)) if !node.pruned => {
+                let (left_cost, left_leaves, left_link) =
+                    DecisionTree::weakest_link(nodes, left, total_weight);
+                let (right_cost, right_leaves, right_link) =
+                    DecisionTree::weakest_link(nodes, right, total_weight);
+
+                let cost = left_cost + right_cost;
+                let num_leaves = left_leaves + right_leaves;
+                let alpha = (leaf_cost - cost) / (num_leaves - 1) as f32;
+
+                let mut weakest = (alpha, node_idx);
+
+                for link in left_link.into_iter().chain(right_link) {
+                    if link.0 < weakest.0 {
+                        weakest = link;
+                    }
                 }
+
+                (cost, num_leaves, Some(weakest))
             }
+            _ => (leaf_cost, 1, None),
+        }
+    }
 
-            let (left_indices, right_indices) =
-                split_indices(X, indices, best_feature_idx, best_feature_threshold);
+    /// Replace the pruned interior nodes of the subtree rooted at `node` with leaves,
+    /// adding the weighted impurity decrease of every remaining split to `importances`.
+    fn apply_pruning(
+        node: Node,
+        nodes: &[PruningNode],
+        node_idx: usize,
+        importances: &mut [f32],
+    ) -> Node {
+        let weighted_impurity =
+            |node_idx: usize| DecisionTree::weighted_impurity(nodes[node_idx].totals);
+
+        match (node, &nodes[node_idx]) {
+            (
+                Node::Interior {
+                    feature,
+                    threshold,
+                    categories,
+                    missing_left,
+                    children,
+                },
+                &PruningNode {
+                    children: Some((left, right)),
+                    pruned: false,
+                    ..
+                },
+            ) => {
+                let (left_child, right_child) = *children;
+
+                importances[feature] += weighted_impurity(node_idx)
+                    - weighted_impurity(left)
+                    - weighted_impurity(right);
+
+                Node::Interior {
+                    feature: feature,
+                    threshold: threshold,
+                    categories: categories,
+                    missing_left: missing_left,
+                    children: Box::new((
+                        DecisionTree::apply_pruning(left_child, nodes, left, importances),
+                        DecisionTree::apply_pruning(right_child, nodes, right, importances),
+                    )),
+                }
+            }
+            (Node::Interior { .. }, pruning_node) => Node::Leaf {
+                probability: DecisionTree::probability(pruning_node.totals),
+            },
+            (leaf, _) => leaf,
+        }
+    }
 
-            if left_indices.len() > 0 && right_indices.len() > 0 {
-                // Cannot split on binary feature more than one time
-                if let FeatureType::Binary = self.feature_types[best_feature_idx] {
-                    feature_indices.mark_as_used(best_feature_idx);
+    fn build_tree<T, F, G>(
+        &mut self,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        total_weight: f32,
+        totals: (f32, f32),
+        indices: &mut [usize],
+        feature_indices: &mut FeatureIndices,
+        candidate_features: &mut Vec<usize>,
+        depth: usize,
+        feature_values: &mut FeatureValues,
+        get_values: &F,
+        split_indices: &G,
+    ) -> Node
+    where
+        T: Copy,
+        F: Fn(T, &Array, &Array, (f32, f32), usize, &[usize], &mut FeatureValues) -> (),
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        let probability = DecisionTree::probability(totals);
+
+        if !self.is_splittable(probability, depth, indices.len()) {
+            return Node::Leaf {
+                probability: probability,
+            };
+        }
+
+        let split = match self.find_split(
+            X,
+            y,
+            sample_weight,
+            totals,
+            indices,
+            feature_indices,
+            candidate_features,
+            feature_values,
+            get_values,
+            split_indices,
+        ) {
+            Some(split) => split,
+            None => {
+                return Node::Leaf {
+                    probability: probability,
                 }
+            }
+        };
+
+        if !self.accepts_split(totals, &split, total_weight) {
+            return Node::Leaf {
+                probability: probability,
+            };
+        }
+
+        // Cannot split on binary feature more than one time
+        if let FeatureType::Binary = self.feature_types[split.feature] {
+            feature_indices.mark_as_used(split.feature);
+        }
+
+        let num_used_features = feature_indices.num_used;
+        let (left_indices, right_indices) = indices.split_at_mut(split.num_left);
+
+        let left_totals = DecisionTree::count_positives(y, sample_weight, left_indices);
+        let right_totals = DecisionTree::count_positives(y, sample_weight, right_indices);
+
+        self.feature_importances[split.feature] += DecisionTree::weighted_impurity(totals)
+            - DecisionTree::weighted_impurity(left_totals)
+            - DecisionTree::weighted_impurity(right_totals);
+
+        let left = self.build_tree(
+            X,
+            y,
+            sample_weight,
+            total_weight,
+            left_totals,
+            left_indices,
+            feature_indices,
+            candidate_features,
+            depth + 1,
+            feature_values,
+            get_values,
+            split_indices,
+        );
+
+        feature_indices.num_used = num_used_features;
+
+        let right = self.build_tree(
+            X,
+            y,
+            sample_weight,
+            total_weight,
+            right_totals,
+            right_indices,
+            feature_indices,
+            candidate_features,
+            depth + 1,
+            feature_values,
+            get_values,
+            split_indices,
+        );
+
+        Node::Interior {
+            feature: split.feature,
+            threshold: split.threshold,
+            categories: split.categories,
+            missing_left: split.missing_left,
+            children: Box::new((left, right)),
+        }
+    }
+
+    /// Grow the tree best-first, always splitting the leaf with the largest
+    /// decrease in weighted impurity, until there are `max_leaf_nodes` leaves.
+    fn build_tree_best_first<T, F, G>(
+        &mut self,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        total_weight: f32,
+        totals: (f32, f32),
+        indices: Vec<usize>,
+        max_leaf_nodes: usize,
+        candidate_features: &mut Vec<usize>,
+        feature_values: &mut FeatureValues,
+        get_values: &F,
+        split_indices: &G,
+    ) -> Node
+    where
+        T: Copy,
+        F: Fn(T, &Array, &Array, (f32, f32), usize, &[usize], &mut FeatureValues) -> (),
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        let nonconstant_features = self.get_nonconstant_feature_indices();
+
+        let mut nodes = Vec::new();
+        let mut candidates = BinaryHeap::new();
+        let mut num_leaves = 1;
+
+        let mut pending = vec![(indices, 0, Vec::new(), totals)];
+
+        loop {
+            // Evaluate the best splits of newly created leaves
+            for (indices, depth, used_features, totals) in pending.drain(..) {
+                let node_idx = nodes.len();
+                let probability = DecisionTree::probability(totals);
 
-                let num_used_features = feature_indices.num_used;
+                nodes.push(TreeNode::Leaf { value: probability });
 
-                let left = self.build_tree(
+                if let Some(candidate) = self.evaluate_candidate(
                     X,
                     y,
-                    left_indices,
-                    feature_indices,
+                    sample_weight,
+                    total_weight,
+                    totals,
+                    node_idx,
+                    indices,
+                    depth,
+                    used_features,
+                    &nonconstant_features,
                     candidate_features,
-                    depth + 1,
                     feature_values,
                     get_values,
                     split_indices,
-                );
+                ) {
+                    candidates.push(candidate);
+                }
+            }
+
+            if num_leaves >= max_leaf_nodes {
+                break;
+            }
+
+            let candidate = match candidates.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+
+            let (left_indices, right_indices) =
+                candidate.indices.split_at(candidate.split.num_left);
+
+            nodes[candidate.node_idx] = TreeNode::Split {
+                feature: candidate.split.feature,
+                threshold: candidate.split.threshold,
+                categories: candidate.split.categories.clone(),
+                missing_left: candidate.split.missing_left,
+                left: nodes.len(),
+                right: nodes.len() + 1,
+            };
+            num_leaves += 1;
+
+            let left_totals = DecisionTree::count_positives(y, sample_weight, left_indices);
+            let right_totals = DecisionTree::count_positives(y, sample_weight, right_indices);
+
+            self.feature_importances[candidate.split.feature] +=
+                DecisionTree::weighted_impurity(candidate.totals)
+                    - DecisionTree::weighted_impurity(left_totals)
+                    - DecisionTree::weighted_impurity(right_totals);
+
+            pending.push((
+                left_indices.to_vec(),
+                candidate.depth + 1,
+                candidate.used_features.clone(),
+                left_totals,
+            ));
+            pending.push((
+                right_indices.to_vec(),
+                candidate.depth + 1,
+                candidate.used_features,
+                right_totals,
+            ));
+        }
 
-                feature_indices.num_used = num_used_features;
+        DecisionTree::node_from_tree_nodes(&nodes, 0)
+    }
+
+    /// Find the best split of a leaf created during best-first growth,
+    /// returning `None` if the leaf cannot be split.
+    fn evaluate_candidate<T, F, G>(
+        &mut self,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        total_weight: f32,
+        totals: (f32, f32),
+        node_idx: usize,
+        mut indices: Vec<usize>,
+        depth: usize,
+        used_features: Vec<usize>,
+        nonconstant_features: &[usize],
+        candidate_features: &mut Vec<usize>,
+        feature_values: &mut FeatureValues,
+        get_values: &F,
+        split_indices: &G,
+    ) -> Option<Candidate>
+    where
+        T: Copy,
+        F: Fn(T, &Array, &Array, (f32, f32), usize, &[usize], &mut FeatureValues) -> (),
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        let probability = DecisionTree::probability(totals);
+
+        if !self.is_splittable(probability, depth, indices.len()) {
+            return None;
+        }
+
+        let mut feature_indices = FeatureIndices::new(nonconstant_features.to_vec());
+
+        for &feature_idx in &used_features {
+            feature_indices.mark_as_used(feature_idx);
+        }
+
+        let split = match self.find_split(
+            X,
+            y,
+            sample_weight,
+            totals,
+            &mut indices[..],
+            &mut feature_indices,
+            candidate_features,
+            feature_values,
+            get_values,
+            split_indices,
+        ) {
+            Some(split) => split,
+            None => return None,
+        };
+
+        if !self.accepts_split(totals, &split, total_weight) {
+            return None;
+        }
+
+        // Cannot split on binary feature more than one time
+        if let FeatureType::Binary = self.feature_types[split.feature] {
+            feature_indices.mark_as_used(split.feature);
+        }
+
+        let (total_count, _) = totals;
+        let impurity = 2.0 * probability * (1.0 - probability);
+
+        Some(Candidate {
+            node_idx: node_idx,
+            totals: totals,
+            indices: indices,
+            depth: depth,
+            used_features: feature_indices.candidate_indices[..feature_indices.num_used].to_vec(),
+            impurity_decrease: total_count * (impurity - split.impurity),
+            split: split,
+        })
+    }
+
+    /// Convert the subtree rooted at `node_idx` of a
+    /// depth-first tree node vector into a tree.
+    fn node_from_tree_nodes(nodes: &[TreeNode], node_idx: usize) -> Node {
+        match nodes[node_idx] {
+            TreeNode::Split {
+                feature,
+                threshold,
+                ref categories,
+                missing_left,
+                left,
+                right,
+            } => Node::Interior {
+                feature: feature,
+                threshold: threshold,
+                categories: categories.clone(),
+                missing_left: missing_left,
+                children: Box::new((
+                    DecisionTree::node_from_tree_nodes(nodes, left),
+                    DecisionTree::node_from_tree_nodes(nodes, right),
+                )),
+            },
+            TreeNode::Leaf { value } => Node::Leaf { probability: value },
+        }
+    }
+
+    /// Whether the impurity decrease of `split`, weighted by the fraction of
+    /// the total sample weight reaching the node, exceeds `min_impurity_decrease`.
+    fn accepts_split(&self, totals: (f32, f32), split: &Split, total_weight: f32) -> bool {
+        if self.min_impurity_decrease <= 0.0 {
+            return true;
+        }
+
+        let (count, _) = totals;
+        let probability = DecisionTree::probability(totals);
+        let impurity = 2.0 * probability * (1.0 - probability);
+
+        count / total_weight * (impurity - split.impurity) > self.min_impurity_decrease
+    }
+
+    /// Gini impurity of a node weighted by the total weight of its samples.
+    fn weighted_impurity(totals: (f32, f32)) -> f32 {
+        let (count, _) = totals;
+        let probability = DecisionTree::probability(totals);
+
+        count * 2.0 * probability * (1.0 - probability)
+    }
+
+    fn is_splittable(&self, probability: f32, depth: usize, num_samples: usize) -> bool {
+        !(probability == 0.0
+            || probability == 1.0
+            || depth > self.max_depth
+            || num_samples < self.min_samples_split)
+    }
+
+    /// Find the best split of the samples in `indices`, partitioning `indices`
+    /// so that the samples going to the left child come first. Returns `None`
+    /// if no split separating the samples was found.
+    fn find_split<T, F, G>(
+        &mut self,
+        X: T,
+        y: &Array,
+        sample_weight: &Array,
+        totals: (f32, f32),
+        indices: &mut [usize],
+        feature_indices: &mut FeatureIndices,
+        candidate_features: &mut Vec<usize>,
+        feature_values: &mut FeatureValues,
+        get_values: &F,
+        split_indices: &G,
+    ) -> Option<Split>
+    where
+        T: Copy,
+        F: Fn(T, &Array, &Array, (f32, f32), usize, &[usize], &mut FeatureValues) -> (),
+        G: for<'b> Fn(
+            T,
+            &'b mut [usize],
+            usize,
+            f32,
+            Option<&[f32]>,
+            bool,
+        ) -> (&'b mut [usize], &'b mut [usize]),
+    {
+        // Multiple attemps to perform a split.
+        for _ in 0..10 {
+            feature_indices.sample_indices(
+                candidate_features,
+                self.max_features,
+                &mut self.rng.rng,
+            );
 
-                let right = self.build_tree(
+            let mut best_feature_idx = 0;
+            let mut best_feature_threshold = 0.0;
+            let mut best_categories = None;
+            let mut best_missing_left = false;
+            let mut best_impurity = f32::INFINITY;
+
+            for &feature_idx in candidate_features.iter() {
+                get_values(
                     X,
                     y,
-                    right_indices,
-                    feature_indices,
-                    candidate_features,
-                    depth + 1,
+                    sample_weight,
+                    totals,
+                    feature_idx,
+                    indices,
                     feature_values,
-                    get_values,
-                    split_indices,
                 );
 
-                return Node::Interior {
+                if let FeatureType::Constant = feature_values.feature_type() {
+                    feature_indices.mark_as_used(feature_idx);
+                    continue;
+                }
+
+                if self.categorical_features.contains(&feature_idx) {
+                    let (categories, missing_left, impurity) =
+                        DecisionTree::calculate_categorical_split(feature_values);
+
+                    if impurity < best_impurity {
+                        best_feature_idx = feature_idx;
+                        best_feature_threshold = 0.0;
+                        best_categories = Some(categories);
+                        best_missing_left = missing_left;
+                        best_impurity = impurity;
+                    }
+                } else {
+                    let (threshold, missing_left, impurity) =
+                        DecisionTree::calculate_split(feature_values);
+
+                    if impurity < best_impurity {
+                        best_feature_idx = feature_idx;
+                        best_feature_threshold = threshold;
+                        best_categories = None;
+                        best_missing_left = missing_left;
+                        best_impurity = impurity;
+                    }
+                }
+            }
+
+            let (left_indices, right_indices) = split_indices(
+                X,
+                indices,
+                best_feature_idx,
+                best_feature_threshold,
+                best_categories.as_ref().map(|x| &x[..]),
+                best_missing_left,
+            );
+
+            if left_indices.len() > 0 && right_indices.len() > 0 {
+                return Some(Split {
                     feature: best_feature_idx,
                     threshold: best_feature_threshold,
-                    children: Box::new((left, right)),
-                };
+                    categories: best_categories,
+                    missing_left: best_missing_left,
+                    impurity: best_impurity,
+                    num_left: left_indices.len(),
+                });
             }
         }
 
-        Node::Leaf {
-            probability: probability,
+        None
+    }
+
+    /// Whether a sample with feature value `value` goes to the left child.
+    fn goes_left(
+        value: f32,
+        threshold: f32,
+        categories: Option<&[f32]>,
+        missing_left: bool,
+    ) -> bool {
+        if value.is_nan() {
+            missing_left
+        } else {
+            match categories {
+                Some(categories) => categories.contains(&value),
+                None => value <= threshold,
+            }
         }
     }
 
@@ -577,13 +1967,45 @@ impl DecisionTree {
         indices: &'a mut [usize],
         feature_idx: usize,
         threshold: f32,
+        categories: Option<&[f32]>,
+        missing_left: bool,
     ) -> (&'a mut [usize], &'a mut [usize]) {
         let mut num_left = 0;
 
         for i in 0..indices.len() {
             let row_idx = indices[i];
 
-            if X.get(row_idx, feature_idx) <= threshold {
+            let value = X.get(row_idx, feature_idx);
+
+            if DecisionTree::goes_left(value, threshold, categories, missing_left) {
+                indices.swap(i, num_left);
+                num_left += 1;
+            }
+        }
+
+        let (left, right) = indices.split_at_mut(num_left);
+
+        // Need to keep the indices sorted
+        right.sort();
+
+        (left, right)
+    }
+
+    fn split_indices_binned<'a>(
+        X: &BinnedArray,
+        indices: &'a mut [usize],
+        feature_idx: usize,
+        threshold: f32,
+        categories: Option<&[f32]>,
+        missing_left: bool,
+    ) -> (&'a mut [usize], &'a mut [usize]) {
+        let bins = X.column(feature_idx);
+        let mut num_left = 0;
+
+        for i in 0..indices.len() {
+            let value = X.bin_value(feature_idx, bins[indices[i]]);
+
+            if DecisionTree::goes_left(value, threshold, categories, missing_left) {
                 indices.swap(i, num_left);
                 num_left += 1;
             }
@@ -602,6 +2024,8 @@ impl DecisionTree {
         indices: &'a mut [usize],
         feature_idx: usize,
         threshold: f32,
+        categories: Option<&[f32]>,
+        missing_left: bool,
     ) -> (&'a mut [usize], &'a mut [usize]) {
         let mut num_left = 0;
         let indices_len = indices.len();
@@ -610,7 +2034,7 @@ impl DecisionTree {
 
         macro_rules! assign {
             ($i:expr, $row_idx:expr, $value:expr) => {{
-                if $value <= threshold {
+                if DecisionTree::goes_left($value, threshold, categories, missing_left) {
                     indices[$i] = indices[num_left];
                     indices[num_left] = $row_idx;
                     num_left += 1;
@@ -657,24 +2081,31 @@ impl DecisionTree {
         (left, right)
     }
 
-    fn calculate_split(values: &FeatureValues) -> (f32, f32) {
+    /// Find the best split threshold, returning the threshold, whether
+    /// samples with missing values should go to the left child, and the
+    /// impurity of the split.
+    fn calculate_split(values: &FeatureValues) -> (f32, bool, f32) {
         let (_, max_value) = values.value_bounds();
 
         let mut split_impurity = f32::INFINITY;
         let mut split_x = 0.0;
+        let mut split_missing_left = false;
 
-        let total_count = values.count as f32;
+        let total_count = values.count;
         let total_y = values.total_y;
 
+        let nan_count = values.nan_count;
+        let nan_y = values.nan_y;
+
         let mut cumulative_count = 0.0;
         let mut cumulative_y = 0.0;
 
-        for &(x, y) in &values.xy_pairs {
+        for &(x, y, weight) in &values.xyw_triples {
             if x == 0.0 {
-                cumulative_count += values.zero_count as f32;
+                cumulative_count += values.zero_count;
                 cumulative_y += values.zero_y;
             } else {
-                cumulative_count += 1.0;
+                cumulative_count += weight;
                 cumulative_y += y;
             }
 
@@ -682,16 +2113,34 @@ impl DecisionTree {
                 continue;
             }
 
-            let left_child_proportion = cumulative_count / total_count;
-            let left_child_positive_probability = cumulative_y / cumulative_count;
-            let right_child_positive_probability =
-                (total_y - cumulative_y) / (total_count - cumulative_count);
+            let right_child_count = total_count - cumulative_count - nan_count;
+            let right_child_y = total_y - cumulative_y - nan_y;
 
-            let impurity = DecisionTree::proxy_gini_impurity(
-                left_child_proportion,
-                left_child_positive_probability,
-                right_child_positive_probability,
+            let mut impurity = DecisionTree::split_impurity(
+                total_count,
+                cumulative_count,
+                cumulative_y,
+                right_child_count + nan_count,
+                right_child_y + nan_y,
             );
+            let mut missing_left = false;
+
+            // Missing values go to the right child unless
+            // sending them to the left child is strictly better.
+            if nan_count > 0.0 {
+                let missing_left_impurity = DecisionTree::split_impurity(
+                    total_count,
+                    cumulative_count + nan_count,
+                    cumulative_y + nan_y,
+                    right_child_count,
+                    right_child_y,
+                );
+
+                if missing_left_impurity < impurity {
+                    impurity = missing_left_impurity;
+                    missing_left = true;
+                }
+            }
 
             // It's important that this is less than or equal rather
             // than less than: subject to no decrease in impurity
@@ -699,18 +2148,175 @@ impl DecisionTree {
             if impurity <= split_impurity {
                 split_impurity = impurity;
                 split_x = x;
+                split_missing_left = missing_left;
             }
         }
 
-        (split_x, split_impurity)
-    }
+        // Splitting at the largest value separates the samples with
+        // missing values from all others
+        let missing_impurity = DecisionTree::missing_split_impurity(values);
 
-    fn proxy_gini_impurity(
-        left_child_proportion: f32,
-        left_child_probability: f32,
-        right_child_probability: f32,
-    ) -> f32 {
-        let right_child_proportion = 1.0 - left_child_proportion;
+        if missing_impurity < split_impurity {
+            split_impurity = missing_impurity;
+            split_x = max_value;
+            split_missing_left = false;
+        }
+
+        (split_x, split_missing_left, split_impurity)
+    }
+
+    /// Find the best split of a categorical feature into the categories going to
+    /// the left child and the remaining categories, returning the sorted categories
+    /// going to the left child, whether samples with missing values should go to the
+    /// left child, and the impurity of the split.
+    ///
+    /// For two classes, the best subset under Gini impurity is always a prefix of
+    /// the categories ordered by their fraction of positive samples (Breiman et al.,
+    /// 1984), so only the splits of this ordering need to be considered.
+    fn calculate_categorical_split(values: &FeatureValues) -> (Vec<f32>, bool, f32) {
+        // Totals of every category, as triples of value, weighted label, and weight
+        let mut categories: Vec<(f32, f32, f32)> = Vec::new();
+
+        for &(x, y, weight) in &values.xyw_triples {
+            let (y, weight) = if x == 0.0 {
+                (values.zero_y, values.zero_count)
+            } else {
+                (y, weight)
+            };
+
+            match categories.last_mut() {
+                Some(category) if category.0 == x => {
+                    category.1 += y;
+                    category.2 += weight;
+                    continue;
+                }
+                _ => {}
+            }
+
+            categories.push((x, y, weight));
+        }
+
+        let positive_fraction = |&(_, y, weight): &(f32, f32, f32)| {
+            if weight > 0.0 {
+                y / weight
+            } else {
+                0.0
+            }
+        };
+
+        categories.sort_by(|a, b| {
+            positive_fraction(a)
+                .partial_cmp(&positive_fraction(b))
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut split_impurity = f32::INFINITY;
+        let mut split_num_left = 0;
+        let mut split_missing_left = false;
+
+        let total_count = values.count;
+        let total_y = values.total_y;
+
+        let nan_count = values.nan_count;
+        let nan_y = values.nan_y;
+
+        let mut cumulative_count = 0.0;
+        let mut cumulative_y = 0.0;
+
+        for num_left in 1..categories.len() {
+            cumulative_count += categories[num_left - 1].2;
+            cumulative_y += categories[num_left - 1].1;
+
+            let right_child_count = total_count - cumulative_count - nan_count;
+            let right_child_y = total_y - cumulative_y - nan_y;
+
+            let mut impurity = DecisionTree::split_impurity(
+                total_count,
+                cumulative_count,
+                cumulative_y,
+                right_child_count + nan_count,
+                right_child_y + nan_y,
+            );
+            let mut missing_left = false;
+
+            if nan_count > 0.0 {
+                let missing_left_impurity = DecisionTree::split_impurity(
+                    total_count,
+                    cumulative_count + nan_count,
+                    cumulative_y + nan_y,
+                    right_child_count,
+                    right_child_y,
+                );
+
+                if missing_left_impurity < impurity {
+                    impurity = missing_left_impurity;
+                    missing_left = true;
+                }
+            }
+
+            if impurity < split_impurity {
+                split_impurity = impurity;
+                split_num_left = num_left;
+                split_missing_left = missing_left;
+            }
+        }
+
+        // Sending all categories to the left child separates the
+        // samples with missing values from all others
+        let missing_impurity = DecisionTree::missing_split_impurity(values);
+
+        if missing_impurity < split_impurity {
+            split_impurity = missing_impurity;
+            split_num_left = categories.len();
+            split_missing_left = false;
+        }
+
+        let mut left_categories = categories[..split_num_left]
+            .iter()
+            .map(|&(x, _, _)| x)
+            .collect::<Vec<_>>();
+        left_categories.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        (left_categories, split_missing_left, split_impurity)
+    }
+
+    /// Impurity of the split sending all samples with a non-missing value to
+    /// the left child and all samples with a missing value to the right child.
+    /// Infinite if either child would be empty.
+    fn missing_split_impurity(values: &FeatureValues) -> f32 {
+        if values.nan_count == 0.0 || values.xyw_triples.is_empty() {
+            return f32::INFINITY;
+        }
+
+        DecisionTree::split_impurity(
+            values.count,
+            values.count - values.nan_count,
+            values.total_y - values.nan_y,
+            values.nan_count,
+            values.nan_y,
+        )
+    }
+
+    fn split_impurity(
+        total_count: f32,
+        left_count: f32,
+        left_y: f32,
+        right_count: f32,
+        right_y: f32,
+    ) -> f32 {
+        DecisionTree::proxy_gini_impurity(
+            left_count / total_count,
+            left_y / left_count,
+            right_y / right_count,
+        )
+    }
+
+    fn proxy_gini_impurity(
+        left_child_proportion: f32,
+        left_child_probability: f32,
+        right_child_probability: f32,
+    ) -> f32 {
+        let right_child_proportion = 1.0 - left_child_proportion;
 
         let left_impurity =
             1.0 - left_child_probability.powi(2) - (1.0 - left_child_probability).powi(2);
@@ -724,7 +2330,8 @@ impl DecisionTree {
     fn get_values(
         X: &Array,
         y: &Array,
-        num_positives: usize,
+        sample_weight: &Array,
+        totals: (f32, f32),
         feature_idx: usize,
         indices: &[usize],
         values: &mut FeatureValues,
@@ -732,16 +2339,65 @@ impl DecisionTree {
         values.clear();
 
         for &row_idx in indices.iter() {
-            values.push(X.get(row_idx, feature_idx), y.get(row_idx, 0));
+            values.push(
+                X.get(row_idx, feature_idx),
+                y.get(row_idx, 0),
+                sample_weight.get(row_idx, 0),
+            );
         }
 
         values.sort();
     }
 
+    /// Accumulate the label and weight totals of the samples in
+    /// `indices` per bin, in increasing order of bins.
+    #[allow(unused_variables)]
+    fn get_values_binned(
+        X: &BinnedArray,
+        y: &Array,
+        sample_weight: &Array,
+        totals: (f32, f32),
+        feature_idx: usize,
+        indices: &[usize],
+        values: &mut FeatureValues,
+    ) {
+        let bins = X.column(feature_idx);
+        let y = y.data();
+        let weights = sample_weight.data();
+
+        let num_bins = X.edges[feature_idx].len();
+        let mut bin_y = vec![0.0; num_bins];
+        let mut bin_weight = vec![0.0; num_bins];
+        let mut bin_occupied = vec![false; num_bins];
+
+        values.clear();
+
+        for &row_idx in indices.iter() {
+            let bin = bins[row_idx];
+
+            if bin == MISSING_BIN {
+                values.push(f32::NAN, y[row_idx], weights[row_idx]);
+            } else {
+                let bin = bin as usize;
+
+                bin_y[bin] += weights[row_idx] * y[row_idx];
+                bin_weight[bin] += weights[row_idx];
+                bin_occupied[bin] = true;
+            }
+        }
+
+        for bin in 0..num_bins {
+            if bin_occupied[bin] {
+                values.push_totals(X.edges[feature_idx][bin], bin_y[bin], bin_weight[bin]);
+            }
+        }
+    }
+
     fn get_values_sparse(
         x: &SparseColumnArray,
         y: &Array,
-        num_positives: usize,
+        sample_weight: &Array,
+        totals: (f32, f32),
         feature_idx: usize,
         indices: &[usize],
         values: &mut FeatureValues,
@@ -753,9 +2409,16 @@ impl DecisionTree {
         let x_nnz = x.nnz() as f32;
 
         if x_nnz * (indices.len() as f32).log(2.0) < x_nnz + indices.len() as f32 {
-            DecisionTree::get_values_sparse_by_search(x, y, num_positives, indices, values);
+            DecisionTree::get_values_sparse_by_search(x, y, sample_weight, totals, indices, values);
         } else {
-            DecisionTree::get_values_sparse_by_iteration(x, y, num_positives, indices, values);
+            DecisionTree::get_values_sparse_by_iteration(
+                x,
+                y,
+                sample_weight,
+                totals,
+                indices,
+                values,
+            );
         }
 
         values.sort();
@@ -764,7 +2427,8 @@ impl DecisionTree {
     fn get_values_sparse_by_iteration(
         x: SparseArrayView,
         y: &Array,
-        num_positives: usize,
+        sample_weight: &Array,
+        totals: (f32, f32),
         indices: &[usize],
         values: &mut FeatureValues,
     ) {
@@ -779,12 +2443,20 @@ impl DecisionTree {
                 match indices_idx.cmp(&nonzero_idx) {
                     // Haven't reached the first nonzero in column
                     Ordering::Less => {
-                        values.push(0.0, y.get(indices_idx, 0));
+                        values.push(
+                            0.0,
+                            y.get(indices_idx, 0),
+                            sample_weight.get(indices_idx, 0),
+                        );
                         indices_option = indices_iter.next();
                     }
                     // Reached a nonzero at index we are interested in
                     Ordering::Equal => {
-                        values.push(nonzero_value, y.get(nonzero_idx, 0));
+                        values.push(
+                            nonzero_value,
+                            y.get(nonzero_idx, 0),
+                            sample_weight.get(nonzero_idx, 0),
+                        );
                         indices_option = indices_iter.next();
                         nonzero_option = nonzero_iter.next();
                     }
@@ -795,8 +2467,9 @@ impl DecisionTree {
                 }
             } else {
                 // We've exhausted all nonzero indices
-                let remaining_zeros = indices.len() - values.count;
-                let remaining_positives = num_positives as f32 - values.total_y;
+                let (total_count, total_positives) = totals;
+                let remaining_zeros = total_count - values.count;
+                let remaining_positives = total_positives - values.total_y;
 
                 values.fill_remaining_zeros(remaining_zeros, remaining_positives);
                 break;
@@ -807,20 +2480,23 @@ impl DecisionTree {
     fn get_values_sparse_by_search(
         x: SparseArrayView,
         y: &Array,
-        num_positives: usize,
+        sample_weight: &Array,
+        totals: (f32, f32),
         indices: &[usize],
         values: &mut FeatureValues,
     ) {
         let y = y.data();
+        let weights = sample_weight.data();
 
         for (row_idx, value) in x.iter_nonzero() {
             if let Ok(_) = indices.binary_search(&row_idx) {
-                values.push(value, y[row_idx]);
+                values.push(value, y[row_idx], weights[row_idx]);
             }
         }
 
-        let remaining_zeros = indices.len() - values.count;
-        let remaining_positives = num_positives as f32 - values.total_y;
+        let (total_count, total_positives) = totals;
+        let remaining_zeros = total_count - values.count;
+        let remaining_positives = total_positives - values.total_y;
 
         values.fill_remaining_zeros(remaining_zeros, remaining_positives);
     }
@@ -830,9 +2506,14 @@ impl DecisionTree {
             Node::Interior {
                 feature,
                 threshold,
+                ref categories,
+                missing_left,
                 ref children,
             } => {
-                if x.get(row_idx, feature) <= threshold {
+                let categories = categories.as_ref().map(|x| &x[..]);
+                let value = x.get(row_idx, feature);
+
+                if DecisionTree::goes_left(value, threshold, categories, missing_left) {
                     self.query_tree(&children.0, x, row_idx)
                 } else {
                     self.query_tree(&children.1, x, row_idx)
@@ -842,14 +2523,40 @@ impl DecisionTree {
         }
     }
 
+    fn query_tree_row(node: &Node, row: &[f32]) -> f32 {
+        match *node {
+            Node::Interior {
+                feature,
+                threshold,
+                ref categories,
+                missing_left,
+                ref children,
+            } => {
+                let categories = categories.as_ref().map(|x| &x[..]);
+
+                if DecisionTree::goes_left(row[feature], threshold, categories, missing_left) {
+                    DecisionTree::query_tree_row(&children.0, row)
+                } else {
+                    DecisionTree::query_tree_row(&children.1, row)
+                }
+            }
+            Node::Leaf { probability } => probability,
+        }
+    }
+
     fn query_tree_sparse(&self, node: &Node, x: &SparseColumnArray, row_idx: usize) -> f32 {
         match *node {
             Node::Interior {
                 feature,
                 threshold,
+                ref categories,
+                missing_left,
                 ref children,
             } => {
-                if x.get(row_idx, feature) <= threshold {
+                let categories = categories.as_ref().map(|x| &x[..]);
+                let value = x.get(row_idx, feature);
+
+                if DecisionTree::goes_left(value, threshold, categories, missing_left) {
                     self.query_tree_sparse(&children.0, x, row_idx)
                 } else {
                     self.query_tree_sparse(&children.1, x, row_idx)
@@ -862,6 +2569,8 @@ impl DecisionTree {
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::max;
+
     use super::FeatureValues;
     use super::*;
     use cross_validation::cross_validation::CrossValidation;
@@ -869,159 +2578,869 @@ mod tests {
     use metrics::accuracy_score;
     use multiclass::OneVsRestWrapper;
 
+    use rand::distributions::normal::Normal;
+    use rand::distributions::IndependentSample;
     use rand::{SeedableRng, StdRng};
 
     use serde_json;
 
-    use bincode;
+    use bincode;
+
+    #[cfg(feature = "all_tests")]
+    use datasets::newsgroups;
+
+    extern crate time;
+
+    #[test]
+    fn test_gini_impurity() {
+        let impurity = DecisionTree::proxy_gini_impurity(0.5, 0.5, 0.5);
+        let expected = 0.5;
+        assert!(impurity == expected);
+
+        let impurity = DecisionTree::proxy_gini_impurity(0.5, 1.0, 0.0);
+        let expected = 0.0;
+        assert!(impurity == expected);
+
+        let impurity = DecisionTree::proxy_gini_impurity(0.2, 1.0, 0.5);
+        let expected = 0.8 * 0.5;
+        assert!(impurity == expected);
+    }
+
+    #[test]
+    fn calculate_split_1() {
+        let x = SparseColumnArray::from(&Array::from(&vec![
+            vec![-1.0],
+            vec![-0.5],
+            vec![0.0],
+            vec![0.0],
+            vec![0.0],
+            vec![0.5],
+            vec![1.0],
+        ]));
+        let y = Array::from(vec![1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let weights = Array::ones(y.rows(), 1);
+        let mut values = FeatureValues::with_capacity(y.rows());
+        let mut indices = (0..y.rows()).collect::<Vec<_>>();
+
+        DecisionTree::get_values_sparse(
+            &x,
+            &y,
+            &weights,
+            (7.0, 2.0),
+            0,
+            &mut indices[..],
+            &mut values,
+        );
+
+        let (threshold, _, impurity) = DecisionTree::calculate_split(&values);
+
+        assert!(threshold == -0.5);
+        assert!(impurity == 0.0);
+    }
+
+    #[test]
+    fn calculate_split_2() {
+        let x = SparseColumnArray::from(&Array::from(&vec![
+            vec![-1.0],
+            vec![-0.5],
+            vec![0.0],
+            vec![0.0],
+            vec![0.0],
+            vec![0.5],
+            vec![1.0],
+        ]));
+        let y = Array::from(vec![1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+        let weights = Array::ones(y.rows(), 1);
+        let mut values = FeatureValues::with_capacity(y.rows());
+        let mut indices = (0..y.rows()).collect::<Vec<_>>();
+
+        DecisionTree::get_values_sparse(
+            &x,
+            &y,
+            &weights,
+            (7.0, 5.0),
+            0,
+            &mut indices[..],
+            &mut values,
+        );
+
+        let (threshold, _, impurity) = DecisionTree::calculate_split(&values);
+
+        assert!(threshold == 0.0);
+        assert!(impurity == 0.0);
+    }
+
+    #[test]
+    fn calculate_split_missing() {
+        let x = Array::from(vec![0.1, 0.2, f32::NAN, 0.8, 0.9, f32::NAN]);
+        let y = Array::from(vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+        let weights = Array::ones(y.rows(), 1);
+        let mut values = FeatureValues::with_capacity(y.rows());
+        let indices = (0..y.rows()).collect::<Vec<_>>();
+
+        DecisionTree::get_values(&x, &y, &weights, (6.0, 2.0), 0, &indices[..], &mut values);
+
+        let (threshold, missing_left, impurity) = DecisionTree::calculate_split(&values);
+
+        assert!(threshold == 0.2);
+        assert!(missing_left);
+        assert!(impurity == 0.0);
+    }
+
+    #[test]
+    fn test_missing_values() {
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0..30 {
+            let value = i as f32 / 30.0;
+            // Every third row has a missing first feature, and is negative.
+            if i % 3 == 0 {
+                rows.push(vec![f32::NAN, 1.0]);
+                labels.push(0.0);
+            } else {
+                rows.push(vec![value, 1.0]);
+                labels.push(if value > 0.5 { 1.0 } else { 0.0 });
+            }
+        }
+
+        let X = Array::from(&rows);
+        let y = Array::from(labels);
+
+        let mut model = Hyperparameters::new(2)
+            .max_features(2)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+
+        model.fit(&X, &y).unwrap();
+
+        let prediction = model.predict(&X).unwrap();
+        assert!(accuracy_score(&y, &prediction) == 1.0);
+
+        let X_sparse = SparseColumnArray::from(&X);
+
+        model.fit(&X_sparse, &y).unwrap();
+
+        let prediction = model.predict(&X_sparse).unwrap();
+        assert!(accuracy_score(&y, &prediction) == 1.0);
+
+        // Missing values are routed to the negative side,
+        // even when they would otherwise default to the right.
+        let query = Array::from(&vec![vec![f32::NAN, 1.0]]);
+        assert!(model.predict(&query).unwrap().data() == &vec![0.0]);
+    }
+
+    #[test]
+    fn test_missing_single_value() {
+        // The first feature takes a single value, but whether
+        // it is missing determines the label.
+        let rows = (0..30)
+            .map(|i| {
+                if i % 3 == 0 {
+                    vec![f32::NAN, 1.0]
+                } else {
+                    vec![2.0, 1.0]
+                }
+            })
+            .collect::<Vec<_>>();
+        let labels = (0..30)
+            .map(|i| if i % 3 == 0 { 1.0 } else { 0.0 })
+            .collect::<Vec<_>>();
+
+        let X = Array::from(&rows);
+        let y = Array::from(labels);
+
+        let weights = Array::ones(y.rows(), 1);
+        let mut values = FeatureValues::with_capacity(y.rows());
+        let indices = (0..y.rows()).collect::<Vec<_>>();
+
+        DecisionTree::get_values(&X, &y, &weights, (30.0, 10.0), 0, &indices[..], &mut values);
+
+        let (threshold, missing_left, impurity) = DecisionTree::calculate_split(&values);
+
+        assert!(threshold == 2.0);
+        assert!(!missing_left);
+        assert!(impurity == 0.0);
+
+        let mut hyperparameters = Hyperparameters::new(2);
+        hyperparameters
+            .max_features(2)
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut binned = Hyperparameters::new(2);
+        binned
+            .max_features(2)
+            .max_bins(4)
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut categorical = Hyperparameters::new(2);
+        categorical
+            .max_features(2)
+            .categorical_features(vec![0])
+            .rng(StdRng::from_seed(&[100]));
+
+        for params in &[hyperparameters, binned, categorical] {
+            let mut model = params.build();
+
+            model.fit(&X, &y).unwrap();
+            assert!(accuracy_score(&y, &model.predict(&X).unwrap()) == 1.0);
+
+            let X_sparse = SparseColumnArray::from(&X);
+
+            model.fit(&X_sparse, &y).unwrap();
+            assert!(accuracy_score(&y, &model.predict(&X_sparse).unwrap()) == 1.0);
+        }
+    }
+
+    #[test]
+    fn test_missing_values_iris() {
+        let (data, target) = load_data();
+
+        let mut data = data;
+
+        for row_idx in 0..data.rows() {
+            if row_idx % 5 == 0 {
+                data.set(row_idx, row_idx % 4, f32::NAN);
+            }
+        }
+
+        let mut model = Hyperparameters::new(data.cols())
+            .min_samples_split(5)
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+
+        model.fit(&data, &target).unwrap();
+
+        let prediction = model.predict(&data).unwrap();
+        let accuracy = accuracy_score(&target, &prediction);
+
+        println!("Accuracy {}", accuracy);
+
+        assert!(accuracy > 0.9);
+    }
+
+    #[test]
+    fn test_indices_split() {
+        let x = Array::from(&vec![
+            vec![-1.0, 1.0],
+            vec![-0.5, 1.0],
+            vec![0.5, 0.0],
+            vec![1.0, 0.0],
+        ]);
+        let mut indices = vec![0, 1, 2, 3];
+        let (left, right) = DecisionTree::split_indices(&x, &mut indices[..], 0, -0.5, None, false);
+        assert!(left.to_owned() == vec![0, 1]);
+        assert!(right.to_owned() == vec![2, 3]);
+    }
+
+    #[test]
+    fn test_indices_split_sparse() {
+        let x = SparseColumnArray::from(&Array::from(&vec![
+            vec![-1.0, 1.0],
+            vec![0.0, 2.0],
+            vec![0.0, 3.0],
+            vec![1.0, 0.0],
+            vec![0.0, 0.0],
+        ]));
+        let mut indices = (0..5).collect::<Vec<_>>();
+        let ind_bor = &mut indices[..];
+
+        let (left, right) = DecisionTree::split_indices_sparse(&x, ind_bor, 0, 0.0, None, false);
+
+        assert!(left.to_owned() == vec![0, 1, 2, 4]);
+        assert!(right.to_owned() == vec![3]);
+
+        let mut indices = (0..5).collect::<Vec<_>>();
+        let ind_bor = &mut indices[..];
+
+        let (left, right) = DecisionTree::split_indices_sparse(&x, ind_bor, 1, 0.5, None, false);
+
+        assert!(left.to_owned() == vec![3, 4]);
+        assert!(right.to_owned() == vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_get_values_sparse() {
+        let x = SparseColumnArray::from(&Array::from(&vec![
+            vec![-1.0, 1.0],
+            vec![0.0, 2.0],
+            vec![0.0, 3.0],
+            vec![1.0, 0.0],
+            vec![0.0, 0.0],
+        ]));
+        let y = Array::from(vec![0.0, 1.0, 0.0, 1.0, 1.0]);
+        let weights = Array::ones(5, 1);
+        let mut values = FeatureValues::with_capacity(5);
+        let mut indices = (0..5).collect::<Vec<_>>();
+
+        DecisionTree::get_values_sparse(
+            &x,
+            &y,
+            &weights,
+            (5.0, 3.0),
+            0,
+            &mut indices[..],
+            &mut values,
+        );
+
+        assert!(values.xyw_triples == vec![(-1.0, 0.0, 1.0), (0.0, 0.0, 0.0), (1.0, 1.0, 1.0)]);
+        assert!(values.zero_count == 3.0);
+        assert!(values.zero_y == 2.0);
+        assert!(values.total_y == 3.0);
+
+        values.clear();
+
+        DecisionTree::get_values_sparse(
+            &x,
+            &y,
+            &weights,
+            (5.0, 3.0),
+            1,
+            &mut indices[..],
+            &mut values,
+        );
+
+        assert!(
+            values.xyw_triples
+                == vec![
+                    (0.0, 0.0, 0.0),
+                    (1.0, 0.0, 1.0),
+                    (2.0, 1.0, 1.0),
+                    (3.0, 0.0, 1.0)
+                ]
+        );
+        assert!(values.zero_count == 2.0);
+        assert!(values.zero_y == 2.0);
+        assert!(values.total_y == 3.0);
+    }
+
+    #[test]
+    fn test_basic_tree_building() {
+        let X = Array::from(&vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        let y = Array::from(vec![1.0, 0.0]);
+
+        let params = Hyperparameters::new(2);
+        let mut model = params.build();
+
+        model.fit(&X, &y).unwrap();
+
+        assert!(allclose(&y, &model.decision_function(&X).unwrap()));
+    }
+
+    /// 180 negative and 20 overlapping positive samples.
+    fn imbalanced_data() -> (Array, Array) {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0..200 {
+            let (center, label) = if i < 180 { (0.0, 0.0) } else { (1.5, 1.0) };
+
+            rows.push(vec![
+                center + normal.ind_sample(&mut rng) as f32,
+                center + normal.ind_sample(&mut rng) as f32,
+            ]);
+            labels.push(label);
+        }
+
+        (Array::from(&rows), Array::from(labels))
+    }
+
+    fn num_leaves(model: &DecisionTree) -> usize {
+        model
+            .nodes()
+            .iter()
+            .filter(|node| match **node {
+                TreeNode::Leaf { .. } => true,
+                _ => false,
+            })
+            .count()
+    }
+
+    fn depth(model: &DecisionTree) -> usize {
+        fn node_depth(nodes: &[TreeNode], node_idx: usize) -> usize {
+            match nodes[node_idx] {
+                TreeNode::Split { left, right, .. } => {
+                    1 + max(node_depth(nodes, left), node_depth(nodes, right))
+                }
+                TreeNode::Leaf { .. } => 0,
+            }
+        }
+
+        node_depth(&model.nodes(), 0)
+    }
+
+    fn positive_recall(y: &Array, prediction: &Array) -> f32 {
+        let positives = y.data().iter().filter(|&&y| y == 1.0).count();
+        let true_positives = y
+            .data()
+            .iter()
+            .zip(prediction.data().iter())
+            .filter(|&(&y, &y_hat)| y == 1.0 && y_hat == 1.0)
+            .count();
+
+        true_positives as f32 / positives as f32
+    }
+
+    #[test]
+    fn test_equal_weights() {
+        let (data, target) = load_data();
+        let sparse_data = SparseColumnArray::from(&data);
+        let weights = Array::ones(data.rows(), 1).times(2.0);
+
+        let mut model = Hyperparameters::new(data.cols())
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        model.fit(&data, &target).unwrap();
+
+        let mut weighted_model = Hyperparameters::new(data.cols())
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        weighted_model
+            .fit_weighted(&data, &target, &weights)
+            .unwrap();
+
+        assert!(
+            model.decision_function(&data).unwrap()
+                == weighted_model.decision_function(&data).unwrap()
+        );
+
+        let mut model = Hyperparameters::new(data.cols())
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        model.fit(&sparse_data, &target).unwrap();
+
+        let mut weighted_model = Hyperparameters::new(data.cols())
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        weighted_model
+            .fit_weighted(&sparse_data, &target, &weights)
+            .unwrap();
+
+        assert!(
+            model.decision_function(&sparse_data).unwrap()
+                == weighted_model.decision_function(&sparse_data).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sample_weights_recall() {
+        let (X, y) = imbalanced_data();
+        let weights = Array::from(
+            y.data()
+                .iter()
+                .map(|&y| if y == 1.0 { 9.0 } else { 1.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut params = Hyperparameters::new(X.cols());
+        params
+            .max_features(2)
+            .max_depth(1)
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut model = params.build();
+        model.fit(&X, &y).unwrap();
+        let recall = positive_recall(&y, &model.predict(&X).unwrap());
+
+        let mut model = params.build();
+        model.fit_weighted(&X, &y, &weights).unwrap();
+        let weighted_recall = positive_recall(&y, &model.predict(&X).unwrap());
+
+        let X_sparse = SparseColumnArray::from(&X);
+        let mut model = params.build();
+        model.fit_weighted(&X_sparse, &y, &weights).unwrap();
+        let weighted_recall_sparse = positive_recall(&y, &model.predict(&X_sparse).unwrap());
+
+        println!(
+            "Recall {}, weighted {}, weighted sparse {}",
+            recall, weighted_recall, weighted_recall_sparse
+        );
+
+        assert!(weighted_recall > recall + 0.1);
+        assert!(weighted_recall_sparse > recall + 0.1);
+    }
+
+    #[test]
+    fn test_sample_weights_errors() {
+        let X = Array::from(&vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        let y = Array::from(vec![1.0, 0.0]);
+
+        let mut model = Hyperparameters::new(2).build();
+
+        assert!(model.fit_weighted(&X, &y, &Array::ones(3, 1)).is_err());
+        assert!(model.fit_weighted(&X, &y, &Array::zeros(2, 1)).is_err());
+        assert!(model
+            .fit_weighted(&X, &y, &Array::from(vec![1.0, -1.0]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_stump_export() {
+        let X = Array::from(&vec![
+            vec![1.0, 0.0],
+            vec![2.0, 0.0],
+            vec![3.0, 0.0],
+            vec![4.0, 0.0],
+        ]);
+        let y = Array::from(vec![0.0, 0.0, 1.0, 1.0]);
+
+        let mut model = Hyperparameters::new(2).max_features(1).build();
+        model.fit(&X, &y).unwrap();
+
+        assert!(
+            model.nodes()
+                == vec![
+                    TreeNode::Split {
+                        feature: 0,
+                        threshold: 2.0,
+                        categories: None,
+                        missing_left: false,
+                        left: 1,
+                        right: 2,
+                    },
+                    TreeNode::Leaf { value: 0.0 },
+                    TreeNode::Leaf { value: 1.0 },
+                ]
+        );
+
+        let dot = model.to_dot(Some(&["size", "\"quoted\""])).unwrap();
+
+        println!("{}", dot);
+
+        assert!(dot.starts_with("digraph Tree {"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.matches("<=").count() == 1);
+        assert!(dot.matches("p = ").count() == 2);
+        assert!(dot.matches("->").count() == 2);
+        assert!(dot.contains("size <= 2"));
+
+        let dot = model.to_dot(None).unwrap();
+        assert!(dot.contains("X[0] <= 2"));
+
+        assert!(model.to_dot(Some(&["size"])).is_err());
+        assert!(Hyperparameters::new(2).build().to_dot(None).is_err());
+        assert!(Hyperparameters::new(2).build().nodes().is_empty());
+    }
+
+    #[test]
+    fn test_feature_importances() {
+        let (data, target) = load_data();
+        let setosa = target.map(|x| if x == 0.0 { 1.0 } else { 0.0 });
+        let virginica = target.map(|x| if x == 2.0 { 1.0 } else { 0.0 });
+
+        let mut model = Hyperparameters::new(4).max_features(4).build();
+
+        assert!(model.feature_importances().is_err());
+
+        model.fit(&data, &setosa).unwrap();
+        let importances = model.feature_importances().unwrap();
+
+        // A single split on a petal measurement separates setosa
+        assert!(importances.len() == 4);
+        assert!(importances[2] + importances[3] == 1.0);
+
+        model.fit(&data, &virginica).unwrap();
+        let importances = model.feature_importances().unwrap();
+
+        println!("Importances {:?}", importances);
+
+        assert!((importances.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+        assert!(importances.iter().all(|&x| x >= 0.0));
+        assert!(importances[2] + importances[3] > 0.8);
+
+        // Importances accumulated while growing the tree agree with
+        // those recomputed from the training statistics of its nodes
+        for model in &mut [
+            Hyperparameters::new(4).max_features(4).build(),
+            Hyperparameters::new(4)
+                .max_features(4)
+                .max_leaf_nodes(6)
+                .build(),
+        ] {
+            model.fit(&data, &virginica).unwrap();
+
+            let root = model.root.clone().unwrap();
+            let mut statistics = Vec::new();
+            let mut indices = (0..data.rows()).collect::<Vec<usize>>();
+
+            DecisionTree::collect_pruning_nodes(
+                &root,
+                &data,
+                &virginica,
+                &Array::ones(data.rows(), 1),
+                &mut indices[..],
+                &DecisionTree::split_indices,
+                &mut statistics,
+            );
+
+            let mut expected = vec![0.0; 4];
+            DecisionTree::apply_pruning(root, &statistics, 0, &mut expected);
+
+            let total = expected.iter().sum::<f32>();
+
+            for (importance, expected) in model
+                .feature_importances()
+                .unwrap()
+                .iter()
+                .zip(expected.iter())
+            {
+                assert!((importance - expected / total).abs() < 1e-5);
+            }
+        }
+
+        let mut stump = Hyperparameters::new(4).ccp_alpha(1.0).build();
+        stump.fit(&data, &virginica).unwrap();
+        assert!(stump.feature_importances().unwrap() == vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_decision_path() {
+        let X = Array::from(&vec![
+            vec![1.0, 0.0],
+            vec![2.0, 0.0],
+            vec![3.0, 0.0],
+            vec![4.0, 0.0],
+        ]);
+        let y = Array::from(vec![0.0, 0.0, 1.0, 1.0]);
+
+        let mut model = Hyperparameters::new(2).max_features(1).build();
+
+        assert!(model.decision_path(&[1.0, 0.0]).is_err());
+
+        model.fit(&X, &y).unwrap();
+
+        assert!(model.decision_path(&[1.0, 0.0]).unwrap() == vec![0, 1]);
+        assert!(model.decision_path(&[4.0, 0.0]).unwrap() == vec![0, 2]);
+        assert!(model.decision_path(&[1.0]).is_err());
+
+        let (data, target) = load_data();
+        let target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&y| if y == 1.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut model = Hyperparameters::new(data.cols()).build();
+        model.fit(&data, &target).unwrap();
+
+        let nodes = model.nodes();
+
+        for row in data.iter_rows() {
+            let row = row.iter().collect::<Vec<_>>();
+            let path = model.decision_path(&row).unwrap();
+
+            assert!(path[0] == 0);
+
+            for pair in path.windows(2) {
+                match nodes[pair[0]] {
+                    TreeNode::Split { left, right, .. } => {
+                        assert!(pair[1] == left || pair[1] == right)
+                    }
+                    TreeNode::Leaf { .. } => panic!("Path continues past a leaf"),
+                }
+            }
+
+            match nodes[path[path.len() - 1]] {
+                TreeNode::Leaf { value } => {
+                    assert!(value == model.decision_function_row(&row).unwrap())
+                }
+                TreeNode::Split { .. } => panic!("Path does not end in a leaf"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_features_fraction() {
+        let (data, target) = load_data();
 
-    #[cfg(feature = "all_tests")]
-    use datasets::newsgroups;
+        let mut model = Hyperparameters::new(data.cols())
+            .max_features_fraction(0.5)
+            .rng(StdRng::from_seed(&[100]))
+            .one_vs_rest();
+        model.fit(&data, &target).unwrap();
 
-    extern crate time;
+        for tree in model.models() {
+            assert!(tree.max_features == 2);
+        }
 
-    #[test]
-    fn test_gini_impurity() {
-        let impurity = DecisionTree::proxy_gini_impurity(0.5, 0.5, 0.5);
-        let expected = 0.5;
-        assert!(impurity == expected);
+        let accuracy = accuracy_score(&target, &model.predict(&data).unwrap());
+        println!("Accuracy {}", accuracy);
+        assert!(accuracy > 0.9);
 
-        let impurity = DecisionTree::proxy_gini_impurity(0.5, 1.0, 0.0);
-        let expected = 0.0;
-        assert!(impurity == expected);
+        assert!(MaxFeatures::Fraction(0.01).num_features(4) == 1);
+        assert!(MaxFeatures::Sqrt.num_features(16) == 4);
+        assert!(MaxFeatures::Log2.num_features(16) == 4);
+        assert!(MaxFeatures::Log2.num_features(1) == 1);
 
-        let impurity = DecisionTree::proxy_gini_impurity(0.2, 1.0, 0.5);
-        let expected = 0.8 * 0.5;
-        assert!(impurity == expected);
+        // Setting an absolute count overrides the mode
+        let tree = Hyperparameters::new(data.cols())
+            .max_features_mode(MaxFeatures::Log2)
+            .max_features(3)
+            .build();
+        assert!(tree.max_features_mode.is_none());
     }
 
     #[test]
-    fn calculate_split_1() {
-        let x = SparseColumnArray::from(&Array::from(&vec![
-            vec![-1.0],
-            vec![-0.5],
-            vec![0.0],
-            vec![0.0],
-            vec![0.0],
-            vec![0.5],
-            vec![1.0],
-        ]));
-        let y = Array::from(vec![1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
-        let mut values = FeatureValues::with_capacity(y.rows());
-        let mut indices = (0..y.rows()).collect::<Vec<_>>();
+    fn test_max_leaf_nodes() {
+        let (data, target) = load_data();
+        let target = Array::from(
+            target
+                .data()
+                .iter()
+                .map(|&y| if y == 2.0 { 1.0 } else { 0.0 })
+                .collect::<Vec<_>>(),
+        );
 
-        DecisionTree::get_values_sparse(&x, &y, 2, 0, &mut indices[..], &mut values);
+        let mut unlimited = Hyperparameters::new(data.cols())
+            .max_features(4)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        unlimited.fit(&data, &target).unwrap();
 
-        let (threshold, impurity) = DecisionTree::calculate_split(&values);
+        println!("Unlimited leaves {}", num_leaves(&unlimited));
+        assert!(num_leaves(&unlimited) > 4);
 
-        assert!(threshold == -0.5);
-        assert!(impurity == 0.0);
-    }
+        for &max_leaf_nodes in &[1, 2, 4] {
+            let mut model = Hyperparameters::new(data.cols())
+                .max_features(4)
+                .max_leaf_nodes(max_leaf_nodes)
+                .rng(StdRng::from_seed(&[100]))
+                .build();
 
-    #[test]
-    fn calculate_split_2() {
-        let x = SparseColumnArray::from(&Array::from(&vec![
-            vec![-1.0],
-            vec![-0.5],
-            vec![0.0],
-            vec![0.0],
-            vec![0.0],
-            vec![0.5],
-            vec![1.0],
-        ]));
-        let y = Array::from(vec![1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
-        let mut values = FeatureValues::with_capacity(y.rows());
-        let mut indices = (0..y.rows()).collect::<Vec<_>>();
+            model.fit(&data, &target).unwrap();
+            assert!(num_leaves(&model) == max_leaf_nodes);
 
-        DecisionTree::get_values_sparse(&x, &y, 5, 0, &mut indices[..], &mut values);
+            let mut sparse_model = Hyperparameters::new(data.cols())
+                .max_features(4)
+                .max_leaf_nodes(max_leaf_nodes)
+                .rng(StdRng::from_seed(&[100]))
+                .build();
 
-        let (threshold, impurity) = DecisionTree::calculate_split(&values);
+            sparse_model
+                .fit(&SparseColumnArray::from(&data), &target)
+                .unwrap();
+            assert!(num_leaves(&sparse_model) == max_leaf_nodes);
+        }
 
-        assert!(threshold == 0.0);
-        assert!(impurity == 0.0);
-    }
+        // The most informative splits are made first
+        let mut model = Hyperparameters::new(data.cols())
+            .max_features(4)
+            .max_leaf_nodes(4)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        model.fit(&data, &target).unwrap();
 
-    #[test]
-    fn test_indices_split() {
-        let x = Array::from(&vec![
-            vec![-1.0, 1.0],
-            vec![-0.5, 1.0],
-            vec![0.5, 0.0],
-            vec![1.0, 0.0],
-        ]);
-        let mut indices = vec![0, 1, 2, 3];
-        let (left, right) = DecisionTree::split_indices(&x, &mut indices[..], 0, -0.5);
-        assert!(left.to_owned() == vec![0, 1]);
-        assert!(right.to_owned() == vec![2, 3]);
+        let accuracy = accuracy_score(&target, &model.predict(&data).unwrap());
+
+        println!("Accuracy {}", accuracy);
+        assert!(accuracy > 0.95);
     }
 
     #[test]
-    fn test_indices_split_sparse() {
-        let x = SparseColumnArray::from(&Array::from(&vec![
-            vec![-1.0, 1.0],
-            vec![0.0, 2.0],
-            vec![0.0, 3.0],
-            vec![1.0, 0.0],
-            vec![0.0, 0.0],
-        ]));
-        let mut indices = (0..5).collect::<Vec<_>>();
-        let ind_bor = &mut indices[..];
+    fn test_ccp_alpha() {
+        let (X, y) = imbalanced_data();
 
-        let (left, right) = DecisionTree::split_indices_sparse(&x, ind_bor, 0, 0.0);
-
-        assert!(left.to_owned() == vec![0, 1, 2, 4]);
-        assert!(right.to_owned() == vec![3]);
+        let fit = |ccp_alpha: f32| {
+            let mut model = Hyperparameters::new(X.cols())
+                .max_features(2)
+                .ccp_alpha(ccp_alpha)
+                .rng(StdRng::from_seed(&[100]))
+                .build();
+            model.fit(&X, &y).unwrap();
+            model
+        };
 
-        let mut indices = (0..5).collect::<Vec<_>>();
-        let ind_bor = &mut indices[..];
+        let full = fit(0.0);
+        let full_accuracy = accuracy_score(&y, &full.predict(&X).unwrap());
 
-        let (left, right) = DecisionTree::split_indices_sparse(&x, ind_bor, 1, 0.5);
+        // Without pruning, the tree is unchanged
+        let mut unpruned = Hyperparameters::new(X.cols())
+            .max_features(2)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        unpruned.fit(&X, &y).unwrap();
 
-        assert!(left.to_owned() == vec![3, 4]);
-        assert!(right.to_owned() == vec![0, 1, 2]);
-    }
+        assert!(full.nodes() == unpruned.nodes());
 
-    #[test]
-    fn test_get_values_sparse() {
-        let x = SparseColumnArray::from(&Array::from(&vec![
-            vec![-1.0, 1.0],
-            vec![0.0, 2.0],
-            vec![0.0, 3.0],
-            vec![1.0, 0.0],
-            vec![0.0, 0.0],
-        ]));
-        let y = Array::from(vec![0.0, 1.0, 0.0, 1.0, 1.0]);
-        let mut values = FeatureValues::with_capacity(5);
-        let mut indices = (0..5).collect::<Vec<_>>();
+        let pruned = fit(0.005);
+        let pruned_accuracy = accuracy_score(&y, &pruned.predict(&X).unwrap());
 
-        DecisionTree::get_values_sparse(&x, &y, 3, 0, &mut indices[..], &mut values);
+        println!(
+            "Leaves {} -> {}, accuracy {} -> {}",
+            num_leaves(&full),
+            num_leaves(&pruned),
+            full_accuracy,
+            pruned_accuracy
+        );
 
-        assert!(values.xy_pairs == vec![(-1.0, 0.0), (0.0, 0.0), (1.0, 1.0)]);
-        assert!(values.zero_count == 3);
-        assert!(values.zero_y == 2.0);
-        assert!(values.total_y == 3.0);
+        assert!(num_leaves(&pruned) < num_leaves(&full));
+        assert!(pruned_accuracy > full_accuracy - 0.1);
 
-        values.clear();
+        // Larger alphas prune more, down to a single leaf
+        assert!(num_leaves(&fit(0.02)) <= num_leaves(&pruned));
+        assert!(num_leaves(&fit(1.0)) == 1);
 
-        DecisionTree::get_values_sparse(&x, &y, 3, 1, &mut indices[..], &mut values);
+        let mut sparse_pruned = Hyperparameters::new(X.cols())
+            .max_features(2)
+            .ccp_alpha(0.005)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        sparse_pruned.fit(&SparseColumnArray::from(&X), &y).unwrap();
 
-        assert!(values.xy_pairs == vec![(0.0, 0.0), (1.0, 0.0), (2.0, 1.0), (3.0, 0.0)]);
-        assert!(values.zero_count == 2);
-        assert!(values.zero_y == 2.0);
-        assert!(values.total_y == 3.0);
+        assert!(sparse_pruned.nodes() == pruned.nodes());
     }
 
     #[test]
-    fn test_basic_tree_building() {
-        let X = Array::from(&vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
-        let y = Array::from(vec![1.0, 0.0]);
+    fn test_min_impurity_decrease() {
+        let (X, y) = imbalanced_data();
+
+        let fit = |min_impurity_decrease: f32, max_leaf_nodes: Option<usize>| {
+            let mut params = Hyperparameters::new(X.cols());
+            params
+                .max_features(2)
+                .min_impurity_decrease(min_impurity_decrease)
+                .rng(StdRng::from_seed(&[100]));
+
+            if let Some(max_leaf_nodes) = max_leaf_nodes {
+                params.max_leaf_nodes(max_leaf_nodes);
+            }
 
-        let params = Hyperparameters::new(2);
-        let mut model = params.build();
+            let mut model = params.build();
+            model.fit(&X, &y).unwrap();
+            model
+        };
 
-        model.fit(&X, &y).unwrap();
+        // A zero threshold leaves the tree unchanged
+        let mut unrestricted = Hyperparameters::new(X.cols())
+            .max_features(2)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        unrestricted.fit(&X, &y).unwrap();
 
-        assert!(allclose(&y, &model.decision_function(&X).unwrap()));
+        let full = fit(0.0, None);
+        assert!(full.nodes() == unrestricted.nodes());
+
+        let shallow = fit(0.01, None);
+
+        println!(
+            "Depth {} -> {}, leaves {} -> {}",
+            depth(&full),
+            depth(&shallow),
+            num_leaves(&full),
+            num_leaves(&shallow)
+        );
+
+        assert!(depth(&shallow) < depth(&full));
+        assert!(depth(&shallow) <= 3);
+        assert!(num_leaves(&fit(1.0, None)) == 1);
+
+        // The threshold also applies when growing best-first
+        assert!(num_leaves(&fit(0.01, Some(100))) < num_leaves(&fit(0.0, Some(100))));
     }
 
     #[test]
@@ -1061,6 +3480,207 @@ mod tests {
         assert!(test_accuracy > 0.96);
     }
 
+    #[test]
+    fn test_categorical_features() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        // Categories 1, 3 and 4 are positive: no single
+        // threshold on the category separates the classes.
+        let positive_categories = [1.0, 3.0, 4.0];
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for i in 0..120 {
+            let category = (i % 6) as f32;
+
+            rows.push(vec![category, normal.ind_sample(&mut rng) as f32]);
+            labels.push(if positive_categories.contains(&category) {
+                1.0
+            } else {
+                0.0
+            });
+        }
+
+        let X = Array::from(&rows);
+        let y = Array::from(labels);
+
+        let mut numeric_model = Hyperparameters::new(2)
+            .max_features(2)
+            .max_depth(0)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        numeric_model.fit(&X, &y).unwrap();
+
+        let numeric_accuracy = accuracy_score(&y, &numeric_model.predict(&X).unwrap());
+
+        let mut hyperparams = Hyperparameters::new(2);
+        hyperparams
+            .max_features(2)
+            .max_depth(0)
+            .categorical_features(vec![0])
+            .rng(StdRng::from_seed(&[100]));
+
+        let mut model = hyperparams.build();
+        model.fit(&X, &y).unwrap();
+
+        println!(
+            "Numeric stump accuracy {}, categorical stump accuracy {}",
+            numeric_accuracy,
+            accuracy_score(&y, &model.predict(&X).unwrap())
+        );
+
+        assert!(numeric_accuracy < 1.0);
+        assert!(model.predict(&X).unwrap() == y);
+
+        match model.nodes()[0] {
+            TreeNode::Split {
+                feature,
+                ref categories,
+                ..
+            } => {
+                assert!(feature == 0);
+
+                // Either side of the split may hold the positive categories
+                let categories = categories.clone().unwrap();
+                assert!(
+                    categories == positive_categories.to_vec() || categories == vec![0.0, 2.0, 5.0]
+                );
+            }
+            _ => panic!("Expected a split"),
+        }
+
+        // Routing uses set membership for every input type
+        let sparse_X = SparseColumnArray::from(&X);
+        assert!(
+            model.decision_function(&sparse_X).unwrap() == model.decision_function(&X).unwrap()
+        );
+        assert!(model.predict_row(&[3.0, 0.0]).unwrap() == 1.0);
+        assert!(model.predict_row(&[2.0, 0.0]).unwrap() == 0.0);
+
+        let mut sparse_model = hyperparams.build();
+        sparse_model.fit(&sparse_X, &y).unwrap();
+        assert!(sparse_model.predict(&sparse_X).unwrap() == y);
+
+        // Categories are never merged into bins
+        let mut binned_model = hyperparams.max_bins(2).build();
+        binned_model.fit(&X, &y).unwrap();
+        assert!(binned_model.predict(&X).unwrap() == y);
+
+        let dot = model.to_dot(None).unwrap();
+        assert!(dot.contains("X[0] in {"));
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: DecisionTree = bincode::deserialize(&encoded).unwrap();
+        assert!(decoded.predict(&X).unwrap() == y);
+    }
+
+    #[test]
+    fn test_max_bins_iris() {
+        let (data, target) = load_data();
+
+        let no_splits = 10;
+
+        let mut cv = CrossValidation::new(data.rows(), no_splits);
+        cv.set_rng(StdRng::from_seed(&[100]));
+
+        let mut exact_accuracy = 0.0;
+        let mut binned_accuracy = 0.0;
+
+        for (train_idx, test_idx) in cv {
+            let x_train = data.get_rows(&train_idx);
+            let x_test = data.get_rows(&test_idx);
+
+            let y_train = target.get_rows(&train_idx);
+            let y_test = target.get_rows(&test_idx);
+
+            let mut params = Hyperparameters::new(data.cols());
+            params
+                .min_samples_split(5)
+                .max_features(4)
+                .rng(StdRng::from_seed(&[100]));
+
+            let mut model = params.one_vs_rest();
+            model.fit(&x_train, &y_train).unwrap();
+            exact_accuracy += accuracy_score(&y_test, &model.predict(&x_test).unwrap());
+
+            let mut model = params.max_bins(255).one_vs_rest();
+            model.fit(&x_train, &y_train).unwrap();
+            binned_accuracy += accuracy_score(&y_test, &model.predict(&x_test).unwrap());
+        }
+
+        exact_accuracy /= no_splits as f32;
+        binned_accuracy /= no_splits as f32;
+
+        println!(
+            "Exact accuracy {}, binned accuracy {}",
+            exact_accuracy, binned_accuracy
+        );
+
+        assert!(binned_accuracy > 0.94);
+        assert!((binned_accuracy - exact_accuracy).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_max_bins_thresholds() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let normal = Normal::new(0.0, 1.0);
+
+        let rows = 2000;
+        let X = Array::from_vec(
+            (0..rows * 2)
+                .map(|_| normal.ind_sample(&mut rng) as f32)
+                .collect(),
+            rows,
+            2,
+        )
+        .unwrap();
+        let y = Array::from(
+            (0..rows)
+                .map(|row_idx| {
+                    if X.get(row_idx, 0) + X.get(row_idx, 1) > 0.0 {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let mut model = Hyperparameters::new(2)
+            .max_features(2)
+            .max_bins(4)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+        model.fit(&X, &y).unwrap();
+
+        // At most three bin boundaries per feature can serve as thresholds
+        for feature in 0..2 {
+            let mut thresholds = model
+                .nodes()
+                .iter()
+                .filter_map(|node| match *node {
+                    TreeNode::Split {
+                        feature: split_feature,
+                        threshold,
+                        ..
+                    } if split_feature == feature => Some(threshold),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            thresholds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            thresholds.dedup();
+
+            assert!(thresholds.len() > 0 && thresholds.len() <= 3);
+        }
+
+        // Thresholds still apply to the raw feature values
+        let accuracy = accuracy_score(&y, &model.predict(&X).unwrap());
+        println!("Accuracy with 4 bins {}", accuracy);
+        assert!(accuracy > 0.8);
+    }
+
     #[test]
     /// Reproduces https://github.com/maciejkula/rustlearn/issues/28
     fn test_decision_tree_iris_constant_features() {
@@ -1366,6 +3986,37 @@ mod bench {
         });
     }
 
+    #[bench]
+    fn bench_tall_binned(b: &mut Bencher) {
+        let rows = 5000;
+        let cols = 10;
+
+        let mut rng = StdRng::new().unwrap();
+
+        let mut X = Array::from(
+            (0..(rows * cols))
+                .map(|_| rng.next_f32())
+                .collect::<Vec<_>>(),
+        );
+        X.reshape(rows, cols);
+
+        let y = Array::from(
+            (0..rows)
+                .map(|_| *rng.choose(&vec![0.0, 1.0][..]).unwrap())
+                .collect::<Vec<_>>(),
+        );
+
+        let mut model = Hyperparameters::new(cols)
+            .min_samples_split(5)
+            .max_bins(64)
+            .rng(StdRng::from_seed(&[100]))
+            .build();
+
+        b.iter(|| {
+            model.fit(&X, &y).unwrap();
+        });
+    }
+
     #[bench]
     fn bench_wide_sparse(b: &mut Bencher) {
         let rows = 100;
@@ -1,3 +1,4 @@
 //! Decision trees.
 
 pub mod decision_tree;
+pub mod regression_tree;
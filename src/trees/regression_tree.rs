@@ -0,0 +1,670 @@
+//! A regression tree.
+//!
+//! This model implements the CART algorithm for continuous targets
+//! on dense data. At every node all features are considered, and the
+//! split minimizing the squared error of the two children is chosen.
+//! Leaves predict the mean target value of the training samples that
+//! reach them.
+//!
+//! Missing feature values may be encoded as `NaN`; they are always
+//! routed to the right child.
+//!
+//! Several continuous targets can be predicted at once by fitting on an
+//! `(n, t)` target array. A single tree is then grown for all targets,
+//! choosing splits by the reduction in squared error summed over the target
+//! columns, and every leaf predicts the mean of each target. Predictions
+//! are returned as an `(n, t)` array.
+//!
+//! # Examples
+//!
+//! ```
+//! extern crate rand;
+//! extern crate rustlearn;
+//!
+//! use rustlearn::prelude::*;
+//! use rustlearn::trees::regression_tree::Hyperparameters;
+//! use rustlearn::datasets::synthetic::make_regression;
+//!
+//! use rand::{SeedableRng, StdRng};
+//!
+//! # fn main() {
+//! let mut rng = StdRng::from_seed(&[100]);
+//! let (X, y) = make_regression(100, 5, 3, 0.1, &mut rng);
+//!
+//! let mut model = Hyperparameters::new(X.cols())
+//!                                 .max_depth(4)
+//!                                 .build();
+//!
+//! model.fit(&X, &y).unwrap();
+//!
+//! let prediction = model.predict(&X).unwrap();
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::f32;
+use std::usize;
+
+use prelude::*;
+
+use utils::{check_data_dimensionality, check_matched_dimensions};
+
+fn default_num_outputs() -> usize {
+    1
+}
+
+/// Hyperparameters for a `RegressionTree` model.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Hyperparameters {
+    dim: usize,
+
+    min_samples_split: usize,
+    max_depth: usize,
+}
+
+impl Hyperparameters {
+    /// Creates new Hyperparameters.
+    pub fn new(dim: usize) -> Hyperparameters {
+        Hyperparameters {
+            dim: dim,
+            min_samples_split: 2,
+            max_depth: usize::MAX,
+        }
+    }
+
+    /// Set the minimum number of samples that must be present
+    /// in order for further splitting to take place.
+    ///
+    /// Defaults to 2.
+    pub fn min_samples_split(&mut self, min_samples_split: usize) -> &mut Hyperparameters {
+        self.min_samples_split = min_samples_split;
+        self
+    }
+    /// Set the maximum depth of the tree. A tree of depth
+    /// one consists of a single split.
+    ///
+    /// Defaults to `usize::MAX`.
+    pub fn max_depth(&mut self, max_depth: usize) -> &mut Hyperparameters {
+        self.max_depth = max_depth;
+        self
+    }
+    /// Build a regression tree model.
+    pub fn build(&self) -> RegressionTree {
+        RegressionTree {
+            dim: self.dim,
+            min_samples_split: self.min_samples_split,
+            max_depth: self.max_depth,
+            root: None,
+            num_leaves: 0,
+            num_outputs: 1,
+            leaf_values: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+enum Node {
+    Interior {
+        feature: usize,
+        threshold: f32,
+        children: Box<(Node, Node)>,
+    },
+    Leaf {
+        index: usize,
+        value: f32,
+    },
+}
+
+/// A regression tree.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RegressionTree {
+    dim: usize,
+
+    min_samples_split: usize,
+    max_depth: usize,
+
+    root: Option<Node>,
+    num_leaves: usize,
+    #[serde(default = "default_num_outputs")]
+    num_outputs: usize,
+    // Row-major `(num_leaves, num_outputs)` leaf means, only
+    // stored for trees with more than one output.
+    #[serde(default)]
+    leaf_values: Vec<f32>,
+}
+
+impl<'a> SupervisedModel<&'a Array> for RegressionTree {
+    fn fit(&mut self, X: &Array, y: &Array) -> Result<(), RustlearnError> {
+        try!(check_data_dimensionality(self.dim, X));
+        try!(check_matched_dimensions(X, y));
+
+        if X.rows() == 0 {
+            return Err(RustlearnError::EmptyInput(
+                "Cannot fit a tree on an empty array.",
+            ));
+        }
+
+        if y.cols() == 0 {
+            return Err(RustlearnError::InvalidInput(
+                "Target array must have at least one column.",
+            ));
+        }
+
+        let mut xy_pairs = Vec::with_capacity(X.rows());
+        let mut num_leaves = 0;
+        let mut leaf_values = Vec::new();
+
+        self.root = Some(self.build_tree(
+            X,
+            y,
+            &mut (0..X.rows()).collect::<Vec<usize>>()[..],
+            0,
+            &mut xy_pairs,
+            &mut num_leaves,
+            &mut leaf_values,
+        ));
+        self.num_leaves = num_leaves;
+        self.num_outputs = y.cols();
+        self.leaf_values = leaf_values;
+
+        Ok(())
+    }
+
+    fn decision_function(&self, X: &Array) -> Result<Array, RustlearnError> {
+        try!(check_data_dimensionality(self.dim, X));
+
+        match self.root {
+            Some(ref node) if self.num_outputs > 1 => {
+                let mut predictions = Array::zeros(X.rows(), self.num_outputs);
+                for row_idx in 0..X.rows() {
+                    let (leaf_idx, _) = RegressionTree::query_tree(node, X, row_idx);
+                    let start = leaf_idx * self.num_outputs;
+
+                    for (col_idx, &value) in self.leaf_values[start..start + self.num_outputs]
+                        .iter()
+                        .enumerate()
+                    {
+                        predictions.set(row_idx, col_idx, value);
+                    }
+                }
+                Ok(predictions)
+            }
+            Some(ref node) => {
+                let mut data = Vec::with_capacity(X.rows());
+                for row_idx in 0..X.rows() {
+                    data.push(RegressionTree::query_tree(node, X, row_idx).1);
+                }
+                Ok(Array::from(data))
+            }
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before predicting",
+            )),
+        }
+    }
+
+    /// Predict the target values; this is the same as the decision function.
+    fn predict(&self, X: &Array) -> Result<Array, RustlearnError> {
+        self.decision_function(X)
+    }
+}
+
+impl RegressionTree {
+    /// Return the number of leaves of the fitted tree.
+    pub fn num_leaves(&self) -> usize {
+        self.num_leaves
+    }
+
+    /// Return the number of target columns the tree was fitted on.
+    pub fn num_outputs(&self) -> usize {
+        self.num_outputs
+    }
+
+    /// Return the index of the leaf reached by each row of `X`.
+    ///
+    /// Leaves are numbered from zero to `num_leaves() - 1` in
+    /// depth-first order, left child first.
+    pub fn apply(&self, X: &Array) -> Result<Vec<usize>, RustlearnError> {
+        try!(check_data_dimensionality(self.dim, X));
+
+        match self.root {
+            Some(ref node) => Ok((0..X.rows())
+                .map(|row_idx| RegressionTree::query_tree(node, X, row_idx).0)
+                .collect()),
+            None => Err(RustlearnError::NotFitted(
+                "Tree must be built before predicting",
+            )),
+        }
+    }
+
+    fn build_tree(
+        &self,
+        X: &Array,
+        y: &Array,
+        indices: &mut [usize],
+        depth: usize,
+        xy_pairs: &mut Vec<(f32, f32)>,
+        num_leaves: &mut usize,
+        leaf_values: &mut Vec<f32>,
+    ) -> Node {
+        let count = indices.len() as f32;
+        let total_y = (0..y.cols())
+            .map(|col_idx| {
+                indices
+                    .iter()
+                    .map(|&row_idx| y.get(row_idx, col_idx))
+                    .sum::<f32>()
+            })
+            .collect::<Vec<f32>>();
+
+        let is_constant = (0..y.cols()).all(|col_idx| {
+            let first_y = y.get(indices[0], col_idx);
+            indices
+                .iter()
+                .all(|&row_idx| y.get(row_idx, col_idx) == first_y)
+        });
+
+        if is_constant || depth >= self.max_depth || indices.len() < self.min_samples_split {
+            return RegressionTree::leaf(&total_y, count, num_leaves, leaf_values);
+        }
+
+        // A split must improve on the score of not splitting at all.
+        let mut best_score = total_y.iter().map(|total| total.powi(2)).sum::<f32>() / count;
+        let mut best_split = None;
+
+        let mut x_pairs = Vec::new();
+
+        for feature_idx in 0..self.dim {
+            let split = if y.cols() == 1 {
+                xy_pairs.clear();
+                xy_pairs.extend(
+                    indices
+                        .iter()
+                        .map(|&row_idx| (X.get(row_idx, feature_idx), y.get(row_idx, 0))),
+                );
+                xy_pairs.sort_by(|a, b| RegressionTree::compare_values(a.0, b.0));
+
+                RegressionTree::calculate_split(xy_pairs, total_y[0])
+            } else {
+                x_pairs.clear();
+                x_pairs.extend(
+                    indices
+                        .iter()
+                        .map(|&row_idx| (X.get(row_idx, feature_idx), row_idx)),
+                );
+                x_pairs.sort_by(|a, b| RegressionTree::compare_values(a.0, b.0));
+
+                RegressionTree::calculate_multi_output_split(&x_pairs, y, &total_y)
+            };
+
+            if let Some((threshold, score)) = split {
+                if score > best_score {
+                    best_score = score;
+                    best_split = Some((feature_idx, threshold));
+                }
+            }
+        }
+
+        if let Some((feature, threshold)) = best_split {
+            let (left_indices, right_indices) =
+                RegressionTree::split_indices(X, indices, feature, threshold);
+
+            let left = self.build_tree(
+                X,
+                y,
+                left_indices,
+                depth + 1,
+                xy_pairs,
+                num_leaves,
+                leaf_values,
+            );
+            let right = self.build_tree(
+                X,
+                y,
+                right_indices,
+                depth + 1,
+                xy_pairs,
+                num_leaves,
+                leaf_values,
+            );
+
+            return Node::Interior {
+                feature: feature,
+                threshold: threshold,
+                children: Box::new((left, right)),
+            };
+        }
+
+        RegressionTree::leaf(&total_y, count, num_leaves, leaf_values)
+    }
+
+    /// Create a leaf predicting the mean of every target column. The means
+    /// of trees with multiple outputs are appended to `leaf_values`; the node
+    /// itself stores the mean of the first target.
+    fn leaf(
+        total_y: &[f32],
+        count: f32,
+        num_leaves: &mut usize,
+        leaf_values: &mut Vec<f32>,
+    ) -> Node {
+        let index = *num_leaves;
+        *num_leaves += 1;
+
+        if total_y.len() > 1 {
+            leaf_values.extend(total_y.iter().map(|total| total / count));
+        }
+
+        Node::Leaf {
+            index: index,
+            value: total_y[0] / count,
+        }
+    }
+
+    /// Order feature values, placing missing values last.
+    fn compare_values(a: f32, b: f32) -> Ordering {
+        match (a.is_nan(), b.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        }
+    }
+
+    /// Find the best split threshold among sorted `(x, y)` pairs, returning the
+    /// threshold and its score. The score is the sum over both children of the
+    /// squared total target divided by the child size; maximizing it minimizes
+    /// the squared error of the split.
+    fn calculate_split(xy_pairs: &[(f32, f32)], total_y: f32) -> Option<(f32, f32)> {
+        let total_count = xy_pairs.len() as f32;
+
+        let mut best_split = None;
+        let mut best_score = f32::NEG_INFINITY;
+
+        let mut cumulative_y = 0.0;
+
+        for (idx, window) in xy_pairs.windows(2).enumerate() {
+            let (x, y) = window[0];
+            let next_x = window[1].0;
+
+            if x.is_nan() {
+                break;
+            }
+
+            cumulative_y += y;
+
+            if x == next_x {
+                continue;
+            }
+
+            let left_count = (idx + 1) as f32;
+            let right_count = total_count - left_count;
+
+            let score =
+                cumulative_y.powi(2) / left_count + (total_y - cumulative_y).powi(2) / right_count;
+
+            if score > best_score {
+                best_score = score;
+                best_split = Some((x, score));
+            }
+        }
+
+        best_split
+    }
+
+    /// Find the best split threshold among `(x, row index)` pairs sorted by `x`
+    /// when there are several target columns, returning the threshold and its
+    /// score. The score is the sum of the single-target scores of every column
+    /// of `y`, so that maximizing it minimizes the total squared error.
+    fn calculate_multi_output_split(
+        x_pairs: &[(f32, usize)],
+        y: &Array,
+        total_y: &[f32],
+    ) -> Option<(f32, f32)> {
+        let total_count = x_pairs.len() as f32;
+
+        let mut best_split = None;
+        let mut best_score = f32::NEG_INFINITY;
+
+        let mut cumulative_y = vec![0.0; total_y.len()];
+
+        for (idx, window) in x_pairs.windows(2).enumerate() {
+            let (x, row_idx) = window[0];
+            let next_x = window[1].0;
+
+            if x.is_nan() {
+                break;
+            }
+
+            for (col_idx, cumulative) in cumulative_y.iter_mut().enumerate() {
+                *cumulative += y.get(row_idx, col_idx);
+            }
+
+            if x == next_x {
+                continue;
+            }
+
+            let left_count = (idx + 1) as f32;
+            let right_count = total_count - left_count;
+
+            let score = cumulative_y
+                .iter()
+                .zip(total_y.iter())
+                .map(|(&cumulative, &total)| {
+                    cumulative.powi(2) / left_count + (total - cumulative).powi(2) / right_count
+                })
+                .sum::<f32>();
+
+            if score > best_score {
+                best_score = score;
+                best_split = Some((x, score));
+            }
+        }
+
+        best_split
+    }
+
+    /// Whether a sample with feature value `value` goes to the left child.
+    fn goes_left(value: f32, threshold: f32) -> bool {
+        value <= threshold
+    }
+
+    fn split_indices<'a>(
+        X: &Array,
+        indices: &'a mut [usize],
+        feature_idx: usize,
+        threshold: f32,
+    ) -> (&'a mut [usize], &'a mut [usize]) {
+        let mut num_left = 0;
+
+        for i in 0..indices.len() {
+            if RegressionTree::goes_left(X.get(indices[i], feature_idx), threshold) {
+                indices.swap(i, num_left);
+                num_left += 1;
+            }
+        }
+
+        indices.split_at_mut(num_left)
+    }
+
+    /// Return the index and the value of the leaf reached by a sample.
+    fn query_tree(node: &Node, x: &Array, row_idx: usize) -> (usize, f32) {
+        match *node {
+            Node::Interior {
+                feature,
+                threshold,
+                ref children,
+            } => {
+                if RegressionTree::goes_left(x.get(row_idx, feature), threshold) {
+                    RegressionTree::query_tree(&children.0, x, row_idx)
+                } else {
+                    RegressionTree::query_tree(&children.1, x, row_idx)
+                }
+            }
+            Node::Leaf { index, value } => (index, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32;
+
+    use prelude::*;
+
+    use datasets::synthetic::make_regression;
+    use metrics::mean_squared_error;
+
+    use rand::{SeedableRng, StdRng};
+
+    use bincode;
+
+    use super::*;
+
+    #[test]
+    fn calculate_split() {
+        let xy_pairs = vec![(1.0, 1.0), (2.0, 1.0), (3.0, 5.0), (4.0, 5.0)];
+
+        let (threshold, score) = RegressionTree::calculate_split(&xy_pairs, 12.0).unwrap();
+
+        assert!(threshold == 2.0);
+        assert!(score == 2.0 + 50.0);
+    }
+
+    #[test]
+    fn calculate_split_missing() {
+        let xy_pairs = vec![(1.0, 1.0), (1.0, 3.0), (f32::NAN, 5.0)];
+
+        let (threshold, _) = RegressionTree::calculate_split(&xy_pairs, 9.0).unwrap();
+
+        assert!(threshold == 1.0);
+        assert!(RegressionTree::calculate_split(&xy_pairs[..2], 4.0).is_none());
+    }
+
+    #[test]
+    fn step_function() {
+        let X = Array::from(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let y = Array::from(vec![0.0, 0.0, 0.0, 10.0, 10.0, 10.0]);
+
+        let mut model = Hyperparameters::new(1).max_depth(1).build();
+        model.fit(&X, &y).unwrap();
+
+        let prediction = model
+            .predict(&Array::from(vec![0.0, 3.5, f32::NAN]))
+            .unwrap();
+
+        assert!(prediction.data() == &[0.0, 10.0, 10.0]);
+
+        assert!(model.num_leaves() == 2);
+        assert!(model.apply(&X).unwrap() == vec![0, 0, 0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_regression_tree_fit() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_regression(200, 5, 3, 0.0, &mut rng);
+
+        let variance = mean_squared_error(&y, &Array::zeros(y.rows(), 1));
+
+        let mut shallow = Hyperparameters::new(X.cols()).max_depth(2).build();
+        shallow.fit(&X, &y).unwrap();
+        let shallow_error = mean_squared_error(&y, &shallow.predict(&X).unwrap());
+
+        let mut deep = Hyperparameters::new(X.cols()).build();
+        deep.fit(&X, &y).unwrap();
+        let deep_error = mean_squared_error(&y, &deep.predict(&X).unwrap());
+
+        println!(
+            "Variance {} shallow {} deep {}",
+            variance, shallow_error, deep_error
+        );
+
+        assert!(shallow_error < variance);
+        assert!(deep_error < 1e-6);
+    }
+
+    #[test]
+    fn multi_output() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_regression(200, 4, 3, 0.0, &mut rng);
+
+        // The second target depends on a feature the first one ignores;
+        // both targets have similar variance.
+        let targets = (0..X.rows())
+            .map(|row_idx| vec![0.1 * y.get(row_idx, 0), 10.0 * X.get(row_idx, 3).signum()])
+            .collect::<Vec<_>>();
+        let Y = Array::from(&targets);
+
+        let mut model = Hyperparameters::new(X.cols()).max_depth(6).build();
+        model.fit(&X, &Y).unwrap();
+
+        let prediction = model.predict(&X).unwrap();
+
+        assert!(model.num_outputs() == 2);
+        assert!(prediction.rows() == X.rows() && prediction.cols() == 2);
+
+        for col_idx in 0..2 {
+            let target = Y.submatrix(0..Y.rows(), col_idx..col_idx + 1);
+            let predicted = prediction.submatrix(0..X.rows(), col_idx..col_idx + 1);
+
+            let variance = mean_squared_error(&target, &Array::zeros(target.rows(), 1));
+            let error = mean_squared_error(&target, &predicted);
+
+            println!("Target {} variance {} error {}", col_idx, variance, error);
+
+            assert!(error < 0.2 * variance);
+        }
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: RegressionTree = bincode::deserialize(&encoded).unwrap();
+        assert!(decoded.predict(&X).unwrap() == prediction);
+    }
+
+    #[test]
+    fn multi_output_duplicated_target() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_regression(100, 3, 3, 0.1, &mut rng);
+
+        let Y = Array::from(
+            &(0..y.rows())
+                .map(|row_idx| vec![y.get(row_idx, 0), y.get(row_idx, 0)])
+                .collect::<Vec<_>>(),
+        );
+
+        let mut single = Hyperparameters::new(X.cols()).max_depth(4).build();
+        single.fit(&X, &y).unwrap();
+
+        let mut multi = Hyperparameters::new(X.cols()).max_depth(4).build();
+        multi.fit(&X, &Y).unwrap();
+
+        let single_prediction = single.predict(&X).unwrap();
+        let multi_prediction = multi.predict(&X).unwrap();
+
+        assert!(single.num_outputs() == 1 && single_prediction.cols() == 1);
+        assert!(multi.apply(&X).unwrap() == single.apply(&X).unwrap());
+
+        for col_idx in 0..2 {
+            let column = multi_prediction.submatrix(0..X.rows(), col_idx..col_idx + 1);
+            assert!(column == single_prediction);
+        }
+    }
+
+    #[test]
+    fn serialization() {
+        let mut rng = StdRng::from_seed(&[100]);
+        let (X, y) = make_regression(50, 3, 3, 0.1, &mut rng);
+
+        let mut model = Hyperparameters::new(X.cols()).max_depth(3).build();
+        model.fit(&X, &y).unwrap();
+
+        let encoded = bincode::serialize(&model).unwrap();
+        let decoded: RegressionTree = bincode::deserialize(&encoded).unwrap();
+
+        assert!(model.predict(&X).unwrap() == decoded.predict(&X).unwrap());
+    }
+
+    #[test]
+    fn predict_before_fit() {
+        let model = Hyperparameters::new(2).build();
+
+        assert!(model.predict(&Array::zeros(2, 2)).is_err());
+    }
+}
@@ -4,7 +4,9 @@
 //!
 //! Made public to make extending rustlearn easier, but should be treated as semi-public
 //! and subject to change.
-use rand::StdRng;
+use std::f32;
+
+use rand::{SeedableRng, StdRng};
 
 use prelude::*;
 
@@ -12,6 +14,43 @@ fn default_stdrng() -> StdRng {
     StdRng::new().unwrap()
 }
 
+/// Advance a splitmix64 generator, returning the next output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+    z ^ (z >> 31)
+}
+
+/// Deterministically derive a new seed from `seed` and `index`.
+///
+/// Distinct indices yield distinct, statistically unrelated seeds, making this suitable
+/// for giving independent components (such as the trees of a forest) their own generators.
+pub fn derive_seed(seed: u64, index: u64) -> u64 {
+    let mut state = seed;
+    let mut state = splitmix64(&mut state) ^ index;
+
+    splitmix64(&mut state)
+}
+
+/// Create a random number generator deterministically seeded from a single integer.
+///
+/// The seed is expanded into 32 bytes of generator seed using splitmix64,
+/// so nearby seeds produce unrelated generators. Generators created from
+/// the same seed produce identical sequences, on both 32- and 64-bit platforms.
+pub fn seeded_rng(seed: u64) -> StdRng {
+    let mut state = seed;
+
+    let seed_words = (0..8)
+        .map(|_| (splitmix64(&mut state) >> 32) as usize)
+        .collect::<Vec<_>>();
+
+    StdRng::from_seed(&seed_words[..])
+}
+
 /// Wrapper for making random number generators serializable.
 /// Does no actual encoding, and merely creates a new
 /// generator on decoding.
@@ -28,6 +67,16 @@ impl EncodableRng {
             rng: StdRng::new().unwrap(),
         }
     }
+
+    /// Create a new generator deterministically seeded from `seed`,
+    /// as in [`seeded_rng`](fn.seeded_rng.html).
+    ///
+    /// Note that the seed is not preserved by serialization.
+    pub fn from_seed(seed: u64) -> EncodableRng {
+        EncodableRng {
+            rng: seeded_rng(seed),
+        }
+    }
 }
 
 impl Default for EncodableRng {
@@ -37,15 +86,19 @@ impl Default for EncodableRng {
 }
 
 /// Check that the input array contains valid binary classification labels.
-pub fn check_valid_labels(y: &Array) -> Result<(), &'static str> {
+pub fn check_valid_labels(y: &Array) -> Result<(), RustlearnError> {
     if y.cols() != 1 {
-        return Err("Target array has more than one column.");
+        return Err(RustlearnError::DimensionMismatch(
+            "Target array has more than one column.",
+        ));
     }
 
     if y.data().iter().all(|&x| x == 0.0 || x == 1.0) {
         Ok(())
     } else {
-        Err("Invalid labels: target data is not either 0.0 or 1.0")
+        Err(RustlearnError::InvalidInput(
+            "Invalid labels: target data is not either 0.0 or 1.0",
+        ))
     }
 }
 
@@ -53,28 +106,202 @@ pub fn check_valid_labels(y: &Array) -> Result<(), &'static str> {
 pub fn check_data_dimensionality<T: IndexableMatrix>(
     model_dim: usize,
     X: &T,
-) -> Result<(), &'static str> {
+) -> Result<(), RustlearnError> {
     if X.cols() == model_dim {
         Ok(())
     } else {
-        Err("Model input and model dimensionality differ.")
+        Err(RustlearnError::DimensionMismatch(
+            "Model input and model dimensionality differ.",
+        ))
+    }
+}
+
+// Check that a single row of features has the model's dimensionality.
+pub fn check_row_dimensionality(model_dim: usize, row: &[f32]) -> Result<(), RustlearnError> {
+    if row.len() == model_dim {
+        Ok(())
+    } else {
+        Err(RustlearnError::DimensionMismatch(
+            "Model input and model dimensionality differ.",
+        ))
     }
 }
 
 // Check that X and y have the same number of rows.
-pub fn check_matched_dimensions<T: IndexableMatrix>(X: &T, y: &Array) -> Result<(), &'static str> {
+pub fn check_matched_dimensions<T: IndexableMatrix>(
+    X: &T,
+    y: &Array,
+) -> Result<(), RustlearnError> {
     if X.rows() == y.rows() {
         Ok(())
     } else {
-        Err("Data matrix and target array do not have the same number of rows")
+        Err(RustlearnError::DimensionMismatch(
+            "Data matrix and target array do not have the same number of rows",
+        ))
     }
 }
 
+/// Check that the sample weights form a single column with one non-negative,
+/// finite weight per row of `y`, and that not all weights are zero.
+pub fn check_sample_weights(y: &Array, sample_weight: &Array) -> Result<(), RustlearnError> {
+    if sample_weight.cols() != 1 {
+        return Err(RustlearnError::DimensionMismatch(
+            "Sample weight array has more than one column.",
+        ));
+    }
+
+    if sample_weight.rows() != y.rows() {
+        return Err(RustlearnError::DimensionMismatch(
+            "Sample weight and target arrays do not have the same number of rows",
+        ));
+    }
+
+    if !sample_weight
+        .data()
+        .iter()
+        .all(|&x| x.is_finite() && x >= 0.0)
+    {
+        return Err(RustlearnError::InvalidInput(
+            "Sample weights must be non-negative and finite.",
+        ));
+    }
+
+    if sample_weight.data().iter().all(|&x| x == 0.0) {
+        return Err(RustlearnError::InvalidInput(
+            "Sample weights must not all be zero.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that `weights` holds one non-negative, finite weight for each of
+/// `num_models` ensemble members, not all zero, and normalize them to sum to one.
+pub fn normalize_model_weights(
+    weights: &[f32],
+    num_models: usize,
+) -> Result<Vec<f32>, RustlearnError> {
+    if weights.len() != num_models {
+        return Err(RustlearnError::DimensionMismatch(
+            "Number of weights and number of models differ.",
+        ));
+    }
+
+    if !weights.iter().all(|&x| x.is_finite() && x >= 0.0) {
+        return Err(RustlearnError::InvalidInput(
+            "Model weights must be non-negative and finite.",
+        ));
+    }
+
+    let total = weights.iter().sum::<f32>();
+
+    if total == 0.0 {
+        return Err(RustlearnError::InvalidInput(
+            "Model weights must not all be zero.",
+        ));
+    }
+
+    Ok(weights.iter().map(|x| x / total).collect())
+}
+
+/// Predict class labels from the combined decision function of an ensemble.
+///
+/// A single-column decision function predicts the positive class where it exceeds
+/// `threshold`. Otherwise, the decision function must have one column for each of
+/// the sorted `class_labels`, and the label of the largest column is predicted.
+pub fn predict_from_decision(
+    decision: &Array,
+    class_labels: &[f32],
+    threshold: f32,
+) -> Result<Array, RustlearnError> {
+    if decision.cols() == 1 {
+        return Ok(decision.map(|x| if x > threshold { 1.0 } else { 0.0 }));
+    }
+
+    if decision.cols() != class_labels.len() {
+        return Err(RustlearnError::DimensionMismatch(
+            "Number of classes does not match the training data.",
+        ));
+    }
+
+    Ok(Array::from(
+        decision
+            .iter_rows()
+            .map(|row| {
+                let mut max_value = f32::NEG_INFINITY;
+                let mut max_class = 0;
+
+                for (class_idx, value) in row.iter_nonzero() {
+                    if value > max_value {
+                        max_value = value;
+                        max_class = class_idx;
+                    }
+                }
+
+                class_labels[max_class]
+            })
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Check that a model has been fit before being used for prediction.
+pub fn check_fitted(fitted: bool) -> Result<(), RustlearnError> {
+    if fitted {
+        Ok(())
+    } else {
+        Err(RustlearnError::NotFitted(
+            "Model must be fit before predicting",
+        ))
+    }
+}
+
+/// Default value of the fitted-state flag of deserialized models: models
+/// serialized before the flag was introduced are assumed to be fitted.
+pub fn assume_fitted() -> bool {
+    true
+}
+
+/// The `q`-th quantile of the sorted, non-empty `values`, linearly
+/// interpolating between the two nearest values.
+pub fn percentile(values: &[f32], q: f32) -> f32 {
+    let position = q * (values.len() - 1) as f32;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+
+    values[lower] + (position - lower as f32) * (values[upper] - values[lower])
+}
+
 #[cfg(test)]
 mod tests {
-    use super::EncodableRng;
+    use super::{derive_seed, seeded_rng, EncodableRng};
+    use rand::Rng;
     use serde_json;
 
+    #[test]
+    fn test_seeded_rng() {
+        let draw = |seed| {
+            seeded_rng(seed)
+                .gen_iter::<u64>()
+                .take(10)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(draw(42) == draw(42));
+        assert!(draw(42) != draw(43));
+        assert!(draw(0) != draw(1));
+
+        assert!(derive_seed(42, 0) == derive_seed(42, 0));
+        assert!(derive_seed(42, 0) != derive_seed(42, 1));
+        assert!(derive_seed(42, 0) != derive_seed(43, 0));
+
+        let mut first = EncodableRng::from_seed(42);
+        let mut second = EncodableRng::from_seed(42);
+
+        for _ in 0..10 {
+            assert!(first.rng.gen::<f32>() == second.rng.gen::<f32>());
+        }
+    }
+
     #[test]
     fn test_encodable_rng_serialization() {
         let rng = EncodableRng::new();